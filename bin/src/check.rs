@@ -0,0 +1,65 @@
+use anyhow::{Context, Result, bail};
+use cuttle_lang::{
+    ErrorReporter, Symbol, lint_source, outline, parse_geometry_nodes_file_with_errors,
+};
+use std::path::Path;
+
+/// Parse the file at `path`, with its imports, and report any errors and lint warnings. Warnings
+/// only fail the check with `deny_warnings`. With `show_outline` the file only has to parse, and
+/// its named statements are listed instead.
+pub fn check(path: &Path, show_outline: bool, deny_warnings: bool) -> Result<()> {
+    let source = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    if !show_outline {
+        if let Err(report) = parse_geometry_nodes_file_with_errors(path) {
+            eprint!("{report}");
+            bail!("{} has errors", path.display());
+        }
+        // Files with imports only build through the file loader, which doesn't keep spans, so
+        // only files that stand alone are linted
+        if let Ok((warnings, spans)) = lint_source(&source) {
+            if !warnings.is_empty() {
+                let name = path.display().to_string();
+                eprint!(
+                    "{}",
+                    ErrorReporter::new().report_lints(&warnings, &spans, &source, &name)
+                );
+                if deny_warnings {
+                    bail!("{name} has warnings");
+                }
+            }
+        }
+        println!("{}: ok", path.display());
+        return Ok(());
+    }
+
+    match outline(&source) {
+        Ok(symbols) => {
+            print_symbols(&source, &symbols, 0);
+            Ok(())
+        }
+        Err(errors) => {
+            let name = path.display().to_string();
+            eprint!(
+                "{}",
+                ErrorReporter::new().report_errors(&errors, &source, &name)
+            );
+            bail!("{name} has errors")
+        }
+    }
+}
+
+// One symbol per line after its line number, with group members indented under the group
+fn print_symbols(source: &str, symbols: &[Symbol], depth: usize) {
+    for symbol in symbols {
+        let line = source[..symbol.span.start].matches('\n').count() + 1;
+        println!(
+            "{line:>4}  {}{} {}  {}",
+            "  ".repeat(depth),
+            symbol.kind.name(),
+            symbol.name,
+            symbol.detail
+        );
+        print_symbols(source, &symbol.children, depth + 1);
+    }
+}
@@ -14,6 +14,61 @@ pub struct Cli {
 pub enum Commands {
     /// Blender state validation harness
     Validation(ValidationCommand),
+
+    /// Host a cuttle runtime for another process
+    Serve {
+        /// Speak JSON-RPC 2.0 in Content-Length framed messages over stdin/stdout
+        #[arg(long)]
+        stdio: bool,
+    },
+
+    /// Inspect the scene of a cuttle runtime
+    Scene(SceneCommand),
+
+    /// Check that a geometry nodes file parses and its graph resolves
+    Check {
+        /// Path to the source file
+        path: PathBuf,
+
+        /// List the file's labeled nodes, groups and constants instead
+        #[arg(long)]
+        outline: bool,
+
+        /// Fail when the file has lint warnings, like nodes that aren't connected to anything
+        #[arg(long)]
+        deny_warnings: bool,
+    },
+
+    /// Print the tree-sitter grammar used for syntax highlighting
+    Grammar {
+        /// Write grammar.js and queries/highlights.scm into this directory instead
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Parser)]
+pub struct SceneCommand {
+    #[command(subcommand)]
+    pub command: SceneSubcommands,
+}
+
+#[derive(Subcommand)]
+pub enum SceneSubcommands {
+    /// Show a live-updating table of objects, materials and counts
+    Watch {
+        /// Refresh interval in milliseconds
+        #[arg(long, default_value = "500")]
+        interval: u64,
+
+        /// Number of refreshes a changed row stays highlighted
+        #[arg(long, default_value = "4")]
+        highlight: u64,
+
+        /// Watch the Blender addon listening at this address instead of the mock
+        #[arg(long, value_name = "HOST:PORT")]
+        connect: Option<String>,
+    },
 }
 
 #[derive(Parser)]
@@ -40,6 +95,18 @@ pub enum ValidationSubcommands {
         /// Timeout for each validation in seconds
         #[arg(long, default_value = "30")]
         timeout: u64,
+
+        /// Export each validation's scene as an artifact (gltf, obj, usd)
+        #[arg(long)]
+        export: Option<String>,
+
+        /// Only capture material nodes within this many links of the output node
+        #[arg(long)]
+        node_depth: Option<usize>,
+
+        /// Drive the Blender addon listening at this address instead of the mock
+        #[arg(long, value_name = "HOST:PORT")]
+        connect: Option<String>,
     },
 
     /// List available validations
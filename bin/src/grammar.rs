@@ -0,0 +1,28 @@
+use anyhow::{Context, Result};
+use cuttle_lang::{highlights_query, tree_sitter_grammar};
+use std::path::Path;
+
+/// Print the tree-sitter grammar, or with `out` write it and its highlight queries into the
+/// layout `tree-sitter generate` expects.
+pub fn grammar(out: Option<&Path>) -> Result<()> {
+    let Some(out) = out else {
+        print!("{}", tree_sitter_grammar());
+        return Ok(());
+    };
+
+    let queries = out.join("queries");
+    std::fs::create_dir_all(&queries)
+        .with_context(|| format!("Failed to create {}", queries.display()))?;
+    for (path, contents) in [
+        (out.join("grammar.js"), tree_sitter_grammar()),
+        (
+            queries.join("highlights.scm"),
+            highlights_query().to_string(),
+        ),
+    ] {
+        std::fs::write(&path, contents)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        println!("Wrote {}", path.display());
+    }
+    Ok(())
+}
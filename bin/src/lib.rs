@@ -1,4 +1,9 @@
+pub mod check;
 pub mod cli;
+pub mod grammar;
+pub mod runtime;
+pub mod scene;
+pub mod serve;
 pub mod validation;
 
 use anyhow::Result;
@@ -12,6 +17,22 @@ pub async fn run() -> Result<()> {
         cli::Commands::Validation(validation_cmd) => {
             validation::handle_command(validation_cmd).await?;
         }
+        cli::Commands::Serve { stdio } => {
+            serve::serve(stdio).await?;
+        }
+        cli::Commands::Scene(scene_cmd) => {
+            scene::handle_command(scene_cmd).await?;
+        }
+        cli::Commands::Check {
+            path,
+            outline,
+            deny_warnings,
+        } => {
+            check::check(&path, outline, deny_warnings)?;
+        }
+        cli::Commands::Grammar { out } => {
+            grammar::grammar(out.as_deref())?;
+        }
     }
 
     Ok(())
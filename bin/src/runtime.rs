@@ -0,0 +1,38 @@
+use anyhow::{Context, Result};
+use cuttle::{PyBridge, RuntimeConfig, ServiceMessage, ServiceResponse, TcpTransport};
+use std::time::Duration;
+
+/// Start a cuttle runtime, driving the Blender addon listening at `connect` when given and
+/// the mock otherwise.
+pub fn start_bridge(connect: Option<&str>, config: RuntimeConfig) -> Result<PyBridge> {
+    // The addon is given as long as the services give their slowest operation
+    let io_timeout = config
+        .operation_timeouts
+        .values()
+        .copied()
+        .fold(config.default_timeout, Duration::max);
+    let (mut bridge, async_bridge) = PyBridge::new();
+    bridge.set_runtime_config(config);
+
+    if let Some(address) = connect {
+        let transport = TcpTransport::connect_timeout(address, io_timeout)
+            .with_context(|| format!("Failed to connect to Blender at {address}"))?;
+        bridge.set_blender_transport(transport);
+    }
+
+    bridge.start_runtime(async_bridge);
+    Ok(bridge)
+}
+
+/// Send `message` and block until its response, for as long as the services can take to answer
+/// it. A late reply to an earlier message is never taken as the answer.
+pub fn request(bridge: &PyBridge, message: ServiceMessage) -> Result<ServiceResponse> {
+    let operation = message.operation_name();
+    let timeout = bridge.response_timeout(&message);
+    let id = bridge
+        .send_awaited(message)
+        .with_context(|| format!("Failed to send {operation} message"))?;
+    bridge
+        .recv_reply_to(id, timeout)
+        .with_context(|| format!("No response to {operation}"))
+}
@@ -0,0 +1,274 @@
+use crate::cli::{SceneCommand, SceneSubcommands};
+use crate::runtime::{request, start_bridge};
+use anyhow::{Context, Result};
+use cuttle::{PyBridge, RuntimeConfig, ServiceMessage, ServiceResponse};
+use cuttle_blender_api::{MaterialData, ObjectData, SceneState};
+use std::collections::BTreeMap;
+use std::io::Write;
+use tokio::time::Duration;
+
+const HIGHLIGHT: &str = "\x1b[1;33m";
+const RESET: &str = "\x1b[0m";
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+pub async fn handle_command(cmd: SceneCommand) -> Result<()> {
+    match cmd.command {
+        SceneSubcommands::Watch {
+            interval,
+            highlight,
+            connect,
+        } => {
+            watch(
+                Duration::from_millis(interval),
+                highlight,
+                connect.as_deref(),
+            )
+            .await
+        }
+    }
+}
+
+/// Poll the runtime's scene and redraw the dashboard until interrupted.
+///
+/// There is no scene event stream yet, so changes are found by diffing successive snapshots.
+/// Without `connect` this watches the mock scene of a runtime started just for the dashboard.
+async fn watch(interval: Duration, highlight: u64, connect: Option<&str>) -> Result<()> {
+    let bridge = start_bridge(connect, RuntimeConfig::default())?;
+
+    let mut dashboard = Dashboard::new(highlight);
+    let result = loop {
+        let state = match query_scene_state(&bridge) {
+            Ok(state) => state,
+            Err(e) => break Err(e),
+        };
+        dashboard.update(&state);
+
+        let mut stdout = std::io::stdout();
+        write!(stdout, "{CLEAR_SCREEN}{}", dashboard.render(true))
+            .and_then(|_| stdout.flush())
+            .context("Failed to draw dashboard")?;
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break Ok(()),
+            _ = tokio::time::sleep(interval) => {}
+        }
+    };
+
+    bridge.stop();
+    result
+}
+
+fn query_scene_state(bridge: &PyBridge) -> Result<SceneState> {
+    match request(bridge, ServiceMessage::GetSceneState)? {
+        ServiceResponse::SceneState(state) => Ok(state),
+        ServiceResponse::Error(e) => Err(anyhow::anyhow!("Service error: {}", e)),
+        response => Err(anyhow::anyhow!("Unexpected response: {:?}", response)),
+    }
+}
+
+struct Row {
+    cells: Vec<String>,
+    changed_at: u64,
+}
+
+/// Scene tables plus the refresh at which each row last changed.
+pub struct Dashboard {
+    refresh: u64,
+    highlight: u64,
+    objects: BTreeMap<String, Row>,
+    materials: BTreeMap<String, Row>,
+    mesh_count: usize,
+}
+
+impl Dashboard {
+    pub fn new(highlight: u64) -> Self {
+        Self {
+            refresh: 0,
+            highlight,
+            objects: BTreeMap::new(),
+            materials: BTreeMap::new(),
+            mesh_count: 0,
+        }
+    }
+
+    pub fn update(&mut self, state: &SceneState) {
+        self.refresh += 1;
+        self.mesh_count = state.meshes.len();
+
+        let objects = state
+            .objects
+            .iter()
+            .map(|object| (object.name.clone(), object_cells(object)));
+        update_rows(&mut self.objects, objects, self.refresh);
+
+        let materials = state
+            .materials
+            .iter()
+            .map(|material| (material.name.clone(), material_cells(material)));
+        update_rows(&mut self.materials, materials, self.refresh);
+    }
+
+    pub fn render(&self, color: bool) -> String {
+        let mut output = format!(
+            "cuttle scene watch (refresh {}, Ctrl-C to exit)\n\
+             Objects: {}  Materials: {}  Meshes: {}\n\n",
+            self.refresh,
+            self.objects.len(),
+            self.materials.len(),
+            self.mesh_count
+        );
+
+        output.push_str("OBJECTS\n");
+        output.push_str(&self.render_table(
+            &["NAME", "TYPE", "LOCATION", "VERTS", "FACES", "MATERIALS"],
+            &self.objects,
+            color,
+        ));
+        output.push_str("\nMATERIALS\n");
+        output.push_str(&self.render_table(
+            &["NAME", "BASE COLOR", "METALLIC", "ROUGHNESS", "NODES"],
+            &self.materials,
+            color,
+        ));
+
+        output
+    }
+
+    fn is_recent(&self, row: &Row) -> bool {
+        self.refresh - row.changed_at < self.highlight
+    }
+
+    fn render_table(&self, headers: &[&str], rows: &BTreeMap<String, Row>, color: bool) -> String {
+        let mut widths = headers.iter().map(|h| h.len()).collect::<Vec<_>>();
+        for row in rows.values() {
+            for (width, cell) in widths.iter_mut().zip(&row.cells) {
+                *width = (*width).max(cell.len());
+            }
+        }
+        let format_cells = |cells: &[String]| {
+            cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        };
+
+        let headers = headers.iter().map(|h| h.to_string()).collect::<Vec<_>>();
+        let mut output = format!("  {}\n", format_cells(&headers));
+        for row in rows.values() {
+            let line = format_cells(&row.cells);
+            // Rows that changed recently get a marker, and a color when drawing to a terminal
+            match (self.is_recent(row), color) {
+                (true, true) => output.push_str(&format!("{HIGHLIGHT}* {line}{RESET}\n")),
+                (true, false) => output.push_str(&format!("* {line}\n")),
+                (false, _) => output.push_str(&format!("  {line}\n")),
+            }
+        }
+        if rows.is_empty() {
+            output.push_str("  (none)\n");
+        }
+
+        output
+    }
+}
+
+fn update_rows(
+    rows: &mut BTreeMap<String, Row>,
+    current: impl Iterator<Item = (String, Vec<String>)>,
+    refresh: u64,
+) {
+    let current = current.collect::<BTreeMap<_, _>>();
+    rows.retain(|name, _| current.contains_key(name));
+
+    for (name, cells) in current {
+        match rows.get_mut(&name) {
+            Some(row) if row.cells == cells => {}
+            Some(row) => {
+                row.cells = cells;
+                row.changed_at = refresh;
+            }
+            None => {
+                rows.insert(
+                    name,
+                    Row {
+                        cells,
+                        changed_at: refresh,
+                    },
+                );
+            }
+        }
+    }
+}
+
+fn object_cells(object: &ObjectData) -> Vec<String> {
+    let count = |count: Option<usize>| count.map_or("-".to_string(), |c| c.to_string());
+    vec![
+        object.name.clone(),
+        object.object_type.to_string(),
+        format!(
+            "({:.2}, {:.2}, {:.2})",
+            object.location.x, object.location.y, object.location.z
+        ),
+        count(object.vertex_count),
+        count(object.face_count),
+        object.materials.join(", "),
+    ]
+}
+
+fn material_cells(material: &MaterialData) -> Vec<String> {
+    let color = &material.base_color;
+    vec![
+        material.name.clone(),
+        format!("({:.2}, {:.2}, {:.2})", color.r, color.g, color.b),
+        format!("{:.2}", material.metallic),
+        format!("{:.2}", material.roughness),
+        material.node_count.to_string(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cuttle_blender_api::{
+        BlenderApi, CreateCubeParams, CreateMaterialParams, MockBlenderApi, Vec3,
+    };
+
+    fn marked_lines(output: &str) -> Vec<&str> {
+        output
+            .lines()
+            .filter(|line| line.starts_with('*'))
+            .collect()
+    }
+
+    #[test]
+    fn highlights_new_and_changed_rows_until_they_settle() {
+        let mut api = MockBlenderApi::new();
+        api.create_cube(CreateCubeParams {
+            location: Vec3::zero(),
+            name: "Cube".to_string(),
+            size: 2.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
+
+        let mut dashboard = Dashboard::new(2);
+        dashboard.update(&api.get_scene_state().expect("Failed to get state"));
+        assert_eq!(marked_lines(&dashboard.render(false)).len(), 1);
+
+        dashboard.update(&api.get_scene_state().expect("Failed to get state"));
+        dashboard.update(&api.get_scene_state().expect("Failed to get state"));
+        assert!(marked_lines(&dashboard.render(false)).is_empty());
+
+        api.create_material(CreateMaterialParams::default())
+            .expect("Failed to create material");
+        dashboard.update(&api.get_scene_state().expect("Failed to get state"));
+        let output = dashboard.render(false);
+        let marked = marked_lines(&output);
+        assert_eq!(marked.len(), 1);
+        assert!(marked[0].contains("Material"));
+        assert!(output.contains("Objects: 1  Materials: 1  Meshes: 1"));
+    }
+}
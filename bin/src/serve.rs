@@ -0,0 +1,437 @@
+use anyhow::{Context, Result};
+use cuttle::{BridgeError, PyBridge, ServiceMessage, ServiceResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader,
+};
+
+pub async fn serve(stdio: bool) -> Result<()> {
+    if !stdio {
+        return Err(anyhow::anyhow!(
+            "No transport selected. Use 'cuttle serve --stdio' to serve over stdin/stdout."
+        ));
+    }
+
+    serve_stdio().await
+}
+
+// Refuse frames larger than this rather than allocating whatever a bad header asks for
+const MAX_FRAME_LEN: usize = 256 * 1024 * 1024;
+
+// JSON-RPC 2.0 error codes, the server defined ones taken from -32000 down
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const INVALID_PARAMS: i64 = -32602;
+const SERVICE_ERROR: i64 = -32000;
+const TIMED_OUT: i64 = -32001;
+const RUNTIME_STOPPED: i64 = -32002;
+
+/// A JSON-RPC 2.0 request. `method` names a `ServiceMessage` variant and `params` holds its
+/// fields, left out for variants without any.
+#[derive(Debug, Deserialize)]
+struct Request {
+    jsonrpc: String,
+    // Absent for notifications, which get no response
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Option<Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<ServiceResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ResponseError>,
+}
+
+#[derive(Debug, Serialize)]
+struct ResponseError {
+    code: i64,
+    message: String,
+}
+
+impl Response {
+    fn result(id: Value, response: ServiceResponse) -> Self {
+        // Failures the services answer with are errors to the caller too
+        match response {
+            ServiceResponse::Error(message) => Self::error(id, SERVICE_ERROR, message),
+            ServiceResponse::TimedOut {
+                operation,
+                timeout_ms,
+                attempts,
+            } => Self::error(
+                id,
+                TIMED_OUT,
+                format!("{operation} timed out after {timeout_ms}ms ({attempts} attempts)"),
+            ),
+            response => Self {
+                jsonrpc: "2.0",
+                id,
+                result: Some(response),
+                error: None,
+            },
+        }
+    }
+
+    fn error(id: Value, code: i64, message: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: None,
+            error: Some(ResponseError {
+                code,
+                message: message.into(),
+            }),
+        }
+    }
+}
+
+/// The message a request asks for, in the enum's own encoding of a variant and its fields.
+fn request_message(request: &Request) -> Result<ServiceMessage, ResponseError> {
+    if request.jsonrpc != "2.0" {
+        return Err(ResponseError {
+            code: INVALID_REQUEST,
+            message: format!("Unsupported jsonrpc version: {}", request.jsonrpc),
+        });
+    }
+    let message = match &request.params {
+        None | Some(Value::Null) => Value::String(request.method.clone()),
+        Some(params) => Value::Object(
+            [(request.method.clone(), params.clone())]
+                .into_iter()
+                .collect(),
+        ),
+    };
+    serde_json::from_value(message).map_err(|e| ResponseError {
+        code: INVALID_PARAMS,
+        message: format!("Invalid {} request: {e}", request.method),
+    })
+}
+
+/// Serve the runtime over stdin/stdout as JSON-RPC 2.0, in `Content-Length` framed messages.
+///
+/// A request's `method` is a `ServiceMessage` variant and `params` its fields, such as
+/// `{"jsonrpc": "2.0", "id": 1, "method": "CreateCube", "params": {"name": "Cube"}}`. The
+/// `result` of a response is the `ServiceResponse`, except that service errors and timeouts
+/// come back as JSON-RPC errors. Batch requests aren't supported, send a `Batch` message.
+///
+/// Stdout is reserved for framed responses, so nothing else may be printed while serving.
+async fn serve_stdio() -> Result<()> {
+    let (mut bridge, async_bridge) = PyBridge::new();
+    bridge.start_runtime(async_bridge);
+
+    let mut reader = BufReader::new(tokio::io::stdin());
+    let mut writer = tokio::io::stdout();
+    serve_frames(Arc::new(bridge), &mut reader, &mut writer).await
+}
+
+/// Answer the requests framed in `reader` until it ends or a `Stop` is requested.
+async fn serve_frames<R, W>(bridge: Arc<PyBridge>, reader: &mut R, writer: &mut W) -> Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    while let Some(body) = read_frame(reader).await? {
+        let request = match serde_json::from_slice::<Request>(&body) {
+            Ok(request) => request,
+            Err(e) => {
+                let code = if serde_json::from_slice::<Value>(&body).is_ok() {
+                    INVALID_REQUEST
+                } else {
+                    PARSE_ERROR
+                };
+                let response = Response::error(Value::Null, code, format!("Invalid request: {e}"));
+                write_response(writer, &response).await?;
+                continue;
+            }
+        };
+        let notification = request.id.is_none();
+        let id = request.id.clone().unwrap_or_default();
+        let message = match request_message(&request) {
+            Ok(message) => message,
+            Err(error) => {
+                if !notification {
+                    let response = Response::error(id, error.code, error.message);
+                    write_response(writer, &response).await?;
+                }
+                continue;
+            }
+        };
+
+        let should_stop = matches!(message, ServiceMessage::Stop);
+        let mut closed = false;
+        let response = match send_and_wait(bridge.clone(), message).await? {
+            Ok(response) => Response::result(id, response),
+            Err(BridgeError::TimedOut { timeout, .. }) => Response::error(
+                id,
+                TIMED_OUT,
+                format!("No response from the runtime within {timeout:?}"),
+            ),
+            Err(e @ BridgeError::BridgeClosed) => {
+                closed = true;
+                Response::error(id, RUNTIME_STOPPED, e.to_string())
+            }
+        };
+        if !notification {
+            write_response(writer, &response).await?;
+        }
+        if closed {
+            return Err(anyhow::anyhow!("Runtime stopped while serving"));
+        }
+        if should_stop {
+            return Ok(());
+        }
+    }
+
+    // Input closed without an explicit stop, shut the runtime down ourselves
+    bridge.stop();
+    Ok(())
+}
+
+/// Send `message` and wait for its response, giving up a little after the services would have.
+async fn send_and_wait(
+    bridge: Arc<PyBridge>,
+    message: ServiceMessage,
+) -> Result<Result<ServiceResponse, BridgeError>> {
+    let timeout = bridge.response_timeout(&message);
+    tokio::task::spawn_blocking(move || {
+        let id = bridge.send_awaited(message)?;
+        bridge.recv_reply_to(id, timeout)
+    })
+    .await
+    .context("Failed to wait for the service response")
+}
+
+async fn write_response<W>(writer: &mut W, response: &Response) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let body = serde_json::to_vec(response).context("Failed to serialize response")?;
+    write_frame(writer, &body).await
+}
+
+/// Read a single `Content-Length` framed message, returning `None` on a clean end of input.
+pub async fn read_frame<R>(reader: &mut R) -> Result<Option<Vec<u8>>>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut content_length = None;
+    let mut saw_header = false;
+
+    loop {
+        let mut line = String::new();
+        let read = reader
+            .read_line(&mut line)
+            .await
+            .context("Failed to read frame header")?;
+
+        if read == 0 {
+            if saw_header {
+                return Err(anyhow::anyhow!("Unexpected end of input in frame header"));
+            }
+            return Ok(None);
+        }
+
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.is_empty() {
+            if saw_header {
+                break;
+            }
+            // Tolerate blank lines between frames
+            continue;
+        }
+        saw_header = true;
+
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                let length = value
+                    .trim()
+                    .parse::<usize>()
+                    .with_context(|| format!("Invalid Content-Length: {}", value.trim()))?;
+                content_length = Some(length);
+            }
+        } else {
+            return Err(anyhow::anyhow!("Malformed frame header: {}", line));
+        }
+    }
+
+    let length = content_length.context("Frame is missing a Content-Length header")?;
+    if length > MAX_FRAME_LEN {
+        return Err(anyhow::anyhow!(
+            "Frame of {length} bytes exceeds the {MAX_FRAME_LEN} byte limit"
+        ));
+    }
+    let mut body = vec![0; length];
+    reader
+        .read_exact(&mut body)
+        .await
+        .context("Failed to read frame body")?;
+
+    Ok(Some(body))
+}
+
+/// Write a single `Content-Length` framed message and flush it.
+pub async fn write_frame<W>(writer: &mut W, body: &[u8]) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    writer
+        .write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+        .await
+        .context("Failed to write frame header")?;
+    writer
+        .write_all(body)
+        .await
+        .context("Failed to write frame body")?;
+    writer.flush().await.context("Failed to flush frame")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn frame_round_trip() {
+        let mut buffer = Vec::new();
+        write_frame(&mut buffer, b"\"Ping\"")
+            .await
+            .expect("Failed to write frame");
+        write_frame(&mut buffer, b"\"Stop\"")
+            .await
+            .expect("Failed to write frame");
+
+        let mut reader = BufReader::new(buffer.as_slice());
+        let first = read_frame(&mut reader).await.expect("Failed to read frame");
+        let second = read_frame(&mut reader).await.expect("Failed to read frame");
+        let end = read_frame(&mut reader).await.expect("Failed to read frame");
+
+        assert_eq!(first.as_deref(), Some(&b"\"Ping\""[..]));
+        assert_eq!(second.as_deref(), Some(&b"\"Stop\""[..]));
+        assert!(end.is_none());
+    }
+
+    #[tokio::test]
+    async fn frame_without_content_length_is_rejected() {
+        let input = b"Content-Type: application/json\r\n\r\n{}";
+        let mut reader = BufReader::new(&input[..]);
+        assert!(read_frame(&mut reader).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn oversized_frame_is_rejected() {
+        let input = format!("Content-Length: {}\r\n\r\n{{}}", MAX_FRAME_LEN + 1);
+        let mut reader = BufReader::new(input.as_bytes());
+        let error = read_frame(&mut reader)
+            .await
+            .expect_err("Expected the frame to be refused");
+        assert!(error.to_string().contains("exceeds"), "{error}");
+    }
+
+    #[tokio::test]
+    async fn frame_body_decodes_service_message() {
+        let mut buffer = Vec::new();
+        let body = serde_json::to_vec(&ServiceMessage::ListObjects).expect("Failed to serialize");
+        write_frame(&mut buffer, &body)
+            .await
+            .expect("Failed to write frame");
+
+        let mut reader = BufReader::new(buffer.as_slice());
+        let frame = read_frame(&mut reader)
+            .await
+            .expect("Failed to read frame")
+            .expect("Expected a frame");
+        let message: ServiceMessage =
+            serde_json::from_slice(&frame).expect("Failed to decode message");
+        assert!(matches!(message, ServiceMessage::ListObjects));
+    }
+
+    async fn serve_requests(bridge: PyBridge, requests: &[&str]) -> (Result<()>, Vec<Value>) {
+        let mut input = Vec::new();
+        for request in requests {
+            write_frame(&mut input, request.as_bytes())
+                .await
+                .expect("Failed to write frame");
+        }
+        let mut output = Vec::new();
+        let result = serve_frames(
+            Arc::new(bridge),
+            &mut BufReader::new(input.as_slice()),
+            &mut output,
+        )
+        .await;
+
+        let mut reader = BufReader::new(output.as_slice());
+        let mut responses = Vec::new();
+        while let Some(frame) = read_frame(&mut reader).await.expect("Failed to read frame") {
+            responses.push(serde_json::from_slice(&frame).expect("Failed to decode response"));
+        }
+        (result, responses)
+    }
+
+    #[tokio::test]
+    async fn serves_json_rpc_requests() {
+        let (mut bridge, async_bridge) = PyBridge::new();
+        bridge.start_runtime(async_bridge);
+
+        let (result, responses) = serve_requests(
+            bridge,
+            &[
+                r#"{"jsonrpc": "2.0", "id": 1, "method": "Ping"}"#,
+                r#"{"jsonrpc": "2.0", "method": "Ping"}"#,
+                r#"{"jsonrpc": "2.0", "id": "missing", "method": "GetObject", "params": {"name": "Missing"}}"#,
+                r#"{"jsonrpc": "2.0", "id": 2, "method": "Teleport", "params": {}}"#,
+                r#"{"jsonrpc": "2.0", "id": 3"#,
+                r#"{"jsonrpc": "2.0", "id": 4, "method": "Stop"}"#,
+                r#"{"jsonrpc": "2.0", "id": 5, "method": "Ping"}"#,
+            ],
+        )
+        .await;
+        result.expect("Failed to serve");
+
+        // The notification gets no response and nothing is read after the stop
+        let summary: Vec<_> = responses
+            .iter()
+            .map(|response| {
+                assert_eq!(response["jsonrpc"], "2.0");
+                (
+                    response["id"].clone(),
+                    response["result"].clone(),
+                    response["error"]["code"].clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1.into(), "Pong".into(), Value::Null),
+                ("missing".into(), Value::Null, SERVICE_ERROR.into()),
+                (2.into(), Value::Null, INVALID_PARAMS.into()),
+                (Value::Null, Value::Null, PARSE_ERROR.into()),
+                (4.into(), "Stopped".into(), Value::Null),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn stopped_runtime_is_an_error_response() {
+        let (bridge, async_bridge) = PyBridge::new();
+        drop(async_bridge);
+
+        let (result, responses) = serve_requests(
+            bridge,
+            &[r#"{"jsonrpc": "2.0", "id": 1, "method": "Ping"}"#],
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0]["error"]["code"], RUNTIME_STOPPED);
+    }
+}
@@ -1,5 +1,7 @@
 pub mod baseline;
+pub mod canonical;
 pub mod diff;
+pub mod expr;
 pub mod run;
 pub mod suite;
 
@@ -13,7 +15,21 @@ pub async fn handle_command(cmd: ValidationCommand) -> Result<()> {
             output,
             compare_baseline,
             timeout,
-        } => run::run_validations(name, output, compare_baseline, timeout).await,
+            export,
+            node_depth,
+            connect,
+        } => {
+            run::run_validations(
+                name,
+                output,
+                compare_baseline,
+                timeout,
+                export,
+                node_depth,
+                connect,
+            )
+            .await
+        }
         ValidationSubcommands::List => {
             suite::list_validations();
             Ok(())
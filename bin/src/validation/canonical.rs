@@ -0,0 +1,90 @@
+use anyhow::{Context, Result};
+use serde_json::{Map, Number, Value};
+
+/// Decimal places kept for floats in captured state.
+pub const FLOAT_PRECISION: usize = 6;
+
+/// Normalize a state value so it serializes identically across machines.
+///
+/// Floats are rounded to [`FLOAT_PRECISION`] decimal places (which also hides f32 to f64
+/// widening noise like `0.20000000298023224`), negative zero becomes zero and object keys
+/// are sorted.
+pub fn canonicalize(value: &Value) -> Value {
+    match value {
+        Value::Number(number) => canonicalize_number(number),
+        Value::Array(items) => Value::Array(items.iter().map(canonicalize).collect()),
+        Value::Object(object) => {
+            let mut keys = object.keys().collect::<Vec<_>>();
+            keys.sort();
+
+            let mut sorted = Map::new();
+            for key in keys {
+                sorted.insert(key.clone(), canonicalize(&object[key]));
+            }
+            Value::Object(sorted)
+        }
+        other => other.clone(),
+    }
+}
+
+fn canonicalize_number(number: &Number) -> Value {
+    if number.is_i64() || number.is_u64() {
+        return Value::Number(number.clone());
+    }
+
+    let Some(float) = number.as_f64() else {
+        return Value::Number(number.clone());
+    };
+
+    let rounded = format!("{float:.FLOAT_PRECISION$}")
+        .parse::<f64>()
+        .unwrap_or(float);
+    let rounded = if rounded == 0.0 { 0.0 } else { rounded };
+
+    Number::from_f64(rounded)
+        .map(Value::Number)
+        .unwrap_or(Value::Null)
+}
+
+/// Serialize a state value in canonical form as pretty printed JSON.
+pub fn to_canonical_string_pretty(value: &Value) -> Result<String> {
+    serde_json::to_string_pretty(&canonicalize(value)).context("Failed to serialize state to JSON")
+}
+
+/// Format a single value in canonical form for human readable output.
+pub fn format_value(value: &Value) -> String {
+    canonicalize(value).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn rounds_widened_floats() {
+        let value = json!({ "r": 0.800000011920929, "g": 0.20000000298023224, "a": 1.0 });
+        assert_eq!(
+            canonicalize(&value),
+            json!({ "a": 1.0, "g": 0.2, "r": 0.8 })
+        );
+    }
+
+    #[test]
+    fn keeps_integers_and_normalizes_negative_zero() {
+        let value = json!([8, -0.0, "MESH", null]);
+        assert_eq!(canonicalize(&value), json!([8, 0.0, "MESH", null]));
+    }
+
+    #[test]
+    fn sorts_keys_in_output() {
+        let value = json!({ "z": 1, "a": { "y": 2.5, "b": 3 } });
+        let output = to_canonical_string_pretty(&value).expect("Failed to serialize");
+        let a = output.find("\"a\"").expect("Missing key a");
+        let z = output.find("\"z\"").expect("Missing key z");
+        let b = output.find("\"b\"").expect("Missing key b");
+        let y = output.find("\"y\"").expect("Missing key y");
+        assert!(a < z);
+        assert!(b < y);
+    }
+}
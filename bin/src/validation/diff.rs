@@ -1,5 +1,8 @@
+use crate::validation::canonical;
 use anyhow::{Context, Result};
+use cuttle_blender_api::ObjectType;
 use serde_json::{Map, Value};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -53,6 +56,58 @@ pub struct DiffResult {
     pub differences: Vec<Difference>,
     pub baseline_only: Vec<String>,
     pub current_only: Vec<String>,
+    pub structural_changes: Vec<StructuralChange>,
+}
+
+/// A change to the scene hierarchy or an object's dependencies, keyed by object name
+/// rather than by array index.
+#[derive(Debug, PartialEq)]
+pub enum StructuralChange {
+    ObjectAdded {
+        name: String,
+    },
+    ObjectRemoved {
+        name: String,
+    },
+    Reparented {
+        name: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    TypeChanged {
+        name: String,
+        from: ObjectType,
+        to: ObjectType,
+    },
+    MaterialsChanged {
+        name: String,
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+}
+
+impl std::fmt::Display for StructuralChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parent = |parent: &Option<String>| parent.clone().unwrap_or_else(|| "<none>".into());
+        match self {
+            Self::ObjectAdded { name } => write!(f, "{name}: added"),
+            Self::ObjectRemoved { name } => write!(f, "{name}: removed"),
+            Self::Reparented { name, from, to } => {
+                write!(f, "{name}: parent {} -> {}", parent(from), parent(to))
+            }
+            Self::TypeChanged { name, from, to } => write!(f, "{name}: type {from} -> {to}"),
+            Self::MaterialsChanged {
+                name,
+                added,
+                removed,
+            } => write!(
+                f,
+                "{name}: materials +[{}] -[{}]",
+                added.join(", "),
+                removed.join(", ")
+            ),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -75,8 +130,11 @@ fn load_state_file(path: &PathBuf) -> Result<Value> {
     let content = fs::read_to_string(path)
         .with_context(|| format!("Failed to read state file: {}", path.display()))?;
 
-    serde_json::from_str(&content)
-        .with_context(|| format!("Failed to parse JSON from: {}", path.display()))
+    let state: Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse JSON from: {}", path.display()))?;
+
+    // Compare canonical forms so float formatting noise isn't reported as a change
+    Ok(canonical::canonicalize(&state))
 }
 
 fn compare_json_states(baseline: &Value, current: &Value) -> Result<DiffResult> {
@@ -84,6 +142,7 @@ fn compare_json_states(baseline: &Value, current: &Value) -> Result<DiffResult>
         differences: Vec::new(),
         baseline_only: Vec::new(),
         current_only: Vec::new(),
+        structural_changes: compare_structure(baseline, current),
     };
 
     compare_values("", baseline, current, &mut result);
@@ -91,6 +150,107 @@ fn compare_json_states(baseline: &Value, current: &Value) -> Result<DiffResult>
     Ok(result)
 }
 
+struct ObjectStructure {
+    object_type: Option<ObjectType>,
+    parent: Option<String>,
+    materials: Vec<String>,
+}
+
+// Objects keyed by name, with the parts of them that make up the scene structure
+fn object_structure(state: &Value) -> BTreeMap<String, ObjectStructure> {
+    let Some(objects) = state.get("objects").and_then(Value::as_array) else {
+        return BTreeMap::new();
+    };
+
+    objects
+        .iter()
+        .filter_map(|object| {
+            let name = object.get("name")?.as_str()?.to_string();
+            let object_type = object
+                .get("object_type")
+                .and_then(Value::as_str)
+                .map(|object_type| ObjectType::from(object_type.to_string()));
+            let parent = object
+                .get("parent")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            let materials = object
+                .get("materials")
+                .and_then(Value::as_array)
+                .map(|materials| {
+                    materials
+                        .iter()
+                        .filter_map(|m| m.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let structure = ObjectStructure {
+                object_type,
+                parent,
+                materials,
+            };
+            Some((name, structure))
+        })
+        .collect()
+}
+
+fn compare_structure(baseline: &Value, current: &Value) -> Vec<StructuralChange> {
+    let baseline = object_structure(baseline);
+    let current = object_structure(current);
+    let mut changes = Vec::new();
+
+    for name in baseline.keys().filter(|name| !current.contains_key(*name)) {
+        changes.push(StructuralChange::ObjectRemoved { name: name.clone() });
+    }
+
+    for (name, object) in &current {
+        let Some(baseline_object) = baseline.get(name) else {
+            changes.push(StructuralChange::ObjectAdded { name: name.clone() });
+            continue;
+        };
+
+        if let (Some(from), Some(to)) = (&baseline_object.object_type, &object.object_type) {
+            if from != to {
+                changes.push(StructuralChange::TypeChanged {
+                    name: name.clone(),
+                    from: from.clone(),
+                    to: to.clone(),
+                });
+            }
+        }
+
+        if object.parent != baseline_object.parent {
+            changes.push(StructuralChange::Reparented {
+                name: name.clone(),
+                from: baseline_object.parent.clone(),
+                to: object.parent.clone(),
+            });
+        }
+
+        let added: Vec<String> = object
+            .materials
+            .iter()
+            .filter(|m| !baseline_object.materials.contains(m))
+            .cloned()
+            .collect();
+        let removed: Vec<String> = baseline_object
+            .materials
+            .iter()
+            .filter(|m| !object.materials.contains(m))
+            .cloned()
+            .collect();
+        if !added.is_empty() || !removed.is_empty() {
+            changes.push(StructuralChange::MaterialsChanged {
+                name: name.clone(),
+                added,
+                removed,
+            });
+        }
+    }
+
+    changes
+}
+
 fn compare_values(path: &str, baseline: &Value, current: &Value, result: &mut DiffResult) {
     match (baseline, current) {
         (Value::Object(baseline_obj), Value::Object(current_obj)) => {
@@ -190,18 +350,32 @@ fn format_diff_as_text(diff: &DiffResult) -> Result<String> {
 
     output.push_str("=== BLENDER STATE DIFF ===\n\n");
 
-    if diff.differences.is_empty() && diff.baseline_only.is_empty() && diff.current_only.is_empty()
+    if diff.differences.is_empty()
+        && diff.baseline_only.is_empty()
+        && diff.current_only.is_empty()
+        && diff.structural_changes.is_empty()
     {
         output.push_str("No differences found.\n");
         return Ok(output);
     }
 
+    if !diff.structural_changes.is_empty() {
+        output.push_str("--- STRUCTURAL CHANGES ---\n");
+        for change in &diff.structural_changes {
+            output.push_str(&format!("  {change}\n"));
+        }
+        output.push('\n');
+    }
+
     if !diff.differences.is_empty() {
         output.push_str("--- VALUE CHANGES ---\n");
         for diff in &diff.differences {
             output.push_str(&format!(
                 "Path: {}\n  Baseline: {}\n  Current:  {}\n  Type: {:?}\n\n",
-                diff.path, diff.baseline_value, diff.current_value, diff.diff_type
+                diff.path,
+                canonical::format_value(&diff.baseline_value),
+                canonical::format_value(&diff.current_value),
+                diff.diff_type
             ));
         }
     }
@@ -275,6 +449,16 @@ fn format_diff_as_json(diff: &DiffResult) -> Result<String> {
         ),
     );
 
+    json_diff.insert(
+        "structural_changes".to_string(),
+        Value::Array(
+            diff.structural_changes
+                .iter()
+                .map(|c| Value::String(c.to_string()))
+                .collect(),
+        ),
+    );
+
     serde_json::to_string_pretty(&Value::Object(json_diff))
         .context("Failed to serialize diff as JSON")
 }
@@ -284,3 +468,49 @@ fn format_diff_as_yaml(diff: &DiffResult) -> Result<String> {
     // In a real implementation, you'd use a proper YAML library
     format_diff_as_json(diff)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn structural_changes_are_keyed_by_name() {
+        let baseline = json!({"objects": [
+            {"name": "Arm", "object_type": "MESH", "parent": null, "materials": ["Red"]},
+            {"name": "Old", "materials": []},
+        ]});
+        // Reordering alone must not be reported as a structural change
+        let current = json!({"objects": [
+            {"name": "New", "materials": []},
+            {"name": "Arm", "object_type": "CURVE", "parent": "Root", "materials": ["Blue"]},
+        ]});
+
+        assert_eq!(
+            compare_structure(&baseline, &current),
+            vec![
+                StructuralChange::ObjectRemoved {
+                    name: "Old".to_string()
+                },
+                StructuralChange::TypeChanged {
+                    name: "Arm".to_string(),
+                    from: ObjectType::Mesh,
+                    to: ObjectType::Curve,
+                },
+                StructuralChange::Reparented {
+                    name: "Arm".to_string(),
+                    from: None,
+                    to: Some("Root".to_string()),
+                },
+                StructuralChange::MaterialsChanged {
+                    name: "Arm".to_string(),
+                    added: vec!["Blue".to_string()],
+                    removed: vec!["Red".to_string()],
+                },
+                StructuralChange::ObjectAdded {
+                    name: "New".to_string()
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,449 @@
+//! A small assertion language evaluated against captured state JSON.
+//!
+//! ```text
+//! objects['TestCube'].vertex_count >= 8 && materials | length == 2
+//! ```
+//!
+//! Paths start at the state root. Indexing an array with a string selects the element whose
+//! `name` field matches, so `objects['TestCube']` finds the object named `TestCube`. Missing
+//! paths evaluate to `null`. Filters are applied with `|`, currently only `length`.
+
+use anyhow::{Result, bail};
+use serde_json::Value;
+
+/// Evaluate an assertion against a state value, returning whether it holds.
+pub fn evaluate(source: &str, state: &Value) -> Result<bool> {
+    let tokens = tokenize(source)?;
+    let mut parser = ExprParser { tokens, pos: 0 };
+    let expr = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+        bail!("Unexpected token {:?} in '{}'", token, source);
+    }
+
+    match eval(&expr, state)? {
+        Value::Bool(result) => Ok(result),
+        other => bail!(
+            "Assertion '{}' evaluated to {} instead of a boolean",
+            source,
+            other
+        ),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Number(f64),
+    Dot,
+    LBracket,
+    RBracket,
+    LParen,
+    RParen,
+    Pipe,
+    Not,
+    And,
+    Or,
+    Op(CompareOp),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Value),
+    Root(String),
+    Field(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Filter(Box<Expr>, String),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, CompareOp, Box<Expr>),
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let chars = source.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '.' => {
+                tokens.push(Token::Dot);
+                i += 1;
+            }
+            '[' => {
+                tokens.push(Token::LBracket);
+                i += 1;
+            }
+            ']' => {
+                tokens.push(Token::RBracket);
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '&' if next == Some('&') => {
+                tokens.push(Token::And);
+                i += 2;
+            }
+            '|' if next == Some('|') => {
+                tokens.push(Token::Or);
+                i += 2;
+            }
+            '|' => {
+                tokens.push(Token::Pipe);
+                i += 1;
+            }
+            '=' if next == Some('=') => {
+                tokens.push(Token::Op(CompareOp::Eq));
+                i += 2;
+            }
+            '!' if next == Some('=') => {
+                tokens.push(Token::Op(CompareOp::Ne));
+                i += 2;
+            }
+            '!' => {
+                tokens.push(Token::Not);
+                i += 1;
+            }
+            '<' | '>' => {
+                let op = match (c, next == Some('=')) {
+                    ('<', true) => CompareOp::Le,
+                    ('<', false) => CompareOp::Lt,
+                    (_, true) => CompareOp::Ge,
+                    (_, false) => CompareOp::Gt,
+                };
+                tokens.push(Token::Op(op));
+                i += if next == Some('=') { 2 } else { 1 };
+            }
+            '\'' | '"' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&ch| ch == c)
+                    .map(|offset| i + 1 + offset);
+                let Some(end) = end else {
+                    bail!("Unterminated string starting at column {}", i + 1);
+                };
+                tokens.push(Token::Str(chars[i + 1..end].iter().collect()));
+                i = end + 1;
+            }
+            c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text = chars[start..i].iter().collect::<String>();
+                let number = text
+                    .parse::<f64>()
+                    .map_err(|_| anyhow::anyhow!("'{}' is not a valid number", text))?;
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Ident(chars[start..i].iter().collect()));
+            }
+            other => bail!("Unexpected character '{}' at column {}", other, i + 1),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct ExprParser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl ExprParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => bail!("Expected {:?}, found {:?}", expected, token),
+            None => bail!("Expected {:?}, found end of expression", expected),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            let rhs = self.parse_and()?;
+            lhs = Expr::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_compare()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            let rhs = self.parse_compare()?;
+            lhs = Expr::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_compare(&mut self) -> Result<Expr> {
+        let lhs = self.parse_filter()?;
+        if let Some(Token::Op(op)) = self.peek().cloned() {
+            self.next();
+            let rhs = self.parse_filter()?;
+            return Ok(Expr::Compare(Box::new(lhs), op, Box::new(rhs)));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_filter(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_unary()?;
+        while self.peek() == Some(&Token::Pipe) {
+            self.next();
+            match self.next() {
+                Some(Token::Ident(name)) => expr = Expr::Filter(Box::new(expr), name),
+                other => bail!("Expected filter name after '|', found {:?}", other),
+            }
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr> {
+        if self.peek() == Some(&Token::Not) {
+            self.next();
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_postfix()
+    }
+
+    fn parse_postfix(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_primary()?;
+        loop {
+            match self.peek() {
+                Some(Token::Dot) => {
+                    self.next();
+                    match self.next() {
+                        Some(Token::Ident(name)) => expr = Expr::Field(Box::new(expr), name),
+                        other => bail!("Expected field name after '.', found {:?}", other),
+                    }
+                }
+                Some(Token::LBracket) => {
+                    self.next();
+                    let index = self.parse_or()?;
+                    self.expect(Token::RBracket)?;
+                    expr = Expr::Index(Box::new(expr), Box::new(index));
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Literal(number_value(n))),
+            Some(Token::Str(s)) => Ok(Expr::Literal(Value::String(s))),
+            Some(Token::Ident(name)) => Ok(match name.as_str() {
+                "true" => Expr::Literal(Value::Bool(true)),
+                "false" => Expr::Literal(Value::Bool(false)),
+                "null" => Expr::Literal(Value::Null),
+                _ => Expr::Root(name),
+            }),
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            Some(token) => bail!("Unexpected token {:?}", token),
+            None => bail!("Unexpected end of expression"),
+        }
+    }
+}
+
+fn number_value(n: f64) -> Value {
+    serde_json::Number::from_f64(n)
+        .map(Value::Number)
+        .unwrap_or(Value::Null)
+}
+
+fn eval(expr: &Expr, state: &Value) -> Result<Value> {
+    Ok(match expr {
+        Expr::Literal(value) => value.clone(),
+        Expr::Root(name) => state.get(name).cloned().unwrap_or(Value::Null),
+        Expr::Field(target, name) => eval(target, state)?
+            .get(name)
+            .cloned()
+            .unwrap_or(Value::Null),
+        Expr::Index(target, index) => {
+            let target = eval(target, state)?;
+            match (&target, eval(index, state)?) {
+                (Value::Array(items), Value::String(name)) => items
+                    .iter()
+                    .find(|item| item.get("name").and_then(Value::as_str) == Some(name.as_str()))
+                    .cloned()
+                    .unwrap_or(Value::Null),
+                (Value::Array(items), Value::Number(n)) => {
+                    let Some(i) = n.as_f64().filter(|i| *i >= 0.0 && i.fract() == 0.0) else {
+                        bail!("Index {} is not a non-negative integer", n);
+                    };
+                    items.get(i as usize).cloned().unwrap_or(Value::Null)
+                }
+                (Value::Object(map), Value::String(key)) => {
+                    map.get(&key).cloned().unwrap_or(Value::Null)
+                }
+                _ => Value::Null,
+            }
+        }
+        Expr::Filter(target, name) => {
+            let target = eval(target, state)?;
+            match name.as_str() {
+                "length" => {
+                    let length = match &target {
+                        Value::Array(items) => items.len(),
+                        Value::Object(map) => map.len(),
+                        Value::String(s) => s.chars().count(),
+                        Value::Null => 0,
+                        other => bail!("Cannot take the length of {}", other),
+                    };
+                    Value::from(length)
+                }
+                other => bail!("Unknown filter '{}'", other),
+            }
+        }
+        Expr::Not(inner) => Value::Bool(!as_bool(&eval(inner, state)?)?),
+        Expr::And(lhs, rhs) => {
+            Value::Bool(as_bool(&eval(lhs, state)?)? && as_bool(&eval(rhs, state)?)?)
+        }
+        Expr::Or(lhs, rhs) => {
+            Value::Bool(as_bool(&eval(lhs, state)?)? || as_bool(&eval(rhs, state)?)?)
+        }
+        Expr::Compare(lhs, op, rhs) => {
+            Value::Bool(compare(&eval(lhs, state)?, *op, &eval(rhs, state)?)?)
+        }
+    })
+}
+
+fn as_bool(value: &Value) -> Result<bool> {
+    match value {
+        Value::Bool(b) => Ok(*b),
+        other => bail!("Expected a boolean, found {}", other),
+    }
+}
+
+fn compare(lhs: &Value, op: CompareOp, rhs: &Value) -> Result<bool> {
+    if let (Some(a), Some(b)) = (lhs.as_f64(), rhs.as_f64()) {
+        return Ok(match op {
+            CompareOp::Eq => a == b,
+            CompareOp::Ne => a != b,
+            CompareOp::Lt => a < b,
+            CompareOp::Le => a <= b,
+            CompareOp::Gt => a > b,
+            CompareOp::Ge => a >= b,
+        });
+    }
+
+    match op {
+        CompareOp::Eq => Ok(lhs == rhs),
+        CompareOp::Ne => Ok(lhs != rhs),
+        _ => match (lhs, rhs) {
+            (Value::String(a), Value::String(b)) => Ok(match op {
+                CompareOp::Lt => a < b,
+                CompareOp::Le => a <= b,
+                CompareOp::Gt => a > b,
+                _ => a >= b,
+            }),
+            _ => bail!("Cannot order {} and {}", lhs, rhs),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn state() -> Value {
+        json!({
+            "objects": [
+                { "name": "TestCube", "vertex_count": 8, "location": { "x": 1.5 } },
+                { "name": "Sphere", "vertex_count": 26, "location": { "x": -2.0 } },
+            ],
+            "materials": [{ "name": "Red" }, { "name": "Blue" }],
+            "object_count": 2,
+        })
+    }
+
+    #[test]
+    fn evaluates_paths_filters_and_logic() {
+        let state = state();
+        assert!(
+            evaluate(
+                "objects['TestCube'].vertex_count >= 8 && materials | length == 2",
+                &state
+            )
+            .expect("Failed to evaluate")
+        );
+        assert!(evaluate("objects[1].location.x < -1.5", &state).expect("Failed to evaluate"));
+        assert!(
+            !evaluate("object_count != 2 || !(materials[0].name == 'Red')", &state)
+                .expect("Failed to evaluate")
+        );
+    }
+
+    #[test]
+    fn missing_paths_are_null() {
+        let state = state();
+        assert!(evaluate("objects['Missing'] == null", &state).expect("Failed to evaluate"));
+        assert!(evaluate("lights | length == 0", &state).expect("Failed to evaluate"));
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        let state = state();
+        assert!(evaluate("object_count", &state).is_err());
+        assert!(evaluate("object_count >= ", &state).is_err());
+        assert!(evaluate("materials | size == 2", &state).is_err());
+        assert!(evaluate("objects['TestCube'", &state).is_err());
+    }
+
+    #[test]
+    fn rejects_negative_and_fractional_indices() {
+        let state = state();
+        assert!(evaluate("objects[2] == null", &state).expect("Failed to evaluate"));
+        let error = evaluate("objects[-1] == null", &state).expect_err("Expected a bad index");
+        assert_eq!(
+            error.to_string(),
+            "Index -1.0 is not a non-negative integer"
+        );
+        assert!(evaluate("objects[0.5] == null", &state).is_err());
+    }
+}
@@ -1,23 +1,41 @@
+use crate::runtime::{request, start_bridge};
 use crate::validation::suite::{
     ValidationCase, ValidationStep, get_validation_by_name, get_validation_suite,
 };
+use crate::validation::{canonical, expr};
 use anyhow::{Context, Result};
-use cuttle::{PyBridge, ServiceMessage, ServiceResponse};
+use cuttle::{PyBridge, RuntimeConfig, ServiceMessage, ServiceResponse};
 use cuttle_blender_api::{
-    AssignMaterialParams, CreateCubeParams, CreateMaterialParams, CreateSphereParams,
-    GetObjectParams,
+    AddRigidBodyParams, AssignMaterialParams, BakeTextureParams, CreateCubeParams,
+    CreateMaterialFromPresetParams, CreateMaterialParams, CreateSphereParams, ExportFormat,
+    ExportSceneParams, GetObjectParams, ImportFileParams, NameCollisionPolicy, OpenBlendParams,
+    SaveBlendParams, SceneState,
 };
 use serde_json::Value;
 use std::fs;
 use std::path::{Path, PathBuf};
-use tokio::time::{Duration, timeout};
+use tokio::time::Duration;
 
 pub async fn run_validations(
     name: Option<String>,
     output: PathBuf,
     compare_baseline: bool,
     timeout_seconds: u64,
+    export: Option<String>,
+    node_depth: Option<usize>,
+    connect: Option<String>,
 ) -> Result<()> {
+    let export_format = export
+        .map(|format| {
+            ExportFormat::from_extension(&format).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Unknown export format '{}'. Supported formats: gltf, obj, usd",
+                    format
+                )
+            })
+        })
+        .transpose()?;
+
     println!("Running validations...");
     println!("Output directory: {}", output.display());
 
@@ -42,8 +60,14 @@ pub async fn run_validations(
     println!("Running {} validation(s)", validations.len());
 
     // Start Cuttle service
-    let (mut bridge, async_bridge) = PyBridge::new();
-    bridge.start_runtime(async_bridge);
+    // Validations fail on name collisions instead of silently overwriting earlier objects.
+    // Every request waits as long as the services can take to answer it.
+    let config = RuntimeConfig {
+        default_timeout: Duration::from_secs(timeout_seconds),
+        name_collision: Some(NameCollisionPolicy::Error),
+        ..Default::default()
+    };
+    let bridge = start_bridge(connect.as_deref(), config)?;
 
     // Give the runtime a moment to start up
     tokio::time::sleep(Duration::from_millis(100)).await;
@@ -56,7 +80,7 @@ pub async fn run_validations(
         println!("\n--- Running validation: {} ---", validation.name);
         println!("Description: {}", validation.description);
 
-        let result = run_validation(&mut bridge, &validation, &output, timeout_seconds).await?;
+        let result = run_validation(&bridge, &validation, &output, export_format, node_depth)?;
 
         if result.success {
             println!("PASS: {} completed successfully", result.name);
@@ -106,11 +130,12 @@ pub struct ValidationResult {
     pub duration: Duration,
 }
 
-async fn run_validation(
-    bridge: &mut PyBridge,
+fn run_validation(
+    bridge: &PyBridge,
     validation: &ValidationCase,
     output_dir: &Path,
-    timeout_seconds: u64,
+    export_format: Option<ExportFormat>,
+    node_depth: Option<usize>,
 ) -> Result<ValidationResult> {
     let start_time = std::time::Instant::now();
 
@@ -118,14 +143,13 @@ async fn run_validation(
     let mut success = true;
     let mut error_message = None;
 
+    // Steps are sent one at a time, so nothing after a failed step touches the scene
     for (i, step) in validation.steps.iter().enumerate() {
-        match execute_validation_step(bridge, step.clone(), timeout_seconds).await {
-            Ok(_) => {
-                println!("  Step {}/{}: PASS", i + 1, validation.steps.len());
-            }
+        match execute_step(bridge, step) {
+            Ok(()) => println!("  Step {}/{}: PASS", i + 1, validation.steps.len()),
             Err(e) => {
                 success = false;
-                error_message = Some(e.to_string());
+                error_message = Some(format!("Step {} failed: {e}", i + 1));
                 println!("  Step {}/{}: FAIL - {}", i + 1, validation.steps.len(), e);
                 break;
             }
@@ -133,16 +157,14 @@ async fn run_validation(
     }
 
     // Capture final state if successful
-    let state_file = if success {
+    let captured = if success {
         match capture_scene_state(
             bridge,
             output_dir,
             &format!("{}_state.json", validation.name),
-            timeout_seconds,
-        )
-        .await
-        {
-            Ok(file) => Some(file),
+            node_depth,
+        ) {
+            Ok(captured) => Some(captured),
             Err(e) => {
                 println!("Warning: Failed to capture scene state: {e}");
                 None
@@ -152,14 +174,47 @@ async fn run_validation(
         None
     };
 
+    // Evaluate scripted assertions against the captured state
+    if success && !validation.assertions.is_empty() {
+        match &captured {
+            Some((_, state)) => {
+                if let Err(e) = check_assertions(validation, state) {
+                    success = false;
+                    error_message = Some(format!("Assertion failed: {e}"));
+                }
+            }
+            None => {
+                success = false;
+                error_message = Some("Assertions require a captured scene state".to_string());
+            }
+        }
+    }
+    let state_file = captured.map(|(file, _)| file);
+
+    // Export the scene as an interchange artifact if requested
+    if let (true, Some(format)) = (success, export_format) {
+        let path = output_dir.join(format!("{}.{}", validation.name, format.extension()));
+        if let Err(e) = export_scene(bridge, &path, format) {
+            println!("Warning: Failed to export scene: {e}");
+        }
+    }
+
     // Validate expectations if successful
     if success {
-        if let Err(e) = validate_expectations(bridge, validation, timeout_seconds).await {
+        if let Err(e) = validate_expectations(bridge, validation) {
             success = false;
             error_message = Some(format!("Expectation validation failed: {e}"));
         }
     }
 
+    // Enforce resource budgets if everything else passed
+    if success {
+        if let Err(e) = enforce_budget(bridge, validation, start_time) {
+            success = false;
+            error_message = Some(format!("Budget exceeded: {e}"));
+        }
+    }
+
     let duration = start_time.elapsed();
 
     Ok(ValidationResult {
@@ -171,12 +226,13 @@ async fn run_validation(
     })
 }
 
-async fn execute_validation_step(
-    bridge: &mut PyBridge,
-    step: ValidationStep,
-    timeout_seconds: u64,
-) -> Result<()> {
-    let message = match step {
+fn execute_step(bridge: &PyBridge, step: &ValidationStep) -> Result<()> {
+    let response = request(bridge, step_message(step.clone()))?;
+    check_step_response(step, response)
+}
+
+fn step_message(step: ValidationStep) -> ServiceMessage {
+    match step {
         ValidationStep::ClearScene => ServiceMessage::ClearScene,
         ValidationStep::CreateCube {
             name,
@@ -186,6 +242,7 @@ async fn execute_validation_step(
             name,
             location,
             size,
+            on_collision: None,
         }),
         ValidationStep::CreateSphere {
             name,
@@ -197,6 +254,7 @@ async fn execute_validation_step(
             location,
             radius,
             subdivisions,
+            on_collision: None,
         }),
         ValidationStep::CreateMaterial {
             name,
@@ -208,7 +266,15 @@ async fn execute_validation_step(
             base_color: color,
             metallic,
             roughness,
+            ..Default::default()
         }),
+        ValidationStep::CreateMaterialFromPreset { name, preset } => {
+            ServiceMessage::CreateMaterialFromPreset(CreateMaterialFromPresetParams {
+                name,
+                preset,
+                on_collision: None,
+            })
+        }
         ValidationStep::AssignMaterial {
             object_name,
             material_name,
@@ -216,56 +282,101 @@ async fn execute_validation_step(
             object_name,
             material_name,
         }),
-    };
-
-    // Send message
-    bridge
-        .send(message)
-        .context("Failed to send message to service")?;
-
-    // Wait for response with timeout
-    let response = timeout(Duration::from_secs(timeout_seconds), async {
-        loop {
-            if let Some(response) = bridge.try_recv() {
-                return response;
-            }
-            tokio::time::sleep(Duration::from_millis(10)).await;
+        ValidationStep::ImportFile { path, format } => {
+            ServiceMessage::ImportFile(ImportFileParams { path, format })
         }
-    })
-    .await
-    .context("Validation step timed out")?;
+        ValidationStep::SaveBlend { path } => ServiceMessage::SaveBlend(SaveBlendParams { path }),
+        ValidationStep::OpenBlend { path } => ServiceMessage::OpenBlend(OpenBlendParams { path }),
+        ValidationStep::AddRigidBody {
+            object,
+            body_type,
+            mass,
+            friction,
+        } => ServiceMessage::AddRigidBody(AddRigidBodyParams {
+            object,
+            body_type,
+            mass,
+            friction,
+        }),
+        ValidationStep::SetGravity { gravity } => ServiceMessage::SetGravity(gravity),
+        ValidationStep::BakeTexture {
+            object,
+            bake_type,
+            resolution,
+            path,
+        } => ServiceMessage::BakeTexture(BakeTextureParams {
+            object,
+            bake_type,
+            resolution,
+            output_path: path,
+        }),
+    }
+}
 
-    // Check response
+fn check_step_response(step: &ValidationStep, response: ServiceResponse) -> Result<()> {
     match response {
-        ServiceResponse::Created | ServiceResponse::SceneCleared => Ok(()),
+        ServiceResponse::Created
+        | ServiceResponse::SceneCleared
+        | ServiceResponse::BlendSaved
+        | ServiceResponse::BlendOpened => Ok(()),
+        ServiceResponse::CreatedAs(name) => {
+            println!("    Created as: {name}");
+            Ok(())
+        }
+        ServiceResponse::Imported(objects) => {
+            println!("    Imported: {}", objects.join(", "));
+            Ok(())
+        }
+        ServiceResponse::TextureBaked(baked) => {
+            let (width, height) = png_dimensions(Path::new(&baked.path))?;
+            println!("    Baked: {} ({width}x{height})", baked.path);
+            match step {
+                ValidationStep::BakeTexture { resolution, .. }
+                    if (width, height) != (*resolution, *resolution) =>
+                {
+                    Err(anyhow::anyhow!(
+                        "Baked image is {width}x{height}, expected {resolution}x{resolution}"
+                    ))
+                }
+                _ => Ok(()),
+            }
+        }
+        ServiceResponse::TimedOut { timeout_ms, .. } => {
+            Err(anyhow::anyhow!("Step timed out after {timeout_ms}ms"))
+        }
         ServiceResponse::Error(e) => Err(anyhow::anyhow!("Service error: {}", e)),
         _ => Err(anyhow::anyhow!("Unexpected response: {:?}", response)),
     }
 }
 
-async fn validate_expectations(
-    bridge: &mut PyBridge,
-    validation: &ValidationCase,
-    timeout_seconds: u64,
-) -> Result<()> {
+// Read from the file itself rather than trusting the backend's report; the IHDR chunk always
+// comes first, with the width and height right after its type
+fn png_dimensions(path: &Path) -> Result<(u32, u32)> {
+    let bytes =
+        fs::read(path).with_context(|| format!("Baked image missing: {}", path.display()))?;
+    if bytes.len() < 24 || &bytes[..8] != b"\x89PNG\r\n\x1a\n" || &bytes[12..16] != b"IHDR" {
+        return Err(anyhow::anyhow!("Not a PNG image: {}", path.display()));
+    }
+    let read_u32 = |offset: usize| {
+        u32::from_be_bytes([
+            bytes[offset],
+            bytes[offset + 1],
+            bytes[offset + 2],
+            bytes[offset + 3],
+        ])
+    };
+    Ok((read_u32(16), read_u32(20)))
+}
+
+fn validate_expectations(bridge: &PyBridge, validation: &ValidationCase) -> Result<()> {
     // Check expected objects exist
     for expected_object in &validation.expected_objects {
-        bridge
-            .send(ServiceMessage::GetObject(GetObjectParams {
+        let response = request(
+            bridge,
+            ServiceMessage::GetObject(GetObjectParams {
                 name: expected_object.to_string(),
-            }))
-            .context("Failed to send get object message")?;
-
-        let response = timeout(Duration::from_secs(timeout_seconds), async {
-            loop {
-                if let Some(response) = bridge.try_recv() {
-                    return response;
-                }
-                tokio::time::sleep(Duration::from_millis(10)).await;
-            }
-        })
-        .await
-        .context("Get object timed out")?;
+            }),
+        )?;
 
         match response {
             ServiceResponse::ObjectData(_) => {
@@ -288,24 +399,12 @@ async fn validate_expectations(
 
     // Check expected materials exist
     for expected_material in &validation.expected_materials {
-        bridge
-            .send(ServiceMessage::GetMaterial(
-                cuttle_blender_api::GetMaterialParams {
-                    name: expected_material.to_string(),
-                },
-            ))
-            .context("Failed to send get material message")?;
-
-        let response = timeout(Duration::from_secs(timeout_seconds), async {
-            loop {
-                if let Some(response) = bridge.try_recv() {
-                    return response;
-                }
-                tokio::time::sleep(Duration::from_millis(10)).await;
-            }
-        })
-        .await
-        .context("Get material timed out")?;
+        let response = request(
+            bridge,
+            ServiceMessage::GetMaterial(cuttle_blender_api::GetMaterialParams {
+                name: expected_material.to_string(),
+            }),
+        )?;
 
         match response {
             ServiceResponse::MaterialData(_) => {
@@ -329,161 +428,313 @@ async fn validate_expectations(
     Ok(())
 }
 
-async fn capture_scene_state(
-    bridge: &mut PyBridge,
+fn check_assertions(validation: &ValidationCase, state: &Value) -> Result<()> {
+    for assertion in &validation.assertions {
+        if !expr::evaluate(assertion, state)? {
+            return Err(anyhow::anyhow!("{}", assertion));
+        }
+        println!("    Assertion '{assertion}': PASS");
+    }
+
+    Ok(())
+}
+
+fn enforce_budget(
+    bridge: &PyBridge,
+    validation: &ValidationCase,
+    start_time: std::time::Instant,
+) -> Result<()> {
+    let budget = &validation.budget;
+
+    if let Some(max_wall_time) = budget.max_wall_time {
+        let elapsed = start_time.elapsed();
+        if elapsed > max_wall_time {
+            return Err(anyhow::anyhow!(
+                "wall time {:.2?} exceeds limit of {:.2?}",
+                elapsed,
+                max_wall_time
+            ));
+        }
+    }
+
+    if budget.max_objects.is_none() && budget.max_total_polys.is_none() {
+        return Ok(());
+    }
+
+    let scene = query_scene_state(bridge)?;
+
+    if let Some(max_objects) = budget.max_objects {
+        if scene.objects.len() > max_objects {
+            return Err(anyhow::anyhow!(
+                "{} objects exceeds limit of {}",
+                scene.objects.len(),
+                max_objects
+            ));
+        }
+    }
+
+    if let Some(max_total_polys) = budget.max_total_polys {
+        let total_polys = scene
+            .objects
+            .iter()
+            .filter_map(|object| object.face_count)
+            .sum::<usize>();
+
+        if total_polys > max_total_polys {
+            return Err(anyhow::anyhow!(
+                "{} total polygons exceeds limit of {}",
+                total_polys,
+                max_total_polys
+            ));
+        }
+    }
+
+    println!("    Budget: within limits");
+    Ok(())
+}
+
+fn export_scene(bridge: &PyBridge, path: &Path, format: ExportFormat) -> Result<()> {
+    let response = request(
+        bridge,
+        ServiceMessage::ExportScene(ExportSceneParams {
+            path: path.display().to_string(),
+            format,
+            selected_only: false,
+        }),
+    )?;
+
+    match response {
+        ServiceResponse::Exported(objects) => {
+            println!(
+                "  Exported {} object(s) to: {}",
+                objects.len(),
+                path.display()
+            );
+            Ok(())
+        }
+        ServiceResponse::Error(e) => Err(anyhow::anyhow!("Service error: {}", e)),
+        _ => Err(anyhow::anyhow!("Unexpected response: {:?}", response)),
+    }
+}
+
+fn capture_scene_state(
+    bridge: &PyBridge,
     output_dir: &Path,
     filename: &str,
-    timeout_seconds: u64,
-) -> Result<PathBuf> {
-    // Query objects and materials
-    let objects = query_objects(bridge, timeout_seconds).await?;
-    let materials = query_materials(bridge, timeout_seconds).await?;
-
-    // Get detailed object data
-    let mut object_data = Vec::new();
-    for object_name in &objects {
-        match query_object_details(bridge, object_name, timeout_seconds).await {
-            Ok(data) => object_data.push(data),
-            Err(e) => println!("Warning: Failed to get details for object {object_name}: {e}"),
+    node_depth: Option<usize>,
+) -> Result<(PathBuf, Value)> {
+    // A single snapshot request instead of one round trip per object, material and mesh
+    let capture_start = std::time::Instant::now();
+    let scene = query_scene_state(bridge)?;
+
+    // Attach each material's shader node tree so diffs catch shading changes
+    let mut materials = Vec::new();
+    for material in &scene.materials {
+        let mut data = serde_json::to_value(material).context("Failed to serialize material")?;
+        if let (Some(graph), Value::Object(fields)) =
+            (scene.material_nodes.get(&material.name), &mut data)
+        {
+            let graph = match node_depth {
+                Some(depth) => graph.truncate_depth(depth),
+                None => graph.clone(),
+            };
+            let nodes =
+                serde_json::to_value(graph).context("Failed to serialize material nodes")?;
+            fields.insert("nodes".to_string(), nodes);
         }
+        materials.push(data);
     }
 
-    // Get detailed material data
-    let mut material_data = Vec::new();
-    for material_name in &materials {
-        match query_material_details(bridge, material_name, timeout_seconds).await {
-            Ok(data) => material_data.push(data),
-            Err(e) => println!("Warning: Failed to get details for material {material_name}: {e}"),
+    // Attach world-space bounds so assertions can check spatial extents
+    let mut objects = Vec::new();
+    for object in &scene.objects {
+        let mut data = serde_json::to_value(object).context("Failed to serialize object")?;
+        if let (Some(bounds), Value::Object(fields)) =
+            (scene.bounding_boxes.get(&object.name), &mut data)
+        {
+            let bounds =
+                serde_json::to_value(bounds).context("Failed to serialize bounding box")?;
+            fields.insert("bounding_box".to_string(), bounds);
         }
+        objects.push(data);
     }
 
     // Create state JSON
     let state = serde_json::json!({
-        "objects": object_data,
-        "materials": material_data,
-        "object_count": objects.len(),
-        "material_count": materials.len(),
+        "objects": objects,
+        "materials": materials,
+        "meshes": scene.meshes,
+        "world": scene.world,
+        "scene_settings": scene.scene_settings,
+        "units": scene.units,
+        "object_count": scene.objects.len(),
+        "material_count": scene.materials.len(),
         "timestamp": chrono::Utc::now().to_rfc3339(),
     });
 
     // Write state to file
+    let state = canonical::canonicalize(&state);
     let state_file = output_dir.join(filename);
-    let state_content =
-        serde_json::to_string_pretty(&state).context("Failed to serialize state to JSON")?;
+    let state_content = canonical::to_canonical_string_pretty(&state)?;
 
     fs::write(&state_file, state_content)
         .with_context(|| format!("Failed to write state file: {}", state_file.display()))?;
 
-    println!("  Scene state captured to: {}", state_file.display());
-    Ok(state_file)
+    println!(
+        "  Scene state captured to: {} ({:.2?})",
+        state_file.display(),
+        capture_start.elapsed()
+    );
+    Ok((state_file, state))
 }
 
-async fn query_objects(bridge: &mut PyBridge, timeout_seconds: u64) -> Result<Vec<String>> {
-    bridge
-        .send(ServiceMessage::ListObjects)
-        .context("Failed to send list objects message")?;
-
-    let response = timeout(Duration::from_secs(timeout_seconds), async {
-        loop {
-            if let Some(response) = bridge.try_recv() {
-                return response;
-            }
-            tokio::time::sleep(Duration::from_millis(10)).await;
-        }
-    })
-    .await
-    .context("List objects timed out")?;
+fn query_scene_state(bridge: &PyBridge) -> Result<SceneState> {
+    let response = request(bridge, ServiceMessage::GetSceneState)?;
 
     match response {
-        ServiceResponse::ObjectList(objects) => Ok(objects),
+        ServiceResponse::SceneState(state) => Ok(state),
         ServiceResponse::Error(e) => Err(anyhow::anyhow!("Service error: {}", e)),
         _ => Err(anyhow::anyhow!("Unexpected response: {:?}", response)),
     }
 }
 
-async fn query_materials(bridge: &mut PyBridge, timeout_seconds: u64) -> Result<Vec<String>> {
-    bridge
-        .send(ServiceMessage::ListMaterials)
-        .context("Failed to send list materials message")?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::suite::ValidationBudget;
+    use cuttle_blender_api::Vec3;
+
+    fn case(steps: Vec<ValidationStep>) -> ValidationCase {
+        ValidationCase {
+            name: "test",
+            description: "",
+            steps,
+            expected_objects: vec![],
+            expected_materials: vec![],
+            assertions: vec![],
+            budget: Default::default(),
+        }
+    }
 
-    let response = timeout(Duration::from_secs(timeout_seconds), async {
-        loop {
-            if let Some(response) = bridge.try_recv() {
-                return response;
-            }
-            tokio::time::sleep(Duration::from_millis(10)).await;
+    fn create_cube(name: &str) -> ValidationStep {
+        ValidationStep::CreateCube {
+            name: name.to_string(),
+            location: Vec3::zero(),
+            size: 1.0,
         }
-    })
-    .await
-    .context("List materials timed out")?;
+    }
 
-    match response {
-        ServiceResponse::MaterialList(materials) => Ok(materials),
-        ServiceResponse::Error(e) => Err(anyhow::anyhow!("Service error: {}", e)),
-        _ => Err(anyhow::anyhow!("Unexpected response: {:?}", response)),
+    #[tokio::test]
+    async fn steps_stop_at_the_first_failure() {
+        let config = RuntimeConfig {
+            name_collision: Some(NameCollisionPolicy::Error),
+            ..Default::default()
+        };
+        let bridge = start_bridge(None, config).expect("Failed to start bridge");
+        let output = tempfile::tempdir().expect("Failed to create output dir");
+
+        let validation = case(vec![
+            create_cube("Cube"),
+            create_cube("Cube"),
+            create_cube("After"),
+        ]);
+        let result = run_validation(&bridge, &validation, output.path(), None, None)
+            .expect("Failed to run validation");
+        let objects = request(&bridge, ServiceMessage::ListObjects).expect("Failed to list");
+        bridge.stop();
+
+        assert!(!result.success);
+        let error = result.error.expect("Expected the failed step's error");
+        assert!(error.starts_with("Step 2 failed"), "{error}");
+        // The step after the failure never ran
+        assert!(
+            matches!(&objects, ServiceResponse::ObjectList(names) if names == &["Cube"]),
+            "{objects:?}"
+        );
     }
-}
 
-async fn query_object_details(
-    bridge: &mut PyBridge,
-    object_name: &str,
-    timeout_seconds: u64,
-) -> Result<Value> {
-    bridge
-        .send(ServiceMessage::GetObject(GetObjectParams {
-            name: object_name.to_string(),
-        }))
-        .context("Failed to send get object message")?;
-
-    let response = timeout(Duration::from_secs(timeout_seconds), async {
-        loop {
-            if let Some(response) = bridge.try_recv() {
-                return response;
-            }
-            tokio::time::sleep(Duration::from_millis(10)).await;
-        }
-    })
-    .await
-    .context("Get object timed out")?;
+    #[test]
+    fn timed_out_step_fails() {
+        let response = ServiceResponse::TimedOut {
+            operation: "create_cube".to_string(),
+            timeout_ms: 5000,
+            attempts: 1,
+        };
+        let error = check_step_response(&create_cube("Cube"), response)
+            .expect_err("Expected the step to fail");
+        assert_eq!(error.to_string(), "Step timed out after 5000ms");
+    }
 
-    match response {
-        ServiceResponse::ObjectData(data) => {
-            serde_json::to_value(data).context("Failed to serialize object data")
+    // Two cubes of six faces each
+    fn two_cube_scene() -> PyBridge {
+        let bridge = start_bridge(None, RuntimeConfig::default()).expect("Failed to start bridge");
+        for name in ["A", "B"] {
+            execute_step(&bridge, &create_cube(name)).expect("Failed to create cube");
         }
-        ServiceResponse::Error(e) => Err(anyhow::anyhow!("Service error: {}", e)),
-        _ => Err(anyhow::anyhow!("Unexpected response: {:?}", response)),
+        bridge
     }
-}
 
-async fn query_material_details(
-    bridge: &mut PyBridge,
-    material_name: &str,
-    timeout_seconds: u64,
-) -> Result<Value> {
-    bridge
-        .send(ServiceMessage::GetMaterial(
-            cuttle_blender_api::GetMaterialParams {
-                name: material_name.to_string(),
-            },
-        ))
-        .context("Failed to send get material message")?;
-
-    let response = timeout(Duration::from_secs(timeout_seconds), async {
-        loop {
-            if let Some(response) = bridge.try_recv() {
-                return response;
-            }
-            tokio::time::sleep(Duration::from_millis(10)).await;
-        }
-    })
-    .await
-    .context("Get material timed out")?;
+    fn check_budget(
+        bridge: &PyBridge,
+        budget: ValidationBudget,
+        start_time: std::time::Instant,
+    ) -> Result<()> {
+        let validation = ValidationCase {
+            budget,
+            ..case(vec![])
+        };
+        enforce_budget(bridge, &validation, start_time)
+    }
 
-    match response {
-        ServiceResponse::MaterialData(data) => {
-            serde_json::to_value(data).context("Failed to serialize material data")
-        }
-        ServiceResponse::Error(e) => Err(anyhow::anyhow!("Service error: {}", e)),
-        _ => Err(anyhow::anyhow!("Unexpected response: {:?}", response)),
+    #[tokio::test]
+    async fn object_budget_allows_up_to_its_limit() {
+        let bridge = two_cube_scene();
+        let objects = |max_objects| ValidationBudget {
+            max_objects: Some(max_objects),
+            ..Default::default()
+        };
+        let now = std::time::Instant::now();
+
+        let error = check_budget(&bridge, objects(1), now).expect_err("Expected too many objects");
+        assert_eq!(error.to_string(), "2 objects exceeds limit of 1");
+        check_budget(&bridge, objects(2), now).expect("Expected the limit itself to be allowed");
+        check_budget(&bridge, objects(3), now).expect("Expected fewer objects to be allowed");
+        bridge.stop();
+    }
+
+    #[tokio::test]
+    async fn polygon_budget_allows_up_to_its_limit() {
+        let bridge = two_cube_scene();
+        let polys = |max_total_polys| ValidationBudget {
+            max_total_polys: Some(max_total_polys),
+            ..Default::default()
+        };
+        let now = std::time::Instant::now();
+
+        let error = check_budget(&bridge, polys(11), now).expect_err("Expected too many polygons");
+        assert_eq!(error.to_string(), "12 total polygons exceeds limit of 11");
+        check_budget(&bridge, polys(12), now).expect("Expected the limit itself to be allowed");
+        check_budget(&bridge, polys(13), now).expect("Expected fewer polygons to be allowed");
+        bridge.stop();
+    }
+
+    #[tokio::test]
+    async fn wall_time_budget_fails_once_exceeded() {
+        let bridge = two_cube_scene();
+        let budget = ValidationBudget {
+            max_wall_time: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        let now = std::time::Instant::now();
+        let long_ago = now
+            .checked_sub(Duration::from_secs(120))
+            .expect("Expected an instant two minutes ago");
+
+        let error = check_budget(&bridge, budget.clone(), long_ago)
+            .expect_err("Expected the wall time to be exceeded");
+        assert!(error.to_string().contains("exceeds limit of 60"), "{error}");
+        check_budget(&bridge, budget, now)
+            .expect("Expected a fresh run to be within its wall time");
+        bridge.stop();
     }
 }
@@ -1,4 +1,5 @@
-use cuttle_blender_api::{Color, Vec3};
+use cuttle_blender_api::{BakeType, Color, ImportFormat, RigidBodyType, Vec3};
+use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub struct ValidationCase {
@@ -7,6 +8,17 @@ pub struct ValidationCase {
     pub steps: Vec<ValidationStep>,
     pub expected_objects: Vec<&'static str>,
     pub expected_materials: Vec<&'static str>,
+    /// Expressions evaluated against the captured state, see [`crate::validation::expr`]
+    pub assertions: Vec<&'static str>,
+    pub budget: ValidationBudget,
+}
+
+/// Resource limits enforced after a validation's steps have run.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationBudget {
+    pub max_objects: Option<usize>,
+    pub max_total_polys: Option<usize>,
+    pub max_wall_time: Option<Duration>,
 }
 
 #[derive(Debug, Clone)]
@@ -29,10 +41,40 @@ pub enum ValidationStep {
         metallic: f32,
         roughness: f32,
     },
+    CreateMaterialFromPreset {
+        name: String,
+        preset: String,
+    },
     AssignMaterial {
         object_name: String,
         material_name: String,
     },
+    ImportFile {
+        path: String,
+        format: ImportFormat,
+    },
+    SaveBlend {
+        path: String,
+    },
+    OpenBlend {
+        path: String,
+    },
+    AddRigidBody {
+        object: String,
+        body_type: RigidBodyType,
+        mass: f32,
+        friction: f32,
+    },
+    SetGravity {
+        gravity: Vec3,
+    },
+    /// Passes only if the image exists at `path` and is `resolution` pixels square.
+    BakeTexture {
+        object: String,
+        bake_type: BakeType,
+        resolution: u32,
+        path: String,
+    },
 }
 
 pub fn get_validation_suite() -> Vec<ValidationCase> {
@@ -60,6 +102,12 @@ pub fn get_validation_suite() -> Vec<ValidationCase> {
             ],
             expected_objects: vec!["TestCube"],
             expected_materials: vec!["TestMaterial"],
+            assertions: vec![
+                "objects['TestCube'].vertex_count >= 8 && materials | length == 1",
+                "objects['TestCube'].materials[0] == 'TestMaterial'",
+                "objects['TestCube'].bounding_box.dimensions.x == 2.0",
+            ],
+            budget: ValidationBudget::default(),
         },
         ValidationCase {
             name: "multi_object",
@@ -100,6 +148,12 @@ pub fn get_validation_suite() -> Vec<ValidationCase> {
             ],
             expected_objects: vec!["RedCube", "BlueSphere"],
             expected_materials: vec!["RedMaterial", "BlueMaterial"],
+            assertions: vec!["objects['BlueSphere'].location.x > objects['RedCube'].location.x"],
+            budget: ValidationBudget {
+                max_objects: Some(2),
+                max_total_polys: Some(1000),
+                max_wall_time: Some(Duration::from_secs(10)),
+            },
         },
         ValidationCase {
             name: "material_properties",
@@ -124,10 +178,117 @@ pub fn get_validation_suite() -> Vec<ValidationCase> {
             ],
             expected_objects: vec!["MetallicCube"],
             expected_materials: vec!["MetallicMaterial"],
+            assertions: vec!["materials['MetallicMaterial'].metallic == 1.0"],
+            budget: ValidationBudget::default(),
+        },
+        ValidationCase {
+            name: "material_presets",
+            description: "Validate materials created from the builtin preset library",
+            steps: vec![
+                ValidationStep::ClearScene,
+                ValidationStep::CreateSphere {
+                    name: "GoldSphere".to_string(),
+                    location: Vec3::new(0.0, 0.0, 0.0),
+                    radius: 1.0,
+                    subdivisions: 2,
+                },
+                ValidationStep::CreateMaterialFromPreset {
+                    name: "Gold".to_string(),
+                    preset: "gold".to_string(),
+                },
+                ValidationStep::CreateMaterialFromPreset {
+                    name: "Glass".to_string(),
+                    preset: "glass".to_string(),
+                },
+                ValidationStep::AssignMaterial {
+                    object_name: "GoldSphere".to_string(),
+                    material_name: "Gold".to_string(),
+                },
+            ],
+            expected_objects: vec!["GoldSphere"],
+            expected_materials: vec!["Gold", "Glass"],
+            assertions: vec![
+                "materials['Gold'].metallic == 1.0",
+                "materials['Glass'].roughness == 0.0",
+            ],
+            budget: ValidationBudget::default(),
+        },
+        ValidationCase {
+            name: "rigid_bodies",
+            description: "Validate a falling body over a passive floor under custom gravity",
+            steps: vec![
+                ValidationStep::ClearScene,
+                ValidationStep::CreateCube {
+                    name: "Floor".to_string(),
+                    location: Vec3::new(0.0, 0.0, 0.0),
+                    size: 10.0,
+                },
+                ValidationStep::CreateCube {
+                    name: "Crate".to_string(),
+                    location: Vec3::new(0.0, 0.0, 8.0),
+                    size: 1.0,
+                },
+                ValidationStep::AddRigidBody {
+                    object: "Floor".to_string(),
+                    body_type: RigidBodyType::Passive,
+                    mass: 1.0,
+                    friction: 0.8,
+                },
+                ValidationStep::AddRigidBody {
+                    object: "Crate".to_string(),
+                    body_type: RigidBodyType::Active,
+                    mass: 2.5,
+                    friction: 0.5,
+                },
+                ValidationStep::SetGravity {
+                    gravity: Vec3::new(0.0, 0.0, -1.62),
+                },
+            ],
+            expected_objects: vec!["Floor", "Crate"],
+            expected_materials: vec![],
+            assertions: vec![
+                "objects['Crate'].rigid_body.mass == 2.5",
+                "objects['Floor'].rigid_body.body_type == 'PASSIVE'",
+                "scene_settings.gravity.z < -1.6",
+            ],
+            budget: ValidationBudget::default(),
+        },
+        ValidationCase {
+            name: "texture_bake",
+            description: "Validate baked ambient occlusion and normal maps",
+            steps: vec![
+                ValidationStep::ClearScene,
+                ValidationStep::CreateCube {
+                    name: "BakeCube".to_string(),
+                    location: Vec3::new(0.0, 0.0, 0.0),
+                    size: 2.0,
+                },
+                ValidationStep::BakeTexture {
+                    object: "BakeCube".to_string(),
+                    bake_type: BakeType::AmbientOcclusion,
+                    resolution: 64,
+                    path: bake_path("texture_bake_ao.png"),
+                },
+                ValidationStep::BakeTexture {
+                    object: "BakeCube".to_string(),
+                    bake_type: BakeType::Normal,
+                    resolution: 32,
+                    path: bake_path("texture_bake_normal.png"),
+                },
+            ],
+            expected_objects: vec!["BakeCube"],
+            expected_materials: vec![],
+            assertions: vec![],
+            budget: ValidationBudget::default(),
         },
     ]
 }
 
+// Bakes go to the temp directory so running the suite doesn't litter the working directory
+fn bake_path(file: &str) -> String {
+    std::env::temp_dir().join(file).display().to_string()
+}
+
 pub fn list_validations() {
     let suite = get_validation_suite();
 
@@ -0,0 +1,225 @@
+//! Builders for the operation parameter types.
+//!
+//! Every builder starts from the type's `Default` and only needs the fields that differ:
+//!
+//! ```
+//! use cuttle_blender_api::CreateSphereParams;
+//!
+//! let sphere = CreateSphereParams::builder().name("S").radius(1.0).build();
+//! assert_eq!(sphere.subdivisions, 8);
+//! ```
+
+use crate::{
+    AddRigidBodyParams, ApplyGeometryNodesParams, AssignMaterialParams, BakeTextureParams,
+    BakeType, Color, CreateCubeParams, CreateInstanceParams, CreateMaterialFromPresetParams,
+    CreateMaterialParams, CreateMeshParams, CreateSphereParams, ExportFormat, ExportSceneParams,
+    GetMaterialNodesParams, GetMaterialParams, GetMeshGeometryParams, GetObjectParams,
+    GetPropertyParams, ImportFileParams, ImportFormat, NameCollisionPolicy, OpenBlendParams,
+    PropertyTarget, RemoveMaterialSlotParams, RigidBodyType, Rotation, SaveBlendParams,
+    SelectObjectsParams, SetFaceMaterialsParams, SetLightLinkingParams, SetMaterialNodesParams,
+    SetMaterialSlotParams, SetParentParams, SetPropertyParams, SetShadowVisibilityParams,
+    SetTransformParams, SetWorldParams, Vec3, WorldBackground,
+};
+use cuttle_lang::BlenderNodeGraph;
+
+// Setters take `impl Into`, so `&str` works for names and a bare value for `Option` fields
+macro_rules! builder {
+    ($($params:ident => $builder:ident { $($field:ident: $ty:ty),* $(,)? })*) => {$(
+        #[derive(Debug, Clone, Default)]
+        pub struct $builder {
+            params: $params,
+        }
+
+        impl $params {
+            pub fn builder() -> $builder {
+                $builder::default()
+            }
+        }
+
+        impl $builder {
+            $(
+                pub fn $field(mut self, $field: impl Into<$ty>) -> Self {
+                    self.params.$field = $field.into();
+                    self
+                }
+            )*
+
+            pub fn build(self) -> $params {
+                self.params
+            }
+        }
+    )*};
+}
+
+builder! {
+    CreateCubeParams => CreateCubeParamsBuilder {
+        location: Vec3,
+        name: String,
+        size: f32,
+        on_collision: Option<NameCollisionPolicy>,
+    }
+    CreateSphereParams => CreateSphereParamsBuilder {
+        location: Vec3,
+        name: String,
+        radius: f32,
+        subdivisions: u32,
+        on_collision: Option<NameCollisionPolicy>,
+    }
+    CreateMeshParams => CreateMeshParamsBuilder {
+        location: Vec3,
+        name: String,
+        vertices: Vec<Vec3>,
+        faces: Vec<Vec<usize>>,
+        on_collision: Option<NameCollisionPolicy>,
+    }
+    CreateMaterialParams => CreateMaterialParamsBuilder {
+        name: String,
+        base_color: Color,
+        metallic: f32,
+        roughness: f32,
+        emission_color: Color,
+        emission_strength: f32,
+        alpha: f32,
+        ior: f32,
+        specular: f32,
+        transmission: f32,
+        normal_strength: f32,
+        on_collision: Option<NameCollisionPolicy>,
+    }
+    CreateMaterialFromPresetParams => CreateMaterialFromPresetParamsBuilder {
+        name: String,
+        preset: String,
+        on_collision: Option<NameCollisionPolicy>,
+    }
+    AssignMaterialParams => AssignMaterialParamsBuilder {
+        object_name: String,
+        material_name: String,
+    }
+    GetObjectParams => GetObjectParamsBuilder {
+        name: String,
+    }
+    GetMaterialParams => GetMaterialParamsBuilder {
+        name: String,
+    }
+    GetMaterialNodesParams => GetMaterialNodesParamsBuilder {
+        name: String,
+    }
+    SetMaterialNodesParams => SetMaterialNodesParamsBuilder {
+        name: String,
+        graph: BlenderNodeGraph,
+    }
+    GetMeshGeometryParams => GetMeshGeometryParamsBuilder {
+        name: String,
+    }
+    SetWorldParams => SetWorldParamsBuilder {
+        background: WorldBackground,
+        strength: f32,
+    }
+    ImportFileParams => ImportFileParamsBuilder {
+        path: String,
+        format: ImportFormat,
+    }
+    ExportSceneParams => ExportSceneParamsBuilder {
+        path: String,
+        format: ExportFormat,
+        selected_only: bool,
+    }
+    SaveBlendParams => SaveBlendParamsBuilder {
+        path: String,
+    }
+    OpenBlendParams => OpenBlendParamsBuilder {
+        path: String,
+    }
+    SetParentParams => SetParentParamsBuilder {
+        child: String,
+        parent: Option<String>,
+    }
+    SelectObjectsParams => SelectObjectsParamsBuilder {
+        names: Vec<String>,
+    }
+    ApplyGeometryNodesParams => ApplyGeometryNodesParamsBuilder {
+        object: String,
+        graph: BlenderNodeGraph,
+        force: bool,
+    }
+    CreateInstanceParams => CreateInstanceParamsBuilder {
+        source: String,
+        name: String,
+        location: Vec3,
+        on_collision: Option<NameCollisionPolicy>,
+    }
+    SetMaterialSlotParams => SetMaterialSlotParamsBuilder {
+        object: String,
+        index: usize,
+        material: String,
+    }
+    RemoveMaterialSlotParams => RemoveMaterialSlotParamsBuilder {
+        object: String,
+        index: usize,
+    }
+    SetFaceMaterialsParams => SetFaceMaterialsParamsBuilder {
+        object: String,
+        slot: usize,
+        faces: Vec<usize>,
+    }
+    SetTransformParams => SetTransformParamsBuilder {
+        object: String,
+        location: Option<Vec3>,
+        rotation: Option<Rotation>,
+        scale: Option<Vec3>,
+    }
+    BakeTextureParams => BakeTextureParamsBuilder {
+        object: String,
+        bake_type: BakeType,
+        resolution: u32,
+        output_path: String,
+    }
+    AddRigidBodyParams => AddRigidBodyParamsBuilder {
+        object: String,
+        body_type: RigidBodyType,
+        mass: f32,
+        friction: f32,
+    }
+    GetPropertyParams => GetPropertyParamsBuilder {
+        target: PropertyTarget,
+        data_path: String,
+    }
+    SetPropertyParams => SetPropertyParamsBuilder {
+        target: PropertyTarget,
+        data_path: String,
+        value: serde_json::Value,
+    }
+    SetLightLinkingParams => SetLightLinkingParamsBuilder {
+        light: String,
+        include_objects: Vec<String>,
+        exclude_objects: Vec<String>,
+    }
+    SetShadowVisibilityParams => SetShadowVisibilityParamsBuilder {
+        object: String,
+        visible_shadow: Option<bool>,
+        is_shadow_catcher: Option<bool>,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builders_fill_in_defaults() {
+        let cube = CreateCubeParams::builder()
+            .name("Box")
+            .on_collision(NameCollisionPolicy::Error)
+            .build();
+        assert_eq!(cube.name, "Box");
+        assert_eq!(cube.size, 2.0);
+        assert_eq!(cube.on_collision, Some(NameCollisionPolicy::Error));
+
+        let transform = SetTransformParams::builder()
+            .object("Box")
+            .location(Vec3::new(0.0, 0.0, 1.0))
+            .build();
+        assert!(transform.rotation.is_none());
+        assert_eq!(transform.location.map(|l| l.z), Some(1.0));
+    }
+}
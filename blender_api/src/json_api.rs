@@ -0,0 +1,343 @@
+//! `BlenderApi` over any transport that can carry JSON encoded operations.
+//!
+//! Backends that live outside this process (a Python addon, a socket server) only need to
+//! move strings around; the encoding of every operation is shared here.
+
+use crate::{
+    AddRigidBodyParams, ApplyGeometryNodesParams, AssignMaterialParams, BakeTextureParams,
+    BakedTexture, BlenderApi, BlenderApiError, BoundingBox, CreateCubeParams, CreateInstanceParams,
+    CreateMaterialParams, CreateMeshParams, CreateSphereParams, ExportSceneParams,
+    GetMaterialNodesParams, GetMaterialParams, GetMeshGeometryParams, GetObjectParams,
+    GetPropertyParams, ImportFileParams, MaterialData, MeshGeometry, ObjectData,
+    ObjectDependencies, ObjectFilter, OpenBlendParams, RemoveMaterialSlotParams, SaveBlendParams,
+    SceneIr, SceneSettings, SelectObjectsParams, SetFaceMaterialsParams, SetLightLinkingParams,
+    SetMaterialNodesParams, SetMaterialSlotParams, SetParentParams, SetPropertyParams,
+    SetShadowVisibilityParams, SetTransformParams, SetWorldParams, UnitSettings, Validate, Vec3,
+    WorldData,
+};
+use cuttle_lang::BlenderNodeGraph;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+pub trait JsonTransport {
+    /// Perform `operation` (`create_cube`, `list_objects`, ...) with its parameters encoded as
+    /// JSON, `null` when it has none. Returns the JSON encoded result, `null` when it has none.
+    fn call(&self, operation: &str, params: &str) -> Result<String, BlenderApiError>;
+}
+
+pub struct JsonBlenderApi<T> {
+    transport: T,
+}
+
+impl<T> JsonBlenderApi<T> {
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+}
+
+impl<T: JsonTransport> JsonBlenderApi<T> {
+    fn call<P, R>(&self, operation: &str, params: &P) -> Result<R, BlenderApiError>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        let params =
+            serde_json::to_string(params).map_err(|e| BlenderApiError::InvalidParameters {
+                message: format!("Failed to serialize {operation} parameters: {e}"),
+            })?;
+
+        let result = self.transport.call(operation, &params)?;
+
+        serde_json::from_str(&result).map_err(|e| BlenderApiError::OperationFailed {
+            message: format!("Backend returned invalid JSON for {operation}: {e}"),
+        })
+    }
+
+    // Backends that don't report the name they used are assumed to have kept the requested one
+    fn call_create<P: Serialize>(
+        &self,
+        operation: &str,
+        params: &P,
+        name: &str,
+    ) -> Result<String, BlenderApiError> {
+        let created: Option<String> = self.call(operation, params)?;
+        Ok(created.unwrap_or_else(|| name.to_string()))
+    }
+}
+
+impl<T: JsonTransport> BlenderApi for JsonBlenderApi<T> {
+    fn create_cube(&mut self, params: CreateCubeParams) -> Result<String, BlenderApiError> {
+        params.validate()?;
+        self.call_create("create_cube", &params, &params.name)
+    }
+
+    fn create_sphere(&mut self, params: CreateSphereParams) -> Result<String, BlenderApiError> {
+        params.validate()?;
+        self.call_create("create_sphere", &params, &params.name)
+    }
+
+    fn create_mesh(&mut self, params: CreateMeshParams) -> Result<String, BlenderApiError> {
+        params.validate()?;
+        self.call_create("create_mesh", &params, &params.name)
+    }
+
+    fn create_material(&mut self, params: CreateMaterialParams) -> Result<String, BlenderApiError> {
+        params.validate()?;
+        self.call_create("create_material", &params, &params.name)
+    }
+
+    fn assign_material(&mut self, params: AssignMaterialParams) -> Result<(), BlenderApiError> {
+        self.call("assign_material", &params)
+    }
+
+    fn get_object(&self, params: GetObjectParams) -> Result<ObjectData, BlenderApiError> {
+        self.call("get_object", &params)
+    }
+
+    fn get_material(&self, params: GetMaterialParams) -> Result<MaterialData, BlenderApiError> {
+        self.call("get_material", &params)
+    }
+
+    fn get_material_nodes(
+        &self,
+        params: GetMaterialNodesParams,
+    ) -> Result<BlenderNodeGraph, BlenderApiError> {
+        self.call("get_material_nodes", &params)
+    }
+
+    fn set_material_nodes(
+        &mut self,
+        params: SetMaterialNodesParams,
+    ) -> Result<(), BlenderApiError> {
+        self.call("set_material_nodes", &params)
+    }
+
+    fn get_mesh_geometry(
+        &self,
+        params: GetMeshGeometryParams,
+    ) -> Result<MeshGeometry, BlenderApiError> {
+        self.call("get_mesh_geometry", &params)
+    }
+
+    fn list_objects(&self) -> Result<Vec<String>, BlenderApiError> {
+        self.call("list_objects", &())
+    }
+
+    fn list_materials(&self) -> Result<Vec<String>, BlenderApiError> {
+        self.call("list_materials", &())
+    }
+
+    fn list_meshes(&self) -> Result<Vec<String>, BlenderApiError> {
+        self.call("list_meshes", &())
+    }
+
+    fn set_world(&mut self, params: SetWorldParams) -> Result<(), BlenderApiError> {
+        self.call("set_world", &params)
+    }
+
+    fn get_world(&self) -> Result<WorldData, BlenderApiError> {
+        self.call("get_world", &())
+    }
+
+    fn set_scene_settings(&mut self, settings: SceneSettings) -> Result<(), BlenderApiError> {
+        self.call("set_scene_settings", &settings)
+    }
+
+    fn get_scene_settings(&self) -> Result<SceneSettings, BlenderApiError> {
+        self.call("get_scene_settings", &())
+    }
+
+    fn set_units(&mut self, units: UnitSettings) -> Result<(), BlenderApiError> {
+        self.call("set_units", &units)
+    }
+
+    fn get_units(&self) -> Result<UnitSettings, BlenderApiError> {
+        self.call("get_units", &())
+    }
+
+    fn import_file(&mut self, params: ImportFileParams) -> Result<Vec<String>, BlenderApiError> {
+        self.call("import_file", &params)
+    }
+
+    fn export_scene(&self, params: ExportSceneParams) -> Result<Vec<String>, BlenderApiError> {
+        self.call("export_scene", &params)
+    }
+
+    fn get_scene_ir(&self) -> Result<SceneIr, BlenderApiError> {
+        self.call("get_scene_ir", &())
+    }
+
+    fn save_blend(&self, params: SaveBlendParams) -> Result<(), BlenderApiError> {
+        self.call("save_blend", &params)
+    }
+
+    fn open_blend(&mut self, params: OpenBlendParams) -> Result<(), BlenderApiError> {
+        self.call("open_blend", &params)
+    }
+
+    fn set_parent(&mut self, params: SetParentParams) -> Result<(), BlenderApiError> {
+        self.call("set_parent", &params)
+    }
+
+    fn get_children(&self, params: GetObjectParams) -> Result<Vec<String>, BlenderApiError> {
+        self.call("get_children", &params)
+    }
+
+    fn get_ancestors(&self, params: GetObjectParams) -> Result<Vec<String>, BlenderApiError> {
+        self.call("get_ancestors", &params)
+    }
+
+    fn get_dependencies(
+        &self,
+        params: GetObjectParams,
+    ) -> Result<ObjectDependencies, BlenderApiError> {
+        self.call("get_dependencies", &params)
+    }
+
+    fn get_bounding_box(&self, params: GetObjectParams) -> Result<BoundingBox, BlenderApiError> {
+        self.call("get_bounding_box", &params)
+    }
+
+    fn select_objects(&mut self, params: SelectObjectsParams) -> Result<(), BlenderApiError> {
+        self.call("select_objects", &params)
+    }
+
+    fn deselect_all(&mut self) -> Result<(), BlenderApiError> {
+        self.call("deselect_all", &())
+    }
+
+    fn get_selected(&self) -> Result<Vec<String>, BlenderApiError> {
+        self.call("get_selected", &())
+    }
+
+    fn apply_geometry_nodes(
+        &mut self,
+        params: ApplyGeometryNodesParams,
+    ) -> Result<String, BlenderApiError> {
+        self.call("apply_geometry_nodes", &params)
+    }
+
+    fn create_instance(&mut self, params: CreateInstanceParams) -> Result<String, BlenderApiError> {
+        params.validate()?;
+        self.call_create("create_instance", &params, &params.name)
+    }
+
+    fn set_material_slot(&mut self, params: SetMaterialSlotParams) -> Result<(), BlenderApiError> {
+        self.call("set_material_slot", &params)
+    }
+
+    fn remove_material_slot(
+        &mut self,
+        params: RemoveMaterialSlotParams,
+    ) -> Result<(), BlenderApiError> {
+        self.call("remove_material_slot", &params)
+    }
+
+    fn set_face_materials(
+        &mut self,
+        params: SetFaceMaterialsParams,
+    ) -> Result<(), BlenderApiError> {
+        self.call("set_face_materials", &params)
+    }
+
+    fn set_transform(&mut self, params: SetTransformParams) -> Result<(), BlenderApiError> {
+        params.validate()?;
+        self.call("set_transform", &params)
+    }
+
+    fn bake_texture(&mut self, params: BakeTextureParams) -> Result<BakedTexture, BlenderApiError> {
+        params.validate()?;
+        self.call("bake_texture", &params)
+    }
+
+    fn add_rigid_body(&mut self, params: AddRigidBodyParams) -> Result<(), BlenderApiError> {
+        params.validate()?;
+        self.call("add_rigid_body", &params)
+    }
+
+    fn set_gravity(&mut self, gravity: Vec3) -> Result<(), BlenderApiError> {
+        self.call("set_gravity", &gravity)
+    }
+
+    fn get_property(
+        &self,
+        params: GetPropertyParams,
+    ) -> Result<serde_json::Value, BlenderApiError> {
+        self.call("get_property", &params)
+    }
+
+    fn set_property(&mut self, params: SetPropertyParams) -> Result<(), BlenderApiError> {
+        self.call("set_property", &params)
+    }
+
+    fn set_light_linking(&mut self, params: SetLightLinkingParams) -> Result<(), BlenderApiError> {
+        self.call("set_light_linking", &params)
+    }
+
+    fn set_shadow_visibility(
+        &mut self,
+        params: SetShadowVisibilityParams,
+    ) -> Result<(), BlenderApiError> {
+        self.call("set_shadow_visibility", &params)
+    }
+
+    fn query_objects(&self, filter: ObjectFilter) -> Result<Vec<ObjectData>, BlenderApiError> {
+        self.call("query_objects", &filter)
+    }
+
+    fn clear_scene(&mut self) -> Result<(), BlenderApiError> {
+        self.call("clear_scene", &())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    struct RecordingTransport {
+        calls: Mutex<Vec<(String, String)>>,
+    }
+
+    impl JsonTransport for RecordingTransport {
+        fn call(&self, operation: &str, params: &str) -> Result<String, BlenderApiError> {
+            if let Ok(mut calls) = self.calls.lock() {
+                calls.push((operation.to_string(), params.to_string()));
+            }
+            match operation {
+                "list_objects" => Ok(r#"["Cube"]"#.to_string()),
+                "create_cube" => Ok("null".to_string()),
+                _ => Err(BlenderApiError::OperationFailed {
+                    message: format!("Unsupported operation {operation}"),
+                }),
+            }
+        }
+    }
+
+    #[test]
+    fn encodes_operations_as_json() {
+        let mut api = JsonBlenderApi::new(RecordingTransport {
+            calls: Mutex::new(Vec::new()),
+        });
+
+        api.create_cube(CreateCubeParams {
+            location: Vec3::zero(),
+            name: "Cube".to_string(),
+            size: 2.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
+        let objects = api.list_objects().expect("Failed to list objects");
+        assert_eq!(objects, vec!["Cube"]);
+        assert!(api.get_world().is_err());
+
+        let calls = api.transport().calls.lock().expect("Failed to lock calls");
+        assert_eq!(calls[0].0, "create_cube");
+        assert!(calls[0].1.contains(r#""name":"Cube""#));
+        assert_eq!(calls[1], ("list_objects".to_string(), "null".to_string()));
+    }
+}
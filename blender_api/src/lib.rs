@@ -1,9 +1,22 @@
+use crate::property::{read_property, write_property};
 use anyhow::Result;
+use cuttle_lang::{BlenderNode, BlenderNodeGraph, BlenderSocket, BlenderValue};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+pub mod builder;
+pub mod json_api;
+pub mod material_library;
+pub mod property;
+pub mod scene_ir;
+pub mod validate;
+pub use json_api::*;
+pub use material_library::*;
+pub use scene_ir::*;
+pub use validate::Validate;
 
 // Core data types for Blender objects
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Vec3 {
     pub x: f32,
     pub y: f32,
@@ -40,19 +53,293 @@ impl Color {
     pub fn white() -> Self {
         Self::new(1.0, 1.0, 1.0, 1.0)
     }
+
+    pub fn black() -> Self {
+        Self::new(0.0, 0.0, 0.0, 1.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EulerOrder {
+    #[default]
+    XYZ,
+    XZY,
+    YXZ,
+    YZX,
+    ZXY,
+    ZYX,
+}
+
+impl EulerOrder {
+    // Axes in the order they're applied, 0 = x
+    fn axes(self) -> [usize; 3] {
+        match self {
+            Self::XYZ => [0, 1, 2],
+            Self::XZY => [0, 2, 1],
+            Self::YXZ => [1, 0, 2],
+            Self::YZX => [1, 2, 0],
+            Self::ZXY => [2, 0, 1],
+            Self::ZYX => [2, 1, 0],
+        }
+    }
+}
+
+/// An object's rotation in any of Blender's rotation modes, angles in radians.
+///
+/// Serialized without a tag, the variant follows from the fields present. A bare `{x, y, z}`,
+/// as written before rotation modes existed, reads as an XYZ euler.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Rotation {
+    Quaternion {
+        w: f32,
+        x: f32,
+        y: f32,
+        z: f32,
+    },
+    AxisAngle {
+        angle: f32,
+        x: f32,
+        y: f32,
+        z: f32,
+    },
+    Euler {
+        #[serde(default)]
+        order: EulerOrder,
+        x: f32,
+        y: f32,
+        z: f32,
+    },
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Self::euler(0.0, 0.0, 0.0)
+    }
+}
+
+impl Rotation {
+    pub fn euler(x: f32, y: f32, z: f32) -> Self {
+        Self::Euler {
+            order: EulerOrder::XYZ,
+            x,
+            y,
+            z,
+        }
+    }
+
+    /// Rotate `point` about the origin.
+    pub fn rotate(&self, point: &Vec3) -> Vec3 {
+        match *self {
+            Self::Euler { order, x, y, z } => {
+                let angles = [x, y, z];
+                let mut p = [point.x, point.y, point.z];
+                for axis in order.axes() {
+                    let (sin, cos) = angles[axis].sin_cos();
+                    let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+                    (p[a], p[b]) = (p[a] * cos - p[b] * sin, p[a] * sin + p[b] * cos);
+                }
+                Vec3::new(p[0], p[1], p[2])
+            }
+            Self::Quaternion { w, x, y, z } => {
+                let norm = (w * w + x * x + y * y + z * z).sqrt();
+                if norm == 0.0 {
+                    return point.clone();
+                }
+                let (w, u) = (w / norm, Vec3::new(x / norm, y / norm, z / norm));
+                // p + 2w(u × p) + 2u × (u × p)
+                let t = cross(&u, point);
+                let t = Vec3::new(2.0 * t.x, 2.0 * t.y, 2.0 * t.z);
+                let ut = cross(&u, &t);
+                Vec3::new(
+                    point.x + w * t.x + ut.x,
+                    point.y + w * t.y + ut.y,
+                    point.z + w * t.z + ut.z,
+                )
+            }
+            Self::AxisAngle { angle, x, y, z } => {
+                let (half_sin, half_cos) = (angle / 2.0).sin_cos();
+                let norm = (x * x + y * y + z * z).sqrt();
+                if norm == 0.0 {
+                    return point.clone();
+                }
+                let scale = half_sin / norm;
+                Self::Quaternion {
+                    w: half_cos,
+                    x: x * scale,
+                    y: y * scale,
+                    z: z * scale,
+                }
+                .rotate(point)
+            }
+        }
+    }
+}
+
+fn cross(a: &Vec3, b: &Vec3) -> Vec3 {
+    Vec3::new(
+        a.y * b.z - a.z * b.y,
+        a.z * b.x - a.x * b.z,
+        a.x * b.y - a.y * b.x,
+    )
+}
+
+/// Blender's `Object.type`, serialized as Blender's identifier such as `"MESH"`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum ObjectType {
+    Mesh,
+    Curve,
+    Surface,
+    Meta,
+    Font,
+    Curves,
+    PointCloud,
+    Volume,
+    GreasePencil,
+    Armature,
+    Lattice,
+    Empty,
+    Light,
+    LightProbe,
+    Camera,
+    Speaker,
+    // Types added by newer Blender versions or addons
+    Custom(String),
+}
+
+impl ObjectType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Mesh => "MESH",
+            Self::Curve => "CURVE",
+            Self::Surface => "SURFACE",
+            Self::Meta => "META",
+            Self::Font => "FONT",
+            Self::Curves => "CURVES",
+            Self::PointCloud => "POINTCLOUD",
+            Self::Volume => "VOLUME",
+            Self::GreasePencil => "GREASEPENCIL",
+            Self::Armature => "ARMATURE",
+            Self::Lattice => "LATTICE",
+            Self::Empty => "EMPTY",
+            Self::Light => "LIGHT",
+            Self::LightProbe => "LIGHT_PROBE",
+            Self::Camera => "CAMERA",
+            Self::Speaker => "SPEAKER",
+            Self::Custom(name) => name,
+        }
+    }
+}
+
+impl From<String> for ObjectType {
+    fn from(name: String) -> Self {
+        match name.as_str() {
+            "MESH" => Self::Mesh,
+            "CURVE" => Self::Curve,
+            "SURFACE" => Self::Surface,
+            "META" => Self::Meta,
+            "FONT" => Self::Font,
+            "CURVES" => Self::Curves,
+            "POINTCLOUD" => Self::PointCloud,
+            "VOLUME" => Self::Volume,
+            "GREASEPENCIL" => Self::GreasePencil,
+            "ARMATURE" => Self::Armature,
+            "LATTICE" => Self::Lattice,
+            "EMPTY" => Self::Empty,
+            "LIGHT" => Self::Light,
+            "LIGHT_PROBE" => Self::LightProbe,
+            "CAMERA" => Self::Camera,
+            "SPEAKER" => Self::Speaker,
+            _ => Self::Custom(name),
+        }
+    }
+}
+
+impl From<ObjectType> for String {
+    fn from(object_type: ObjectType) -> Self {
+        match object_type {
+            ObjectType::Custom(name) => name,
+            other => other.as_str().to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for ObjectType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 // Blender object data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ObjectData {
     pub name: String,
-    pub object_type: String,
+    pub object_type: ObjectType,
     pub location: Vec3,
-    pub rotation: Vec3,
+    pub rotation: Rotation,
     pub scale: Vec3,
     pub materials: Vec<String>,
     pub vertex_count: Option<usize>,
     pub face_count: Option<usize>,
+    // Older state files and backends don't report parents
+    #[serde(default)]
+    pub parent: Option<String>,
+    // Linked duplicates name the object whose mesh data they share
+    #[serde(default)]
+    pub instance_of: Option<String>,
+    #[serde(default)]
+    pub rigid_body: Option<RigidBody>,
+    // Only lights have linking
+    #[serde(default)]
+    pub light_linking: Option<LightLinking>,
+    #[serde(default)]
+    pub shadow: ShadowVisibility,
+}
+
+/// The objects a light illuminates, by name.
+///
+/// An empty `include_objects` lights everything that isn't excluded, like a light without a
+/// receiver collection in Blender.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LightLinking {
+    pub include_objects: Vec<String>,
+    pub exclude_objects: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShadowVisibility {
+    // Whether the object casts shadows
+    pub visible_shadow: bool,
+    // Whether the object only receives shadows, rendering transparent otherwise
+    pub is_shadow_catcher: bool,
+}
+
+impl Default for ShadowVisibility {
+    fn default() -> Self {
+        Self {
+            visible_shadow: true,
+            is_shadow_catcher: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RigidBodyType {
+    #[default]
+    #[serde(rename = "ACTIVE")]
+    Active,
+    #[serde(rename = "PASSIVE")]
+    Passive,
+}
+
+/// An object's `rigid_body` settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RigidBody {
+    pub body_type: RigidBodyType,
+    // Kilograms, ignored by the simulation for passive bodies
+    pub mass: f32,
+    pub friction: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +349,21 @@ pub struct MaterialData {
     pub base_color: Color,
     pub metallic: f32,
     pub roughness: f32,
+    // Older addons and state files predate these, so they default to a new material's values
+    #[serde(default = "default_emission_color")]
+    pub emission_color: Color,
+    #[serde(default)]
+    pub emission_strength: f32,
+    #[serde(default = "default_alpha")]
+    pub alpha: f32,
+    #[serde(default = "default_ior")]
+    pub ior: f32,
+    #[serde(default = "default_specular")]
+    pub specular: f32,
+    #[serde(default)]
+    pub transmission: f32,
+    #[serde(default = "default_normal_strength")]
+    pub normal_strength: f32,
     pub node_count: usize,
 }
 
@@ -73,12 +375,296 @@ pub struct MeshData {
     pub face_count: usize,
 }
 
+// Full mesh topology in object local space
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeshGeometry {
+    pub name: String,
+    pub vertices: Vec<Vec3>,
+    pub edges: Vec<[usize; 2]>,
+    pub faces: Vec<Vec<usize>>,
+    // Material slot per face, empty when every face uses the first slot
+    #[serde(default)]
+    pub face_materials: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WorldBackground {
+    Color(Color),
+    Hdri { path: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldData {
+    pub background: WorldBackground,
+    pub strength: f32,
+}
+
+impl Default for WorldData {
+    fn default() -> Self {
+        // Blender's default world is a flat dark grey
+        Self {
+            background: WorldBackground::Color(Color::new(0.05, 0.05, 0.05, 1.0)),
+            strength: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RenderEngine {
+    #[serde(rename = "BLENDER_EEVEE_NEXT")]
+    Eevee,
+    #[serde(rename = "CYCLES")]
+    Cycles,
+    #[serde(rename = "BLENDER_WORKBENCH")]
+    Workbench,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneSettings {
+    pub frame_start: i32,
+    pub frame_end: i32,
+    pub fps: u32,
+    pub resolution_x: u32,
+    pub resolution_y: u32,
+    pub render_engine: RenderEngine,
+    // Older state files predate physics settings
+    #[serde(default = "default_gravity")]
+    pub gravity: Vec3,
+}
+
+fn default_gravity() -> Vec3 {
+    Vec3::new(0.0, 0.0, -9.81)
+}
+
+impl Default for SceneSettings {
+    fn default() -> Self {
+        Self {
+            frame_start: 1,
+            frame_end: 250,
+            fps: 24,
+            resolution_x: 1920,
+            resolution_y: 1080,
+            render_engine: RenderEngine::Eevee,
+            gravity: default_gravity(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnitSystem {
+    #[serde(rename = "NONE")]
+    None,
+    #[serde(rename = "METRIC")]
+    Metric,
+    #[serde(rename = "IMPERIAL")]
+    Imperial,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnitSettings {
+    pub system: UnitSystem,
+    // Blender units per scene unit, e.g. 0.001 for a millimetre scene
+    pub scale_length: f32,
+}
+
+impl Default for UnitSettings {
+    fn default() -> Self {
+        Self {
+            system: UnitSystem::Metric,
+            scale_length: 1.0,
+        }
+    }
+}
+
+/// Data blocks and objects an object depends on.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ObjectDependencies {
+    pub materials: Vec<String>,
+    pub modifiers: Vec<String>,
+    pub constraint_targets: Vec<String>,
+}
+
+/// World-space axis-aligned bounds of an object.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub min: Vec3,
+    pub max: Vec3,
+    pub dimensions: Vec3,
+}
+
+impl BoundingBox {
+    pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let (min, max) = points.fold((first.clone(), first), |(min, max), p| {
+            (
+                Vec3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+                Vec3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+            )
+        });
+        let dimensions = Vec3::new(max.x - min.x, max.y - min.y, max.z - min.z);
+        Some(Self {
+            min,
+            max,
+            dimensions,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SpatialFilter {
+    // The object's bounding box lies entirely within min..max
+    Inside { min: Vec3, max: Vec3 },
+    // The object's bounding box overlaps min..max
+    Intersects { min: Vec3, max: Vec3 },
+    // The object's origin is at most `radius` from `center`
+    Near { center: Vec3, radius: f32 },
+}
+
+impl SpatialFilter {
+    pub fn matches(&self, origin: &Vec3, bounds: &BoundingBox) -> bool {
+        let (lo, hi) = (&bounds.min, &bounds.max);
+        match self {
+            Self::Inside { min, max } => {
+                lo.x >= min.x
+                    && lo.y >= min.y
+                    && lo.z >= min.z
+                    && hi.x <= max.x
+                    && hi.y <= max.y
+                    && hi.z <= max.z
+            }
+            Self::Intersects { min, max } => {
+                lo.x <= max.x
+                    && lo.y <= max.y
+                    && lo.z <= max.z
+                    && hi.x >= min.x
+                    && hi.y >= min.y
+                    && hi.z >= min.z
+            }
+            Self::Near { center, radius } => {
+                let (dx, dy, dz) = (
+                    origin.x - center.x,
+                    origin.y - center.y,
+                    origin.z - center.z,
+                );
+                (dx * dx + dy * dy + dz * dz).sqrt() <= *radius
+            }
+        }
+    }
+}
+
+/// Predicates for `query_objects`, an object has to pass every one that is set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ObjectFilter {
+    #[serde(default)]
+    pub object_type: Option<ObjectType>,
+    // Glob where `*` matches any run of characters and `?` a single one
+    #[serde(default)]
+    pub name: Option<String>,
+    // Uses this material in any slot
+    #[serde(default)]
+    pub material: Option<String>,
+    #[serde(default)]
+    pub collection: Option<String>,
+    #[serde(default)]
+    pub spatial: Option<SpatialFilter>,
+}
+
+impl ObjectFilter {
+    /// Check everything but `collection` and `spatial`, which need scene data `ObjectData`
+    /// doesn't carry.
+    pub fn matches_object(&self, object: &ObjectData) -> bool {
+        self.object_type
+            .as_ref()
+            .is_none_or(|object_type| *object_type == object.object_type)
+            && self
+                .name
+                .as_ref()
+                .is_none_or(|pattern| glob_match(pattern, &object.name))
+            && self
+                .material
+                .as_ref()
+                .is_none_or(|material| object.materials.contains(material))
+    }
+}
+
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let (pattern, text) = (pattern.as_bytes(), text.as_bytes());
+    let (mut p, mut t) = (0, 0);
+    // Where the last `*` was and how much of the text it has swallowed so far
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == b'?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, consumed)) => {
+                    p = star + 1;
+                    t = consumed + 1;
+                    backtrack = Some((star, consumed + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// Everything a state capture needs, fetched in a single call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneState {
+    pub objects: Vec<ObjectData>,
+    pub materials: Vec<MaterialData>,
+    pub material_nodes: BTreeMap<String, BlenderNodeGraph>,
+    pub meshes: Vec<MeshGeometry>,
+    pub world: WorldData,
+    pub scene_settings: SceneSettings,
+    #[serde(default)]
+    pub bounding_boxes: BTreeMap<String, BoundingBox>,
+    #[serde(default)]
+    pub units: UnitSettings,
+}
+
 // Operation parameters
+/// What a create operation does when its name is already taken.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NameCollisionPolicy {
+    /// Fail with `ObjectAlreadyExists` or `MaterialAlreadyExists`.
+    Error,
+    /// Overwrite the existing object or material.
+    #[default]
+    Replace,
+    /// Pick the next free `.001` style name, like Blender does.
+    AutoSuffix,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateCubeParams {
     pub location: Vec3,
     pub name: String,
     pub size: f32,
+    // None defers to the service's policy
+    #[serde(default)]
+    pub on_collision: Option<NameCollisionPolicy>,
+}
+
+// Matches `bpy.ops.mesh.primitive_cube_add()`
+impl Default for CreateCubeParams {
+    fn default() -> Self {
+        Self {
+            location: Vec3::zero(),
+            name: "Cube".to_string(),
+            size: 2.0,
+            on_collision: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -87,6 +673,48 @@ pub struct CreateSphereParams {
     pub name: String,
     pub radius: f32,
     pub subdivisions: u32,
+    // None defers to the service's policy
+    #[serde(default)]
+    pub on_collision: Option<NameCollisionPolicy>,
+}
+
+// 8 subdivisions gives Blender's default 32 segments and 16 rings
+impl Default for CreateSphereParams {
+    fn default() -> Self {
+        Self {
+            location: Vec3::zero(),
+            name: "Sphere".to_string(),
+            radius: 1.0,
+            subdivisions: 8,
+            on_collision: None,
+        }
+    }
+}
+
+// A mesh object built from raw geometry, such as a scan or another tool's output. Edges are
+// derived from the faces
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateMeshParams {
+    pub location: Vec3,
+    pub name: String,
+    pub vertices: Vec<Vec3>,
+    // Indices into `vertices`, at least 3 per face
+    pub faces: Vec<Vec<usize>>,
+    // None defers to the service's policy
+    #[serde(default)]
+    pub on_collision: Option<NameCollisionPolicy>,
+}
+
+impl Default for CreateMeshParams {
+    fn default() -> Self {
+        Self {
+            location: Vec3::zero(),
+            name: "Mesh".to_string(),
+            vertices: Vec::new(),
+            faces: Vec::new(),
+            on_collision: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,120 +723,1234 @@ pub struct CreateMaterialParams {
     pub base_color: Color,
     pub metallic: f32,
     pub roughness: f32,
+    // Older requests predate these, so they default to a new material's values
+    #[serde(default = "default_emission_color")]
+    pub emission_color: Color,
+    #[serde(default)]
+    pub emission_strength: f32,
+    #[serde(default = "default_alpha")]
+    pub alpha: f32,
+    #[serde(default = "default_ior")]
+    pub ior: f32,
+    #[serde(default = "default_specular")]
+    pub specular: f32,
+    #[serde(default)]
+    pub transmission: f32,
+    #[serde(default = "default_normal_strength")]
+    pub normal_strength: f32,
+    // None defers to the service's policy
+    #[serde(default)]
+    pub on_collision: Option<NameCollisionPolicy>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+fn default_emission_color() -> Color {
+    Color::black()
+}
+
+fn default_alpha() -> f32 {
+    1.0
+}
+
+fn default_ior() -> f32 {
+    1.5
+}
+
+fn default_specular() -> f32 {
+    0.5
+}
+
+fn default_normal_strength() -> f32 {
+    1.0
+}
+
+// Matches the defaults of a new Principled BSDF material in Blender
+impl Default for CreateMaterialParams {
+    fn default() -> Self {
+        Self {
+            name: "Material".to_string(),
+            base_color: Color::new(0.8, 0.8, 0.8, 1.0),
+            metallic: 0.0,
+            roughness: 0.5,
+            emission_color: default_emission_color(),
+            emission_strength: 0.0,
+            alpha: default_alpha(),
+            ior: default_ior(),
+            specular: default_specular(),
+            transmission: 0.0,
+            normal_strength: default_normal_strength(),
+            on_collision: None,
+        }
+    }
+}
+
+/// A material named `name` with the values of a `MaterialLibrary` preset.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateMaterialFromPresetParams {
+    pub name: String,
+    pub preset: String,
+    #[serde(default)]
+    pub on_collision: Option<NameCollisionPolicy>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AssignMaterialParams {
     pub object_name: String,
     pub material_name: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GetObjectParams {
     pub name: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GetMaterialParams {
     pub name: String,
 }
 
-// Error types
-#[derive(Debug, thiserror::Error)]
-pub enum BlenderApiError {
-    #[error("Object not found: {name}")]
-    ObjectNotFound { name: String },
-    #[error("Material not found: {name}")]
-    MaterialNotFound { name: String },
-    #[error("Operation failed: {message}")]
-    OperationFailed { message: String },
-    #[error("Invalid parameters: {message}")]
-    InvalidParameters { message: String },
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetMaterialNodesParams {
+    pub name: String,
 }
 
-// The actual API trait - this will be implemented by the service
-pub trait BlenderApi {
-    fn create_cube(&mut self, params: CreateCubeParams) -> Result<(), BlenderApiError>;
-    fn create_sphere(&mut self, params: CreateSphereParams) -> Result<(), BlenderApiError>;
-    fn create_material(&mut self, params: CreateMaterialParams) -> Result<(), BlenderApiError>;
-    fn assign_material(&mut self, params: AssignMaterialParams) -> Result<(), BlenderApiError>;
-    fn get_object(&self, params: GetObjectParams) -> Result<ObjectData, BlenderApiError>;
-    fn get_material(&self, params: GetMaterialParams) -> Result<MaterialData, BlenderApiError>;
-    fn list_objects(&self) -> Result<Vec<String>, BlenderApiError>;
-    fn list_materials(&self) -> Result<Vec<String>, BlenderApiError>;
-    fn list_meshes(&self) -> Result<Vec<String>, BlenderApiError>;
-    fn clear_scene(&mut self) -> Result<(), BlenderApiError>;
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetMaterialNodesParams {
+    pub name: String,
+    pub graph: BlenderNodeGraph,
 }
 
-// Mock implementation for testing
-pub struct MockBlenderApi {
-    objects: HashMap<String, ObjectData>,
-    materials: HashMap<String, MaterialData>,
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetMeshGeometryParams {
+    pub name: String,
 }
 
-impl MockBlenderApi {
-    pub fn new() -> Self {
-        Self {
-            objects: HashMap::new(),
-            materials: HashMap::new(),
-        }
-    }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetWorldParams {
+    pub background: WorldBackground,
+    pub strength: f32,
 }
 
-impl Default for MockBlenderApi {
+impl Default for SetWorldParams {
     fn default() -> Self {
-        Self::new()
+        let world = WorldData::default();
+        Self {
+            background: world.background,
+            strength: world.strength,
+        }
     }
 }
 
-impl BlenderApi for MockBlenderApi {
-    fn create_cube(&mut self, params: CreateCubeParams) -> Result<(), BlenderApiError> {
-        let object = ObjectData {
-            name: params.name.clone(),
-            object_type: "MESH".to_string(),
-            location: params.location,
-            rotation: Vec3::zero(),
-            scale: Vec3::new(params.size, params.size, params.size),
-            materials: Vec::new(),
-            vertex_count: Some(8),
-            face_count: Some(6),
-        };
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportFormat {
+    #[default]
+    Obj,
+    Gltf,
+    Fbx,
+    Stl,
+}
 
-        self.objects.insert(params.name, object);
-        Ok(())
+impl ImportFormat {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "obj" => Some(Self::Obj),
+            "gltf" | "glb" => Some(Self::Gltf),
+            "fbx" => Some(Self::Fbx),
+            "stl" => Some(Self::Stl),
+            _ => None,
+        }
     }
+}
 
-    fn create_sphere(&mut self, params: CreateSphereParams) -> Result<(), BlenderApiError> {
-        let vertex_count = (params.subdivisions * params.subdivisions * 4) as usize;
-        let face_count = (params.subdivisions * params.subdivisions * 4) as usize;
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportFileParams {
+    pub path: String,
+    pub format: ImportFormat,
+}
 
-        let object = ObjectData {
-            name: params.name.clone(),
-            object_type: "MESH".to_string(),
-            location: params.location,
-            rotation: Vec3::zero(),
-            scale: Vec3::new(params.radius, params.radius, params.radius),
-            materials: Vec::new(),
-            vertex_count: Some(vertex_count),
-            face_count: Some(face_count),
-        };
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    Gltf,
+    #[default]
+    Obj,
+    Usd,
+}
 
-        self.objects.insert(params.name, object);
-        Ok(())
+impl ExportFormat {
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "gltf" | "glb" => Some(Self::Gltf),
+            "obj" => Some(Self::Obj),
+            "usd" | "usda" | "usdc" => Some(Self::Usd),
+            _ => None,
+        }
     }
 
-    fn create_material(&mut self, params: CreateMaterialParams) -> Result<(), BlenderApiError> {
-        let material = MaterialData {
-            name: params.name.clone(),
-            use_nodes: true,
-            base_color: params.base_color,
-            metallic: params.metallic,
-            roughness: params.roughness,
-            node_count: 1, // Basic principled BSDF
-        };
-
-        self.materials.insert(params.name, material);
-        Ok(())
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Gltf => "gltf",
+            Self::Obj => "obj",
+            Self::Usd => "usd",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportSceneParams {
+    pub path: String,
+    pub format: ExportFormat,
+    pub selected_only: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SaveBlendParams {
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OpenBlendParams {
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetParentParams {
+    pub child: String,
+    // None clears the parent
+    pub parent: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SelectObjectsParams {
+    pub names: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApplyGeometryNodesParams {
+    pub object: String,
+    pub graph: BlenderNodeGraph,
+    /// Reapply the graph even when it's the one last applied to `object`.
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateInstanceParams {
+    pub source: String,
+    pub name: String,
+    pub location: Vec3,
+    // None defers to the service's policy
+    #[serde(default)]
+    pub on_collision: Option<NameCollisionPolicy>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetMaterialSlotParams {
+    pub object: String,
+    // An index one past the last slot appends a new slot
+    pub index: usize,
+    pub material: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemoveMaterialSlotParams {
+    pub object: String,
+    pub index: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetFaceMaterialsParams {
+    pub object: String,
+    pub slot: usize,
+    pub faces: Vec<usize>,
+}
+
+// Fields left as None keep their current value
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetTransformParams {
+    pub object: String,
+    #[serde(default)]
+    pub location: Option<Vec3>,
+    #[serde(default)]
+    pub rotation: Option<Rotation>,
+    #[serde(default)]
+    pub scale: Option<Vec3>,
+}
+
+// Names match Blender's `bake_type` enum
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BakeType {
+    #[default]
+    #[serde(rename = "COMBINED")]
+    Combined,
+    #[serde(rename = "AO")]
+    AmbientOcclusion,
+    #[serde(rename = "NORMAL")]
+    Normal,
+    #[serde(rename = "DIFFUSE")]
+    Diffuse,
+    #[serde(rename = "ROUGHNESS")]
+    Roughness,
+    #[serde(rename = "EMIT")]
+    Emit,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BakeTextureParams {
+    pub object: String,
+    pub bake_type: BakeType,
+    // Width and height of the square output image in pixels
+    pub resolution: u32,
+    pub output_path: String,
+}
+
+impl Default for BakeTextureParams {
+    fn default() -> Self {
+        Self {
+            object: String::new(),
+            bake_type: BakeType::default(),
+            resolution: 1024,
+            output_path: String::new(),
+        }
+    }
+}
+
+/// An image written by `bake_texture`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BakedTexture {
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddRigidBodyParams {
+    pub object: String,
+    pub body_type: RigidBodyType,
+    pub mass: f32,
+    pub friction: f32,
+}
+
+// Blender's defaults for a new rigid body
+impl Default for AddRigidBodyParams {
+    fn default() -> Self {
+        Self {
+            object: String::new(),
+            body_type: RigidBodyType::default(),
+            mass: 1.0,
+            friction: 0.5,
+        }
+    }
+}
+
+/// The data block a property path starts from, like `bpy.data.objects["Cube"]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PropertyTarget {
+    Object(String),
+    Material(String),
+    Mesh(String),
+    #[default]
+    Scene,
+    World,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GetPropertyParams {
+    pub target: PropertyTarget,
+    pub data_path: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetPropertyParams {
+    pub target: PropertyTarget,
+    pub data_path: String,
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetLightLinkingParams {
+    pub light: String,
+    pub include_objects: Vec<String>,
+    pub exclude_objects: Vec<String>,
+}
+
+// Flags left as None keep their current value
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetShadowVisibilityParams {
+    pub object: String,
+    #[serde(default)]
+    pub visible_shadow: Option<bool>,
+    #[serde(default)]
+    pub is_shadow_catcher: Option<bool>,
+}
+
+// Error types
+#[derive(Debug, thiserror::Error)]
+pub enum BlenderApiError {
+    #[error("Object not found: {name}")]
+    ObjectNotFound { name: String },
+    #[error("Material not found: {name}")]
+    MaterialNotFound { name: String },
+    #[error("Object already exists: {name}")]
+    ObjectAlreadyExists { name: String },
+    #[error("Material already exists: {name}")]
+    MaterialAlreadyExists { name: String },
+    #[error("Material preset not found: {name}")]
+    PresetNotFound { name: String },
+    #[error("Operation failed: {message}")]
+    OperationFailed { message: String },
+    #[error("Invalid parameters: {message}")]
+    InvalidParameters { message: String },
+}
+
+impl BlenderApiError {
+    /// The error `message` was written from, for errors that crossed the bridge as text.
+    /// Messages that don't match any error are `OperationFailed`.
+    pub fn from_message(message: &str) -> Self {
+        let field = |prefix: &str| message.strip_prefix(prefix).map(str::to_string);
+        if let Some(name) = field("Object not found: ") {
+            Self::ObjectNotFound { name }
+        } else if let Some(name) = field("Material not found: ") {
+            Self::MaterialNotFound { name }
+        } else if let Some(name) = field("Object already exists: ") {
+            Self::ObjectAlreadyExists { name }
+        } else if let Some(name) = field("Material already exists: ") {
+            Self::MaterialAlreadyExists { name }
+        } else if let Some(name) = field("Material preset not found: ") {
+            Self::PresetNotFound { name }
+        } else if let Some(message) = field("Invalid parameters: ") {
+            Self::InvalidParameters { message }
+        } else {
+            Self::OperationFailed {
+                message: field("Operation failed: ").unwrap_or_else(|| message.to_string()),
+            }
+        }
+    }
+}
+
+// The actual API trait - this will be implemented by the service
+pub trait BlenderApi {
+    // Create operations return the name they ended up using, see `NameCollisionPolicy`
+    fn create_cube(&mut self, params: CreateCubeParams) -> Result<String, BlenderApiError>;
+    fn create_sphere(&mut self, params: CreateSphereParams) -> Result<String, BlenderApiError>;
+    fn create_mesh(&mut self, params: CreateMeshParams) -> Result<String, BlenderApiError>;
+    fn create_material(&mut self, params: CreateMaterialParams) -> Result<String, BlenderApiError>;
+    fn assign_material(&mut self, params: AssignMaterialParams) -> Result<(), BlenderApiError>;
+    fn set_material_slot(&mut self, params: SetMaterialSlotParams) -> Result<(), BlenderApiError>;
+    fn remove_material_slot(
+        &mut self,
+        params: RemoveMaterialSlotParams,
+    ) -> Result<(), BlenderApiError>;
+    fn set_face_materials(&mut self, params: SetFaceMaterialsParams)
+    -> Result<(), BlenderApiError>;
+    fn get_object(&self, params: GetObjectParams) -> Result<ObjectData, BlenderApiError>;
+    // Also switches the object's rotation mode to that of the given rotation
+    fn set_transform(&mut self, params: SetTransformParams) -> Result<(), BlenderApiError>;
+    // Bakes the object's first UV map into an image written to `output_path`
+    fn bake_texture(&mut self, params: BakeTextureParams) -> Result<BakedTexture, BlenderApiError>;
+    fn get_material(&self, params: GetMaterialParams) -> Result<MaterialData, BlenderApiError>;
+    fn get_material_nodes(
+        &self,
+        params: GetMaterialNodesParams,
+    ) -> Result<BlenderNodeGraph, BlenderApiError>;
+    fn set_material_nodes(&mut self, params: SetMaterialNodesParams)
+    -> Result<(), BlenderApiError>;
+    fn get_mesh_geometry(
+        &self,
+        params: GetMeshGeometryParams,
+    ) -> Result<MeshGeometry, BlenderApiError>;
+    fn list_objects(&self) -> Result<Vec<String>, BlenderApiError>;
+    fn list_materials(&self) -> Result<Vec<String>, BlenderApiError>;
+    fn list_meshes(&self) -> Result<Vec<String>, BlenderApiError>;
+    fn set_world(&mut self, params: SetWorldParams) -> Result<(), BlenderApiError>;
+    fn get_world(&self) -> Result<WorldData, BlenderApiError>;
+    fn set_scene_settings(&mut self, settings: SceneSettings) -> Result<(), BlenderApiError>;
+    fn get_scene_settings(&self) -> Result<SceneSettings, BlenderApiError>;
+    fn set_units(&mut self, units: UnitSettings) -> Result<(), BlenderApiError>;
+    fn get_units(&self) -> Result<UnitSettings, BlenderApiError>;
+    // Replaces the settings of an object that already has a rigid body
+    fn add_rigid_body(&mut self, params: AddRigidBodyParams) -> Result<(), BlenderApiError>;
+    // Stored in `SceneSettings::gravity`
+    fn set_gravity(&mut self, gravity: Vec3) -> Result<(), BlenderApiError>;
+    fn set_light_linking(&mut self, params: SetLightLinkingParams) -> Result<(), BlenderApiError>;
+    fn set_shadow_visibility(
+        &mut self,
+        params: SetShadowVisibilityParams,
+    ) -> Result<(), BlenderApiError>;
+    // Sorted by name
+    fn query_objects(&self, filter: ObjectFilter) -> Result<Vec<ObjectData>, BlenderApiError>;
+    // Escape hatches for properties the typed API doesn't cover yet
+    fn get_property(&self, params: GetPropertyParams)
+    -> Result<serde_json::Value, BlenderApiError>;
+    fn set_property(&mut self, params: SetPropertyParams) -> Result<(), BlenderApiError>;
+    fn import_file(&mut self, params: ImportFileParams) -> Result<Vec<String>, BlenderApiError>;
+    fn export_scene(&self, params: ExportSceneParams) -> Result<Vec<String>, BlenderApiError>;
+    fn get_scene_ir(&self) -> Result<SceneIr, BlenderApiError>;
+    fn save_blend(&self, params: SaveBlendParams) -> Result<(), BlenderApiError>;
+    fn open_blend(&mut self, params: OpenBlendParams) -> Result<(), BlenderApiError>;
+    fn set_parent(&mut self, params: SetParentParams) -> Result<(), BlenderApiError>;
+    // Direct children only, sorted by name
+    fn get_children(&self, params: GetObjectParams) -> Result<Vec<String>, BlenderApiError>;
+    // Nearest parent first
+    fn get_ancestors(&self, params: GetObjectParams) -> Result<Vec<String>, BlenderApiError>;
+    fn get_dependencies(
+        &self,
+        params: GetObjectParams,
+    ) -> Result<ObjectDependencies, BlenderApiError>;
+    fn get_bounding_box(&self, params: GetObjectParams) -> Result<BoundingBox, BlenderApiError>;
+    // Adds to the current selection, like `select_set(True)`
+    fn select_objects(&mut self, params: SelectObjectsParams) -> Result<(), BlenderApiError>;
+    fn deselect_all(&mut self) -> Result<(), BlenderApiError>;
+    fn get_selected(&self) -> Result<Vec<String>, BlenderApiError>;
+    // Linked duplicate sharing the source's mesh data
+    fn create_instance(&mut self, params: CreateInstanceParams) -> Result<String, BlenderApiError>;
+    // Adds a geometry nodes modifier and returns its name
+    fn apply_geometry_nodes(
+        &mut self,
+        params: ApplyGeometryNodesParams,
+    ) -> Result<String, BlenderApiError>;
+
+    // Backends that can read the whole scene in one pass should override this, the default
+    // goes through the per-item getters
+    fn get_scene_state(&self) -> Result<SceneState, BlenderApiError> {
+        let objects = self
+            .list_objects()?
+            .into_iter()
+            .map(|name| self.get_object(GetObjectParams { name }))
+            .collect::<Result<Vec<_>, _>>()?;
+        let materials = self
+            .list_materials()?
+            .into_iter()
+            .map(|name| self.get_material(GetMaterialParams { name }))
+            .collect::<Result<Vec<_>, _>>()?;
+        let material_nodes = materials
+            .iter()
+            .map(|material| {
+                let name = material.name.clone();
+                let graph = self.get_material_nodes(GetMaterialNodesParams { name: name.clone() });
+                graph.map(|graph| (name, graph))
+            })
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+        let meshes = self
+            .list_meshes()?
+            .into_iter()
+            .map(|name| self.get_mesh_geometry(GetMeshGeometryParams { name }))
+            .collect::<Result<Vec<_>, _>>()?;
+        let bounding_boxes = objects
+            .iter()
+            .map(|object| {
+                let name = object.name.clone();
+                let bounds = self.get_bounding_box(GetObjectParams { name: name.clone() });
+                bounds.map(|bounds| (name, bounds))
+            })
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+
+        Ok(SceneState {
+            objects,
+            materials,
+            material_nodes,
+            meshes,
+            world: self.get_world()?,
+            scene_settings: self.get_scene_settings()?,
+            bounding_boxes,
+            units: self.get_units()?,
+        })
+    }
+
+    fn clear_scene(&mut self) -> Result<(), BlenderApiError>;
+}
+
+// Mock implementation for testing
+pub struct MockBlenderApi {
+    objects: HashMap<String, ObjectData>,
+    materials: HashMap<String, MaterialData>,
+    material_nodes: HashMap<String, BlenderNodeGraph>,
+    meshes: HashMap<String, MeshGeometry>,
+    world: WorldData,
+    scene_settings: SceneSettings,
+    units: UnitSettings,
+    selected: BTreeSet<String>,
+    geometry_nodes: HashMap<String, Vec<GeometryNodesModifier>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GeometryNodesModifier {
+    name: String,
+    graph: BlenderNodeGraph,
+}
+
+/// Full copy of a `MockBlenderApi` scene, also used as the mock's stand-in for .blend files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SceneSnapshot {
+    objects: HashMap<String, ObjectData>,
+    materials: HashMap<String, MaterialData>,
+    material_nodes: HashMap<String, BlenderNodeGraph>,
+    meshes: HashMap<String, MeshGeometry>,
+    world: WorldData,
+    scene_settings: SceneSettings,
+    #[serde(default)]
+    units: UnitSettings,
+    #[serde(default)]
+    selected: BTreeSet<String>,
+    #[serde(default)]
+    geometry_nodes: HashMap<String, Vec<GeometryNodesModifier>>,
+}
+
+impl MockBlenderApi {
+    pub fn new() -> Self {
+        Self {
+            objects: HashMap::new(),
+            materials: HashMap::new(),
+            material_nodes: HashMap::new(),
+            meshes: HashMap::new(),
+            world: WorldData::default(),
+            scene_settings: SceneSettings::default(),
+            units: UnitSettings::default(),
+            selected: BTreeSet::new(),
+            geometry_nodes: HashMap::new(),
+        }
+    }
+
+    /// Copy the whole scene, materials and settings included, so it can be restored later.
+    pub fn snapshot_scene(&self) -> SceneSnapshot {
+        SceneSnapshot {
+            objects: self.objects.clone(),
+            materials: self.materials.clone(),
+            material_nodes: self.material_nodes.clone(),
+            meshes: self.meshes.clone(),
+            world: self.world.clone(),
+            scene_settings: self.scene_settings.clone(),
+            units: self.units.clone(),
+            selected: self.selected.clone(),
+            geometry_nodes: self.geometry_nodes.clone(),
+        }
+    }
+
+    /// Replace the whole scene with a snapshot taken by [`Self::snapshot_scene`].
+    pub fn restore_scene(&mut self, snapshot: SceneSnapshot) {
+        self.objects = snapshot.objects;
+        self.materials = snapshot.materials;
+        self.material_nodes = snapshot.material_nodes;
+        self.meshes = snapshot.meshes;
+        self.world = snapshot.world;
+        self.scene_settings = snapshot.scene_settings;
+        self.units = snapshot.units;
+        self.selected = snapshot.selected;
+        self.geometry_nodes = snapshot.geometry_nodes;
+    }
+
+    // Applies the collision policy for a new object, clearing what a replaced object leaves
+    fn claim_object_name(
+        &mut self,
+        name: String,
+        policy: Option<NameCollisionPolicy>,
+    ) -> Result<String, BlenderApiError> {
+        if !self.objects.contains_key(&name) {
+            return Ok(name);
+        }
+
+        match policy.unwrap_or_default() {
+            NameCollisionPolicy::Error => Err(BlenderApiError::ObjectAlreadyExists { name }),
+            NameCollisionPolicy::Replace => {
+                self.meshes.remove(&name);
+                self.geometry_nodes.remove(&name);
+                self.selected.remove(&name);
+                Ok(name)
+            }
+            NameCollisionPolicy::AutoSuffix => {
+                unique_name(&name, |name| self.objects.contains_key(name))
+            }
+        }
+    }
+
+    fn claim_material_name(
+        &self,
+        name: String,
+        policy: Option<NameCollisionPolicy>,
+    ) -> Result<String, BlenderApiError> {
+        if !self.materials.contains_key(&name) {
+            return Ok(name);
+        }
+
+        match policy.unwrap_or_default() {
+            NameCollisionPolicy::Error => Err(BlenderApiError::MaterialAlreadyExists { name }),
+            NameCollisionPolicy::Replace => Ok(name),
+            NameCollisionPolicy::AutoSuffix => {
+                unique_name(&name, |name| self.materials.contains_key(name))
+            }
+        }
+    }
+
+    // Meshes are keyed by the name of the object that owns them
+    fn object_mesh(&self, name: &str) -> Option<&MeshGeometry> {
+        let owner = self
+            .objects
+            .get(name)
+            .and_then(|object| object.instance_of.as_deref())
+            .unwrap_or(name);
+        self.meshes.get(owner)
+    }
+
+    fn object_mesh_mut(&mut self, name: &str) -> Option<&mut MeshGeometry> {
+        let owner = self
+            .objects
+            .get(name)
+            .and_then(|object| object.instance_of.clone())
+            .unwrap_or_else(|| name.to_string());
+        self.meshes.get_mut(&owner)
+    }
+
+    /// Snapshot the scene as a [`SceneIr`], with objects and materials in name order.
+    pub fn to_scene_ir(&self) -> SceneIr {
+        let mut object_names = self.objects.keys().collect::<Vec<_>>();
+        object_names.sort();
+        let mut material_names = self.materials.keys().collect::<Vec<_>>();
+        material_names.sort();
+
+        let mut scene = SceneIr::default();
+        for name in object_names {
+            let object = &self.objects[name];
+            let kind = match self.object_mesh(name) {
+                Some(mesh) => {
+                    // Linked duplicates share their source's mesh
+                    if scene.find_mesh(&mesh.name).is_none() {
+                        scene.meshes.push(mesh.clone());
+                    }
+                    IrObjectKind::Mesh {
+                        mesh: mesh.name.clone(),
+                    }
+                }
+                None => match object.object_type {
+                    ObjectType::Light => IrObjectKind::Light {
+                        light: name.clone(),
+                    },
+                    ObjectType::Camera => IrObjectKind::Camera {
+                        camera: name.clone(),
+                    },
+                    _ => IrObjectKind::Empty,
+                },
+            };
+
+            scene.objects.push(IrObject {
+                name: object.name.clone(),
+                kind,
+                transform: Transform {
+                    location: object.location.clone(),
+                    rotation: object.rotation.clone(),
+                    scale: object.scale.clone(),
+                },
+                materials: object.materials.clone(),
+                parent: object.parent.clone(),
+                light_linking: object.light_linking.clone(),
+                shadow: object.shadow,
+            });
+        }
+        scene.materials = material_names
+            .into_iter()
+            .map(|name| self.materials[name].clone())
+            .collect();
+
+        scene
+    }
+
+    /// Add everything in a [`SceneIr`] to the scene, returning the names of the new objects.
+    ///
+    /// Names already in the scene get a numeric suffix, as Blender's importers do, and
+    /// references between the imported objects follow the renames. The mock has no light or
+    /// camera data, so those objects only keep their type and transform.
+    pub fn load_scene_ir(&mut self, scene: SceneIr) -> Result<Vec<String>, BlenderApiError> {
+        let mut material_names = HashMap::new();
+        for mut material in scene.materials {
+            let name = self.claim_material_name(
+                material.name.clone(),
+                Some(NameCollisionPolicy::AutoSuffix),
+            )?;
+            material_names.insert(material.name.clone(), name.clone());
+            material.name = name;
+            self.material_nodes.insert(
+                material.name.clone(),
+                principled_node_graph(&material_params(&material)),
+            );
+            self.materials.insert(material.name.clone(), material);
+        }
+        let material_name = |name: String| material_names.get(&name).cloned().unwrap_or(name);
+
+        // A mesh named after an object in the scene belongs to that object, anything else
+        // using it is a linked duplicate
+        let owners = scene
+            .objects
+            .iter()
+            .filter(|object| matches!(&object.kind, IrObjectKind::Mesh { mesh } if mesh == &object.name))
+            .map(|object| object.name.clone())
+            .collect::<HashSet<_>>();
+
+        let mut object_names = HashMap::new();
+        let mut names = Vec::new();
+        for object in scene.objects {
+            let name =
+                self.claim_object_name(object.name.clone(), Some(NameCollisionPolicy::AutoSuffix))?;
+            object_names.insert(object.name.clone(), name.clone());
+
+            let (object_type, mesh) = match &object.kind {
+                IrObjectKind::Mesh { mesh } => (
+                    ObjectType::Mesh,
+                    scene.meshes.iter().find(|m| &m.name == mesh),
+                ),
+                IrObjectKind::Light { .. } => (ObjectType::Light, None),
+                IrObjectKind::Camera { .. } => (ObjectType::Camera, None),
+                IrObjectKind::Empty => (ObjectType::Empty, None),
+            };
+
+            let instance_of = match &object.kind {
+                IrObjectKind::Mesh { mesh } if mesh != &object.name && owners.contains(mesh) => {
+                    Some(mesh.clone())
+                }
+                _ => None,
+            };
+
+            // Meshes are keyed by object name in the mock
+            if let (Some(mesh), None) = (mesh, &instance_of) {
+                let mut mesh = mesh.clone();
+                mesh.name = name.clone();
+                self.meshes.insert(name.clone(), mesh);
+            }
+
+            names.push(name.clone());
+            self.objects.insert(
+                name.clone(),
+                ObjectData {
+                    name,
+                    object_type,
+                    location: object.transform.location,
+                    rotation: object.transform.rotation,
+                    scale: object.transform.scale,
+                    materials: object.materials.into_iter().map(material_name).collect(),
+                    vertex_count: mesh.map(|mesh| mesh.vertices.len()),
+                    face_count: mesh.map(|mesh| mesh.faces.len()),
+                    parent: object.parent,
+                    instance_of,
+                    rigid_body: None,
+                    light_linking: object.light_linking,
+                    shadow: object.shadow,
+                },
+            );
+        }
+
+        // References are written against the names in the IR, so point them at the final ones
+        let rename = |name: &mut String| {
+            if let Some(renamed) = object_names.get(name.as_str()) {
+                name.clone_from(renamed);
+            }
+        };
+        for name in &names {
+            let Some(object) = self.objects.get_mut(name) else {
+                continue;
+            };
+            object.parent.iter_mut().for_each(rename);
+            object.instance_of.iter_mut().for_each(rename);
+            if let Some(linking) = &mut object.light_linking {
+                linking.include_objects.iter_mut().for_each(rename);
+                linking.exclude_objects.iter_mut().for_each(rename);
+            }
+        }
+
+        Ok(names)
+    }
+}
+
+// The parameters that would create `material` as it is now
+fn material_params(material: &MaterialData) -> CreateMaterialParams {
+    CreateMaterialParams {
+        name: material.name.clone(),
+        base_color: material.base_color.clone(),
+        metallic: material.metallic,
+        roughness: material.roughness,
+        emission_color: material.emission_color.clone(),
+        emission_strength: material.emission_strength,
+        alpha: material.alpha,
+        ior: material.ior,
+        specular: material.specular,
+        transmission: material.transmission,
+        normal_strength: material.normal_strength,
+        on_collision: None,
+    }
+}
+
+// Default shader network for a new material: a single Principled BSDF
+fn principled_node_graph(params: &CreateMaterialParams) -> BlenderNodeGraph {
+    let color = &params.base_color;
+    let emission = &params.emission_color;
+    let socket = |name: &str, socket_type: &str, value: BlenderValue| BlenderSocket {
+        name: name.to_string(),
+        socket_type: socket_type.to_string(),
+        default_value: Some(value),
+    };
+
+    BlenderNodeGraph {
+        nodes: vec![BlenderNode {
+            node_type: "ShaderNodeBsdfPrincipled".to_string(),
+            location: (0.0, 0.0),
+            inputs: vec![
+                socket(
+                    "Base Color",
+                    "NodeSocketColor",
+                    BlenderValue::Color(
+                        color.r.into(),
+                        color.g.into(),
+                        color.b.into(),
+                        color.a.into(),
+                    ),
+                ),
+                socket(
+                    "Metallic",
+                    "NodeSocketFloat",
+                    BlenderValue::Float(params.metallic.into()),
+                ),
+                socket(
+                    "Roughness",
+                    "NodeSocketFloat",
+                    BlenderValue::Float(params.roughness.into()),
+                ),
+                socket(
+                    "IOR",
+                    "NodeSocketFloat",
+                    BlenderValue::Float(params.ior.into()),
+                ),
+                socket(
+                    "Alpha",
+                    "NodeSocketFloat",
+                    BlenderValue::Float(params.alpha.into()),
+                ),
+                socket(
+                    "Specular IOR Level",
+                    "NodeSocketFloat",
+                    BlenderValue::Float(params.specular.into()),
+                ),
+                socket(
+                    "Transmission Weight",
+                    "NodeSocketFloat",
+                    BlenderValue::Float(params.transmission.into()),
+                ),
+                socket(
+                    "Emission Color",
+                    "NodeSocketColor",
+                    BlenderValue::Color(
+                        emission.r.into(),
+                        emission.g.into(),
+                        emission.b.into(),
+                        emission.a.into(),
+                    ),
+                ),
+                socket(
+                    "Emission Strength",
+                    "NodeSocketFloat",
+                    BlenderValue::Float(params.emission_strength.into()),
+                ),
+            ],
+            outputs: vec![BlenderSocket {
+                name: "BSDF".to_string(),
+                socket_type: "NodeSocketShader".to_string(),
+                default_value: None,
+            }],
+            parameters: HashMap::new(),
+        }],
+        links: vec![],
+    }
+}
+
+const MOCK_COLLECTION: &str = "Collection";
+
+// Past this the mock gives up rather than searching forever
+const MAX_NAME_SUFFIX: u32 = 999;
+
+// Blender's naming for duplicates: "Cube" and "Cube.001" both continue as "Cube.002" and so on
+fn unique_name(name: &str, taken: impl Fn(&str) -> bool) -> Result<String, BlenderApiError> {
+    let base = match name.rsplit_once('.') {
+        Some((base, suffix)) if suffix.len() >= 3 && suffix.chars().all(|c| c.is_ascii_digit()) => {
+            base
+        }
+        _ => name,
+    };
+
+    (1..=MAX_NAME_SUFFIX)
+        .map(|n| format!("{base}.{n:03}"))
+        .find(|candidate| !taken(candidate))
+        .ok_or_else(|| BlenderApiError::InvalidParameters {
+            message: format!("No unused name left for {name}"),
+        })
+}
+
+fn check_graph_links(graph: &BlenderNodeGraph) -> Result<(), BlenderApiError> {
+    for link in &graph.links {
+        if link.from_node >= graph.nodes.len() || link.to_node >= graph.nodes.len() {
+            return Err(BlenderApiError::InvalidParameters {
+                message: format!(
+                    "Link {} -> {} references a node outside the graph",
+                    link.from_node, link.to_node
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+// Channels are written as is, without the sRGB transform Blender applies to color passes
+fn write_flat_png(path: &str, size: u32, color: &Color) -> Result<(), BlenderApiError> {
+    let failed = |e: &dyn std::fmt::Display| BlenderApiError::OperationFailed {
+        message: format!("Failed to write {path}: {e}"),
+    };
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let pixel = [
+        channel(color.r),
+        channel(color.g),
+        channel(color.b),
+        channel(color.a),
+    ];
+
+    let file = std::fs::File::create(path).map_err(|e| failed(&e))?;
+    let mut encoder = png::Encoder::new(std::io::BufWriter::new(file), size, size);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| failed(&e))?;
+    let data = pixel.repeat(size as usize * size as usize);
+    writer.write_image_data(&data).map_err(|e| failed(&e))
+}
+
+// Applies scale, rotation and location, in that order, like Blender does
+fn to_world_space(object: &ObjectData, point: &Vec3) -> Vec3 {
+    let scaled = Vec3::new(
+        point.x * object.scale.x,
+        point.y * object.scale.y,
+        point.z * object.scale.z,
+    );
+    let rotated = object.rotation.rotate(&scaled);
+
+    Vec3::new(
+        rotated.x + object.location.x,
+        rotated.y + object.location.y,
+        rotated.z + object.location.z,
+    )
+}
+
+// Cube centered on the origin with the size baked into the vertices, like
+// `bpy.ops.mesh.primitive_cube_add`
+fn cube_geometry(name: &str, size: f32) -> MeshGeometry {
+    let h = size / 2.0;
+    let vertices = vec![
+        Vec3::new(-h, -h, -h),
+        Vec3::new(h, -h, -h),
+        Vec3::new(h, h, -h),
+        Vec3::new(-h, h, -h),
+        Vec3::new(-h, -h, h),
+        Vec3::new(h, -h, h),
+        Vec3::new(h, h, h),
+        Vec3::new(-h, h, h),
+    ];
+    let faces = vec![
+        vec![0, 3, 2, 1],
+        vec![4, 5, 6, 7],
+        vec![0, 1, 5, 4],
+        vec![1, 2, 6, 5],
+        vec![2, 3, 7, 6],
+        vec![3, 0, 4, 7],
+    ];
+
+    MeshGeometry {
+        name: name.to_string(),
+        vertices,
+        edges: edges_from_faces(&faces),
+        faces,
+        face_materials: Vec::new(),
+    }
+}
+
+// UV sphere with poles on the z axis and the radius baked into the vertices, like
+// `bpy.ops.mesh.primitive_uv_sphere_add`: segments * (rings - 1) + 2 vertices and
+// segments * rings faces
+fn uv_sphere_geometry(name: &str, radius: f32, segments: usize, rings: usize) -> MeshGeometry {
+    let mut vertices = vec![Vec3::new(0.0, 0.0, radius)];
+    for ring in 1..rings {
+        let theta = std::f32::consts::PI * ring as f32 / rings as f32;
+        for segment in 0..segments {
+            let phi = std::f32::consts::TAU * segment as f32 / segments as f32;
+            vertices.push(Vec3::new(
+                radius * theta.sin() * phi.cos(),
+                radius * theta.sin() * phi.sin(),
+                radius * theta.cos(),
+            ));
+        }
+    }
+    vertices.push(Vec3::new(0.0, 0.0, -radius));
+
+    let bottom = vertices.len() - 1;
+    let ring_start = |ring: usize| 1 + ring * segments;
+    let mut faces = Vec::new();
+
+    for segment in 0..segments {
+        let next = (segment + 1) % segments;
+        faces.push(vec![0, ring_start(0) + segment, ring_start(0) + next]);
+    }
+    for ring in 0..rings.saturating_sub(2) {
+        for segment in 0..segments {
+            let next = (segment + 1) % segments;
+            faces.push(vec![
+                ring_start(ring) + segment,
+                ring_start(ring + 1) + segment,
+                ring_start(ring + 1) + next,
+                ring_start(ring) + next,
+            ]);
+        }
+    }
+    let last_ring = rings - 2;
+    for segment in 0..segments {
+        let next = (segment + 1) % segments;
+        faces.push(vec![
+            ring_start(last_ring) + next,
+            ring_start(last_ring) + segment,
+            bottom,
+        ]);
+    }
+
+    MeshGeometry {
+        name: name.to_string(),
+        vertices,
+        edges: edges_from_faces(&faces),
+        faces,
+        face_materials: Vec::new(),
+    }
+}
+
+// Unique undirected edges in first-seen order
+pub(crate) fn edges_from_faces(faces: &[Vec<usize>]) -> Vec<[usize; 2]> {
+    let mut seen = HashSet::new();
+    let mut edges = Vec::new();
+    for face in faces {
+        for (i, &a) in face.iter().enumerate() {
+            let b = face[(i + 1) % face.len()];
+            let edge = [a.min(b), a.max(b)];
+            if seen.insert(edge) {
+                edges.push(edge);
+            }
+        }
+    }
+    edges
+}
+
+impl Default for MockBlenderApi {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlenderApi for MockBlenderApi {
+    fn create_cube(&mut self, params: CreateCubeParams) -> Result<String, BlenderApiError> {
+        params.validate()?;
+        let name = self.claim_object_name(params.name, params.on_collision)?;
+        let mesh = cube_geometry(&name, params.size);
+        let object = ObjectData {
+            name: name.clone(),
+            object_type: ObjectType::Mesh,
+            location: params.location,
+            rotation: Rotation::default(),
+            scale: Vec3::new(1.0, 1.0, 1.0),
+            materials: Vec::new(),
+            vertex_count: Some(mesh.vertices.len()),
+            face_count: Some(mesh.faces.len()),
+            parent: None,
+            instance_of: None,
+            rigid_body: None,
+            light_linking: None,
+            shadow: ShadowVisibility::default(),
+        };
+
+        self.meshes.insert(name.clone(), mesh);
+        self.objects.insert(name.clone(), object);
+        Ok(name)
+    }
+
+    fn create_sphere(&mut self, params: CreateSphereParams) -> Result<String, BlenderApiError> {
+        params.validate()?;
+        let name = self.claim_object_name(params.name, params.on_collision)?;
+
+        // 8 subdivisions gives Blender's default 32 segment, 16 ring sphere
+        let segments = (params.subdivisions.max(1) * 4) as usize;
+        let rings = (params.subdivisions.max(1) * 2) as usize;
+        let mesh = uv_sphere_geometry(&name, params.radius, segments, rings);
+
+        let object = ObjectData {
+            name: name.clone(),
+            object_type: ObjectType::Mesh,
+            location: params.location,
+            rotation: Rotation::default(),
+            scale: Vec3::new(1.0, 1.0, 1.0),
+            materials: Vec::new(),
+            vertex_count: Some(mesh.vertices.len()),
+            face_count: Some(mesh.faces.len()),
+            parent: None,
+            instance_of: None,
+            rigid_body: None,
+            light_linking: None,
+            shadow: ShadowVisibility::default(),
+        };
+
+        self.meshes.insert(name.clone(), mesh);
+        self.objects.insert(name.clone(), object);
+        Ok(name)
+    }
+
+    fn create_mesh(&mut self, params: CreateMeshParams) -> Result<String, BlenderApiError> {
+        params.validate()?;
+        let name = self.claim_object_name(params.name, params.on_collision)?;
+        let mesh = MeshGeometry {
+            name: name.clone(),
+            vertices: params.vertices,
+            edges: edges_from_faces(&params.faces),
+            faces: params.faces,
+            face_materials: Vec::new(),
+        };
+
+        let object = ObjectData {
+            name: name.clone(),
+            object_type: ObjectType::Mesh,
+            location: params.location,
+            rotation: Rotation::default(),
+            scale: Vec3::new(1.0, 1.0, 1.0),
+            materials: Vec::new(),
+            vertex_count: Some(mesh.vertices.len()),
+            face_count: Some(mesh.faces.len()),
+            parent: None,
+            instance_of: None,
+            rigid_body: None,
+            light_linking: None,
+            shadow: ShadowVisibility::default(),
+        };
+
+        self.meshes.insert(name.clone(), mesh);
+        self.objects.insert(name.clone(), object);
+        Ok(name)
+    }
+
+    fn create_material(&mut self, params: CreateMaterialParams) -> Result<String, BlenderApiError> {
+        params.validate()?;
+        let name = self.claim_material_name(params.name.clone(), params.on_collision)?;
+        let nodes = principled_node_graph(&params);
+        let material = MaterialData {
+            name: name.clone(),
+            use_nodes: true,
+            base_color: params.base_color,
+            metallic: params.metallic,
+            roughness: params.roughness,
+            emission_color: params.emission_color,
+            emission_strength: params.emission_strength,
+            alpha: params.alpha,
+            ior: params.ior,
+            specular: params.specular,
+            transmission: params.transmission,
+            normal_strength: params.normal_strength,
+            node_count: 1, // Basic principled BSDF
+        };
+
+        self.material_nodes.insert(name.clone(), nodes);
+        self.materials.insert(name.clone(), material);
+        Ok(name)
     }
 
     fn assign_material(&mut self, params: AssignMaterialParams) -> Result<(), BlenderApiError> {
@@ -218,120 +1960,2390 @@ impl BlenderApi for MockBlenderApi {
             });
         }
 
-        if let Some(object) = self.objects.get_mut(&params.object_name) {
-            if !object.materials.contains(&params.material_name) {
-                object.materials.push(params.material_name);
-            }
-            Ok(())
-        } else {
-            Err(BlenderApiError::ObjectNotFound {
-                name: params.object_name,
+        if let Some(object) = self.objects.get_mut(&params.object_name) {
+            if !object.materials.contains(&params.material_name) {
+                object.materials.push(params.material_name);
+            }
+            Ok(())
+        } else {
+            Err(BlenderApiError::ObjectNotFound {
+                name: params.object_name,
+            })
+        }
+    }
+
+    fn get_object(&self, params: GetObjectParams) -> Result<ObjectData, BlenderApiError> {
+        self.objects
+            .get(&params.name)
+            .cloned()
+            .ok_or(BlenderApiError::ObjectNotFound { name: params.name })
+    }
+
+    fn get_material(&self, params: GetMaterialParams) -> Result<MaterialData, BlenderApiError> {
+        self.materials
+            .get(&params.name)
+            .cloned()
+            .ok_or(BlenderApiError::MaterialNotFound { name: params.name })
+    }
+
+    fn get_material_nodes(
+        &self,
+        params: GetMaterialNodesParams,
+    ) -> Result<BlenderNodeGraph, BlenderApiError> {
+        self.material_nodes
+            .get(&params.name)
+            .cloned()
+            .ok_or(BlenderApiError::MaterialNotFound { name: params.name })
+    }
+
+    fn set_material_nodes(
+        &mut self,
+        params: SetMaterialNodesParams,
+    ) -> Result<(), BlenderApiError> {
+        let Some(material) = self.materials.get_mut(&params.name) else {
+            return Err(BlenderApiError::MaterialNotFound { name: params.name });
+        };
+
+        check_graph_links(&params.graph)?;
+
+        material.use_nodes = true;
+        material.node_count = params.graph.nodes.len();
+        self.material_nodes.insert(params.name, params.graph);
+        Ok(())
+    }
+
+    fn get_mesh_geometry(
+        &self,
+        params: GetMeshGeometryParams,
+    ) -> Result<MeshGeometry, BlenderApiError> {
+        self.meshes
+            .get(&params.name)
+            .cloned()
+            .ok_or(BlenderApiError::ObjectNotFound { name: params.name })
+    }
+
+    fn set_world(&mut self, params: SetWorldParams) -> Result<(), BlenderApiError> {
+        if let WorldBackground::Hdri { path } = &params.background {
+            if path.is_empty() {
+                return Err(BlenderApiError::InvalidParameters {
+                    message: "HDRI path must not be empty".to_string(),
+                });
+            }
+        }
+
+        self.world = WorldData {
+            background: params.background,
+            strength: params.strength,
+        };
+        Ok(())
+    }
+
+    fn get_world(&self) -> Result<WorldData, BlenderApiError> {
+        Ok(self.world.clone())
+    }
+
+    fn set_scene_settings(&mut self, settings: SceneSettings) -> Result<(), BlenderApiError> {
+        if settings.frame_end < settings.frame_start {
+            return Err(BlenderApiError::InvalidParameters {
+                message: format!(
+                    "frame_end ({}) must not be before frame_start ({})",
+                    settings.frame_end, settings.frame_start
+                ),
+            });
+        }
+        if settings.fps == 0 {
+            return Err(BlenderApiError::InvalidParameters {
+                message: "fps must be greater than zero".to_string(),
+            });
+        }
+        if settings.resolution_x == 0 || settings.resolution_y == 0 {
+            return Err(BlenderApiError::InvalidParameters {
+                message: "resolution must be greater than zero".to_string(),
+            });
+        }
+
+        self.scene_settings = settings;
+        Ok(())
+    }
+
+    fn get_scene_settings(&self) -> Result<SceneSettings, BlenderApiError> {
+        Ok(self.scene_settings.clone())
+    }
+
+    fn set_units(&mut self, units: UnitSettings) -> Result<(), BlenderApiError> {
+        // Blender clamps scale_length to a small positive value
+        if !(units.scale_length > 0.0 && units.scale_length.is_finite()) {
+            return Err(BlenderApiError::InvalidParameters {
+                message: format!("scale_length must be positive, got {}", units.scale_length),
+            });
+        }
+
+        self.units = units;
+        Ok(())
+    }
+
+    fn get_units(&self) -> Result<UnitSettings, BlenderApiError> {
+        Ok(self.units.clone())
+    }
+
+    fn import_file(&mut self, params: ImportFileParams) -> Result<Vec<String>, BlenderApiError> {
+        if params.format != ImportFormat::Obj {
+            return Err(BlenderApiError::OperationFailed {
+                message: format!("Mock import does not support {:?} files", params.format),
+            });
+        }
+
+        let content = std::fs::read_to_string(&params.path).map_err(|e| {
+            BlenderApiError::OperationFailed {
+                message: format!("Failed to read {}: {e}", params.path),
+            }
+        })?;
+        let default_name = std::path::Path::new(&params.path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("Imported");
+
+        let scene = SceneIr::from_obj(&content, default_name)?;
+        self.load_scene_ir(scene)
+    }
+
+    fn export_scene(&self, params: ExportSceneParams) -> Result<Vec<String>, BlenderApiError> {
+        if params.format != ExportFormat::Obj {
+            return Err(BlenderApiError::OperationFailed {
+                message: format!("Mock export does not support {:?} files", params.format),
+            });
+        }
+
+        let mut scene = self.to_scene_ir();
+        if params.selected_only {
+            let selected: Vec<String> = self.selected.iter().cloned().collect();
+            scene.retain_objects(&selected);
+        }
+        let names = scene
+            .objects
+            .iter()
+            .filter(|object| matches!(object.kind, IrObjectKind::Mesh { .. }))
+            .map(|object| object.name.clone())
+            .collect();
+        let output = scene.to_obj();
+
+        std::fs::write(&params.path, output).map_err(|e| BlenderApiError::OperationFailed {
+            message: format!("Failed to write {}: {e}", params.path),
+        })?;
+
+        Ok(names)
+    }
+
+    fn get_scene_ir(&self) -> Result<SceneIr, BlenderApiError> {
+        Ok(self.to_scene_ir())
+    }
+
+    fn save_blend(&self, params: SaveBlendParams) -> Result<(), BlenderApiError> {
+        let content = serde_json::to_string(&self.snapshot_scene()).map_err(|e| {
+            BlenderApiError::OperationFailed {
+                message: format!("Failed to serialize scene: {e}"),
+            }
+        })?;
+
+        std::fs::write(&params.path, content).map_err(|e| BlenderApiError::OperationFailed {
+            message: format!("Failed to write {}: {e}", params.path),
+        })
+    }
+
+    // Opening a file replaces the whole scene, materials included, like it does in Blender
+    fn open_blend(&mut self, params: OpenBlendParams) -> Result<(), BlenderApiError> {
+        let content = std::fs::read_to_string(&params.path).map_err(|e| {
+            BlenderApiError::OperationFailed {
+                message: format!("Failed to read {}: {e}", params.path),
+            }
+        })?;
+        let snapshot: SceneSnapshot =
+            serde_json::from_str(&content).map_err(|e| BlenderApiError::OperationFailed {
+                message: format!("Failed to parse {}: {e}", params.path),
+            })?;
+
+        self.restore_scene(snapshot);
+        Ok(())
+    }
+
+    fn set_parent(&mut self, params: SetParentParams) -> Result<(), BlenderApiError> {
+        if !self.objects.contains_key(&params.child) {
+            return Err(BlenderApiError::ObjectNotFound { name: params.child });
+        }
+
+        if let Some(parent) = &params.parent {
+            if !self.objects.contains_key(parent) {
+                return Err(BlenderApiError::ObjectNotFound {
+                    name: parent.clone(),
+                });
+            }
+
+            let ancestors = self.get_ancestors(GetObjectParams {
+                name: parent.clone(),
+            })?;
+            if parent == &params.child || ancestors.contains(&params.child) {
+                return Err(BlenderApiError::InvalidParameters {
+                    message: format!(
+                        "Parenting {} to {} would create a cycle",
+                        params.child, parent
+                    ),
+                });
+            }
+        }
+
+        if let Some(child) = self.objects.get_mut(&params.child) {
+            child.parent = params.parent;
+        }
+        Ok(())
+    }
+
+    fn get_children(&self, params: GetObjectParams) -> Result<Vec<String>, BlenderApiError> {
+        if !self.objects.contains_key(&params.name) {
+            return Err(BlenderApiError::ObjectNotFound { name: params.name });
+        }
+
+        let mut children = self
+            .objects
+            .values()
+            .filter(|object| object.parent.as_ref() == Some(&params.name))
+            .map(|object| object.name.clone())
+            .collect::<Vec<_>>();
+        children.sort();
+        Ok(children)
+    }
+
+    fn get_ancestors(&self, params: GetObjectParams) -> Result<Vec<String>, BlenderApiError> {
+        let mut current =
+            self.objects
+                .get(&params.name)
+                .ok_or_else(|| BlenderApiError::ObjectNotFound {
+                    name: params.name.clone(),
+                })?;
+
+        let mut ancestors = Vec::new();
+        while let Some(parent) = &current.parent {
+            // set_parent rejects cycles, but don't hang on a corrupted scene
+            if ancestors.contains(parent) {
+                break;
+            }
+            ancestors.push(parent.clone());
+            match self.objects.get(parent) {
+                Some(object) => current = object,
+                None => break,
+            }
+        }
+        Ok(ancestors)
+    }
+
+    // The mock has no constraints, so constraint targets are always empty
+    fn get_dependencies(
+        &self,
+        params: GetObjectParams,
+    ) -> Result<ObjectDependencies, BlenderApiError> {
+        let object =
+            self.objects
+                .get(&params.name)
+                .ok_or_else(|| BlenderApiError::ObjectNotFound {
+                    name: params.name.clone(),
+                })?;
+
+        let modifiers = self
+            .geometry_nodes
+            .get(&params.name)
+            .map(|modifiers| modifiers.iter().map(|m| m.name.clone()).collect())
+            .unwrap_or_default();
+
+        Ok(ObjectDependencies {
+            materials: object.materials.clone(),
+            modifiers,
+            ..Default::default()
+        })
+    }
+
+    // Parent transforms aren't modelled by the mock, so bounds only use the object's own
+    fn get_bounding_box(&self, params: GetObjectParams) -> Result<BoundingBox, BlenderApiError> {
+        let object =
+            self.objects
+                .get(&params.name)
+                .ok_or_else(|| BlenderApiError::ObjectNotFound {
+                    name: params.name.clone(),
+                })?;
+
+        // Objects without geometry collapse to a point at their origin
+        let points = match self.object_mesh(&params.name) {
+            Some(mesh) if !mesh.vertices.is_empty() => mesh
+                .vertices
+                .iter()
+                .map(|vertex| to_world_space(object, vertex))
+                .collect(),
+            _ => vec![object.location.clone()],
+        };
+
+        BoundingBox::from_points(points).ok_or(BlenderApiError::OperationFailed {
+            message: format!("{} has no bounds", params.name),
+        })
+    }
+
+    fn select_objects(&mut self, params: SelectObjectsParams) -> Result<(), BlenderApiError> {
+        // Validate every name first so a bad name doesn't leave a partial selection
+        if let Some(name) = params
+            .names
+            .iter()
+            .find(|name| !self.objects.contains_key(*name))
+        {
+            return Err(BlenderApiError::ObjectNotFound { name: name.clone() });
+        }
+
+        self.selected.extend(params.names);
+        Ok(())
+    }
+
+    fn deselect_all(&mut self) -> Result<(), BlenderApiError> {
+        self.selected.clear();
+        Ok(())
+    }
+
+    fn get_selected(&self) -> Result<Vec<String>, BlenderApiError> {
+        Ok(self.selected.iter().cloned().collect())
+    }
+
+    fn apply_geometry_nodes(
+        &mut self,
+        params: ApplyGeometryNodesParams,
+    ) -> Result<String, BlenderApiError> {
+        if !self.objects.contains_key(&params.object) {
+            return Err(BlenderApiError::ObjectNotFound {
+                name: params.object,
+            });
+        }
+        check_graph_links(&params.graph)?;
+
+        // Named the way Blender names repeated modifiers
+        let modifiers = self.geometry_nodes.entry(params.object).or_default();
+        let name = match modifiers.len() {
+            0 => "GeometryNodes".to_string(),
+            n => format!("GeometryNodes.{n:03}"),
+        };
+        modifiers.push(GeometryNodesModifier {
+            name: name.clone(),
+            graph: params.graph,
+        });
+        Ok(name)
+    }
+
+    fn create_instance(&mut self, params: CreateInstanceParams) -> Result<String, BlenderApiError> {
+        params.validate()?;
+        let source = self.objects.get(&params.source).cloned().ok_or_else(|| {
+            BlenderApiError::ObjectNotFound {
+                name: params.source.clone(),
+            }
+        })?;
+        if params.name == params.source {
+            return Err(BlenderApiError::InvalidParameters {
+                message: format!("{} can't replace itself with an instance", params.name),
+            });
+        }
+        let name = self.claim_object_name(params.name, params.on_collision)?;
+
+        // Instancing an instance links to the original data, not to the intermediate object
+        let object = ObjectData {
+            name: name.clone(),
+            location: params.location,
+            parent: None,
+            instance_of: Some(source.instance_of.clone().unwrap_or(params.source)),
+            ..source
+        };
+        self.objects.insert(name.clone(), object);
+        Ok(name)
+    }
+
+    fn set_material_slot(&mut self, params: SetMaterialSlotParams) -> Result<(), BlenderApiError> {
+        if !self.materials.contains_key(&params.material) {
+            return Err(BlenderApiError::MaterialNotFound {
+                name: params.material,
+            });
+        }
+        let object = self.objects.get_mut(&params.object).ok_or_else(|| {
+            BlenderApiError::ObjectNotFound {
+                name: params.object.clone(),
+            }
+        })?;
+
+        match params.index.cmp(&object.materials.len()) {
+            std::cmp::Ordering::Less => object.materials[params.index] = params.material,
+            std::cmp::Ordering::Equal => object.materials.push(params.material),
+            std::cmp::Ordering::Greater => {
+                return Err(BlenderApiError::InvalidParameters {
+                    message: format!(
+                        "Slot {} is past the end of {}'s {} slots",
+                        params.index,
+                        params.object,
+                        object.materials.len()
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_material_slot(
+        &mut self,
+        params: RemoveMaterialSlotParams,
+    ) -> Result<(), BlenderApiError> {
+        let object = self.objects.get_mut(&params.object).ok_or_else(|| {
+            BlenderApiError::ObjectNotFound {
+                name: params.object.clone(),
+            }
+        })?;
+        if params.index >= object.materials.len() {
+            return Err(BlenderApiError::InvalidParameters {
+                message: format!("{} has no material slot {}", params.object, params.index),
+            });
+        }
+        object.materials.remove(params.index);
+
+        // Like Blender, faces on the removed slot fall back to the slot before it
+        if let Some(mesh) = self.object_mesh_mut(&params.object) {
+            for slot in &mut mesh.face_materials {
+                if *slot >= params.index {
+                    *slot = slot.saturating_sub(1);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn set_face_materials(
+        &mut self,
+        params: SetFaceMaterialsParams,
+    ) -> Result<(), BlenderApiError> {
+        let object =
+            self.objects
+                .get(&params.object)
+                .ok_or_else(|| BlenderApiError::ObjectNotFound {
+                    name: params.object.clone(),
+                })?;
+        if params.slot >= object.materials.len() {
+            return Err(BlenderApiError::InvalidParameters {
+                message: format!("{} has no material slot {}", params.object, params.slot),
+            });
+        }
+
+        let object_name = params.object.clone();
+        let mesh = self.object_mesh_mut(&params.object).ok_or_else(|| {
+            BlenderApiError::InvalidParameters {
+                message: format!("{object_name} has no mesh"),
+            }
+        })?;
+        if let Some(face) = params.faces.iter().find(|&&face| face >= mesh.faces.len()) {
+            return Err(BlenderApiError::InvalidParameters {
+                message: format!("{} has no face {}", params.object, face),
+            });
+        }
+
+        mesh.face_materials.resize(mesh.faces.len(), 0);
+        for face in params.faces {
+            mesh.face_materials[face] = params.slot;
+        }
+        Ok(())
+    }
+
+    fn set_transform(&mut self, params: SetTransformParams) -> Result<(), BlenderApiError> {
+        params.validate()?;
+        let object = self.objects.get_mut(&params.object).ok_or_else(|| {
+            BlenderApiError::ObjectNotFound {
+                name: params.object.clone(),
+            }
+        })?;
+
+        if let Some(Rotation::Quaternion { w, x, y, z }) = params.rotation {
+            if w == 0.0 && x == 0.0 && y == 0.0 && z == 0.0 {
+                return Err(BlenderApiError::InvalidParameters {
+                    message: "rotation quaternion must not be zero".to_string(),
+                });
+            }
+        }
+
+        if let Some(location) = params.location {
+            object.location = location;
+        }
+        if let Some(rotation) = params.rotation {
+            object.rotation = rotation;
+        }
+        if let Some(scale) = params.scale {
+            object.scale = scale;
+        }
+        Ok(())
+    }
+
+    // The mock has no renderer, so every pass bakes to a flat image of the value Blender would
+    // produce for an unlit, untextured surface
+    fn bake_texture(&mut self, params: BakeTextureParams) -> Result<BakedTexture, BlenderApiError> {
+        params.validate()?;
+        let object =
+            self.objects
+                .get(&params.object)
+                .ok_or_else(|| BlenderApiError::ObjectNotFound {
+                    name: params.object.clone(),
+                })?;
+        if self.object_mesh(&params.object).is_none() {
+            return Err(BlenderApiError::OperationFailed {
+                message: format!("{} has no mesh to bake", params.object),
+            });
+        }
+
+        let material = object
+            .materials
+            .first()
+            .and_then(|name| self.materials.get(name));
+        let base_color = material.map_or(Color::new(0.8, 0.8, 0.8, 1.0), |m| m.base_color.clone());
+        let color = match params.bake_type {
+            BakeType::Combined | BakeType::Diffuse => base_color,
+            BakeType::AmbientOcclusion => Color::white(),
+            // A flat tangent space normal pointing straight out of the surface
+            BakeType::Normal => Color::new(0.5, 0.5, 1.0, 1.0),
+            BakeType::Roughness => {
+                let roughness = material.map_or(0.5, |m| m.roughness);
+                Color::new(roughness, roughness, roughness, 1.0)
+            }
+            BakeType::Emit => material.map_or(Color::black(), |m| {
+                let c = &m.emission_color;
+                let s = m.emission_strength;
+                Color::new(c.r * s, c.g * s, c.b * s, 1.0)
+            }),
+        };
+
+        write_flat_png(&params.output_path, params.resolution, &color)?;
+        Ok(BakedTexture {
+            path: params.output_path,
+            width: params.resolution,
+            height: params.resolution,
+        })
+    }
+
+    fn add_rigid_body(&mut self, params: AddRigidBodyParams) -> Result<(), BlenderApiError> {
+        params.validate()?;
+        if self.object_mesh(&params.object).is_none() {
+            return match self.objects.contains_key(&params.object) {
+                true => Err(BlenderApiError::OperationFailed {
+                    message: format!("{} must be a mesh to have a rigid body", params.object),
+                }),
+                false => Err(BlenderApiError::ObjectNotFound {
+                    name: params.object,
+                }),
+            };
+        }
+
+        if let Some(object) = self.objects.get_mut(&params.object) {
+            object.rigid_body = Some(RigidBody {
+                body_type: params.body_type,
+                mass: params.mass,
+                friction: params.friction,
+            });
+        }
+        Ok(())
+    }
+
+    fn set_gravity(&mut self, gravity: Vec3) -> Result<(), BlenderApiError> {
+        if ![gravity.x, gravity.y, gravity.z]
+            .iter()
+            .all(|v| v.is_finite())
+        {
+            return Err(BlenderApiError::InvalidParameters {
+                message: "gravity must be finite".to_string(),
+            });
+        }
+        self.scene_settings.gravity = gravity;
+        Ok(())
+    }
+
+    fn set_light_linking(&mut self, params: SetLightLinkingParams) -> Result<(), BlenderApiError> {
+        let light =
+            self.objects
+                .get(&params.light)
+                .ok_or_else(|| BlenderApiError::ObjectNotFound {
+                    name: params.light.clone(),
+                })?;
+        if light.object_type != ObjectType::Light {
+            return Err(BlenderApiError::InvalidParameters {
+                message: format!("{} is a {}, not a light", params.light, light.object_type),
+            });
+        }
+        let linked = params.include_objects.iter().chain(&params.exclude_objects);
+        if let Some(name) = linked
+            .clone()
+            .find(|name| !self.objects.contains_key(*name))
+        {
+            return Err(BlenderApiError::ObjectNotFound { name: name.clone() });
+        }
+        if let Some(name) = params
+            .include_objects
+            .iter()
+            .find(|name| params.exclude_objects.contains(name))
+        {
+            return Err(BlenderApiError::InvalidParameters {
+                message: format!("{name} cannot be both included and excluded"),
+            });
+        }
+
+        let linking = LightLinking {
+            include_objects: params.include_objects,
+            exclude_objects: params.exclude_objects,
+        };
+        if let Some(light) = self.objects.get_mut(&params.light) {
+            // Linking nothing is the same as having no linking at all
+            light.light_linking = (linking != LightLinking::default()).then_some(linking);
+        }
+        Ok(())
+    }
+
+    fn set_shadow_visibility(
+        &mut self,
+        params: SetShadowVisibilityParams,
+    ) -> Result<(), BlenderApiError> {
+        let object = self.objects.get_mut(&params.object).ok_or_else(|| {
+            BlenderApiError::ObjectNotFound {
+                name: params.object.clone(),
+            }
+        })?;
+        if let Some(visible_shadow) = params.visible_shadow {
+            object.shadow.visible_shadow = visible_shadow;
+        }
+        if let Some(is_shadow_catcher) = params.is_shadow_catcher {
+            object.shadow.is_shadow_catcher = is_shadow_catcher;
+        }
+        Ok(())
+    }
+
+    // The mock has no collections; every object lives in Blender's default "Collection"
+    fn query_objects(&self, filter: ObjectFilter) -> Result<Vec<ObjectData>, BlenderApiError> {
+        if filter
+            .collection
+            .as_ref()
+            .is_some_and(|collection| collection != MOCK_COLLECTION)
+        {
+            return Ok(Vec::new());
+        }
+
+        let mut found = Vec::new();
+        for object in self.objects.values() {
+            if !filter.matches_object(object) {
+                continue;
+            }
+            if let Some(spatial) = &filter.spatial {
+                let bounds = self.get_bounding_box(GetObjectParams {
+                    name: object.name.clone(),
+                })?;
+                if !spatial.matches(&object.location, &bounds) {
+                    continue;
+                }
+            }
+            found.push(object.clone());
+        }
+        found.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(found)
+    }
+
+    // Properties resolve against the mock's own data model, see `property`
+    fn get_property(
+        &self,
+        params: GetPropertyParams,
+    ) -> Result<serde_json::Value, BlenderApiError> {
+        let path = &params.data_path;
+        match params.target {
+            PropertyTarget::Object(name) => match self.objects.get(&name) {
+                Some(object) => read_property(object, path),
+                None => Err(BlenderApiError::ObjectNotFound { name }),
+            },
+            PropertyTarget::Material(name) => match self.materials.get(&name) {
+                Some(material) => read_property(material, path),
+                None => Err(BlenderApiError::MaterialNotFound { name }),
+            },
+            PropertyTarget::Mesh(name) => match self.meshes.get(&name) {
+                Some(mesh) => read_property(mesh, path),
+                None => Err(BlenderApiError::ObjectNotFound { name }),
+            },
+            PropertyTarget::Scene => read_property(&self.scene_settings, path),
+            PropertyTarget::World => read_property(&self.world, path),
+        }
+    }
+
+    fn set_property(&mut self, params: SetPropertyParams) -> Result<(), BlenderApiError> {
+        let path = &params.data_path;
+        // Names key the mock's maps, so renaming through a property would orphan the entry
+        if path == "name" {
+            return Err(BlenderApiError::InvalidParameters {
+                message: "name is read-only".to_string(),
+            });
+        }
+
+        match params.target {
+            // Parenting goes through its setter so it can't make a cycle
+            PropertyTarget::Object(name) if path == "parent" => {
+                let parent = serde_json::from_value(params.value).map_err(|e| {
+                    BlenderApiError::InvalidParameters {
+                        message: format!("Invalid value for parent: {e}"),
+                    }
+                })?;
+                self.set_parent(SetParentParams {
+                    child: name,
+                    parent,
+                })?;
+            }
+            PropertyTarget::Object(name) => {
+                // These follow the object's data, which a property write can't keep in step
+                let field = path.split(['.', '[']).next().unwrap_or(path);
+                if ["object_type", "instance_of"].contains(&field) {
+                    return Err(BlenderApiError::InvalidParameters {
+                        message: format!("{field} can't be set as a property"),
+                    });
+                }
+                let object = self
+                    .objects
+                    .get(&name)
+                    .ok_or_else(|| BlenderApiError::ObjectNotFound { name: name.clone() })?;
+                let object = write_property(object, path, params.value)?;
+                self.objects.insert(name, object);
+            }
+            PropertyTarget::Material(name) => {
+                let material = self
+                    .materials
+                    .get(&name)
+                    .ok_or_else(|| BlenderApiError::MaterialNotFound { name: name.clone() })?;
+                let material: MaterialData = write_property(material, path, params.value)?;
+                material_params(&material).validate()?;
+                self.materials.insert(name, material);
+            }
+            PropertyTarget::Mesh(name) => {
+                let mesh = self
+                    .meshes
+                    .get(&name)
+                    .ok_or_else(|| BlenderApiError::ObjectNotFound { name: name.clone() })?;
+                let mesh: MeshGeometry = write_property(mesh, path, params.value)?;
+                mesh.validate()?;
+
+                // The mesh's owner and its linked duplicates all report its counts
+                for object in self.objects.values_mut() {
+                    if object.name == name || object.instance_of.as_deref() == Some(&name) {
+                        object.vertex_count = Some(mesh.vertices.len());
+                        object.face_count = Some(mesh.faces.len());
+                    }
+                }
+                self.meshes.insert(name, mesh);
+            }
+            // Scene and world changes go through their setters to get the same checks
+            PropertyTarget::Scene => {
+                let settings = write_property(&self.scene_settings, path, params.value)?;
+                self.set_scene_settings(settings)?;
+            }
+            PropertyTarget::World => {
+                let world: WorldData = write_property(&self.world, path, params.value)?;
+                self.set_world(SetWorldParams {
+                    background: world.background,
+                    strength: world.strength,
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    // Listings are sorted so HashMap ordering never leaks into responses
+    fn list_objects(&self) -> Result<Vec<String>, BlenderApiError> {
+        let mut objects: Vec<String> = self.objects.keys().cloned().collect();
+        objects.sort();
+        Ok(objects)
+    }
+
+    fn list_materials(&self) -> Result<Vec<String>, BlenderApiError> {
+        let mut materials: Vec<String> = self.materials.keys().cloned().collect();
+        materials.sort();
+        Ok(materials)
+    }
+
+    fn list_meshes(&self) -> Result<Vec<String>, BlenderApiError> {
+        let mut meshes: Vec<String> = self
+            .objects
+            .values()
+            // Linked duplicates share their source's mesh data
+            .filter(|obj| obj.object_type == ObjectType::Mesh && obj.instance_of.is_none())
+            .map(|obj| obj.name.clone())
+            .collect();
+        meshes.sort();
+        Ok(meshes)
+    }
+
+    fn clear_scene(&mut self) -> Result<(), BlenderApiError> {
+        self.objects.clear();
+        self.meshes.clear();
+        self.selected.clear();
+        self.geometry_nodes.clear();
+        // Note: materials are typically not cleared when clearing scene
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_cube() {
+        let mut api = MockBlenderApi::new();
+
+        let params = CreateCubeParams {
+            location: Vec3::new(1.0, 2.0, 3.0),
+            name: "TestCube".to_string(),
+            size: 2.0,
+            on_collision: None,
+        };
+
+        api.create_cube(params).expect("Failed to create cube");
+
+        let objects = api.list_objects().expect("Failed to list objects");
+        assert_eq!(objects, vec!["TestCube"]);
+
+        let cube = api
+            .get_object(GetObjectParams {
+                name: "TestCube".to_string(),
+            })
+            .expect("Failed to get cube");
+        assert_eq!(cube.name, "TestCube");
+        assert_eq!(cube.location.x, 1.0);
+        assert_eq!(cube.vertex_count, Some(8));
+    }
+
+    #[test]
+    fn test_error_from_message() {
+        let errors = [
+            BlenderApiError::ObjectNotFound {
+                name: "Cube".to_string(),
+            },
+            BlenderApiError::MaterialAlreadyExists {
+                name: "Red".to_string(),
+            },
+            BlenderApiError::InvalidParameters {
+                message: "size must be positive".to_string(),
+            },
+            BlenderApiError::OperationFailed {
+                message: "bpy exploded".to_string(),
+            },
+        ];
+        for error in errors {
+            let parsed = BlenderApiError::from_message(&error.to_string());
+            assert_eq!(parsed.to_string(), error.to_string());
+            assert_eq!(
+                std::mem::discriminant(&parsed),
+                std::mem::discriminant(&error)
+            );
+        }
+
+        assert!(matches!(
+            BlenderApiError::from_message("No service available to handle message"),
+            BlenderApiError::OperationFailed { message } if message == "No service available to handle message"
+        ));
+    }
+
+    #[test]
+    fn test_material_params_default_missing_fields() {
+        let mut json =
+            serde_json::to_value(CreateMaterialParams::default()).expect("Failed to serialize");
+        let fields = json.as_object_mut().expect("Expected an object");
+        for field in [
+            "emission_color",
+            "emission_strength",
+            "alpha",
+            "ior",
+            "specular",
+            "transmission",
+            "normal_strength",
+        ] {
+            fields.remove(field);
+        }
+
+        let params: CreateMaterialParams =
+            serde_json::from_value(json).expect("Failed to deserialize material params");
+        assert_eq!(
+            serde_json::to_value(params).expect("Failed to serialize"),
+            serde_json::to_value(CreateMaterialParams::default()).expect("Failed to serialize")
+        );
+    }
+
+    #[test]
+    fn test_create_material_and_assign() {
+        let mut api = MockBlenderApi::new();
+
+        // Create cube
+        api.create_cube(CreateCubeParams {
+            location: Vec3::zero(),
+            name: "TestCube".to_string(),
+            size: 1.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
+
+        // Create material
+        api.create_material(CreateMaterialParams {
+            name: "TestMaterial".to_string(),
+            base_color: Color::red(),
+            metallic: 0.0,
+            roughness: 0.5,
+            ..Default::default()
+        })
+        .expect("Failed to create material");
+
+        // Assign material
+        api.assign_material(AssignMaterialParams {
+            object_name: "TestCube".to_string(),
+            material_name: "TestMaterial".to_string(),
+        })
+        .expect("Failed to assign material");
+
+        // Verify assignment
+        let cube = api
+            .get_object(GetObjectParams {
+                name: "TestCube".to_string(),
+            })
+            .expect("Failed to get cube");
+        assert_eq!(cube.materials, vec!["TestMaterial"]);
+    }
+
+    #[test]
+    fn test_create_mesh() {
+        let mut api = MockBlenderApi::new();
+
+        let params = CreateMeshParams {
+            name: "Quad".to_string(),
+            vertices: vec![
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(1.0, 1.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+            ],
+            faces: vec![vec![0, 1, 2, 3]],
+            ..Default::default()
+        };
+        assert_eq!(
+            api.create_mesh(params.clone())
+                .expect("Failed to create mesh"),
+            "Quad"
+        );
+
+        let quad = api
+            .get_mesh_geometry(GetMeshGeometryParams {
+                name: "Quad".to_string(),
+            })
+            .expect("Failed to get quad geometry");
+        assert_eq!(quad.vertices.len(), 4);
+        assert_eq!(quad.edges.len(), 4);
+        assert_eq!(quad.faces, vec![vec![0, 1, 2, 3]]);
+
+        let out_of_range = api.create_mesh(CreateMeshParams {
+            name: "Broken".to_string(),
+            faces: vec![vec![0, 1, 4]],
+            ..params
+        });
+        assert!(matches!(
+            out_of_range,
+            Err(BlenderApiError::InvalidParameters { .. })
+        ));
+    }
+
+    #[test]
+    fn test_get_mesh_geometry() {
+        let mut api = MockBlenderApi::new();
+
+        api.create_cube(CreateCubeParams {
+            location: Vec3::zero(),
+            name: "Cube".to_string(),
+            size: 2.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
+
+        api.create_sphere(CreateSphereParams {
+            location: Vec3::zero(),
+            name: "Sphere".to_string(),
+            radius: 1.0,
+            subdivisions: 2,
+            on_collision: None,
+        })
+        .expect("Failed to create sphere");
+
+        let cube = api
+            .get_mesh_geometry(GetMeshGeometryParams {
+                name: "Cube".to_string(),
+            })
+            .expect("Failed to get cube geometry");
+        assert_eq!(cube.vertices.len(), 8);
+        assert_eq!(cube.edges.len(), 12);
+        assert_eq!(cube.faces.len(), 6);
+
+        // 8 segments, 4 rings: two poles plus three rings of 8 vertices
+        let sphere = api
+            .get_mesh_geometry(GetMeshGeometryParams {
+                name: "Sphere".to_string(),
+            })
+            .expect("Failed to get sphere geometry");
+        assert_eq!(sphere.vertices.len(), 26);
+        assert_eq!(sphere.faces.len(), 32);
+        assert_eq!(sphere.edges.len(), 56);
+
+        let missing = api.get_mesh_geometry(GetMeshGeometryParams {
+            name: "Missing".to_string(),
+        });
+        assert!(matches!(
+            missing,
+            Err(BlenderApiError::ObjectNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_material_nodes() {
+        let mut api = MockBlenderApi::new();
+
+        api.create_material(CreateMaterialParams {
+            name: "TestMaterial".to_string(),
+            base_color: Color::red(),
+            metallic: 0.0,
+            roughness: 0.5,
+            ..Default::default()
+        })
+        .expect("Failed to create material");
+
+        let mut graph = api
+            .get_material_nodes(GetMaterialNodesParams {
+                name: "TestMaterial".to_string(),
+            })
+            .expect("Failed to get material nodes");
+        assert_eq!(graph.nodes.len(), 1);
+        assert_eq!(graph.nodes[0].node_type, "ShaderNodeBsdfPrincipled");
+
+        let mut emission = graph.nodes[0].clone();
+        emission.node_type = "ShaderNodeEmission".to_string();
+        graph.nodes.push(emission);
+
+        api.set_material_nodes(SetMaterialNodesParams {
+            name: "TestMaterial".to_string(),
+            graph,
+        })
+        .expect("Failed to set material nodes");
+
+        let material = api
+            .get_material(GetMaterialParams {
+                name: "TestMaterial".to_string(),
+            })
+            .expect("Failed to get material");
+        assert_eq!(material.node_count, 2);
+
+        let missing = api.set_material_nodes(SetMaterialNodesParams {
+            name: "Missing".to_string(),
+            graph: BlenderNodeGraph {
+                nodes: vec![],
+                links: vec![],
+            },
+        });
+        assert!(matches!(
+            missing,
+            Err(BlenderApiError::MaterialNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_extended_pbr_parameters() {
+        let mut api = MockBlenderApi::new();
+
+        api.create_material(CreateMaterialParams {
+            name: "Glass".to_string(),
+            roughness: 0.05,
+            alpha: 0.2,
+            ior: 1.45,
+            transmission: 1.0,
+            emission_color: Color::white(),
+            emission_strength: 2.0,
+            ..Default::default()
+        })
+        .expect("Failed to create material");
+
+        let material = api
+            .get_material(GetMaterialParams {
+                name: "Glass".to_string(),
+            })
+            .expect("Failed to get material");
+        assert_eq!(material.alpha, 0.2);
+        assert_eq!(material.ior, 1.45);
+        assert_eq!(material.transmission, 1.0);
+        assert_eq!(material.emission_strength, 2.0);
+        assert_eq!(material.specular, 0.5);
+        assert_eq!(material.normal_strength, 1.0);
+    }
+
+    #[test]
+    fn test_world_settings() {
+        let mut api = MockBlenderApi::new();
+
+        let world = api.get_world().expect("Failed to get world");
+        assert_eq!(world.strength, 1.0);
+
+        api.set_world(SetWorldParams {
+            background: WorldBackground::Hdri {
+                path: "/tmp/studio.hdr".to_string(),
+            },
+            strength: 0.5,
+        })
+        .expect("Failed to set world");
+
+        let world = api.get_world().expect("Failed to get world");
+        assert_eq!(world.strength, 0.5);
+        assert!(matches!(
+            world.background,
+            WorldBackground::Hdri { ref path } if path == "/tmp/studio.hdr"
+        ));
+
+        let invalid = api.set_world(SetWorldParams {
+            background: WorldBackground::Hdri {
+                path: String::new(),
+            },
+            strength: 1.0,
+        });
+        assert!(matches!(
+            invalid,
+            Err(BlenderApiError::InvalidParameters { .. })
+        ));
+    }
+
+    #[test]
+    fn test_scene_settings() {
+        let mut api = MockBlenderApi::new();
+
+        api.set_scene_settings(SceneSettings {
+            frame_end: 120,
+            fps: 30,
+            render_engine: RenderEngine::Cycles,
+            ..Default::default()
+        })
+        .expect("Failed to set scene settings");
+
+        let settings = api
+            .get_scene_settings()
+            .expect("Failed to get scene settings");
+        assert_eq!(settings.frame_end, 120);
+        assert_eq!(settings.fps, 30);
+        assert_eq!(settings.render_engine, RenderEngine::Cycles);
+
+        let invalid = api.set_scene_settings(SceneSettings {
+            frame_start: 10,
+            frame_end: 5,
+            ..Default::default()
+        });
+        assert!(matches!(
+            invalid,
+            Err(BlenderApiError::InvalidParameters { .. })
+        ));
+    }
+
+    #[test]
+    fn test_import_obj() {
+        let path = std::env::temp_dir().join(format!("cuttle_import_{}.obj", std::process::id()));
+        std::fs::write(
+            &path,
+            "# two objects sharing the file's vertex list\n\
+             o Floor\n\
+             v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+             f 1 2 3 4\n\
+             o Wedge\n\
+             v 0 0 1\n\
+             f 1/1/1 2/2/2 -1\n",
+        )
+        .expect("Failed to write OBJ file");
+
+        let mut api = MockBlenderApi::new();
+        let imported = api
+            .import_file(ImportFileParams {
+                path: path.display().to_string(),
+                format: ImportFormat::Obj,
+            })
+            .expect("Failed to import OBJ");
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(imported, vec!["Floor", "Wedge"]);
+
+        let floor = api
+            .get_object(GetObjectParams {
+                name: "Floor".to_string(),
+            })
+            .expect("Failed to get floor");
+        assert_eq!(floor.vertex_count, Some(4));
+        assert_eq!(floor.face_count, Some(1));
+
+        let wedge = api
+            .get_mesh_geometry(GetMeshGeometryParams {
+                name: "Wedge".to_string(),
+            })
+            .expect("Failed to get wedge geometry");
+        assert_eq!(wedge.vertices.len(), 3);
+        assert_eq!(wedge.faces, vec![vec![0, 1, 2]]);
+
+        let unsupported = api.import_file(ImportFileParams {
+            path: "scene.fbx".to_string(),
+            format: ImportFormat::Fbx,
+        });
+        assert!(matches!(
+            unsupported,
+            Err(BlenderApiError::OperationFailed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_export_obj_round_trip() {
+        let mut api = MockBlenderApi::new();
+        api.create_cube(CreateCubeParams {
+            location: Vec3::zero(),
+            name: "Cube".to_string(),
+            size: 1.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
+
+        let path = std::env::temp_dir().join(format!("cuttle_export_{}.obj", std::process::id()));
+        let exported = api
+            .export_scene(ExportSceneParams {
+                path: path.display().to_string(),
+                format: ExportFormat::Obj,
+                selected_only: false,
+            })
+            .expect("Failed to export scene");
+        assert_eq!(exported, vec!["Cube"]);
+
+        let mut imported_api = MockBlenderApi::new();
+        let imported = imported_api
+            .import_file(ImportFileParams {
+                path: path.display().to_string(),
+                format: ImportFormat::Obj,
+            })
+            .expect("Failed to import exported scene");
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(imported, vec!["Cube"]);
+        let cube = imported_api
+            .get_object(GetObjectParams {
+                name: "Cube".to_string(),
+            })
+            .expect("Failed to get cube");
+        assert_eq!(cube.vertex_count, Some(8));
+        assert_eq!(cube.face_count, Some(6));
+    }
+
+    #[test]
+    fn test_export_selected_only() {
+        let mut api = MockBlenderApi::new();
+        for name in ["Kept", "Dropped"] {
+            api.create_cube(CreateCubeParams {
+                location: Vec3::zero(),
+                name: name.to_string(),
+                size: 1.0,
+                on_collision: None,
+            })
+            .expect("Failed to create cube");
+        }
+        api.select_objects(SelectObjectsParams {
+            names: vec!["Kept".to_string()],
+        })
+        .expect("Failed to select cube");
+
+        let path =
+            std::env::temp_dir().join(format!("cuttle_export_selected_{}.obj", std::process::id()));
+        let exported = api
+            .export_scene(ExportSceneParams {
+                path: path.display().to_string(),
+                format: ExportFormat::Obj,
+                selected_only: true,
+            })
+            .expect("Failed to export scene");
+        let content = std::fs::read_to_string(&path).expect("Failed to read export");
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(exported, vec!["Kept"]);
+        assert!(content.contains("o Kept"));
+        assert!(!content.contains("o Dropped"));
+    }
+
+    #[test]
+    fn test_scene_ir_snapshot() {
+        let mut api = MockBlenderApi::new();
+        api.create_cube(CreateCubeParams {
+            location: Vec3::new(1.0, 2.0, 3.0),
+            name: "Cube".to_string(),
+            size: 2.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
+        api.create_material(CreateMaterialParams {
+            name: "Red".to_string(),
+            base_color: Color::red(),
+            ..Default::default()
+        })
+        .expect("Failed to create material");
+
+        let scene = api.get_scene_ir().expect("Failed to get scene IR");
+        assert_eq!(scene.objects.len(), 1);
+        assert_eq!(scene.objects[0].transform.location.z, 3.0);
+        assert_eq!(scene.materials[0].name, "Red");
+        assert_eq!(
+            scene
+                .find_mesh("Cube")
+                .expect("Missing cube mesh")
+                .faces
+                .len(),
+            6
+        );
+
+        let mut copy = MockBlenderApi::new();
+        let names = copy.load_scene_ir(scene).expect("Failed to load scene IR");
+        assert_eq!(names, vec!["Cube"]);
+        let cube = copy
+            .get_object(GetObjectParams {
+                name: "Cube".to_string(),
+            })
+            .expect("Failed to get cube");
+        assert_eq!(cube.face_count, Some(6));
+        assert!(
+            copy.list_materials()
+                .expect("Failed to list")
+                .contains(&"Red".to_string())
+        );
+    }
+
+    #[test]
+    fn test_scene_state() {
+        let mut api = MockBlenderApi::new();
+        for name in ["B", "A"] {
+            api.create_cube(CreateCubeParams {
+                location: Vec3::zero(),
+                name: name.to_string(),
+                size: 2.0,
+                on_collision: None,
+            })
+            .expect("Failed to create cube");
+        }
+        api.create_material(CreateMaterialParams::default())
+            .expect("Failed to create material");
+
+        let state = api.get_scene_state().expect("Failed to get scene state");
+        let names = state
+            .objects
+            .iter()
+            .map(|o| o.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["A", "B"]);
+        assert_eq!(state.materials.len(), 1);
+        assert_eq!(state.material_nodes["Material"].nodes.len(), 1);
+        assert_eq!(state.meshes.len(), 2);
+        assert_eq!(state.scene_settings.fps, 24);
+    }
+
+    #[test]
+    fn test_save_and_open_blend() {
+        let path = std::env::temp_dir().join(format!("cuttle_mock_{}.blend", std::process::id()));
+        let path = path.display().to_string();
+
+        let mut api = MockBlenderApi::new();
+        api.create_cube(CreateCubeParams {
+            location: Vec3::zero(),
+            name: "Saved".to_string(),
+            size: 2.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
+        api.create_material(CreateMaterialParams::default())
+            .expect("Failed to create material");
+        api.save_blend(SaveBlendParams { path: path.clone() })
+            .expect("Failed to save blend");
+
+        api.clear_scene().expect("Failed to clear scene");
+        api.create_cube(CreateCubeParams {
+            location: Vec3::zero(),
+            name: "Unsaved".to_string(),
+            size: 2.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
+
+        let opened = api.open_blend(OpenBlendParams { path: path.clone() });
+        let _ = std::fs::remove_file(&path);
+        opened.expect("Failed to open blend");
+
+        assert_eq!(api.list_objects().expect("Failed to list"), vec!["Saved"]);
+        assert_eq!(api.list_meshes().expect("Failed to list"), vec!["Saved"]);
+        assert_eq!(
+            api.list_materials().expect("Failed to list"),
+            vec!["Material"]
+        );
+    }
+
+    #[test]
+    fn test_snapshot_and_restore_scene() {
+        let mut api = MockBlenderApi::new();
+        api.create_cube(CreateCubeParams {
+            location: Vec3::zero(),
+            name: "Base".to_string(),
+            size: 2.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
+        let snapshot = api.snapshot_scene();
+
+        api.create_material(CreateMaterialParams::default())
+            .expect("Failed to create material");
+        api.assign_material(AssignMaterialParams {
+            object_name: "Base".to_string(),
+            material_name: "Material".to_string(),
+        })
+        .expect("Failed to assign material");
+        api.set_scene_settings(SceneSettings {
+            fps: 60,
+            ..Default::default()
+        })
+        .expect("Failed to set scene settings");
+
+        api.restore_scene(snapshot);
+
+        let base = api
+            .get_object(GetObjectParams {
+                name: "Base".to_string(),
+            })
+            .expect("Failed to get object");
+        assert!(base.materials.is_empty());
+        assert!(api.list_materials().expect("Failed to list").is_empty());
+        assert_eq!(
+            api.get_scene_settings()
+                .expect("Failed to get settings")
+                .fps,
+            24
+        );
+    }
+
+    #[test]
+    fn test_hierarchy_queries() {
+        let mut api = MockBlenderApi::new();
+        for name in ["Root", "Arm", "Hand"] {
+            api.create_cube(CreateCubeParams {
+                location: Vec3::zero(),
+                name: name.to_string(),
+                size: 1.0,
+                on_collision: None,
+            })
+            .expect("Failed to create cube");
+        }
+        let parent = |child: &str, parent: &str| SetParentParams {
+            child: child.to_string(),
+            parent: Some(parent.to_string()),
+        };
+        let object = |name: &str| GetObjectParams {
+            name: name.to_string(),
+        };
+        api.set_parent(parent("Arm", "Root"))
+            .expect("Failed to parent arm");
+        api.set_parent(parent("Hand", "Arm"))
+            .expect("Failed to parent hand");
+
+        assert_eq!(
+            api.get_children(object("Root"))
+                .expect("Failed to get children"),
+            vec!["Arm"]
+        );
+        assert_eq!(
+            api.get_ancestors(object("Hand"))
+                .expect("Failed to get ancestors"),
+            vec!["Arm", "Root"]
+        );
+        assert!(matches!(
+            api.set_parent(parent("Root", "Hand")),
+            Err(BlenderApiError::InvalidParameters { .. })
+        ));
+
+        api.create_material(CreateMaterialParams::default())
+            .expect("Failed to create material");
+        api.assign_material(AssignMaterialParams {
+            object_name: "Hand".to_string(),
+            material_name: "Material".to_string(),
+        })
+        .expect("Failed to assign material");
+        let dependencies = api
+            .get_dependencies(object("Hand"))
+            .expect("Failed to get dependencies");
+        assert_eq!(dependencies.materials, vec!["Material"]);
+    }
+
+    #[test]
+    fn test_get_bounding_box() {
+        let mut api = MockBlenderApi::new();
+        api.create_cube(CreateCubeParams {
+            location: Vec3::new(1.0, 0.0, 0.0),
+            name: "Cube".to_string(),
+            size: 2.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
+
+        let bounds = api
+            .get_bounding_box(GetObjectParams {
+                name: "Cube".to_string(),
+            })
+            .expect("Failed to get bounding box");
+        assert_eq!(bounds.min.x, 0.0);
+        assert_eq!(bounds.max.x, 2.0);
+        assert_eq!(bounds.dimensions.z, 2.0);
+
+        let state = api.get_scene_state().expect("Failed to get scene state");
+        assert!(state.bounding_boxes.contains_key("Cube"));
+    }
+
+    #[test]
+    fn test_selection() {
+        let mut api = MockBlenderApi::new();
+        for name in ["A", "B"] {
+            api.create_cube(CreateCubeParams {
+                location: Vec3::zero(),
+                name: name.to_string(),
+                size: 1.0,
+                on_collision: None,
+            })
+            .expect("Failed to create cube");
+        }
+        let select = |names: &[&str]| SelectObjectsParams {
+            names: names.iter().map(|name| name.to_string()).collect(),
+        };
+
+        api.select_objects(select(&["B"]))
+            .expect("Failed to select objects");
+        api.select_objects(select(&["A"]))
+            .expect("Failed to select objects");
+        assert_eq!(
+            api.get_selected().expect("Failed to get selection"),
+            vec!["A", "B"]
+        );
+
+        assert!(api.select_objects(select(&["A", "Missing"])).is_err());
+        api.deselect_all().expect("Failed to deselect");
+        assert!(
+            api.get_selected()
+                .expect("Failed to get selection")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_apply_geometry_nodes() {
+        let mut api = MockBlenderApi::new();
+        api.create_cube(CreateCubeParams {
+            location: Vec3::zero(),
+            name: "Cube".to_string(),
+            size: 1.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
+
+        let graph = cuttle_lang::parse_geometry_nodes("cube { size: 2.0 }\noutput")
+            .expect("Failed to parse graph");
+        let graph = BlenderNodeGraph::try_from(graph).expect("Failed to convert graph");
+        let apply = |graph: &BlenderNodeGraph| ApplyGeometryNodesParams {
+            object: "Cube".to_string(),
+            graph: graph.clone(),
+            force: false,
+        };
+        assert_eq!(
+            api.apply_geometry_nodes(apply(&graph))
+                .expect("Failed to apply geometry nodes"),
+            "GeometryNodes"
+        );
+        assert_eq!(
+            api.apply_geometry_nodes(apply(&graph))
+                .expect("Failed to apply geometry nodes"),
+            "GeometryNodes.001"
+        );
+
+        let dependencies = api
+            .get_dependencies(GetObjectParams {
+                name: "Cube".to_string(),
             })
-        }
+            .expect("Failed to get dependencies");
+        assert_eq!(
+            dependencies.modifiers,
+            vec!["GeometryNodes", "GeometryNodes.001"]
+        );
     }
 
-    fn get_object(&self, params: GetObjectParams) -> Result<ObjectData, BlenderApiError> {
-        self.objects
-            .get(&params.name)
-            .cloned()
-            .ok_or(BlenderApiError::ObjectNotFound { name: params.name })
-    }
+    #[test]
+    fn test_create_instance() {
+        let mut api = MockBlenderApi::new();
+        api.create_cube(CreateCubeParams {
+            location: Vec3::zero(),
+            name: "Tree".to_string(),
+            size: 1.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
 
-    fn get_material(&self, params: GetMaterialParams) -> Result<MaterialData, BlenderApiError> {
-        self.materials
-            .get(&params.name)
-            .cloned()
-            .ok_or(BlenderApiError::MaterialNotFound { name: params.name })
+        let instance = |source: &str, name: &str| CreateInstanceParams {
+            source: source.to_string(),
+            name: name.to_string(),
+            location: Vec3::new(4.0, 0.0, 0.0),
+            on_collision: None,
+        };
+        api.create_instance(instance("Tree", "Tree.001"))
+            .expect("Failed to create instance");
+        api.create_instance(instance("Tree.001", "Tree.002"))
+            .expect("Failed to create instance");
+        assert!(matches!(
+            api.create_instance(CreateInstanceParams {
+                on_collision: Some(NameCollisionPolicy::Error),
+                ..instance("Tree", "Tree.001")
+            }),
+            Err(BlenderApiError::ObjectAlreadyExists { .. })
+        ));
+
+        let object = api
+            .get_object(GetObjectParams {
+                name: "Tree.002".to_string(),
+            })
+            .expect("Failed to get instance");
+        assert_eq!(object.instance_of.as_deref(), Some("Tree"));
+        assert_eq!(api.list_meshes().expect("Failed to list meshes").len(), 1);
+
+        let bounds = api
+            .get_bounding_box(GetObjectParams {
+                name: "Tree.002".to_string(),
+            })
+            .expect("Failed to get bounding box");
+        assert_eq!(bounds.min.x, 3.5);
+
+        // Instances survive a round trip through the scene IR
+        let mut copy = MockBlenderApi::new();
+        copy.load_scene_ir(api.to_scene_ir())
+            .expect("Failed to load scene IR");
+        assert_eq!(copy.list_meshes().expect("Failed to list meshes").len(), 1);
+        assert_eq!(
+            copy.get_object(GetObjectParams {
+                name: "Tree.001".to_string(),
+            })
+            .expect("Failed to get instance")
+            .instance_of
+            .as_deref(),
+            Some("Tree")
+        );
     }
 
-    fn list_objects(&self) -> Result<Vec<String>, BlenderApiError> {
-        Ok(self.objects.keys().cloned().collect())
+    #[test]
+    fn test_units() {
+        let mut api = MockBlenderApi::new();
+        assert_eq!(
+            api.get_units().expect("Failed to get units").system,
+            UnitSystem::Metric
+        );
+
+        api.set_units(UnitSettings {
+            system: UnitSystem::Imperial,
+            scale_length: 0.3048,
+        })
+        .expect("Failed to set units");
+        let state = api.get_scene_state().expect("Failed to get scene state");
+        assert_eq!(state.units.system, UnitSystem::Imperial);
+        assert_eq!(state.units.scale_length, 0.3048);
+
+        assert!(
+            api.set_units(UnitSettings {
+                system: UnitSystem::Metric,
+                scale_length: 0.0,
+            })
+            .is_err()
+        );
     }
 
-    fn list_materials(&self) -> Result<Vec<String>, BlenderApiError> {
-        Ok(self.materials.keys().cloned().collect())
+    #[test]
+    fn test_material_slots() {
+        let mut api = MockBlenderApi::new();
+        api.create_cube(CreateCubeParams {
+            location: Vec3::zero(),
+            name: "Cube".to_string(),
+            size: 1.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
+        for name in ["Red", "Blue", "Green"] {
+            api.create_material(CreateMaterialParams {
+                name: name.to_string(),
+                ..Default::default()
+            })
+            .expect("Failed to create material");
+        }
+
+        let slot = |index: usize, material: &str| SetMaterialSlotParams {
+            object: "Cube".to_string(),
+            index,
+            material: material.to_string(),
+        };
+        api.set_material_slot(slot(0, "Red"))
+            .expect("Failed to set slot");
+        api.set_material_slot(slot(1, "Blue"))
+            .expect("Failed to set slot");
+        api.set_material_slot(slot(2, "Green"))
+            .expect("Failed to set slot");
+        assert!(api.set_material_slot(slot(5, "Red")).is_err());
+
+        api.set_face_materials(SetFaceMaterialsParams {
+            object: "Cube".to_string(),
+            slot: 2,
+            faces: vec![0, 1],
+        })
+        .expect("Failed to set face materials");
+        api.remove_material_slot(RemoveMaterialSlotParams {
+            object: "Cube".to_string(),
+            index: 1,
+        })
+        .expect("Failed to remove slot");
+
+        let state = api.get_scene_state().expect("Failed to get scene state");
+        assert_eq!(state.objects[0].materials, vec!["Red", "Green"]);
+        assert_eq!(state.meshes[0].face_materials, vec![1, 1, 0, 0, 0, 0]);
     }
 
-    fn list_meshes(&self) -> Result<Vec<String>, BlenderApiError> {
-        Ok(self
-            .objects
-            .values()
-            .filter(|obj| obj.object_type == "MESH")
-            .map(|obj| obj.name.clone())
-            .collect())
+    #[test]
+    fn test_name_collision_policy() {
+        let mut api = MockBlenderApi::new();
+        let cube = |on_collision| CreateCubeParams {
+            location: Vec3::zero(),
+            name: "Cube".to_string(),
+            size: 1.0,
+            on_collision,
+        };
+
+        assert_eq!(
+            api.create_cube(cube(None)).expect("Failed to create cube"),
+            "Cube"
+        );
+        assert_eq!(
+            api.create_cube(cube(None)).expect("Failed to replace cube"),
+            "Cube"
+        );
+        assert!(matches!(
+            api.create_cube(cube(Some(NameCollisionPolicy::Error))),
+            Err(BlenderApiError::ObjectAlreadyExists { .. })
+        ));
+        assert_eq!(
+            api.create_cube(cube(Some(NameCollisionPolicy::AutoSuffix)))
+                .expect("Failed to create cube"),
+            "Cube.001"
+        );
+        assert_eq!(
+            api.create_cube(cube(Some(NameCollisionPolicy::AutoSuffix)))
+                .expect("Failed to create cube"),
+            "Cube.002"
+        );
+        assert_eq!(api.list_objects().expect("Failed to list objects").len(), 3);
+
+        api.create_material(CreateMaterialParams::default())
+            .expect("Failed to create material");
+        assert!(matches!(
+            api.create_material(CreateMaterialParams {
+                on_collision: Some(NameCollisionPolicy::Error),
+                ..Default::default()
+            }),
+            Err(BlenderApiError::MaterialAlreadyExists { .. })
+        ));
     }
 
-    fn clear_scene(&mut self) -> Result<(), BlenderApiError> {
-        self.objects.clear();
-        // Note: materials are typically not cleared when clearing scene
-        Ok(())
+    #[test]
+    fn test_unique_name_runs_out_of_suffixes() {
+        assert_eq!(
+            unique_name("Cube.001", |name| name == "Cube.001").expect("Expected a name"),
+            "Cube.002"
+        );
+        assert!(matches!(
+            unique_name("Cube", |_| true),
+            Err(BlenderApiError::InvalidParameters { .. })
+        ));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_object_type_serialization() {
+        let types = vec![
+            ObjectType::Mesh,
+            ObjectType::LightProbe,
+            ObjectType::Custom("FUTURE_TYPE".to_string()),
+        ];
+        let json = serde_json::to_string(&types).expect("Failed to serialize object types");
+        assert_eq!(json, r#"["MESH","LIGHT_PROBE","FUTURE_TYPE"]"#);
+
+        let parsed: Vec<ObjectType> =
+            serde_json::from_str(&json).expect("Failed to deserialize object types");
+        assert_eq!(parsed, types);
+    }
 
     #[test]
-    fn test_create_cube() {
-        let mut api = MockBlenderApi::new();
+    fn test_rotation_modes() {
+        let quarter = std::f32::consts::FRAC_PI_2;
+        let point = Vec3::new(1.0, 0.0, 0.0);
+        let close = |a: Vec3, b: Vec3| {
+            (a.x - b.x).abs() < 1e-5 && (a.y - b.y).abs() < 1e-5 && (a.z - b.z).abs() < 1e-5
+        };
 
-        let params = CreateCubeParams {
-            location: Vec3::new(1.0, 2.0, 3.0),
-            name: "TestCube".to_string(),
-            size: 2.0,
+        let euler = Rotation::euler(0.0, 0.0, quarter);
+        let quaternion = Rotation::Quaternion {
+            w: (quarter / 2.0).cos(),
+            x: 0.0,
+            y: 0.0,
+            z: (quarter / 2.0).sin(),
         };
+        let axis_angle = Rotation::AxisAngle {
+            angle: quarter,
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        };
+        for rotation in [&euler, &quaternion, &axis_angle] {
+            assert!(close(rotation.rotate(&point), Vec3::new(0.0, 1.0, 0.0)));
+        }
 
-        api.create_cube(params).expect("Failed to create cube");
+        // Rotation modes survive serialization, and bare vectors read as XYZ eulers
+        let json = serde_json::to_string(&quaternion).expect("Failed to serialize rotation");
+        let parsed: Rotation = serde_json::from_str(&json).expect("Failed to parse rotation");
+        assert_eq!(parsed, quaternion);
+        let legacy: Rotation =
+            serde_json::from_str(r#"{"x": 0.0, "y": 0.0, "z": 1.0}"#).expect("Failed to parse");
+        assert_eq!(legacy, Rotation::euler(0.0, 0.0, 1.0));
 
-        let objects = api.list_objects().expect("Failed to list objects");
-        assert_eq!(objects, vec!["TestCube"]);
+        let mut api = MockBlenderApi::new();
+        api.create_cube(CreateCubeParams {
+            location: Vec3::zero(),
+            name: "Bone".to_string(),
+            size: 1.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
+        api.set_transform(SetTransformParams {
+            object: "Bone".to_string(),
+            location: None,
+            rotation: Some(quaternion.clone()),
+            scale: None,
+        })
+        .expect("Failed to set transform");
+        let object = api
+            .get_object(GetObjectParams {
+                name: "Bone".to_string(),
+            })
+            .expect("Failed to get object");
+        assert_eq!(object.rotation, quaternion);
+    }
 
-        let cube = api
+    #[test]
+    fn test_sphere_topology_matches_blender() {
+        let mut api = MockBlenderApi::new();
+        api.create_sphere(CreateSphereParams {
+            location: Vec3::zero(),
+            name: "Sphere".to_string(),
+            radius: 2.0,
+            subdivisions: 8,
+            on_collision: None,
+        })
+        .expect("Failed to create sphere");
+
+        let sphere = api
             .get_object(GetObjectParams {
-                name: "TestCube".to_string(),
+                name: "Sphere".to_string(),
             })
-            .expect("Failed to get cube");
-        assert_eq!(cube.name, "TestCube");
-        assert_eq!(cube.location.x, 1.0);
-        assert_eq!(cube.vertex_count, Some(8));
+            .expect("Failed to get sphere");
+        assert_eq!(sphere.vertex_count, Some(482));
+        assert_eq!(sphere.face_count, Some(512));
+
+        let mesh = api
+            .get_mesh_geometry(GetMeshGeometryParams {
+                name: "Sphere".to_string(),
+            })
+            .expect("Failed to get mesh");
+        assert_eq!(mesh.vertices.len(), 482);
+        assert_eq!(mesh.edges.len(), 992);
+        // Closed surface: V - E + F = 2
+        assert_eq!(mesh.vertices.len() + mesh.faces.len() - mesh.edges.len(), 2);
+
+        let bounds = api
+            .get_bounding_box(GetObjectParams {
+                name: "Sphere".to_string(),
+            })
+            .expect("Failed to get bounding box");
+        assert!((bounds.dimensions.z - 4.0).abs() < 1e-5);
     }
 
     #[test]
-    fn test_create_material_and_assign() {
+    fn test_bake_texture() {
+        let path = std::env::temp_dir().join(format!("cuttle_bake_{}.png", std::process::id()));
+        let path = path.display().to_string();
+
         let mut api = MockBlenderApi::new();
+        api.create_cube(CreateCubeParams {
+            location: Vec3::zero(),
+            name: "Cube".to_string(),
+            size: 2.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
 
-        // Create cube
+        let bake = |bake_type, resolution| BakeTextureParams {
+            object: "Cube".to_string(),
+            bake_type,
+            resolution,
+            output_path: path.clone(),
+        };
+        assert!(api.bake_texture(bake(BakeType::Normal, 0)).is_err());
+        let baked = api
+            .bake_texture(bake(BakeType::AmbientOcclusion, 16))
+            .expect("Failed to bake texture");
+        assert_eq!((baked.width, baked.height), (16, 16));
+
+        let file = std::fs::File::open(&path).expect("Baked image should exist");
+        let reader = png::Decoder::new(file)
+            .read_info()
+            .expect("Failed to read baked image");
+        assert_eq!((reader.info().width, reader.info().height), (16, 16));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_rigid_bodies_and_gravity() {
+        let mut api = MockBlenderApi::new();
         api.create_cube(CreateCubeParams {
             location: Vec3::zero(),
-            name: "TestCube".to_string(),
+            name: "Crate".to_string(),
             size: 1.0,
+            on_collision: None,
         })
         .expect("Failed to create cube");
 
-        // Create material
+        let body = |object: &str, mass| AddRigidBodyParams {
+            object: object.to_string(),
+            body_type: RigidBodyType::Active,
+            mass,
+            friction: 0.5,
+        };
+        assert!(api.add_rigid_body(body("Crate", 0.0)).is_err());
+        assert!(matches!(
+            api.add_rigid_body(body("Missing", 1.0)),
+            Err(BlenderApiError::ObjectNotFound { .. })
+        ));
+        api.add_rigid_body(body("Crate", 2.5))
+            .expect("Failed to add rigid body");
+        api.set_gravity(Vec3::new(0.0, 0.0, -1.62))
+            .expect("Failed to set gravity");
+
+        let state = api.get_scene_state().expect("Failed to get state");
+        let rigid_body = state.objects[0]
+            .rigid_body
+            .as_ref()
+            .expect("Missing rigid body");
+        assert_eq!(rigid_body.mass, 2.5);
+        assert_eq!(state.scene_settings.gravity.z, -1.62);
+    }
+
+    #[test]
+    fn test_generic_properties() {
+        let mut api = MockBlenderApi::new();
+        api.create_cube(CreateCubeParams {
+            location: Vec3::zero(),
+            name: "Cube".to_string(),
+            size: 2.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
+
+        let cube = PropertyTarget::Object("Cube".to_string());
+        api.set_property(SetPropertyParams {
+            target: cube.clone(),
+            data_path: "location.z".to_string(),
+            value: serde_json::json!(3.0),
+        })
+        .expect("Failed to set property");
+        let z = api
+            .get_property(GetPropertyParams {
+                target: cube.clone(),
+                data_path: "location.z".to_string(),
+            })
+            .expect("Failed to get property");
+        assert_eq!(z, serde_json::json!(3.0));
+
+        let renamed = api.set_property(SetPropertyParams {
+            target: cube,
+            data_path: "name".to_string(),
+            value: serde_json::json!("Box"),
+        });
+        assert!(renamed.is_err());
+
+        // Scene properties go through the same checks as set_scene_settings
+        let fps = api.set_property(SetPropertyParams {
+            target: PropertyTarget::Scene,
+            data_path: "fps".to_string(),
+            value: serde_json::json!(0),
+        });
+        assert!(matches!(
+            fps,
+            Err(BlenderApiError::InvalidParameters { .. })
+        ));
+    }
+
+    #[test]
+    fn test_material_and_mesh_properties_are_checked() {
+        let mut api = MockBlenderApi::new();
+        api.create_cube(CreateCubeParams {
+            location: Vec3::zero(),
+            name: "Cube".to_string(),
+            size: 2.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
+        api.create_instance(CreateInstanceParams {
+            source: "Cube".to_string(),
+            name: "Copy".to_string(),
+            location: Vec3::zero(),
+            on_collision: None,
+        })
+        .expect("Failed to create instance");
         api.create_material(CreateMaterialParams {
-            name: "TestMaterial".to_string(),
+            name: "Red".to_string(),
             base_color: Color::red(),
-            metallic: 0.0,
-            roughness: 0.5,
+            ..Default::default()
         })
         .expect("Failed to create material");
+        let mut set = |target, data_path: &str, value| {
+            api.set_property(SetPropertyParams {
+                target,
+                data_path: data_path.to_string(),
+                value,
+            })
+        };
 
-        // Assign material
-        api.assign_material(AssignMaterialParams {
-            object_name: "TestCube".to_string(),
-            material_name: "TestMaterial".to_string(),
+        let red = PropertyTarget::Material("Red".to_string());
+        let mesh = PropertyTarget::Mesh("Cube".to_string());
+        for (target, data_path, value) in [
+            (red.clone(), "roughness", serde_json::json!(2.0)),
+            (red, "base_color.r", serde_json::json!(-1.0)),
+            (mesh.clone(), "faces", serde_json::json!([[0, 1]])),
+            (mesh.clone(), "faces", serde_json::json!([[0, 1, 99]])),
+        ] {
+            assert!(
+                matches!(
+                    set(target, data_path, value),
+                    Err(BlenderApiError::InvalidParameters { .. })
+                ),
+                "{data_path}"
+            );
+        }
+        set(mesh, "faces", serde_json::json!([[0, 1, 2], [0, 2, 3]])).expect("Failed to set faces");
+
+        for name in ["Cube", "Copy"] {
+            let object = api
+                .get_object(GetObjectParams {
+                    name: name.to_string(),
+                })
+                .expect("Failed to get object");
+            assert_eq!(object.face_count, Some(2), "{name}");
+            assert_eq!(object.vertex_count, Some(8), "{name}");
+        }
+        let material = api
+            .get_material(GetMaterialParams {
+                name: "Red".to_string(),
+            })
+            .expect("Failed to get material");
+        assert!(material.roughness <= 1.0);
+    }
+
+    #[test]
+    fn test_object_links_are_checked_as_properties() {
+        let mut api = MockBlenderApi::new();
+        for name in ["Parent", "Child"] {
+            api.create_cube(CreateCubeParams {
+                location: Vec3::zero(),
+                name: name.to_string(),
+                size: 2.0,
+                on_collision: None,
+            })
+            .expect("Failed to create cube");
+        }
+        let mut set = |object: &str, data_path: &str, value| {
+            api.set_property(SetPropertyParams {
+                target: PropertyTarget::Object(object.to_string()),
+                data_path: data_path.to_string(),
+                value,
+            })
+        };
+
+        set("Child", "parent", serde_json::json!("Parent")).expect("Failed to set parent");
+        let cycle = set("Parent", "parent", serde_json::json!("Child"));
+        assert!(matches!(
+            cycle,
+            Err(BlenderApiError::InvalidParameters { .. })
+        ));
+        let missing = set("Child", "parent", serde_json::json!("Missing"));
+        assert!(matches!(
+            missing,
+            Err(BlenderApiError::ObjectNotFound { .. })
+        ));
+
+        for (data_path, value) in [
+            ("object_type", serde_json::json!("Light")),
+            ("instance_of", serde_json::json!("Parent")),
+        ] {
+            assert!(matches!(
+                set("Child", data_path, value),
+                Err(BlenderApiError::InvalidParameters { .. })
+            ));
+        }
+
+        set("Child", "parent", serde_json::Value::Null).expect("Failed to clear parent");
+        let ancestors = api
+            .get_ancestors(GetObjectParams {
+                name: "Child".to_string(),
+            })
+            .expect("Failed to get ancestors");
+        assert!(ancestors.is_empty());
+    }
+
+    #[test]
+    fn test_query_objects() {
+        let mut api = MockBlenderApi::new();
+        for (name, x) in [("RedCube", 0.0), ("RedCube.001", 10.0), ("BlueCube", 0.0)] {
+            api.create_cube(CreateCubeParams {
+                location: Vec3::new(x, 0.0, 0.0),
+                name: name.to_string(),
+                size: 2.0,
+                on_collision: None,
+            })
+            .expect("Failed to create cube");
+        }
+        api.create_material(CreateMaterialParams {
+            name: "RedMaterial".to_string(),
+            ..Default::default()
         })
-        .expect("Failed to assign material");
+        .expect("Failed to create material");
+        for object in ["RedCube", "RedCube.001"] {
+            api.assign_material(AssignMaterialParams {
+                object_name: object.to_string(),
+                material_name: "RedMaterial".to_string(),
+            })
+            .expect("Failed to assign material");
+        }
 
-        // Verify assignment
-        let cube = api
-            .get_object(GetObjectParams {
-                name: "TestCube".to_string(),
+        let names = |filter| {
+            api.query_objects(filter)
+                .expect("Failed to query objects")
+                .into_iter()
+                .map(|object| object.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(ObjectFilter {
+                object_type: Some(ObjectType::Mesh),
+                material: Some("RedMaterial".to_string()),
+                spatial: Some(SpatialFilter::Inside {
+                    min: Vec3::new(-5.0, -5.0, -5.0),
+                    max: Vec3::new(5.0, 5.0, 5.0),
+                }),
+                ..Default::default()
+            }),
+            vec!["RedCube"]
+        );
+        assert_eq!(
+            names(ObjectFilter {
+                name: Some("*Cube".to_string()),
+                ..Default::default()
+            }),
+            vec!["BlueCube", "RedCube"]
+        );
+        assert!(
+            names(ObjectFilter {
+                collection: Some("Props".to_string()),
+                ..Default::default()
             })
-            .expect("Failed to get cube");
-        assert_eq!(cube.materials, vec!["TestMaterial"]);
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("Red*", "RedCube"));
+        assert!(glob_match("*.00?", "Cube.001"));
+        assert!(glob_match("*a*b", "xaxxab"));
+        assert!(!glob_match("Red*", "BlueCube"));
+        assert!(!glob_match("Cube", "Cube.001"));
+    }
+
+    #[test]
+    fn test_load_scene_ir_suffixes_taken_names() {
+        let mut source = MockBlenderApi::new();
+        source
+            .create_cube(CreateCubeParams {
+                location: Vec3::zero(),
+                name: "Cube".to_string(),
+                size: 1.0,
+                on_collision: None,
+            })
+            .expect("Failed to create cube");
+        source
+            .create_material(CreateMaterialParams {
+                name: "Red".to_string(),
+                base_color: Color::red(),
+                ..Default::default()
+            })
+            .expect("Failed to create material");
+        let mut scene = source.to_scene_ir();
+        scene.objects[0].materials = vec!["Red".to_string()];
+        scene.objects.push(IrObject {
+            name: "Copy".to_string(),
+            parent: Some("Cube".to_string()),
+            ..scene.objects[0].clone()
+        });
+
+        let mut api = MockBlenderApi::new();
+        api.create_cube(CreateCubeParams {
+            location: Vec3::zero(),
+            name: "Cube".to_string(),
+            size: 4.0,
+            on_collision: None,
+        })
+        .expect("Failed to create cube");
+        api.create_material(CreateMaterialParams {
+            name: "Red".to_string(),
+            base_color: Color::white(),
+            ..Default::default()
+        })
+        .expect("Failed to create material");
+
+        let names = api.load_scene_ir(scene).expect("Failed to load scene IR");
+        assert_eq!(names, vec!["Cube.001", "Copy"]);
+
+        let get = |name: &str| {
+            api.get_object(GetObjectParams {
+                name: name.to_string(),
+            })
+            .expect("Failed to get object")
+        };
+        let copy = get("Copy");
+        assert_eq!(copy.parent.as_deref(), Some("Cube.001"));
+        assert_eq!(copy.instance_of.as_deref(), Some("Cube.001"));
+        assert_eq!(get("Cube.001").materials, vec!["Red.001"]);
+        assert!(get("Cube").materials.is_empty());
+        assert_eq!(
+            api.get_mesh_geometry(GetMeshGeometryParams {
+                name: "Cube".to_string(),
+            })
+            .expect("Failed to get mesh")
+            .vertices[0]
+                .x
+                .abs(),
+            2.0
+        );
+    }
+
+    #[test]
+    fn test_replace_drops_the_old_mesh() {
+        let mut api = MockBlenderApi::new();
+        for (name, size) in [("Tree", 1.0), ("Big", 4.0)] {
+            api.create_cube(CreateCubeParams {
+                location: Vec3::zero(),
+                name: name.to_string(),
+                size,
+                on_collision: None,
+            })
+            .expect("Failed to create cube");
+        }
+        api.create_instance(CreateInstanceParams {
+            source: "Big".to_string(),
+            name: "Tree".to_string(),
+            location: Vec3::zero(),
+            on_collision: Some(NameCollisionPolicy::Replace),
+        })
+        .expect("Failed to replace with an instance");
+
+        assert!(
+            api.get_mesh_geometry(GetMeshGeometryParams {
+                name: "Tree".to_string(),
+            })
+            .is_err()
+        );
+        let bounds = api
+            .get_bounding_box(GetObjectParams {
+                name: "Tree".to_string(),
+            })
+            .expect("Failed to get bounding box");
+        assert_eq!(bounds.max.x, 2.0);
+    }
+
+    #[test]
+    fn test_light_linking_survives_rebuild() {
+        let mut api = MockBlenderApi::new();
+        for name in ["Hero", "Background"] {
+            api.create_cube(CreateCubeParams {
+                location: Vec3::zero(),
+                name: name.to_string(),
+                size: 1.0,
+                on_collision: None,
+            })
+            .expect("Failed to create cube");
+        }
+        let mut scene = SceneIr::default();
+        scene.objects.push(IrObject {
+            name: "Key".to_string(),
+            kind: IrObjectKind::Light {
+                light: "Key".to_string(),
+            },
+            transform: Transform::default(),
+            materials: Vec::new(),
+            parent: None,
+            light_linking: None,
+            shadow: ShadowVisibility::default(),
+        });
+        api.load_scene_ir(scene).expect("Failed to load scene IR");
+
+        let linking = |include: &[&str], exclude: &[&str]| SetLightLinkingParams {
+            light: "Key".to_string(),
+            include_objects: include.iter().map(|s| s.to_string()).collect(),
+            exclude_objects: exclude.iter().map(|s| s.to_string()).collect(),
+        };
+        assert!(
+            api.set_light_linking(linking(&["Hero"], &["Hero"]))
+                .is_err()
+        );
+        assert!(
+            api.set_light_linking(SetLightLinkingParams {
+                light: "Hero".to_string(),
+                ..linking(&[], &[])
+            })
+            .is_err()
+        );
+        api.set_light_linking(linking(&["Hero"], &["Background"]))
+            .expect("Failed to set light linking");
+        api.set_shadow_visibility(SetShadowVisibilityParams {
+            object: "Background".to_string(),
+            visible_shadow: Some(false),
+            is_shadow_catcher: None,
+        })
+        .expect("Failed to set shadow visibility");
+
+        // Rebuild the scene from its IR, the way importers and replays do
+        let scene = api.to_scene_ir();
+        api.clear_scene().expect("Failed to clear scene");
+        api.load_scene_ir(scene).expect("Failed to load scene IR");
+
+        let get = |name: &str| {
+            api.get_object(GetObjectParams {
+                name: name.to_string(),
+            })
+            .expect("Failed to get object")
+        };
+        let key = get("Key");
+        assert_eq!(key.object_type, ObjectType::Light);
+        let linking = key.light_linking.expect("Light linking was lost");
+        assert_eq!(linking.include_objects, vec!["Hero"]);
+        assert_eq!(linking.exclude_objects, vec!["Background"]);
+        assert!(!get("Background").shadow.visible_shadow);
+        assert!(get("Hero").shadow.visible_shadow);
     }
 
     #[test]
@@ -343,6 +4355,7 @@ mod tests {
             location: Vec3::zero(),
             name: "Cube1".to_string(),
             size: 1.0,
+            on_collision: None,
         })
         .expect("Failed to create cube1");
 
@@ -351,6 +4364,7 @@ mod tests {
             name: "Sphere1".to_string(),
             radius: 1.0,
             subdivisions: 2,
+            on_collision: None,
         })
         .expect("Failed to create sphere");
 
@@ -0,0 +1,241 @@
+//! Named material presets.
+//!
+//! Presets hold the Principled BSDF values of a look, so suites and users can ask for "gold"
+//! instead of repeating PBR numbers. Libraries are TOML files with one table per preset; any
+//! value a preset leaves out keeps Blender's default.
+//!
+//! ```toml
+//! [copper]
+//! base_color = { r = 0.955, g = 0.638, b = 0.538, a = 1.0 }
+//! metallic = 1.0
+//! roughness = 0.25
+//! ```
+
+use crate::{
+    BlenderApiError, Color, CreateMaterialFromPresetParams, CreateMaterialParams, Validate,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaterialPreset {
+    pub base_color: Color,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emission_color: Color,
+    pub emission_strength: f32,
+    pub alpha: f32,
+    pub ior: f32,
+    pub specular: f32,
+    pub transmission: f32,
+    pub normal_strength: f32,
+}
+
+impl MaterialPreset {
+    pub fn params(&self, name: impl Into<String>) -> CreateMaterialParams {
+        CreateMaterialParams {
+            name: name.into(),
+            base_color: self.base_color.clone(),
+            metallic: self.metallic,
+            roughness: self.roughness,
+            emission_color: self.emission_color.clone(),
+            emission_strength: self.emission_strength,
+            alpha: self.alpha,
+            ior: self.ior,
+            specular: self.specular,
+            transmission: self.transmission,
+            normal_strength: self.normal_strength,
+            on_collision: None,
+        }
+    }
+}
+
+impl Default for MaterialPreset {
+    fn default() -> Self {
+        let defaults = CreateMaterialParams::default();
+        Self {
+            base_color: defaults.base_color,
+            metallic: defaults.metallic,
+            roughness: defaults.roughness,
+            emission_color: defaults.emission_color,
+            emission_strength: defaults.emission_strength,
+            alpha: defaults.alpha,
+            ior: defaults.ior,
+            specular: defaults.specular,
+            transmission: defaults.transmission,
+            normal_strength: defaults.normal_strength,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MaterialLibrary {
+    presets: BTreeMap<String, MaterialPreset>,
+}
+
+impl MaterialLibrary {
+    pub fn empty() -> Self {
+        Self {
+            presets: BTreeMap::new(),
+        }
+    }
+
+    /// The presets every library starts with: glass, gold, plastic and rubber.
+    pub fn builtin() -> Self {
+        let mut library = Self::empty();
+        library.insert(
+            "glass",
+            MaterialPreset {
+                base_color: Color::white(),
+                roughness: 0.0,
+                ior: 1.45,
+                transmission: 1.0,
+                ..Default::default()
+            },
+        );
+        library.insert(
+            "gold",
+            MaterialPreset {
+                base_color: Color::new(1.0, 0.766, 0.336, 1.0),
+                metallic: 1.0,
+                roughness: 0.2,
+                ..Default::default()
+            },
+        );
+        library.insert(
+            "plastic",
+            MaterialPreset {
+                roughness: 0.4,
+                ..Default::default()
+            },
+        );
+        library.insert(
+            "rubber",
+            MaterialPreset {
+                base_color: Color::new(0.05, 0.05, 0.05, 1.0),
+                roughness: 0.9,
+                specular: 0.3,
+                ..Default::default()
+            },
+        );
+        library
+    }
+
+    /// The builtin presets plus those in `content`, which win when names clash.
+    pub fn from_toml(content: &str) -> Result<Self, BlenderApiError> {
+        let parsed: Self =
+            toml::from_str(content).map_err(|e| BlenderApiError::InvalidParameters {
+                message: format!("Invalid material library: {e}"),
+            })?;
+
+        let mut library = Self::builtin();
+        for (name, preset) in parsed.presets {
+            preset.params(name.as_str()).validate().map_err(|e| {
+                BlenderApiError::InvalidParameters {
+                    message: format!("Material preset '{name}': {e}"),
+                }
+            })?;
+            library.insert(name, preset);
+        }
+        Ok(library)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BlenderApiError> {
+        let path = path.as_ref();
+        let content =
+            std::fs::read_to_string(path).map_err(|e| BlenderApiError::OperationFailed {
+                message: format!("Failed to read material library {}: {e}", path.display()),
+            })?;
+        Self::from_toml(&content)
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, preset: MaterialPreset) {
+        self.presets.insert(name.into(), preset);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&MaterialPreset> {
+        self.presets.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.presets.keys().map(String::as_str)
+    }
+
+    /// The material `params` asks for, keeping its collision policy.
+    pub fn material(
+        &self,
+        params: &CreateMaterialFromPresetParams,
+    ) -> Result<CreateMaterialParams, BlenderApiError> {
+        let preset = self
+            .get(&params.preset)
+            .ok_or_else(|| BlenderApiError::PresetNotFound {
+                name: params.preset.clone(),
+            })?;
+        Ok(CreateMaterialParams {
+            on_collision: params.on_collision,
+            ..preset.params(params.name.as_str())
+        })
+    }
+}
+
+impl Default for MaterialLibrary {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_presets_extend_and_override_builtins() {
+        let library = MaterialLibrary::from_toml(
+            "[gold]\nroughness = 0.6\n\n[copper]\nmetallic = 1.0\nbase_color = { r = 0.955, g = 0.638, b = 0.538, a = 1.0 }\n",
+        )
+        .expect("Failed to parse library");
+
+        assert_eq!(
+            library.names().collect::<Vec<_>>(),
+            vec!["copper", "glass", "gold", "plastic", "rubber"]
+        );
+        let gold = library.get("gold").expect("Missing gold");
+        assert_eq!(gold.roughness, 0.6);
+        // Values the file leaves out fall back to Blender's defaults, not the builtin preset
+        assert_eq!(gold.metallic, 0.0);
+        assert_eq!(library.get("copper").map(|p| p.ior), Some(1.5));
+    }
+
+    #[test]
+    fn invalid_presets_are_rejected() {
+        let error = MaterialLibrary::from_toml("[chrome]\nmetallic = 2.0\n")
+            .expect_err("Metallic above 1 should be rejected");
+        assert!(error.to_string().contains("chrome"));
+    }
+
+    #[test]
+    fn material_uses_requested_name() {
+        let params = MaterialLibrary::builtin()
+            .material(&CreateMaterialFromPresetParams {
+                name: "Ring".to_string(),
+                preset: "gold".to_string(),
+                on_collision: None,
+            })
+            .expect("Failed to resolve preset");
+        assert_eq!(params.name, "Ring");
+        assert_eq!(params.metallic, 1.0);
+
+        let missing = MaterialLibrary::builtin().material(&CreateMaterialFromPresetParams {
+            name: "Ring".to_string(),
+            preset: "unobtainium".to_string(),
+            on_collision: None,
+        });
+        assert!(matches!(
+            missing,
+            Err(BlenderApiError::PresetNotFound { .. })
+        ));
+    }
+}
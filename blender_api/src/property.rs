@@ -0,0 +1,130 @@
+//! Untyped property access by data path.
+//!
+//! Paths are field names separated by dots, each optionally followed by `[index]`, like
+//! `location.x` or `materials[0]`. Backends without Blender's RNA resolve them against the JSON
+//! form of their own data blocks, which is what these helpers do.
+
+use crate::BlenderApiError;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+enum Segment<'a> {
+    Field(&'a str),
+    Index(usize),
+}
+
+fn invalid_path(path: &str, message: &str) -> BlenderApiError {
+    BlenderApiError::InvalidParameters {
+        message: format!("Invalid data path '{path}': {message}"),
+    }
+}
+
+fn parse(path: &str) -> Result<Vec<Segment<'_>>, BlenderApiError> {
+    let mut segments = Vec::new();
+    for part in path.split('.') {
+        let (field, mut indices) = match part.find('[') {
+            Some(start) => (&part[..start], &part[start..]),
+            None => (part, ""),
+        };
+        if field.is_empty() {
+            return Err(invalid_path(path, "empty field name"));
+        }
+        segments.push(Segment::Field(field));
+
+        while !indices.is_empty() {
+            let end = indices
+                .find(']')
+                .ok_or_else(|| invalid_path(path, "unclosed '['"))?;
+            let index = indices[1..end]
+                .parse()
+                .map_err(|_| invalid_path(path, "index must be a number"))?;
+            segments.push(Segment::Index(index));
+            indices = &indices[end + 1..];
+            if !indices.is_empty() && !indices.starts_with('[') {
+                return Err(invalid_path(path, "unexpected text after ']'"));
+            }
+        }
+    }
+    Ok(segments)
+}
+
+fn step<'a>(
+    value: &'a mut Value,
+    segment: &Segment<'_>,
+    path: &str,
+) -> Result<&'a mut Value, BlenderApiError> {
+    let found = match segment {
+        Segment::Field(field) => value.get_mut(*field),
+        Segment::Index(index) => value.get_mut(*index),
+    };
+    found.ok_or_else(|| BlenderApiError::InvalidParameters {
+        message: format!("Property not found: {path}"),
+    })
+}
+
+/// Read the property at `path` of `data`.
+pub fn read_property<T: Serialize>(data: &T, path: &str) -> Result<Value, BlenderApiError> {
+    let mut root = to_json(data)?;
+    let mut current = &mut root;
+    for segment in parse(path)? {
+        current = step(current, &segment, path)?;
+    }
+    Ok(current.take())
+}
+
+/// A copy of `data` with the property at `path` replaced by `value`.
+///
+/// Only existing properties can be set, and the result has to still be a valid `T`, so a
+/// mistyped value fails instead of being stored.
+pub fn write_property<T>(data: &T, path: &str, value: Value) -> Result<T, BlenderApiError>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let mut root = to_json(data)?;
+    let mut current = &mut root;
+    for segment in parse(path)? {
+        current = step(current, &segment, path)?;
+    }
+    *current = value;
+
+    serde_json::from_value(root).map_err(|e| BlenderApiError::InvalidParameters {
+        message: format!("Invalid value for {path}: {e}"),
+    })
+}
+
+fn to_json<T: Serialize>(data: &T) -> Result<Value, BlenderApiError> {
+    serde_json::to_value(data).map_err(|e| BlenderApiError::OperationFailed {
+        message: format!("Failed to serialize properties: {e}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Vec3;
+    use serde_json::json;
+
+    #[test]
+    fn paths_read_fields_and_indices() {
+        let data = json!({ "location": { "x": 1.0 }, "materials": ["A", "B"] });
+        assert_eq!(
+            read_property(&data, "location.x").expect("Failed to read"),
+            json!(1.0)
+        );
+        assert_eq!(
+            read_property(&data, "materials[1]").expect("Failed to read"),
+            json!("B")
+        );
+        assert!(read_property(&data, "location.w").is_err());
+        assert!(read_property(&data, "materials[x]").is_err());
+    }
+
+    #[test]
+    fn writes_must_keep_the_type_valid() {
+        let vector = Vec3::new(1.0, 2.0, 3.0);
+        let moved = write_property(&vector, "z", json!(5.0)).expect("Failed to write");
+        assert_eq!(moved.z, 5.0);
+        assert!(write_property(&vector, "z", json!("up")).is_err());
+    }
+}
@@ -0,0 +1,286 @@
+//! Vendor-neutral scene model.
+//!
+//! Snapshots, importers and exporters all convert through `SceneIr`, so each file format only
+//! needs a single conversion instead of one per backend.
+
+use crate::{
+    BlenderApiError, Color, LightLinking, MaterialData, MeshGeometry, Rotation, ShadowVisibility,
+    Vec3, edges_from_faces,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transform {
+    pub location: Vec3,
+    pub rotation: Rotation,
+    pub scale: Vec3,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            location: Vec3::zero(),
+            rotation: Rotation::default(),
+            scale: Vec3::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+/// What an object instantiates, referring to data blocks in the scene by name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum IrObjectKind {
+    Mesh { mesh: String },
+    Light { light: String },
+    Camera { camera: String },
+    Empty,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IrObject {
+    pub name: String,
+    pub kind: IrObjectKind,
+    pub transform: Transform,
+    pub materials: Vec<String>,
+    #[serde(default)]
+    pub parent: Option<String>,
+    #[serde(default)]
+    pub light_linking: Option<LightLinking>,
+    #[serde(default)]
+    pub shadow: ShadowVisibility,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LightType {
+    Point,
+    Sun,
+    Spot,
+    Area,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IrLight {
+    pub name: String,
+    pub light_type: LightType,
+    pub color: Color,
+    pub energy: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IrCamera {
+    pub name: String,
+    pub focal_length: f32,
+    pub clip_start: f32,
+    pub clip_end: f32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SceneIr {
+    pub objects: Vec<IrObject>,
+    pub meshes: Vec<MeshGeometry>,
+    pub materials: Vec<MaterialData>,
+    pub lights: Vec<IrLight>,
+    pub cameras: Vec<IrCamera>,
+}
+
+impl SceneIr {
+    pub fn find_mesh(&self, name: &str) -> Option<&MeshGeometry> {
+        self.meshes.iter().find(|mesh| mesh.name == name)
+    }
+
+    /// Keep only the named objects and the data blocks they still reference.
+    pub fn retain_objects(&mut self, names: &[String]) {
+        self.objects.retain(|object| names.contains(&object.name));
+
+        let mut referenced = HashSet::new();
+        for object in &self.objects {
+            match &object.kind {
+                IrObjectKind::Mesh { mesh } => referenced.insert(mesh.clone()),
+                IrObjectKind::Light { light } => referenced.insert(light.clone()),
+                IrObjectKind::Camera { camera } => referenced.insert(camera.clone()),
+                IrObjectKind::Empty => false,
+            };
+        }
+
+        self.meshes.retain(|mesh| referenced.contains(&mesh.name));
+        self.lights.retain(|light| referenced.contains(&light.name));
+        self.cameras
+            .retain(|camera| referenced.contains(&camera.name));
+    }
+
+    /// Write every mesh object as a Wavefront OBJ `o` block, in object order.
+    pub fn to_obj(&self) -> String {
+        let mut output = String::from("# Exported by cuttle\n");
+        let mut vertex_offset = 1;
+
+        for object in &self.objects {
+            let IrObjectKind::Mesh { mesh } = &object.kind else {
+                continue;
+            };
+            let Some(mesh) = self.find_mesh(mesh) else {
+                continue;
+            };
+
+            output.push_str(&format!("o {}\n", object.name));
+            for vertex in &mesh.vertices {
+                output.push_str(&format!("v {} {} {}\n", vertex.x, vertex.y, vertex.z));
+            }
+            for face in &mesh.faces {
+                let indices = face
+                    .iter()
+                    .map(|index| (index + vertex_offset).to_string())
+                    .collect::<Vec<_>>();
+                output.push_str(&format!("f {}\n", indices.join(" ")));
+            }
+            vertex_offset += mesh.vertices.len();
+        }
+
+        output
+    }
+
+    /// Read a Wavefront OBJ file, creating one mesh object per `o` statement.
+    pub fn from_obj(content: &str, default_name: &str) -> Result<Self, BlenderApiError> {
+        let meshes = parse_obj(content, default_name)?;
+        let objects = meshes
+            .iter()
+            .map(|mesh| IrObject {
+                name: mesh.name.clone(),
+                kind: IrObjectKind::Mesh {
+                    mesh: mesh.name.clone(),
+                },
+                transform: Transform::default(),
+                materials: Vec::new(),
+                parent: None,
+                light_linking: None,
+                shadow: ShadowVisibility::default(),
+            })
+            .collect();
+
+        Ok(Self {
+            objects,
+            meshes,
+            ..Default::default()
+        })
+    }
+}
+
+// Split an OBJ file into one mesh per `o` statement, remapping the file's global
+// 1-based (or negative relative) vertex indices to per-mesh local indices
+fn parse_obj(content: &str, default_name: &str) -> Result<Vec<MeshGeometry>, BlenderApiError> {
+    let mut positions = Vec::new();
+    let mut meshes: Vec<(MeshGeometry, HashMap<usize, usize>)> = Vec::new();
+    let new_mesh = |name: &str| {
+        (
+            MeshGeometry {
+                name: name.to_string(),
+                vertices: Vec::new(),
+                edges: Vec::new(),
+                faces: Vec::new(),
+                face_materials: Vec::new(),
+            },
+            HashMap::new(),
+        )
+    };
+    let invalid = |line: usize, message: &str| BlenderApiError::OperationFailed {
+        message: format!("Invalid OBJ at line {line}: {message}"),
+    };
+
+    for (index, line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        let mut parts = line.split_whitespace();
+
+        match parts.next() {
+            Some("o") => {
+                let name = parts.collect::<Vec<_>>().join(" ");
+                let name = if name.is_empty() { default_name } else { &name };
+                meshes.push(new_mesh(name));
+            }
+            Some("v") => {
+                let coords = parts
+                    .take(3)
+                    .map(|part| part.parse::<f32>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| invalid(line_number, "vertex has a non-numeric coordinate"))?;
+                if coords.len() != 3 {
+                    return Err(invalid(line_number, "vertex needs 3 coordinates"));
+                }
+                positions.push(Vec3::new(coords[0], coords[1], coords[2]));
+            }
+            Some("f") => {
+                if meshes.is_empty() {
+                    meshes.push(new_mesh(default_name));
+                }
+                let Some((mesh, local_indices)) = meshes.last_mut() else {
+                    continue;
+                };
+
+                let mut face = Vec::new();
+                for part in parts {
+                    let raw = part
+                        .split('/')
+                        .next()
+                        .and_then(|index| index.parse::<i64>().ok())
+                        .ok_or_else(|| invalid(line_number, "face has a non-numeric index"))?;
+                    let global = if raw > 0 {
+                        raw - 1
+                    } else {
+                        positions.len() as i64 + raw
+                    };
+                    if global < 0 || global as usize >= positions.len() {
+                        return Err(invalid(line_number, "face references a missing vertex"));
+                    }
+
+                    let global = global as usize;
+                    let local = *local_indices.entry(global).or_insert_with(|| {
+                        mesh.vertices.push(positions[global].clone());
+                        mesh.vertices.len() - 1
+                    });
+                    face.push(local);
+                }
+
+                if face.len() < 3 {
+                    return Err(invalid(line_number, "face needs at least 3 vertices"));
+                }
+                mesh.faces.push(face);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(meshes
+        .into_iter()
+        .map(|(mut mesh, _)| {
+            mesh.edges = edges_from_faces(&mesh.faces);
+            mesh
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn obj_round_trip() {
+        let source = "o Tri\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n";
+        let scene = SceneIr::from_obj(source, "Imported").expect("Failed to parse OBJ");
+        assert_eq!(scene.objects.len(), 1);
+        assert_eq!(scene.meshes[0].edges.len(), 3);
+
+        let reparsed =
+            SceneIr::from_obj(&scene.to_obj(), "Imported").expect("Failed to parse exported OBJ");
+        assert_eq!(reparsed.objects[0].name, "Tri");
+        assert_eq!(reparsed.meshes[0].faces, vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn retain_objects_drops_unreferenced_data() {
+        let source = "o A\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\no B\nf 1 2 3\n";
+        let mut scene = SceneIr::from_obj(source, "Imported").expect("Failed to parse OBJ");
+        scene.retain_objects(&["B".to_string()]);
+
+        assert_eq!(scene.objects.len(), 1);
+        assert_eq!(scene.meshes.len(), 1);
+        assert_eq!(scene.meshes[0].name, "B");
+    }
+}
@@ -0,0 +1,321 @@
+//! Parameter checks shared by every backend.
+//!
+//! Create operations are often fed generated DSL output, so nonsense like negative sizes or NaN
+//! colors is rejected here with the offending field named, before it reaches Blender.
+
+use crate::{
+    AddRigidBodyParams, BakeTextureParams, BlenderApiError, Color, CreateCubeParams,
+    CreateInstanceParams, CreateMaterialParams, CreateMeshParams, CreateSphereParams, MeshGeometry,
+    Rotation, SetTransformParams, Vec3,
+};
+
+pub trait Validate {
+    fn validate(&self) -> Result<(), BlenderApiError>;
+}
+
+/// Largest `BakeTextureParams::resolution`, the most Blender's own bake dialog offers. The image
+/// is held in memory whole, so larger ones are refused rather than risk the allocation.
+pub const MAX_BAKE_RESOLUTION: u32 = 16384;
+
+/// Largest `CreateSphereParams::subdivisions`, giving 4096 segments and 2048 rings. Backends build
+/// the whole mesh at once, so denser spheres are refused before the segment count overflows.
+pub const MAX_SPHERE_SUBDIVISIONS: u32 = 1024;
+
+fn invalid(field: &str, message: impl std::fmt::Display) -> BlenderApiError {
+    BlenderApiError::InvalidParameters {
+        message: format!("{field} {message}"),
+    }
+}
+
+fn name(field: &str, value: &str) -> Result<(), BlenderApiError> {
+    if value.trim().is_empty() {
+        return Err(invalid(field, "must not be empty"));
+    }
+    Ok(())
+}
+
+fn finite(field: &str, value: f32) -> Result<(), BlenderApiError> {
+    if !value.is_finite() {
+        return Err(invalid(field, format!("must be finite, got {value}")));
+    }
+    Ok(())
+}
+
+fn positive(field: &str, value: f32) -> Result<(), BlenderApiError> {
+    finite(field, value)?;
+    if value <= 0.0 {
+        return Err(invalid(field, format!("must be positive, got {value}")));
+    }
+    Ok(())
+}
+
+fn non_negative(field: &str, value: f32) -> Result<(), BlenderApiError> {
+    finite(field, value)?;
+    if value < 0.0 {
+        return Err(invalid(field, format!("must not be negative, got {value}")));
+    }
+    Ok(())
+}
+
+fn unit(field: &str, value: f32) -> Result<(), BlenderApiError> {
+    finite(field, value)?;
+    if !(0.0..=1.0).contains(&value) {
+        return Err(invalid(
+            field,
+            format!("must be between 0 and 1, got {value}"),
+        ));
+    }
+    Ok(())
+}
+
+fn vec3(field: &str, value: &Vec3) -> Result<(), BlenderApiError> {
+    finite(&format!("{field}.x"), value.x)?;
+    finite(&format!("{field}.y"), value.y)?;
+    finite(&format!("{field}.z"), value.z)
+}
+
+// Channels may go above 1 for HDR colors, but never below 0
+fn color(field: &str, value: &Color) -> Result<(), BlenderApiError> {
+    non_negative(&format!("{field}.r"), value.r)?;
+    non_negative(&format!("{field}.g"), value.g)?;
+    non_negative(&format!("{field}.b"), value.b)?;
+    unit(&format!("{field}.a"), value.a)
+}
+
+impl Validate for CreateCubeParams {
+    fn validate(&self) -> Result<(), BlenderApiError> {
+        name("name", &self.name)?;
+        vec3("location", &self.location)?;
+        positive("size", self.size)
+    }
+}
+
+impl Validate for CreateSphereParams {
+    fn validate(&self) -> Result<(), BlenderApiError> {
+        name("name", &self.name)?;
+        vec3("location", &self.location)?;
+        positive("radius", self.radius)?;
+        if !(1..=MAX_SPHERE_SUBDIVISIONS).contains(&self.subdivisions) {
+            return Err(invalid(
+                "subdivisions",
+                format!(
+                    "must be between 1 and {MAX_SPHERE_SUBDIVISIONS}, got {}",
+                    self.subdivisions
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn geometry(vertices: &[Vec3], faces: &[Vec<usize>]) -> Result<(), BlenderApiError> {
+    for (i, vertex) in vertices.iter().enumerate() {
+        vec3(&format!("vertices[{i}]"), vertex)?;
+    }
+    for (i, face) in faces.iter().enumerate() {
+        if face.len() < 3 {
+            return Err(invalid(
+                &format!("faces[{i}]"),
+                format!("must have at least 3 vertices, got {}", face.len()),
+            ));
+        }
+        if let Some(index) = face.iter().find(|&&index| index >= vertices.len()) {
+            return Err(invalid(
+                &format!("faces[{i}]"),
+                format!("refers to vertex {index} of {}", vertices.len()),
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl Validate for CreateMeshParams {
+    fn validate(&self) -> Result<(), BlenderApiError> {
+        name("name", &self.name)?;
+        vec3("location", &self.location)?;
+        geometry(&self.vertices, &self.faces)
+    }
+}
+
+// Checked when a property write replaces part of an existing mesh
+impl Validate for MeshGeometry {
+    fn validate(&self) -> Result<(), BlenderApiError> {
+        geometry(&self.vertices, &self.faces)?;
+        for (i, edge) in self.edges.iter().enumerate() {
+            if let Some(index) = edge.iter().find(|&&index| index >= self.vertices.len()) {
+                return Err(invalid(
+                    &format!("edges[{i}]"),
+                    format!("refers to vertex {index} of {}", self.vertices.len()),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Validate for CreateMaterialParams {
+    fn validate(&self) -> Result<(), BlenderApiError> {
+        name("name", &self.name)?;
+        color("base_color", &self.base_color)?;
+        unit("metallic", self.metallic)?;
+        unit("roughness", self.roughness)?;
+        color("emission_color", &self.emission_color)?;
+        non_negative("emission_strength", self.emission_strength)?;
+        unit("alpha", self.alpha)?;
+        positive("ior", self.ior)?;
+        non_negative("specular", self.specular)?;
+        unit("transmission", self.transmission)?;
+        non_negative("normal_strength", self.normal_strength)
+    }
+}
+
+impl Validate for CreateInstanceParams {
+    fn validate(&self) -> Result<(), BlenderApiError> {
+        name("source", &self.source)?;
+        name("name", &self.name)?;
+        vec3("location", &self.location)
+    }
+}
+
+impl Validate for SetTransformParams {
+    fn validate(&self) -> Result<(), BlenderApiError> {
+        if let Some(location) = &self.location {
+            vec3("location", location)?;
+        }
+        if let Some(rotation) = &self.rotation {
+            let values = match *rotation {
+                Rotation::Euler { x, y, z, .. } => vec![("x", x), ("y", y), ("z", z)],
+                Rotation::Quaternion { w, x, y, z } => vec![("w", w), ("x", x), ("y", y), ("z", z)],
+                Rotation::AxisAngle { angle, x, y, z } => {
+                    vec![("angle", angle), ("x", x), ("y", y), ("z", z)]
+                }
+            };
+            for (axis, value) in values {
+                finite(&format!("rotation.{axis}"), value)?;
+            }
+        }
+        if let Some(scale) = &self.scale {
+            vec3("scale", scale)?;
+        }
+        Ok(())
+    }
+}
+
+impl Validate for BakeTextureParams {
+    fn validate(&self) -> Result<(), BlenderApiError> {
+        name("object", &self.object)?;
+        name("output_path", &self.output_path)?;
+        if !(1..=MAX_BAKE_RESOLUTION).contains(&self.resolution) {
+            return Err(invalid(
+                "resolution",
+                format!(
+                    "must be between 1 and {MAX_BAKE_RESOLUTION}, got {}",
+                    self.resolution
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for AddRigidBodyParams {
+    fn validate(&self) -> Result<(), BlenderApiError> {
+        name("object", &self.object)?;
+        positive("mass", self.mass)?;
+        non_negative("friction", self.friction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(result: Result<(), BlenderApiError>) -> String {
+        match result {
+            Err(BlenderApiError::InvalidParameters { message }) => message,
+            other => panic!("Expected invalid parameters, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn errors_name_the_field() {
+        let cube = CreateCubeParams {
+            location: Vec3::new(0.0, f32::NAN, 0.0),
+            name: "Cube".to_string(),
+            size: 1.0,
+            on_collision: None,
+        };
+        assert_eq!(
+            message(cube.validate()),
+            "location.y must be finite, got NaN"
+        );
+
+        let sphere = CreateSphereParams {
+            location: Vec3::zero(),
+            name: "Sphere".to_string(),
+            radius: -1.0,
+            subdivisions: 4,
+            on_collision: None,
+        };
+        assert_eq!(
+            message(sphere.validate()),
+            "radius must be positive, got -1"
+        );
+
+        let material = CreateMaterialParams {
+            base_color: Color::new(0.5, f32::NAN, 0.5, 1.0),
+            ..Default::default()
+        };
+        assert!(message(material.validate()).starts_with("base_color.g"));
+    }
+
+    #[test]
+    fn bake_resolution_is_bounded() {
+        let bake = |resolution| BakeTextureParams {
+            object: "Cube".to_string(),
+            resolution,
+            output_path: "bake.png".to_string(),
+            ..Default::default()
+        };
+        bake(MAX_BAKE_RESOLUTION)
+            .validate()
+            .expect("Largest resolution should be valid");
+        assert_eq!(
+            message(bake(0).validate()),
+            "resolution must be between 1 and 16384, got 0"
+        );
+        assert_eq!(
+            message(bake(MAX_BAKE_RESOLUTION + 1).validate()),
+            "resolution must be between 1 and 16384, got 16385"
+        );
+    }
+
+    #[test]
+    fn sphere_subdivisions_are_bounded() {
+        let sphere = |subdivisions| CreateSphereParams {
+            location: Vec3::zero(),
+            name: "Sphere".to_string(),
+            radius: 1.0,
+            subdivisions,
+            on_collision: None,
+        };
+        sphere(MAX_SPHERE_SUBDIVISIONS)
+            .validate()
+            .expect("Most subdivisions should be valid");
+        assert_eq!(
+            message(sphere(0).validate()),
+            "subdivisions must be between 1 and 1024, got 0"
+        );
+        assert_eq!(
+            message(sphere(u32::MAX).validate()),
+            "subdivisions must be between 1 and 1024, got 4294967295"
+        );
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        CreateMaterialParams::default()
+            .validate()
+            .expect("Default material should be valid");
+    }
+}
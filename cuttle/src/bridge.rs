@@ -1,15 +1,32 @@
 pub mod msgbus;
+pub mod replay;
 
+use crate::config::RuntimeConfig;
 use crate::service::{BlenderService, PingService, ServiceManager};
 use cuttle_blender_api::{
-    AssignMaterialParams, CreateCubeParams, CreateMaterialParams, CreateSphereParams,
-    GetMaterialParams, GetObjectParams, MaterialData, ObjectData,
+    AddRigidBodyParams, ApplyGeometryNodesParams, AssignMaterialParams, BakeTextureParams,
+    BakedTexture, BlenderApi, BoundingBox, CreateCubeParams, CreateInstanceParams,
+    CreateMaterialFromPresetParams, CreateMaterialParams, CreateMeshParams, CreateSphereParams,
+    ExportSceneParams, GetMaterialNodesParams, GetMaterialParams, GetMeshGeometryParams,
+    GetObjectParams, ImportFileParams, JsonBlenderApi, JsonTransport, MaterialData, MeshGeometry,
+    NameCollisionPolicy, ObjectData, ObjectDependencies, ObjectFilter, OpenBlendParams,
+    PropertyTarget, RemoveMaterialSlotParams, SaveBlendParams, SceneIr, SceneSettings, SceneState,
+    SelectObjectsParams, SetFaceMaterialsParams, SetLightLinkingParams, SetMaterialNodesParams,
+    SetMaterialSlotParams, SetParentParams, SetShadowVisibilityParams, SetTransformParams,
+    SetWorldParams, UnitSettings, Vec3, WorldData,
 };
+use cuttle_lang::BlenderNodeGraph;
 use flume::{Receiver, Sender};
+pub use msgbus::BlenderEvent;
+use replay::{RecordedBackend, RecordedExchange, RecordingTransport, SessionRecording};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ServiceMessage {
@@ -18,14 +35,173 @@ pub enum ServiceMessage {
     // Blender operations
     CreateCube(CreateCubeParams),
     CreateSphere(CreateSphereParams),
+    CreateMesh(CreateMeshParams),
     CreateMaterial(CreateMaterialParams),
+    /// Resolved against `RuntimeConfig::materials` before it reaches the backend.
+    CreateMaterialFromPreset(CreateMaterialFromPresetParams),
     AssignMaterial(AssignMaterialParams),
+    SetMaterialSlot(SetMaterialSlotParams),
+    RemoveMaterialSlot(RemoveMaterialSlotParams),
+    SetFaceMaterials(SetFaceMaterialsParams),
     GetObject(GetObjectParams),
+    SetTransform(SetTransformParams),
     GetMaterial(GetMaterialParams),
+    GetMaterialNodes(GetMaterialNodesParams),
+    SetMaterialNodes(SetMaterialNodesParams),
+    GetMeshGeometry(GetMeshGeometryParams),
+    SetWorld(SetWorldParams),
+    GetWorld,
+    SetSceneSettings(SceneSettings),
+    GetSceneSettings,
+    SetUnits(UnitSettings),
+    GetUnits,
+    AddRigidBody(AddRigidBodyParams),
+    SetGravity(Vec3),
+    GetProperty {
+        target: PropertyTarget,
+        data_path: String,
+    },
+    SetProperty {
+        target: PropertyTarget,
+        data_path: String,
+        value: serde_json::Value,
+    },
+    ImportFile(ImportFileParams),
+    ExportScene(ExportSceneParams),
+    GetSceneIr,
+    GetSceneState,
+    SaveBlend(SaveBlendParams),
+    OpenBlend(OpenBlendParams),
+    SetParent(SetParentParams),
+    GetChildren(GetObjectParams),
+    GetAncestors(GetObjectParams),
+    GetDependencies(GetObjectParams),
+    GetBoundingBox(GetObjectParams),
+    SelectObjects(SelectObjectsParams),
+    DeselectAll,
+    GetSelected,
+    ApplyGeometryNodes(ApplyGeometryNodesParams),
+    CreateInstance(CreateInstanceParams),
+    BakeTexture(BakeTextureParams),
+    SetLightLinking(SetLightLinkingParams),
+    SetShadowVisibility(SetShadowVisibilityParams),
+    QueryObjects(ObjectFilter),
     ListObjects,
     ListMaterials,
     ListMeshes,
     ClearScene,
+    // Something the user changed in Blender, see `msgbus`
+    Event(BlenderEvent),
+    // Handled by the ServiceManager, each message is routed as if it was sent on its own
+    Batch(Vec<ServiceMessage>),
+}
+
+impl ServiceMessage {
+    /// Name of the operation, used as the key for per-operation settings like timeouts.
+    pub fn operation_name(&self) -> &'static str {
+        match self {
+            Self::Ping => "ping",
+            Self::Stop => "stop",
+            Self::CreateCube(_) => "create_cube",
+            Self::CreateSphere(_) => "create_sphere",
+            Self::CreateMesh(_) => "create_mesh",
+            Self::CreateMaterial(_) => "create_material",
+            Self::CreateMaterialFromPreset(_) => "create_material_from_preset",
+            Self::AssignMaterial(_) => "assign_material",
+            Self::SetMaterialSlot(_) => "set_material_slot",
+            Self::RemoveMaterialSlot(_) => "remove_material_slot",
+            Self::SetFaceMaterials(_) => "set_face_materials",
+            Self::GetObject(_) => "get_object",
+            Self::SetTransform(_) => "set_transform",
+            Self::GetMaterial(_) => "get_material",
+            Self::GetMaterialNodes(_) => "get_material_nodes",
+            Self::SetMaterialNodes(_) => "set_material_nodes",
+            Self::GetMeshGeometry(_) => "get_mesh_geometry",
+            Self::SetWorld(_) => "set_world",
+            Self::GetWorld => "get_world",
+            Self::SetSceneSettings(_) => "set_scene_settings",
+            Self::GetSceneSettings => "get_scene_settings",
+            Self::SetUnits(_) => "set_units",
+            Self::GetUnits => "get_units",
+            Self::AddRigidBody(_) => "add_rigid_body",
+            Self::SetGravity(_) => "set_gravity",
+            Self::GetProperty { .. } => "get_property",
+            Self::SetProperty { .. } => "set_property",
+            Self::ImportFile(_) => "import_file",
+            Self::ExportScene(_) => "export_scene",
+            Self::GetSceneIr => "get_scene_ir",
+            Self::GetSceneState => "get_scene_state",
+            Self::SaveBlend(_) => "save_blend",
+            Self::OpenBlend(_) => "open_blend",
+            Self::SetParent(_) => "set_parent",
+            Self::GetChildren(_) => "get_children",
+            Self::GetAncestors(_) => "get_ancestors",
+            Self::GetDependencies(_) => "get_dependencies",
+            Self::GetBoundingBox(_) => "get_bounding_box",
+            Self::SelectObjects(_) => "select_objects",
+            Self::DeselectAll => "deselect_all",
+            Self::GetSelected => "get_selected",
+            Self::ApplyGeometryNodes(_) => "apply_geometry_nodes",
+            Self::BakeTexture(_) => "bake_texture",
+            Self::CreateInstance(_) => "create_instance",
+            Self::SetLightLinking(_) => "set_light_linking",
+            Self::SetShadowVisibility(_) => "set_shadow_visibility",
+            Self::QueryObjects(_) => "query_objects",
+            Self::ListObjects => "list_objects",
+            Self::ListMaterials => "list_materials",
+            Self::ListMeshes => "list_meshes",
+            Self::ClearScene => "clear_scene",
+            Self::Event(event) => event.name(),
+            Self::Batch(_) => "batch",
+        }
+    }
+
+    /// Whether the message only reads state, so repeating it is always safe.
+    pub fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            Self::Ping
+                | Self::GetObject(_)
+                | Self::GetProperty { .. }
+                | Self::GetMaterial(_)
+                | Self::GetMaterialNodes(_)
+                | Self::GetMeshGeometry(_)
+                | Self::GetWorld
+                | Self::GetSceneSettings
+                | Self::GetUnits
+                | Self::GetSceneIr
+                | Self::GetSceneState
+                | Self::GetChildren(_)
+                | Self::GetAncestors(_)
+                | Self::GetDependencies(_)
+                | Self::GetBoundingBox(_)
+                | Self::GetSelected
+                | Self::QueryObjects(_)
+                | Self::ListObjects
+                | Self::ListMaterials
+                | Self::ListMeshes
+        )
+    }
+
+    /// Use `policy` for create operations that don't set their own, including inside batches.
+    pub fn set_default_collision_policy(&mut self, policy: NameCollisionPolicy) {
+        let on_collision = match self {
+            Self::CreateCube(params) => &mut params.on_collision,
+            Self::CreateSphere(params) => &mut params.on_collision,
+            Self::CreateMesh(params) => &mut params.on_collision,
+            Self::CreateMaterial(params) => &mut params.on_collision,
+            Self::CreateMaterialFromPreset(params) => &mut params.on_collision,
+            Self::CreateInstance(params) => &mut params.on_collision,
+            Self::Batch(messages) => {
+                for message in messages {
+                    message.set_default_collision_policy(policy);
+                }
+                return;
+            }
+            _ => return,
+        };
+        on_collision.get_or_insert(policy);
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,25 +209,124 @@ pub enum ServiceResponse {
     Pong,
     Stopped,
     Error(String),
+    // A backend call that didn't finish within its configured timeout, waited on for `attempts`
+    // timeouts in all
+    TimedOut {
+        operation: String,
+        timeout_ms: u64,
+        attempts: u32,
+    },
     // Blender operation responses
-    Created, // For successful create operations
+    Created,           // For successful create operations
+    CreatedAs(String), // A create operation picked a different name than requested
     ObjectData(ObjectData),
     MaterialData(MaterialData),
+    MaterialNodes(BlenderNodeGraph),
+    MeshGeometry(MeshGeometry),
+    WorldData(WorldData),
+    SceneSettings(SceneSettings),
+    Units(UnitSettings),
+    Imported(Vec<String>),
+    Exported(Vec<String>),
+    SceneIr(SceneIr),
+    SceneState(SceneState),
     ObjectList(Vec<String>),
+    Objects(Vec<ObjectData>),
     MaterialList(Vec<String>),
     MeshList(Vec<String>),
     SceneCleared,
+    BlendSaved,
+    BlendOpened,
+    Children(Vec<String>),
+    Ancestors(Vec<String>),
+    Dependencies(ObjectDependencies),
+    BoundingBox(BoundingBox),
+    SelectionChanged,
+    Selection(Vec<String>),
+    GeometryNodesApplied(String),
+    /// The graph matched the one last applied to the object, so its modifier was left as is.
+    GeometryNodesUnchanged(String),
+    TextureBaked(BakedTexture),
+    Property(serde_json::Value),
+    EventReceived,
+    BatchResults(Vec<ServiceResponse>),
 }
 
+/// Identifies a message sent through a `PyBridge`, and the response to it.
+pub type RequestId = u64;
+
+/// A response along with the message it answers.
+#[derive(Debug, Clone)]
+pub struct Reply {
+    pub id: RequestId,
+    pub response: ServiceResponse,
+}
+
+/// Lifecycle of the runtime behind a `PyBridge`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BridgeState {
+    NotStarted,
+    Started,
+    Stopping,
+    Stopped,
+}
+
+/// A snapshot of a `PyBridge` and the runtime behind it, see `PyBridge::status`.
+#[derive(Debug, Clone)]
+pub struct BridgeStatus {
+    pub state: BridgeState,
+    /// Names of the services the runtime started, empty unless it is running.
+    pub services: Vec<String>,
+    /// Messages sent that the runtime hasn't picked up yet.
+    pub queued_messages: usize,
+    /// Responses waiting to be received.
+    pub pending_replies: usize,
+    /// How long the runtime has been running, `None` unless it is.
+    pub uptime: Option<Duration>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BridgeError {
+    #[error("Bridge is closed, the runtime has stopped")]
+    BridgeClosed,
+    #[error("No response to request {id} within {timeout:?}")]
+    TimedOut { id: RequestId, timeout: Duration },
+}
+
+// How long `Drop` waits for the runtime to exit before detaching it. Drop can run from
+// Python's finalizer while Blender is shutting down, so it must never block indefinitely.
+const DROP_JOIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+// How often `recv_reply_to` looks for its reply among those another waiter set aside
+const REPLY_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+// Added to the services' own timeouts in `response_timeout`, for the time a message spends
+// queued and in the runtime outside the backend call
+const RESPONSE_TIMEOUT_MARGIN: Duration = Duration::from_secs(5);
+
 pub struct PyBridge {
-    to_async: Sender<ServiceMessage>,
-    from_async: Receiver<ServiceResponse>,
-    runtime_handle: Option<thread::JoinHandle<()>>,
+    to_async: Sender<(RequestId, ServiceMessage)>,
+    from_async: Receiver<Reply>,
+    next_id: AtomicU64,
+    // Replies passed over while waiting for another, in the order they arrived
+    unclaimed: Mutex<VecDeque<Reply>>,
+    // Messages sent with `send_awaited`, whose replies only `recv_reply_to` hands out
+    awaited: Mutex<HashSet<RequestId>>,
+    // Behind a lock so a bridge shared between threads can still be stopped
+    runtime_handle: Mutex<Option<thread::JoinHandle<()>>>,
+    recording: Option<Arc<Mutex<SessionRecording>>>,
+    config: RuntimeConfig,
+    blender_api: Option<Box<dyn BlenderApi + Send + Sync>>,
+    blender_transport: Option<Box<dyn JsonTransport + Send + Sync>>,
+    state: Arc<Mutex<BridgeState>>,
+    // Set by the runtime once its services have started
+    services: Arc<Mutex<Vec<String>>>,
+    started_at: Mutex<Option<Instant>>,
 }
 
 pub struct PyBridgeAsync {
-    pub rx: Receiver<ServiceMessage>,
-    pub tx: Sender<ServiceResponse>,
+    pub rx: Receiver<(RequestId, ServiceMessage)>,
+    pub tx: Sender<Reply>,
 }
 
 impl PyBridge {
@@ -62,7 +337,17 @@ impl PyBridge {
         let sync_side = PyBridge {
             to_async,
             from_async,
-            runtime_handle: None,
+            next_id: AtomicU64::new(1),
+            unclaimed: Mutex::new(VecDeque::new()),
+            awaited: Mutex::new(HashSet::new()),
+            runtime_handle: Mutex::new(None),
+            recording: None,
+            config: RuntimeConfig::default(),
+            blender_api: None,
+            blender_transport: None,
+            state: Arc::new(Mutex::new(BridgeState::NotStarted)),
+            services: Arc::new(Mutex::new(Vec::new())),
+            started_at: Mutex::new(None),
         };
 
         let async_side = PyBridgeAsync {
@@ -73,17 +358,269 @@ impl PyBridge {
         (sync_side, async_side)
     }
 
-    pub fn send(&self, msg: ServiceMessage) -> Result<(), flume::SendError<ServiceMessage>> {
-        self.to_async.send(msg)
+    pub fn state(&self) -> BridgeState {
+        self.state
+            .lock()
+            .map(|state| *state)
+            .unwrap_or(BridgeState::Stopped)
+    }
+
+    fn set_state(&self, new_state: BridgeState) {
+        set_state(&self.state, new_state);
+    }
+
+    pub fn status(&self) -> BridgeStatus {
+        let state = self.state();
+        let running = state == BridgeState::Started;
+        let started_at = *self.started_at.lock().unwrap_or_else(|e| e.into_inner());
+        BridgeStatus {
+            state,
+            services: if running {
+                self.services
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .clone()
+            } else {
+                Vec::new()
+            },
+            queued_messages: self.to_async.len(),
+            pending_replies: self.from_async.len()
+                + self
+                    .unclaimed
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .len(),
+            uptime: started_at
+                .filter(|_| running)
+                .map(|started_at| started_at.elapsed()),
+        }
+    }
+
+    /// Send `msg` to the runtime. The response to it comes back as a `Reply` with the returned
+    /// id.
+    pub fn send(&self, msg: ServiceMessage) -> Result<RequestId, BridgeError> {
+        self.send_message(msg, false)
+    }
+
+    /// Send `msg` to the runtime for a caller that waits on the response with `recv_reply_to`.
+    /// The other receive methods pass over it, so a thread draining replies can't take it.
+    pub fn send_awaited(&self, msg: ServiceMessage) -> Result<RequestId, BridgeError> {
+        self.send_message(msg, true)
+    }
+
+    fn send_message(&self, msg: ServiceMessage, awaited: bool) -> Result<RequestId, BridgeError> {
+        if matches!(self.state(), BridgeState::Stopping | BridgeState::Stopped) {
+            return Err(BridgeError::BridgeClosed);
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        // Registered before sending, so the reply can't arrive before it is known to be awaited
+        if awaited {
+            self.lock_awaited().insert(id);
+        }
+        if self.to_async.send((id, msg)).is_err() {
+            self.lock_awaited().remove(&id);
+            return Err(BridgeError::BridgeClosed);
+        }
+        Ok(id)
+    }
+
+    fn lock_awaited(&self) -> std::sync::MutexGuard<'_, HashSet<RequestId>> {
+        self.awaited.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // The first set aside reply that isn't awaited
+    fn unclaimed(&self) -> Option<Reply> {
+        let awaited = self.lock_awaited();
+        let mut unclaimed = self.unclaimed.lock().ok()?;
+        let index = unclaimed
+            .iter()
+            .position(|reply| !awaited.contains(&reply.id))?;
+        unclaimed.remove(index)
+    }
+
+    // `reply` when it's not awaited, otherwise it's set aside for its waiter
+    fn deliverable(&self, reply: Reply) -> Option<Reply> {
+        if !self.lock_awaited().contains(&reply.id) {
+            return Some(reply);
+        }
+        match self.unclaimed.lock() {
+            Ok(mut unclaimed) => unclaimed.push_back(reply),
+            Err(e) => error!("Failed to keep reply {}: {}", reply.id, e),
+        }
+        None
     }
 
     pub fn try_recv(&self) -> Option<ServiceResponse> {
-        self.from_async.try_recv().ok()
+        self.try_recv_reply().map(|reply| reply.response)
+    }
+
+    pub fn try_recv_reply(&self) -> Option<Reply> {
+        if let Some(reply) = self.unclaimed() {
+            return Some(reply);
+        }
+        while let Ok(reply) = self.from_async.try_recv() {
+            if let Some(reply) = self.deliverable(reply) {
+                return Some(reply);
+            }
+        }
+        None
+    }
+
+    /// Wait for the next reply, until the runtime has stopped and sent everything it had.
+    pub fn recv(&self) -> Result<Reply, BridgeError> {
+        if let Some(reply) = self.unclaimed() {
+            return Ok(reply);
+        }
+        loop {
+            let reply = self
+                .from_async
+                .recv()
+                .map_err(|_| BridgeError::BridgeClosed)?;
+            if let Some(reply) = self.deliverable(reply) {
+                return Ok(reply);
+            }
+        }
+    }
+
+    /// Wait up to `timeout` for the next reply.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Reply> {
+        if let Some(reply) = self.unclaimed() {
+            return Some(reply);
+        }
+        let deadline = Instant::now() + timeout;
+        loop {
+            let reply = self.from_async.recv_deadline(deadline).ok()?;
+            if let Some(reply) = self.deliverable(reply) {
+                return Some(reply);
+            }
+        }
+    }
+
+    fn claim(&self, id: RequestId) -> Option<Reply> {
+        let mut unclaimed = self.unclaimed.lock().ok()?;
+        let index = unclaimed.iter().position(|reply| reply.id == id)?;
+        unclaimed.remove(index)
+    }
+
+    /// How long to wait for the response to `msg`, a little longer than the services can take
+    /// to answer it themselves, timeouts and retries included.
+    pub fn response_timeout(&self, msg: &ServiceMessage) -> Duration {
+        self.config.response_time_for(msg) + RESPONSE_TIMEOUT_MARGIN
+    }
+
+    /// Wait up to `timeout` for the response to `id`. Replies to other messages that arrive
+    /// first are kept for the other receive methods, and for other threads waiting on their own
+    /// replies. A response arriving after the wait gave up goes to the other receive methods.
+    pub fn recv_reply_to(
+        &self,
+        id: RequestId,
+        timeout: Duration,
+    ) -> Result<ServiceResponse, BridgeError> {
+        let result = self.wait_for(id, timeout);
+        if let Err(BridgeError::TimedOut { .. }) = result {
+            warn!(
+                "Gave up on the response to request {} after {:?}",
+                id, timeout
+            );
+        }
+        self.lock_awaited().remove(&id);
+        result
+    }
+
+    fn wait_for(&self, id: RequestId, timeout: Duration) -> Result<ServiceResponse, BridgeError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(reply) = self.claim(id) {
+                return Ok(reply.response);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(BridgeError::TimedOut { id, timeout });
+            }
+            // Waits are short since another thread may take this reply from the channel
+            let reply = match self
+                .from_async
+                .recv_timeout(REPLY_POLL_INTERVAL.min(deadline - now))
+            {
+                Ok(reply) => reply,
+                Err(flume::RecvTimeoutError::Timeout) => continue,
+                Err(flume::RecvTimeoutError::Disconnected) => {
+                    return self
+                        .claim(id)
+                        .map(|reply| reply.response)
+                        .ok_or(BridgeError::BridgeClosed);
+                }
+            };
+            if reply.id == id {
+                return Ok(reply.response);
+            }
+            match self.unclaimed.lock() {
+                Ok(mut unclaimed) => unclaimed.push_back(reply),
+                Err(e) => error!("Failed to keep reply {}: {}", reply.id, e),
+            }
+        }
+    }
+
+    /// Record every message and response handled by the runtime, so the session can be
+    /// replayed later. Must be called before `start_runtime`.
+    pub fn enable_recording(&mut self) {
+        self.recording = Some(Arc::new(Mutex::new(SessionRecording::default())));
+    }
+
+    /// Snapshot of the exchanges recorded so far, if recording is enabled.
+    pub fn recording(&self) -> Option<SessionRecording> {
+        self.recording
+            .as_ref()
+            .and_then(|recording| recording.lock().ok().map(|r| r.clone()))
+    }
+
+    /// Timeouts and retries for the runtime's services. Must be called before `start_runtime`.
+    pub fn set_runtime_config(&mut self, config: RuntimeConfig) {
+        self.config = config;
+    }
+
+    /// Backend for the runtime's `BlenderService`, instead of the mock. Must be called before
+    /// `start_runtime`.
+    pub fn set_blender_api(&mut self, api: Box<dyn BlenderApi + Send + Sync>) {
+        self.blender_api = Some(api);
+    }
+
+    /// Backend reached through `transport`, such as the Blender addon, instead of the mock.
+    /// Unlike `set_blender_api`, a recording keeps the backend's answers so the session can be
+    /// replayed without it. Must be called before `start_runtime`.
+    pub fn set_blender_transport(&mut self, transport: impl JsonTransport + Send + Sync + 'static) {
+        self.blender_transport = Some(Box::new(transport));
     }
 
     pub fn start_runtime(&mut self, async_bridge: PyBridgeAsync) {
         info!("Starting async runtime");
 
+        let recording = self.recording.clone();
+        let config = self.config.clone();
+        if let Some(recording) = &recording {
+            match recording.lock() {
+                Ok(mut recording) => {
+                    recording.config = config.clone();
+                    recording.backend = match (&self.blender_api, &self.blender_transport) {
+                        (Some(_), _) => RecordedBackend::Unrecorded,
+                        (None, Some(_)) => RecordedBackend::Transport { calls: Vec::new() },
+                        (None, None) => RecordedBackend::Mock,
+                    };
+                }
+                Err(e) => error!("Failed to record runtime config: {}", e),
+            }
+        }
+        let transport_api = self.blender_transport.take().map(|transport| {
+            let transport = RecordingTransport::new(transport, recording.clone());
+            Box::new(JsonBlenderApi::new(transport)) as Box<dyn BlenderApi + Send + Sync>
+        });
+        let blender_api = self.blender_api.take().or(transport_api);
+        let state = self.state.clone();
+        let services = self.services.clone();
+        self.set_state(BridgeState::Started);
+        *self.started_at.get_mut().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+
         let handle = thread::spawn(move || {
             let rt = Runtime::new().expect("Failed to create tokio runtime");
 
@@ -93,19 +630,28 @@ impl PyBridge {
                 // Initialize service manager with basic services
                 let mut service_manager = ServiceManager::new();
                 service_manager.add_service(Box::new(PingService::new("main")));
-                service_manager.add_service(Box::new(BlenderService::new("blender")));
+                let blender_service = match blender_api {
+                    Some(api) => BlenderService::with_api("blender", api, config),
+                    None => BlenderService::with_config("blender", config),
+                };
+                service_manager.add_service(Box::new(blender_service));
 
                 if let Err(e) = service_manager.start_all().await {
                     error!("Failed to start services: {}", e);
                     return;
                 }
+                match services.lock() {
+                    Ok(mut services) => *services = service_manager.service_names(),
+                    Err(e) => error!("Failed to record services: {}", e),
+                }
 
                 // Message handling loop
                 loop {
-                    if let Ok(msg) = async_bridge.rx.recv_async().await {
+                    if let Ok((id, msg)) = async_bridge.rx.recv_async().await {
                         info!("Received message: {:?}", msg);
 
                         let should_stop = matches!(msg, ServiceMessage::Stop);
+                        let recorded_msg = recording.as_ref().map(|_| msg.clone());
 
                         let response = if should_stop {
                             info!("Stopping async runtime");
@@ -117,7 +663,17 @@ impl PyBridge {
                             service_manager.handle_message(msg).await
                         };
 
-                        if let Err(e) = async_bridge.tx.send_async(response).await {
+                        if let (Some(recording), Some(message)) = (&recording, recorded_msg) {
+                            match recording.lock() {
+                                Ok(mut recording) => recording.exchanges.push(RecordedExchange {
+                                    message,
+                                    response: response.clone(),
+                                }),
+                                Err(e) => error!("Failed to record exchange: {}", e),
+                            }
+                        }
+
+                        if let Err(e) = async_bridge.tx.send_async(Reply { id, response }).await {
                             error!("Failed to send response: {}", e);
                             break;
                         }
@@ -134,34 +690,78 @@ impl PyBridge {
                     }
                 }
             });
+
+            set_state(&state, BridgeState::Stopped);
         });
 
-        self.runtime_handle = Some(handle);
+        *self
+            .runtime_handle
+            .get_mut()
+            .unwrap_or_else(|e| e.into_inner()) = Some(handle);
     }
 
-    pub fn stop(&mut self) {
-        if let Err(e) = self.send(ServiceMessage::Stop) {
-            error!("Failed to send stop message: {}", e);
+    /// Stop the runtime and wait for its thread to exit.
+    pub fn stop(&self) {
+        self.shutdown(None);
+    }
+
+    fn shutdown(&self, join_timeout: Option<Duration>) {
+        if self.state() == BridgeState::Started {
+            let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+            if let Err(e) = self.to_async.send((id, ServiceMessage::Stop)) {
+                error!("Failed to send stop message: {}", e);
+            }
+            self.set_state(BridgeState::Stopping);
         }
 
-        if let Some(handle) = self.runtime_handle.take() {
-            if let Err(e) = handle.join() {
-                error!("Failed to join runtime thread: {:?}", e);
+        let handle = self
+            .runtime_handle
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        let Some(handle) = handle else {
+            self.set_state(BridgeState::Stopped);
+            return;
+        };
+
+        if let Some(join_timeout) = join_timeout {
+            let deadline = Instant::now() + join_timeout;
+            while !handle.is_finished() {
+                if Instant::now() >= deadline {
+                    warn!(
+                        "Runtime thread did not stop in {:?}, detaching",
+                        join_timeout
+                    );
+                    self.set_state(BridgeState::Stopped);
+                    return;
+                }
+                thread::sleep(Duration::from_millis(1));
             }
         }
+
+        if let Err(e) = handle.join() {
+            error!("Failed to join runtime thread: {:?}", e);
+        }
+        self.set_state(BridgeState::Stopped);
+    }
+}
+
+fn set_state(state: &Mutex<BridgeState>, new_state: BridgeState) {
+    match state.lock() {
+        Ok(mut state) => *state = new_state,
+        Err(e) => error!("Failed to update bridge state: {}", e),
     }
 }
 
 impl Drop for PyBridge {
     fn drop(&mut self) {
-        self.stop();
+        self.shutdown(Some(DROP_JOIN_TIMEOUT));
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::time::Duration;
 
     #[test]
     fn test_ping_pong() {
@@ -189,4 +789,172 @@ mod tests {
         // Clean shutdown
         bridge.stop();
     }
+
+    #[test]
+    fn test_status() {
+        let (mut bridge, async_bridge) = PyBridge::new();
+        let status = bridge.status();
+        assert_eq!(status.state, BridgeState::NotStarted);
+        assert!(status.uptime.is_none());
+
+        bridge.start_runtime(async_bridge);
+        bridge
+            .send(ServiceMessage::Ping)
+            .expect("Failed to send ping message");
+        bridge
+            .recv_timeout(Duration::from_secs(5))
+            .expect("No reply received");
+
+        let status = bridge.status();
+        assert_eq!(status.state, BridgeState::Started);
+        assert_eq!(status.services, vec!["main", "blender"]);
+        assert_eq!(status.queued_messages, 0);
+        assert_eq!(status.pending_replies, 0);
+        assert!(status.uptime.is_some());
+
+        bridge.stop();
+        let status = bridge.status();
+        assert_eq!(status.state, BridgeState::Stopped);
+        assert!(status.services.is_empty());
+        assert!(status.uptime.is_none());
+    }
+
+    #[test]
+    fn test_recv_timeout() {
+        let (mut bridge, async_bridge) = PyBridge::new();
+        bridge.start_runtime(async_bridge);
+
+        assert!(bridge.recv_timeout(Duration::from_millis(10)).is_none());
+
+        let id = bridge
+            .send(ServiceMessage::Ping)
+            .expect("Failed to send ping message");
+        let reply = bridge
+            .recv_timeout(Duration::from_secs(5))
+            .expect("No reply received");
+        assert_eq!(reply.id, id);
+        assert!(matches!(reply.response, ServiceResponse::Pong));
+
+        bridge.stop();
+    }
+
+    #[test]
+    fn test_recv_reply_to_keeps_other_replies() {
+        let (mut bridge, async_bridge) = PyBridge::new();
+        bridge.start_runtime(async_bridge);
+
+        let first = bridge
+            .send(ServiceMessage::ListObjects)
+            .expect("Failed to send message");
+        let second = bridge
+            .send(ServiceMessage::Ping)
+            .expect("Failed to send ping message");
+        assert_ne!(first, second);
+
+        assert!(matches!(
+            bridge.recv_reply_to(second, Duration::from_secs(5)),
+            Ok(ServiceResponse::Pong)
+        ));
+        let reply = bridge
+            .try_recv_reply()
+            .expect("Reply to the first message was lost");
+        assert_eq!(reply.id, first);
+        assert!(matches!(reply.response, ServiceResponse::ObjectList(_)));
+
+        bridge.stop();
+    }
+
+    #[test]
+    fn test_recv_reply_to_gives_up() {
+        // Never started, so nothing answers
+        let (bridge, async_bridge) = PyBridge::new();
+        let id = bridge
+            .send_awaited(ServiceMessage::Ping)
+            .expect("Failed to send ping message");
+        assert!(matches!(
+            bridge.recv_reply_to(id, Duration::from_millis(20)),
+            Err(BridgeError::TimedOut { .. })
+        ));
+
+        let id = bridge
+            .send_awaited(ServiceMessage::Ping)
+            .expect("Failed to send ping message");
+        drop(async_bridge);
+        assert!(matches!(
+            bridge.recv_reply_to(id, Duration::from_secs(5)),
+            Err(BridgeError::BridgeClosed)
+        ));
+    }
+
+    #[test]
+    fn test_awaited_replies_are_passed_over() {
+        let (mut bridge, async_bridge) = PyBridge::new();
+        bridge.start_runtime(async_bridge);
+
+        let awaited = bridge
+            .send_awaited(ServiceMessage::Ping)
+            .expect("Failed to send ping message");
+        let other = bridge
+            .send(ServiceMessage::Ping)
+            .expect("Failed to send ping message");
+
+        // Draining replies only finds the one nobody waits on
+        let reply = bridge
+            .recv_timeout(Duration::from_secs(5))
+            .expect("No reply received");
+        assert_eq!(reply.id, other);
+        assert!(bridge.try_recv_reply().is_none());
+
+        assert!(matches!(
+            bridge.recv_reply_to(awaited, Duration::from_secs(5)),
+            Ok(ServiceResponse::Pong)
+        ));
+
+        bridge.stop();
+    }
+
+    #[test]
+    fn test_concurrent_waiters_get_their_own_replies() {
+        let (mut bridge, async_bridge) = PyBridge::new();
+        bridge.start_runtime(async_bridge);
+        let bridge = Arc::new(bridge);
+
+        let waiters = (0..4)
+            .map(|_| {
+                let bridge = bridge.clone();
+                thread::spawn(move || {
+                    let id = bridge
+                        .send(ServiceMessage::Ping)
+                        .expect("Failed to send ping message");
+                    bridge.recv_reply_to(id, Duration::from_secs(5))
+                })
+            })
+            .collect::<Vec<_>>();
+        for waiter in waiters {
+            let response = waiter.join().expect("Waiter panicked");
+            assert!(matches!(response, Ok(ServiceResponse::Pong)));
+        }
+        assert!(bridge.try_recv_reply().is_none());
+    }
+
+    #[test]
+    fn test_send_after_stop_is_bridge_closed() {
+        let (mut bridge, async_bridge) = PyBridge::new();
+        assert_eq!(bridge.state(), BridgeState::NotStarted);
+
+        bridge.start_runtime(async_bridge);
+        assert_eq!(bridge.state(), BridgeState::Started);
+
+        bridge.stop();
+        assert_eq!(bridge.state(), BridgeState::Stopped);
+
+        assert!(matches!(
+            bridge.send(ServiceMessage::Ping),
+            Err(BridgeError::BridgeClosed)
+        ));
+
+        // Stopping twice is a no-op
+        bridge.stop();
+        assert_eq!(bridge.state(), BridgeState::Stopped);
+    }
 }
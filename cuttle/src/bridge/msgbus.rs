@@ -1,78 +1,58 @@
 /*!
-# Future: Blender to Services Integration via msgbus
+# Blender to Services events via msgbus
 
-This module will handle Blender to Services communication using Blender's msgbus system
-for event-driven updates when the user modifies nodes in the Blender UI.
+Changes the user makes in the Blender UI reach the services as `ServiceMessage::Event`, so
+the Cuttle language/REPL/LSP side can stay in sync with the scene.
 
-## Planned Architecture
+The Blender addon subscribes to Blender's msgbus and file handlers and forwards what they
+report through the same PyBridge channels as every other message. `cuttle_py` does the
+subscribing:
 
 ```python
 # In Blender addon
-import bpy
+import cuttle_py
 
-def on_node_change(scene):
-    # Called when user adds/removes/modifies nodes
-    cuttle_py.send_message("node_changed", scene_data)
-
-def on_file_save():
-    # Called when user saves .blend file
-    cuttle_py.send_message("file_saved")
-
-# Register callbacks
-bpy.msgbus.subscribe_rna(
-    key=(bpy.types.Scene, "objects"),
-    callback=on_node_change
-)
+owner = cuttle_py.subscribe_msgbus()
+...
+cuttle_py.unsubscribe_msgbus(owner)
 ```
 
-## Planned Events
-
-- **Node Creation**: When user adds new geometry nodes
-- **Node Deletion**: When user removes nodes
-- **Property Changes**: When user modifies node parameters
-- **Connection Changes**: When user connects/disconnects node sockets
-- **File Operations**: Save/load events for bidirectional sync
+which amounts to
 
-## Integration with PyBridge
-
-The msgbus callbacks will send messages through the same PyBridge channels,
-extending the ServiceMessage enum with Blender-specific events:
-
-```rust,ignore
-pub enum ServiceMessage {
-    // Current messages
-    Ping,
-    Stop,
+```python
+def on_object_change():
+    obj = bpy.context.view_layer.objects.active
+    if obj is not None:
+        cuttle_py.notify_object_changed(obj.name)
 
-    // Future: Blender events
-    NodeCreated { node_type: String, properties: HashMap<String, Value> },
-    NodeDeleted { node_id: String },
-    NodeModified { node_id: String, property: String, value: Value },
-    ConnectionChanged { from_node: String, to_node: String },
-    FileOperation { operation: FileOp, path: String },
-}
+bpy.msgbus.subscribe_rna(key=bpy.types.Object, owner=owner, args=(), notify=on_object_change)
+bpy.app.handlers.save_post.append(on_file_save)  # cuttle_py.notify_file_saved(bpy.data.filepath)
 ```
 
-This will enable true bidirectional sync between the Cuttle language/REPL/LSP
-and the Blender UI, making the tool seamless for users working in either environment.
+and similarly for nodes. msgbus doesn't say which object or node changed, so the active one is
+reported.
+
+Each event is answered with `ServiceResponse::EventReceived`.
 */
 
-// Placeholder for future implementation
-pub struct MsgbusHandler;
+use serde::{Deserialize, Serialize};
 
-impl Default for MsgbusHandler {
-    fn default() -> Self {
-        Self::new()
-    }
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BlenderEvent {
+    /// A property of the object changed, such as its transform or name.
+    ObjectChanged { name: String },
+    /// A node in the node tree `tree` was added, removed, relinked or edited.
+    NodeChanged { tree: String, node: String },
+    /// The .blend file was saved to `path`.
+    FileSaved { path: String },
 }
 
-impl MsgbusHandler {
-    pub fn new() -> Self {
-        Self
-    }
-
-    // Future: Register msgbus callbacks
-    pub fn register_callbacks(&self) {
-        todo!("Implement msgbus callback registration")
+impl BlenderEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ObjectChanged { .. } => "object_changed",
+            Self::NodeChanged { .. } => "node_changed",
+            Self::FileSaved { .. } => "file_saved",
+        }
     }
 }
@@ -0,0 +1,338 @@
+use crate::bridge::{PyBridge, ServiceMessage, ServiceResponse};
+use crate::config::RuntimeConfig;
+use cuttle_blender_api::{BlenderApiError, JsonTransport};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tracing::error;
+
+/// A single message sent through the bridge and the response the runtime produced for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedExchange {
+    pub message: ServiceMessage,
+    pub response: ServiceResponse,
+}
+
+/// A call the runtime made through a backend's transport, and what the backend answered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCall {
+    pub operation: String,
+    pub params: String,
+    pub result: Result<String, String>,
+}
+
+/// The backend a recorded session ran against.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum RecordedBackend {
+    /// The built in mock, which answers the same messages the same way every time.
+    #[default]
+    Mock,
+    /// A backend set with `PyBridge::set_blender_transport`, with every call made to it.
+    Transport { calls: Vec<RecordedCall> },
+    /// A backend set with `PyBridge::set_blender_api`, whose calls can't be recorded.
+    Unrecorded,
+}
+
+/// Every exchange of a bridge session, in the order the runtime handled them, along with the
+/// inputs that decided the responses.
+///
+/// The runtime has no timestamps or random seeds of its own. Its responses come from the
+/// messages, the config and the backend: the mock is deterministic, and the answers of any other
+/// backend are recorded, including the names it generated. The one input left out is the clock,
+/// so a call that timed out when recorded may not time out when replayed, and shows up as a
+/// divergence.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionRecording {
+    #[serde(default)]
+    pub config: RuntimeConfig,
+    #[serde(default)]
+    pub backend: RecordedBackend,
+    pub exchanges: Vec<RecordedExchange>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReplayError {
+    #[error(
+        "Replay diverged at message {index} ({message}):\n  expected: {expected}\n  actual:   {actual}"
+    )]
+    Diverged {
+        index: usize,
+        message: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("Failed to send message {index} during replay: {reason}")]
+    SendFailed { index: usize, reason: String },
+    #[error("Timed out waiting for the response to message {index}")]
+    Timeout { index: usize },
+    #[error("The session's backend calls weren't recorded, so it can't be replayed")]
+    UnrecordedBackend,
+}
+
+impl SessionRecording {
+    /// Replay the recorded messages against a fresh runtime with the recorded config and
+    /// backend, failing on the first response that differs from the recording.
+    pub fn replay(&self) -> Result<(), ReplayError> {
+        let (mut bridge, async_bridge) = PyBridge::new();
+        bridge.set_runtime_config(self.config.clone());
+        match &self.backend {
+            RecordedBackend::Mock => {}
+            RecordedBackend::Transport { calls } => {
+                bridge.set_blender_transport(PlaybackTransport::new(calls.clone()));
+            }
+            RecordedBackend::Unrecorded => return Err(ReplayError::UnrecordedBackend),
+        }
+        bridge.start_runtime(async_bridge);
+
+        let result = self.replay_exchanges(&bridge);
+        bridge.stop();
+        result
+    }
+
+    fn replay_exchanges(&self, bridge: &PyBridge) -> Result<(), ReplayError> {
+        for (index, exchange) in self.exchanges.iter().enumerate() {
+            let timeout = bridge.response_timeout(&exchange.message);
+            let id = bridge.send_awaited(exchange.message.clone()).map_err(|e| {
+                ReplayError::SendFailed {
+                    index,
+                    reason: e.to_string(),
+                }
+            })?;
+            let actual = bridge
+                .recv_reply_to(id, timeout)
+                .map_err(|_| ReplayError::Timeout { index })?;
+
+            // Compared as JSON values, since maps in the responses serialize in no set order
+            if to_value(&exchange.response) != to_value(&actual) {
+                return Err(ReplayError::Diverged {
+                    index,
+                    message: serialize(&exchange.message),
+                    expected: serialize(&exchange.response),
+                    actual: serialize(&actual),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Passes calls on to the backend's transport, adding each to the recording when there is one
+pub(crate) struct RecordingTransport {
+    transport: Box<dyn JsonTransport + Send + Sync>,
+    recording: Option<Arc<Mutex<SessionRecording>>>,
+}
+
+impl RecordingTransport {
+    pub(crate) fn new(
+        transport: Box<dyn JsonTransport + Send + Sync>,
+        recording: Option<Arc<Mutex<SessionRecording>>>,
+    ) -> Self {
+        Self {
+            transport,
+            recording,
+        }
+    }
+}
+
+impl JsonTransport for RecordingTransport {
+    fn call(&self, operation: &str, params: &str) -> Result<String, BlenderApiError> {
+        let result = self.transport.call(operation, params);
+        if let Some(recording) = &self.recording {
+            match recording.lock() {
+                Ok(mut recording) => {
+                    if let RecordedBackend::Transport { calls } = &mut recording.backend {
+                        calls.push(RecordedCall {
+                            operation: operation.to_string(),
+                            params: params.to_string(),
+                            result: result.as_ref().map_err(ToString::to_string).cloned(),
+                        });
+                    }
+                }
+                Err(e) => error!("Failed to record backend call: {}", e),
+            }
+        }
+        result
+    }
+}
+
+// Answers each call with the next recorded one, as long as the calls come in the same order
+struct PlaybackTransport {
+    calls: Mutex<VecDeque<RecordedCall>>,
+}
+
+impl PlaybackTransport {
+    fn new(calls: Vec<RecordedCall>) -> Self {
+        Self {
+            calls: Mutex::new(calls.into()),
+        }
+    }
+}
+
+impl JsonTransport for PlaybackTransport {
+    fn call(&self, operation: &str, params: &str) -> Result<String, BlenderApiError> {
+        let mut calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+        match calls.pop_front() {
+            Some(call) if call.operation == operation && call.params == params => call
+                .result
+                .map_err(|message| BlenderApiError::from_message(&message)),
+            Some(call) => Err(BlenderApiError::OperationFailed {
+                message: format!(
+                    "Replay called {operation} with {params} where the recording called {} with {}",
+                    call.operation, call.params
+                ),
+            }),
+            None => Err(BlenderApiError::OperationFailed {
+                message: format!("Replay called {operation} after the recorded calls ran out"),
+            }),
+        }
+    }
+}
+
+fn to_value<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or_else(|e| Value::String(format!("<unserializable: {e}>")))
+}
+
+fn serialize<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).unwrap_or_else(|e| format!("<unserializable: {e}>"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bridge::PyBridgeAsync;
+    use cuttle_blender_api::{CreateCubeParams, NameCollisionPolicy, Vec3};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn create_cube(name: &str) -> ServiceMessage {
+        ServiceMessage::CreateCube(CreateCubeParams {
+            location: Vec3::zero(),
+            name: name.to_string(),
+            size: 2.0,
+            on_collision: None,
+        })
+    }
+
+    fn record(mut bridge: PyBridge, async_bridge: PyBridgeAsync) -> SessionRecording {
+        bridge.enable_recording();
+        bridge.start_runtime(async_bridge);
+
+        for message in [
+            ServiceMessage::Ping,
+            create_cube("Cube"),
+            create_cube("Cube"),
+            ServiceMessage::ListObjects,
+        ] {
+            let timeout = bridge.response_timeout(&message);
+            let id = bridge
+                .send_awaited(message)
+                .expect("Failed to send message");
+            bridge
+                .recv_reply_to(id, timeout)
+                .expect("No response received");
+        }
+
+        let recording = bridge.recording().expect("Recording was not enabled");
+        bridge.stop();
+        recording
+    }
+
+    fn record_session() -> SessionRecording {
+        let (bridge, async_bridge) = PyBridge::new();
+        record(bridge, async_bridge)
+    }
+
+    #[test]
+    fn test_replay_matches_recording() {
+        let recording = record_session();
+        assert_eq!(recording.exchanges.len(), 4);
+        recording.replay().expect("Replay diverged");
+    }
+
+    #[test]
+    fn test_replay_reports_first_divergence() {
+        let mut recording = record_session();
+        recording.exchanges[3].response = ServiceResponse::ObjectList(vec!["Other".to_string()]);
+
+        match recording.replay() {
+            Err(ReplayError::Diverged { index, .. }) => assert_eq!(index, 3),
+            other => panic!("Expected divergence, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_replay_uses_the_recorded_config() {
+        let (mut bridge, async_bridge) = PyBridge::new();
+        bridge.set_runtime_config(RuntimeConfig {
+            name_collision: Some(NameCollisionPolicy::AutoSuffix),
+            ..Default::default()
+        });
+        let recording = record(bridge, async_bridge);
+        assert!(matches!(
+            &recording.exchanges[2].response,
+            ServiceResponse::CreatedAs(name) if name == "Cube.001"
+        ));
+
+        // Survives being saved and loaded, as a recording on disk would be
+        let json = serde_json::to_string(&recording).expect("Failed to serialize recording");
+        let recording: SessionRecording =
+            serde_json::from_str(&json).expect("Failed to deserialize recording");
+        recording.replay().expect("Replay diverged");
+    }
+
+    // Names each created object after how many calls came before it, like a backend
+    // generating names the mock wouldn't
+    struct NamingTransport {
+        calls: AtomicU32,
+    }
+
+    impl JsonTransport for NamingTransport {
+        fn call(&self, operation: &str, _params: &str) -> Result<String, BlenderApiError> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(match operation {
+                "list_objects" => format!(r#"["Object.{call}"]"#),
+                _ => format!(r#""Object.{call}""#),
+            })
+        }
+    }
+
+    #[test]
+    fn test_replay_plays_back_the_backend() {
+        let (mut bridge, async_bridge) = PyBridge::new();
+        bridge.set_blender_transport(NamingTransport {
+            calls: AtomicU32::new(0),
+        });
+        let mut recording = record(bridge, async_bridge);
+        match &recording.backend {
+            RecordedBackend::Transport { calls } => assert_eq!(calls.len(), 3),
+            other => panic!("Expected recorded backend calls, got {other:?}"),
+        }
+        assert!(matches!(
+            &recording.exchanges[1].response,
+            ServiceResponse::CreatedAs(name) if name == "Object.0"
+        ));
+        recording.replay().expect("Replay diverged");
+
+        // A backend answering differently than recorded is a divergence
+        if let RecordedBackend::Transport { calls } = &mut recording.backend {
+            calls[1].result = Ok(r#""Other""#.to_string());
+        }
+        match recording.replay() {
+            Err(ReplayError::Diverged { index, .. }) => assert_eq!(index, 2),
+            other => panic!("Expected divergence, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_unrecorded_backend_is_not_replayed() {
+        let recording = SessionRecording {
+            backend: RecordedBackend::Unrecorded,
+            ..Default::default()
+        };
+        assert!(matches!(
+            recording.replay(),
+            Err(ReplayError::UnrecordedBackend)
+        ));
+    }
+}
@@ -0,0 +1,80 @@
+use crate::bridge::ServiceMessage;
+use cuttle_blender_api::{MaterialLibrary, NameCollisionPolicy};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Settings for the services started by `PyBridge::start_runtime`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeConfig {
+    /// Time a single backend call may take when no per-operation timeout is set.
+    pub default_timeout: Duration,
+    /// Timeouts keyed by operation name, see `ServiceMessage::operation_name`.
+    pub operation_timeouts: HashMap<String, Duration>,
+    pub retry: RetryPolicy,
+    /// Collision policy for create operations that don't set their own, the backend's
+    /// default when unset.
+    pub name_collision: Option<NameCollisionPolicy>,
+    /// Presets available to `ServiceMessage::CreateMaterialFromPreset`.
+    pub materials: MaterialLibrary,
+}
+
+impl RuntimeConfig {
+    pub fn timeout_for(&self, operation: &str) -> Duration {
+        self.operation_timeouts
+            .get(operation)
+            .copied()
+            .unwrap_or(self.default_timeout)
+    }
+
+    /// The longest the services can take to answer `msg`: its timeout for every attempt it is
+    /// allowed, with the backoff between them. A batch takes as long as its messages together.
+    pub fn response_time_for(&self, msg: &ServiceMessage) -> Duration {
+        if let ServiceMessage::Batch(messages) = msg {
+            return messages
+                .iter()
+                .map(|message| self.response_time_for(message))
+                .sum();
+        }
+
+        let retries = if msg.is_read_only() {
+            self.retry.max_retries
+        } else {
+            0
+        };
+        self.timeout_for(msg.operation_name()) * (retries + 1) + self.retry.backoff * retries
+    }
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            default_timeout: Duration::from_secs(30),
+            operation_timeouts: HashMap::new(),
+            retry: RetryPolicy::default(),
+            name_collision: None,
+            materials: MaterialLibrary::builtin(),
+        }
+    }
+}
+
+/// Retries for read-only operations that fail or time out.
+///
+/// A call that fails with `OperationFailed`, such as one whose connection dropped, is made again
+/// after `backoff`. One that times out is given another timeout to finish instead, since it still
+/// holds the backend and a second call would only queue behind it. Writes are never retried, since
+/// a failed or timed out write may still complete in the backend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            backoff: Duration::from_millis(100),
+        }
+    }
+}
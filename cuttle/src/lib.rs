@@ -1,7 +1,11 @@
 pub mod bridge;
+pub mod config;
 pub mod logging;
+pub mod remote;
 pub mod service;
 
 pub use bridge::*;
+pub use config::*;
 pub use logging::*;
+pub use remote::{RemoteBlenderApi, TcpTransport};
 pub use service::*;
@@ -0,0 +1,229 @@
+use cuttle_blender_api::{BlenderApiError, JsonBlenderApi, JsonTransport};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// `BlenderApi` client for a Blender addon serving length-prefixed JSON over TCP.
+///
+/// Each frame is a big-endian `u32` byte length followed by a JSON body. Requests are
+/// `{"operation": "create_cube", "params": {...}}` and the addon answers every request with
+/// either `{"result": ...}` or `{"error": "..."}`.
+pub type RemoteBlenderApi = JsonBlenderApi<TcpTransport>;
+
+// Refuse frames larger than this rather than allocating whatever a bad header asks for
+const MAX_FRAME_LEN: usize = 256 * 1024 * 1024;
+
+/// How long `TcpTransport::connect` waits on the addon to connect, and for each read and write.
+pub const DEFAULT_IO_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Serialize)]
+struct RemoteRequest<'a> {
+    operation: &'a str,
+    params: Value,
+}
+
+#[derive(Deserialize)]
+struct RemoteResponse {
+    #[serde(default)]
+    result: Value,
+    error: Option<String>,
+}
+
+pub struct TcpTransport {
+    addresses: Vec<SocketAddr>,
+    timeout: Duration,
+    // Taken for each round trip and only put back after a clean one. A stream left mid-frame by
+    // an error or timeout is dropped, and the next call connects again.
+    stream: Mutex<Option<TcpStream>>,
+}
+
+impl TcpTransport {
+    pub fn connect(address: impl ToSocketAddrs) -> std::io::Result<Self> {
+        Self::connect_timeout(address, DEFAULT_IO_TIMEOUT)
+    }
+
+    /// Connect, giving up on the connection and on each read and write after `timeout`.
+    pub fn connect_timeout(
+        address: impl ToSocketAddrs,
+        timeout: Duration,
+    ) -> std::io::Result<Self> {
+        let addresses = address.to_socket_addrs()?.collect::<Vec<_>>();
+        let stream = open(&addresses, timeout)?;
+        Ok(Self {
+            addresses,
+            timeout,
+            stream: Mutex::new(Some(stream)),
+        })
+    }
+}
+
+fn open(addresses: &[SocketAddr], timeout: Duration) -> std::io::Result<TcpStream> {
+    let mut last_error = None;
+    for address in addresses {
+        match TcpStream::connect_timeout(address, timeout) {
+            Ok(stream) => {
+                stream.set_nodelay(true)?;
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                return Ok(stream);
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "Address resolved to nothing",
+        )
+    }))
+}
+
+impl JsonTransport for TcpTransport {
+    fn call(&self, operation: &str, params: &str) -> Result<String, BlenderApiError> {
+        let failed = |message: String| BlenderApiError::OperationFailed { message };
+
+        let params = serde_json::from_str(params)
+            .map_err(|e| failed(format!("Invalid {operation} parameters: {e}")))?;
+        let request = serde_json::to_vec(&RemoteRequest { operation, params })
+            .map_err(|e| failed(format!("Failed to encode {operation} request: {e}")))?;
+
+        // Requests and responses are paired, so the stream stays locked for the round trip
+        let mut stream = self
+            .stream
+            .lock()
+            .map_err(|_| failed("Remote connection lock poisoned".to_string()))?;
+        let mut connection = match stream.take() {
+            Some(connection) => connection,
+            None => open(&self.addresses, self.timeout)
+                .map_err(|e| failed(format!("Failed to reconnect to Blender: {e}")))?,
+        };
+        write_frame(&mut connection, &request)
+            .map_err(|e| failed(format!("Failed to send {operation} to Blender: {e}")))?;
+        let body = read_frame(&mut connection).map_err(|e| {
+            failed(format!(
+                "Failed to read {operation} response from Blender: {e}"
+            ))
+        })?;
+        *stream = Some(connection);
+
+        let response: RemoteResponse = serde_json::from_slice(&body)
+            .map_err(|e| failed(format!("Invalid {operation} response from Blender: {e}")))?;
+        // Errors in `BlenderApiError`'s display form keep their kind, anything else failed outright
+        match response.error {
+            Some(message) => Err(BlenderApiError::from_message(&message)),
+            None => Ok(response.result.to_string()),
+        }
+    }
+}
+
+pub fn write_frame(writer: &mut impl Write, body: &[u8]) -> std::io::Result<()> {
+    let length = u32::try_from(body.len())
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "Frame is too large"))?;
+    writer.write_all(&length.to_be_bytes())?;
+    writer.write_all(body)?;
+    writer.flush()
+}
+
+pub fn read_frame(reader: &mut impl Read) -> std::io::Result<Vec<u8>> {
+    let mut header = [0; 4];
+    reader.read_exact(&mut header)?;
+
+    let length = u32::from_be_bytes(header) as usize;
+    if length > MAX_FRAME_LEN {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Frame of {length} bytes exceeds the {MAX_FRAME_LEN} byte limit"),
+        ));
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cuttle_blender_api::BlenderApi;
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn test_remote_round_trip() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind listener");
+        let address = listener.local_addr().expect("Failed to get address");
+
+        // Stand-in for the Blender addon
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("Failed to accept");
+            let mut operations = Vec::new();
+            for reply in [
+                r#"{"result":["Cube"]}"#,
+                r#"{"error":"Object not found: Missing"}"#,
+            ] {
+                let request = read_frame(&mut stream).expect("Failed to read request");
+                let request: Value =
+                    serde_json::from_slice(&request).expect("Failed to decode request");
+                operations.push(
+                    request["operation"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                );
+                write_frame(&mut stream, reply.as_bytes()).expect("Failed to write reply");
+            }
+            operations
+        });
+
+        let api = RemoteBlenderApi::new(TcpTransport::connect(address).expect("Failed to connect"));
+        let objects = api.list_objects().expect("Failed to list objects");
+        assert_eq!(objects, vec!["Cube"]);
+
+        let missing = api.get_object(cuttle_blender_api::GetObjectParams {
+            name: "Missing".to_string(),
+        });
+        assert!(matches!(
+            missing,
+            Err(BlenderApiError::ObjectNotFound { name }) if name == "Missing"
+        ));
+
+        let operations = server.join().expect("Server thread panicked");
+        assert_eq!(operations, vec!["list_objects", "get_object"]);
+    }
+
+    #[test]
+    fn test_unanswered_call_times_out_and_reconnects() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind listener");
+        let address = listener.local_addr().expect("Failed to get address");
+
+        // Never answers the first connection, then serves a second one
+        let server = thread::spawn(move || {
+            let (mut stalled, _) = listener.accept().expect("Failed to accept");
+            read_frame(&mut stalled).expect("Failed to read request");
+
+            let (mut stream, _) = listener.accept().expect("Failed to accept reconnect");
+            read_frame(&mut stream).expect("Failed to read request");
+            write_frame(&mut stream, br#"{"result":["Cube"]}"#).expect("Failed to write reply");
+        });
+
+        let transport = TcpTransport::connect_timeout(address, Duration::from_millis(200))
+            .expect("Failed to connect");
+        let api = RemoteBlenderApi::new(transport);
+        assert!(api.list_objects().is_err());
+        assert_eq!(
+            api.list_objects().expect("Failed to list objects"),
+            vec!["Cube"]
+        );
+
+        server.join().expect("Server thread panicked");
+    }
+
+    #[test]
+    fn test_oversized_frame_is_rejected() {
+        let header = u32::MAX.to_be_bytes();
+        assert!(read_frame(&mut &header[..]).is_err());
+    }
+}
@@ -1,9 +1,16 @@
-use crate::bridge::{ServiceMessage, ServiceResponse};
+use crate::bridge::{BlenderEvent, ServiceMessage, ServiceResponse};
+use crate::config::RuntimeConfig;
 use async_trait::async_trait;
+use cuttle_blender_api::{BlenderApi, BlenderApiError, GetPropertyParams, SetPropertyParams};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
 use tracing::{info, warn};
 
 #[async_trait]
 pub trait Service: Send + Sync {
+    fn name(&self) -> &str;
     async fn start(&mut self) -> Result<(), ServiceError>;
     async fn handle_message(&mut self, msg: ServiceMessage) -> ServiceResponse;
     async fn stop(&mut self) -> Result<(), ServiceError>;
@@ -34,6 +41,13 @@ impl ServiceManager {
         self.services.push(service);
     }
 
+    pub fn service_names(&self) -> Vec<String> {
+        self.services
+            .iter()
+            .map(|service| service.name().to_string())
+            .collect()
+    }
+
     pub async fn start_all(&mut self) -> Result<(), ServiceError> {
         info!("Starting {} services", self.services.len());
 
@@ -64,6 +78,29 @@ impl ServiceManager {
         match msg {
             ServiceMessage::Ping => ServiceResponse::Pong,
             ServiceMessage::Stop => ServiceResponse::Stopped,
+            ServiceMessage::Event(event) => {
+                info!("Blender event: {:?}", event);
+                // Services keep state about the scene, such as what was last applied to it
+                for service in &mut self.services {
+                    service
+                        .handle_message(ServiceMessage::Event(event.clone()))
+                        .await;
+                }
+                ServiceResponse::EventReceived
+            }
+            ServiceMessage::Batch(messages) => {
+                let mut results = Vec::with_capacity(messages.len());
+                for message in messages {
+                    // Only the runtime loop can act on a stop, so it can't be buried in a batch
+                    let response = if matches!(message, ServiceMessage::Stop) {
+                        ServiceResponse::Error("Stop cannot be sent in a batch".to_string())
+                    } else {
+                        Box::pin(self.handle_message(message)).await
+                    };
+                    results.push(response);
+                }
+                ServiceResponse::BatchResults(results)
+            }
             // Route Blender messages to the first available service that can handle them
             blender_msg => {
                 for service in &mut self.services {
@@ -99,6 +136,10 @@ impl PingService {
 
 #[async_trait]
 impl Service for PingService {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
     async fn start(&mut self) -> Result<(), ServiceError> {
         info!("Starting PingService: {}", self.name);
         Ok(())
@@ -123,74 +164,155 @@ impl Service for PingService {
 // BlenderService implementation
 pub struct BlenderService {
     name: String,
-    api: Box<dyn cuttle_blender_api::BlenderApi + Send + Sync>,
+    // Calls run on the blocking pool so a hung backend can be timed out
+    api: Arc<Mutex<Box<dyn BlenderApi + Send + Sync>>>,
+    config: RuntimeConfig,
+    // A call that timed out but is still running and holding the api. Until it finishes the
+    // backend is treated as unresponsive and messages fail fast rather than queue behind it.
+    stalled: Option<(&'static str, JoinHandle<ServiceResponse>)>,
+    // The content hash and modifier of the graph last applied to each object, so an unchanged
+    // graph isn't applied again. Forgotten whenever the scene may have changed underneath it.
+    applied: HashMap<String, (u64, String)>,
 }
 
 impl BlenderService {
     pub fn new(name: impl Into<String>) -> Self {
+        Self::with_config(name, RuntimeConfig::default())
+    }
+
+    pub fn with_config(name: impl Into<String>, config: RuntimeConfig) -> Self {
+        // Use mock implementation unless a backend is provided
+        Self::with_api(
+            name,
+            Box::new(cuttle_blender_api::MockBlenderApi::new()),
+            config,
+        )
+    }
+
+    pub fn with_api(
+        name: impl Into<String>,
+        api: Box<dyn BlenderApi + Send + Sync>,
+        config: RuntimeConfig,
+    ) -> Self {
         Self {
             name: name.into(),
-            // Use mock implementation for now
-            api: Box::new(cuttle_blender_api::MockBlenderApi::new()),
+            api: Arc::new(Mutex::new(api)),
+            config,
+            stalled: None,
+            applied: HashMap::new(),
         }
     }
 }
 
 #[async_trait]
 impl Service for BlenderService {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
     async fn start(&mut self) -> Result<(), ServiceError> {
         info!("Starting BlenderService: {}", self.name);
         Ok(())
     }
 
-    async fn handle_message(&mut self, msg: ServiceMessage) -> ServiceResponse {
+    async fn handle_message(&mut self, mut msg: ServiceMessage) -> ServiceResponse {
         info!("BlenderService {} handling message: {:?}", self.name, msg);
 
-        match msg {
-            ServiceMessage::CreateCube(params) => match self.api.create_cube(params) {
-                Ok(()) => ServiceResponse::Created,
-                Err(e) => ServiceResponse::Error(e.to_string()),
-            },
-            ServiceMessage::CreateSphere(params) => match self.api.create_sphere(params) {
-                Ok(()) => ServiceResponse::Created,
-                Err(e) => ServiceResponse::Error(e.to_string()),
-            },
-            ServiceMessage::CreateMaterial(params) => match self.api.create_material(params) {
-                Ok(()) => ServiceResponse::Created,
-                Err(e) => ServiceResponse::Error(e.to_string()),
-            },
-            ServiceMessage::AssignMaterial(params) => match self.api.assign_material(params) {
-                Ok(()) => ServiceResponse::Created,
-                Err(e) => ServiceResponse::Error(e.to_string()),
-            },
-            ServiceMessage::GetObject(params) => match self.api.get_object(params) {
-                Ok(data) => ServiceResponse::ObjectData(data),
-                Err(e) => ServiceResponse::Error(e.to_string()),
-            },
-            ServiceMessage::GetMaterial(params) => match self.api.get_material(params) {
-                Ok(data) => ServiceResponse::MaterialData(data),
-                Err(e) => ServiceResponse::Error(e.to_string()),
-            },
-            ServiceMessage::ListObjects => match self.api.list_objects() {
-                Ok(objects) => ServiceResponse::ObjectList(objects),
-                Err(e) => ServiceResponse::Error(e.to_string()),
-            },
-            ServiceMessage::ListMaterials => match self.api.list_materials() {
-                Ok(materials) => ServiceResponse::MaterialList(materials),
-                Err(e) => ServiceResponse::Error(e.to_string()),
-            },
-            ServiceMessage::ListMeshes => match self.api.list_meshes() {
-                Ok(meshes) => ServiceResponse::MeshList(meshes),
-                Err(e) => ServiceResponse::Error(e.to_string()),
-            },
-            ServiceMessage::ClearScene => match self.api.clear_scene() {
-                Ok(()) => ServiceResponse::SceneCleared,
-                Err(e) => ServiceResponse::Error(e.to_string()),
-            },
-            // BlenderService doesn't handle basic messages
-            _ => ServiceResponse::Error(
-                "BlenderService doesn't handle this message type".to_string(),
-            ),
+        if let Some(policy) = self.config.name_collision {
+            msg.set_default_collision_policy(policy);
+        }
+
+        // Backends only ever see plain materials; unknown presets are left for dispatch to report
+        if let ServiceMessage::CreateMaterialFromPreset(params) = &msg {
+            if let Ok(material) = self.config.materials.material(params) {
+                msg = ServiceMessage::CreateMaterial(material);
+            }
+        }
+
+        if let ServiceMessage::Event(event) = &msg {
+            match event {
+                BlenderEvent::ObjectChanged { name } => {
+                    self.applied.remove(name);
+                }
+                BlenderEvent::NodeChanged { .. } => self.applied.clear(),
+                BlenderEvent::FileSaved { .. } => {}
+            }
+            return ServiceResponse::EventReceived;
+        }
+
+        let applying = match &msg {
+            ServiceMessage::ApplyGeometryNodes(params) => {
+                let hash = params.graph.content_hash();
+                match self.applied.get(&params.object) {
+                    Some((applied, modifier)) if *applied == hash && !params.force => {
+                        return ServiceResponse::GeometryNodesUnchanged(modifier.clone());
+                    }
+                    _ => {
+                        // A failed apply may leave the modifier half updated
+                        self.applied.remove(&params.object);
+                        Some((params.object.clone(), hash))
+                    }
+                }
+            }
+            msg if !msg.is_read_only() => {
+                self.applied.clear();
+                None
+            }
+            _ => None,
+        };
+
+        if let Some((operation, call)) = &self.stalled {
+            if !call.is_finished() {
+                return ServiceResponse::Error(format!(
+                    "Backend is unresponsive, {operation} is still running after timing out"
+                ));
+            }
+            warn!(
+                "Timed out {} call finished late, its result was dropped",
+                operation
+            );
+            self.stalled = None;
+        }
+
+        let operation = msg.operation_name();
+        let attempts = if msg.is_read_only() {
+            self.config.retry.max_retries + 1
+        } else {
+            1
+        };
+        let api = &self.api;
+        let start = || {
+            let api = api.clone();
+            let msg = msg.clone();
+            tokio::task::spawn_blocking(move || {
+                // A panicked call leaves the api as it was, so keep using it
+                let mut api = api.lock().unwrap_or_else(|e| e.into_inner());
+                dispatch(api.as_mut(), msg)
+            })
+        };
+
+        match call_with_retry(
+            operation,
+            self.config.timeout_for(operation),
+            attempts,
+            self.config.retry.backoff,
+            start,
+            is_transient,
+        )
+        .await
+        {
+            Ok(response) => {
+                if let (Some((object, hash)), ServiceResponse::GeometryNodesApplied(modifier)) =
+                    (applying, &response)
+                {
+                    self.applied.insert(object, (hash, modifier.clone()));
+                }
+                response
+            }
+            Err(failed) => {
+                self.stalled = failed.still_running.map(|call| (operation, call));
+                failed.response
+            }
         }
     }
 
@@ -200,9 +322,309 @@ impl Service for BlenderService {
     }
 }
 
+fn created_response(requested: String, result: Result<String, BlenderApiError>) -> ServiceResponse {
+    match result {
+        Ok(name) if name == requested => ServiceResponse::Created,
+        Ok(name) => ServiceResponse::CreatedAs(name),
+        Err(e) => ServiceResponse::Error(e.to_string()),
+    }
+}
+
+fn dispatch(api: &mut (dyn BlenderApi + Send + Sync), msg: ServiceMessage) -> ServiceResponse {
+    match msg {
+        ServiceMessage::CreateCube(params) => {
+            let requested = params.name.clone();
+            created_response(requested, api.create_cube(params))
+        }
+        ServiceMessage::CreateSphere(params) => {
+            let requested = params.name.clone();
+            created_response(requested, api.create_sphere(params))
+        }
+        ServiceMessage::CreateMesh(params) => {
+            let requested = params.name.clone();
+            created_response(requested, api.create_mesh(params))
+        }
+        ServiceMessage::CreateMaterial(params) => {
+            let requested = params.name.clone();
+            created_response(requested, api.create_material(params))
+        }
+        ServiceMessage::CreateMaterialFromPreset(params) => ServiceResponse::Error(
+            BlenderApiError::PresetNotFound {
+                name: params.preset,
+            }
+            .to_string(),
+        ),
+        ServiceMessage::AssignMaterial(params) => match api.assign_material(params) {
+            Ok(()) => ServiceResponse::Created,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::GetObject(params) => match api.get_object(params) {
+            Ok(data) => ServiceResponse::ObjectData(data),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::GetMaterial(params) => match api.get_material(params) {
+            Ok(data) => ServiceResponse::MaterialData(data),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::GetMaterialNodes(params) => match api.get_material_nodes(params) {
+            Ok(graph) => ServiceResponse::MaterialNodes(graph),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::SetMaterialNodes(params) => match api.set_material_nodes(params) {
+            Ok(()) => ServiceResponse::Created,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::GetMeshGeometry(params) => match api.get_mesh_geometry(params) {
+            Ok(geometry) => ServiceResponse::MeshGeometry(geometry),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::SetWorld(params) => match api.set_world(params) {
+            Ok(()) => ServiceResponse::Created,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::GetWorld => match api.get_world() {
+            Ok(world) => ServiceResponse::WorldData(world),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::SetSceneSettings(settings) => match api.set_scene_settings(settings) {
+            Ok(()) => ServiceResponse::Created,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::GetSceneSettings => match api.get_scene_settings() {
+            Ok(settings) => ServiceResponse::SceneSettings(settings),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::SetUnits(units) => match api.set_units(units) {
+            Ok(()) => ServiceResponse::Created,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::AddRigidBody(params) => match api.add_rigid_body(params) {
+            Ok(()) => ServiceResponse::Created,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::SetGravity(gravity) => match api.set_gravity(gravity) {
+            Ok(()) => ServiceResponse::Created,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::GetProperty { target, data_path } => {
+            match api.get_property(GetPropertyParams { target, data_path }) {
+                Ok(value) => ServiceResponse::Property(value),
+                Err(e) => ServiceResponse::Error(e.to_string()),
+            }
+        }
+        ServiceMessage::SetProperty {
+            target,
+            data_path,
+            value,
+        } => match api.set_property(SetPropertyParams {
+            target,
+            data_path,
+            value,
+        }) {
+            Ok(()) => ServiceResponse::Created,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::GetUnits => match api.get_units() {
+            Ok(units) => ServiceResponse::Units(units),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::ImportFile(params) => match api.import_file(params) {
+            Ok(objects) => ServiceResponse::Imported(objects),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::ExportScene(params) => match api.export_scene(params) {
+            Ok(objects) => ServiceResponse::Exported(objects),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::GetSceneIr => match api.get_scene_ir() {
+            Ok(scene) => ServiceResponse::SceneIr(scene),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::GetSceneState => match api.get_scene_state() {
+            Ok(state) => ServiceResponse::SceneState(state),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::SaveBlend(params) => match api.save_blend(params) {
+            Ok(()) => ServiceResponse::BlendSaved,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::OpenBlend(params) => match api.open_blend(params) {
+            Ok(()) => ServiceResponse::BlendOpened,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::SetParent(params) => match api.set_parent(params) {
+            Ok(()) => ServiceResponse::Created,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::GetChildren(params) => match api.get_children(params) {
+            Ok(children) => ServiceResponse::Children(children),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::GetAncestors(params) => match api.get_ancestors(params) {
+            Ok(ancestors) => ServiceResponse::Ancestors(ancestors),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::GetDependencies(params) => match api.get_dependencies(params) {
+            Ok(dependencies) => ServiceResponse::Dependencies(dependencies),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::GetBoundingBox(params) => match api.get_bounding_box(params) {
+            Ok(bounds) => ServiceResponse::BoundingBox(bounds),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::SelectObjects(params) => match api.select_objects(params) {
+            Ok(()) => ServiceResponse::SelectionChanged,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::DeselectAll => match api.deselect_all() {
+            Ok(()) => ServiceResponse::SelectionChanged,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::GetSelected => match api.get_selected() {
+            Ok(names) => ServiceResponse::Selection(names),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::ApplyGeometryNodes(params) => match api.apply_geometry_nodes(params) {
+            Ok(modifier) => ServiceResponse::GeometryNodesApplied(modifier),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::CreateInstance(params) => {
+            let requested = params.name.clone();
+            created_response(requested, api.create_instance(params))
+        }
+        ServiceMessage::SetMaterialSlot(params) => match api.set_material_slot(params) {
+            Ok(()) => ServiceResponse::Created,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::RemoveMaterialSlot(params) => match api.remove_material_slot(params) {
+            Ok(()) => ServiceResponse::Created,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::SetFaceMaterials(params) => match api.set_face_materials(params) {
+            Ok(()) => ServiceResponse::Created,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::SetTransform(params) => match api.set_transform(params) {
+            Ok(()) => ServiceResponse::Created,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::BakeTexture(params) => match api.bake_texture(params) {
+            Ok(baked) => ServiceResponse::TextureBaked(baked),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::SetLightLinking(params) => match api.set_light_linking(params) {
+            Ok(()) => ServiceResponse::Created,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::SetShadowVisibility(params) => match api.set_shadow_visibility(params) {
+            Ok(()) => ServiceResponse::Created,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::QueryObjects(filter) => match api.query_objects(filter) {
+            Ok(objects) => ServiceResponse::Objects(objects),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::ListObjects => match api.list_objects() {
+            Ok(objects) => ServiceResponse::ObjectList(objects),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::ListMaterials => match api.list_materials() {
+            Ok(materials) => ServiceResponse::MaterialList(materials),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::ListMeshes => match api.list_meshes() {
+            Ok(meshes) => ServiceResponse::MeshList(meshes),
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        ServiceMessage::ClearScene => match api.clear_scene() {
+            Ok(()) => ServiceResponse::SceneCleared,
+            Err(e) => ServiceResponse::Error(e.to_string()),
+        },
+        // BlenderService doesn't handle basic messages
+        _ => ServiceResponse::Error("BlenderService doesn't handle this message type".to_string()),
+    }
+}
+
+// The response for a call that failed, and the call when it timed out and is still running
+struct FailedCall<T> {
+    response: ServiceResponse,
+    still_running: Option<JoinHandle<T>>,
+}
+
+// Run a blocking call from `start` with a timeout, making up to `attempts` attempts. A call
+// that panics, or whose result is `failed`, is started again after `backoff`. One that times
+// out is waited on for another attempt rather than started again: it holds the api, so a second
+// call would only queue behind it. Returns the response to send back when every attempt fails,
+// along with the call itself when it is still running.
+async fn call_with_retry<T>(
+    operation: &str,
+    timeout: Duration,
+    attempts: u32,
+    backoff: Duration,
+    mut start: impl FnMut() -> JoinHandle<T>,
+    failed: impl Fn(&T) -> bool,
+) -> Result<T, Box<FailedCall<T>>> {
+    let mut call = start();
+    for attempt in 1..=attempts {
+        let last = attempt == attempts;
+        match tokio::time::timeout(timeout, &mut call).await {
+            Ok(Ok(value)) if last || !failed(&value) => return Ok(value),
+            Ok(Ok(_)) => warn!(
+                "Operation {} failed (attempt {}/{})",
+                operation, attempt, attempts
+            ),
+            Ok(Err(e)) if last => {
+                return Err(Box::new(FailedCall {
+                    response: ServiceResponse::Error(format!("Operation {operation} failed: {e}")),
+                    still_running: None,
+                }));
+            }
+            Ok(Err(e)) => warn!(
+                "Operation {} failed: {} (attempt {}/{})",
+                operation, e, attempt, attempts
+            ),
+            Err(_) => {
+                warn!(
+                    "Operation {} timed out after {:?} (attempt {}/{})",
+                    operation, timeout, attempt, attempts
+                );
+                if !last {
+                    tokio::time::sleep(backoff).await;
+                }
+                continue;
+            }
+        }
+        tokio::time::sleep(backoff).await;
+        call = start();
+    }
+
+    Err(Box::new(FailedCall {
+        response: ServiceResponse::TimedOut {
+            operation: operation.to_string(),
+            timeout_ms: timeout.as_millis() as u64,
+            attempts,
+        },
+        still_running: Some(call),
+    }))
+}
+
+// Errors worth another attempt, such as a dropped connection, rather than ones the same call
+// would only run into again
+fn is_transient(response: &ServiceResponse) -> bool {
+    matches!(
+        response,
+        ServiceResponse::Error(message)
+            if matches!(
+                BlenderApiError::from_message(message),
+                BlenderApiError::OperationFailed { .. }
+            )
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::bridge::BlenderEvent;
 
     #[tokio::test]
     async fn test_service_manager() {
@@ -225,6 +647,325 @@ mod tests {
         manager.stop_all().await.expect("Failed to stop services");
     }
 
+    #[tokio::test]
+    async fn test_events_are_received() {
+        let mut manager = ServiceManager::new();
+        manager.add_service(Box::new(BlenderService::new("blender")));
+
+        let event = ServiceMessage::Event(BlenderEvent::FileSaved {
+            path: "/tmp/scene.blend".to_string(),
+        });
+        assert_eq!(event.operation_name(), "file_saved");
+        assert!(matches!(
+            manager.handle_message(event).await,
+            ServiceResponse::EventReceived
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_graph_is_not_reapplied() {
+        async fn apply(manager: &mut ServiceManager, force: bool) -> (String, bool) {
+            let graph = cuttle_lang::parse_geometry_nodes("cube { size: 2.0 }\noutput")
+                .expect("Failed to parse graph");
+            let message =
+                ServiceMessage::ApplyGeometryNodes(cuttle_blender_api::ApplyGeometryNodesParams {
+                    object: "Cube".to_string(),
+                    graph: graph.try_into().expect("Failed to convert graph"),
+                    force,
+                });
+            match manager.handle_message(message).await {
+                ServiceResponse::GeometryNodesApplied(modifier) => (modifier, false),
+                ServiceResponse::GeometryNodesUnchanged(modifier) => (modifier, true),
+                other => panic!("Expected geometry nodes response, got {other:?}"),
+            }
+        }
+        let create = |name: &str| {
+            ServiceMessage::CreateCube(cuttle_blender_api::CreateCubeParams {
+                name: name.to_string(),
+                ..Default::default()
+            })
+        };
+
+        let mut manager = ServiceManager::new();
+        manager.add_service(Box::new(BlenderService::new("blender")));
+        manager.handle_message(create("Cube")).await;
+
+        let modifier = |name: &str, unchanged| (name.to_string(), unchanged);
+        assert_eq!(
+            apply(&mut manager, false).await,
+            modifier("GeometryNodes", false)
+        );
+        assert_eq!(
+            apply(&mut manager, false).await,
+            modifier("GeometryNodes", true)
+        );
+        assert_eq!(
+            apply(&mut manager, true).await,
+            modifier("GeometryNodes.001", false)
+        );
+        assert_eq!(
+            apply(&mut manager, false).await,
+            modifier("GeometryNodes.001", true)
+        );
+
+        // Edits to the scene, from cuttle or from Blender, mean the graph has to be applied again
+        manager.handle_message(create("Other")).await;
+        assert_eq!(
+            apply(&mut manager, false).await,
+            modifier("GeometryNodes.002", false)
+        );
+        manager
+            .handle_message(ServiceMessage::Event(BlenderEvent::ObjectChanged {
+                name: "Cube".to_string(),
+            }))
+            .await;
+        assert_eq!(
+            apply(&mut manager, false).await,
+            modifier("GeometryNodes.003", false)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_messages() {
+        let mut manager = ServiceManager::new();
+        manager.add_service(Box::new(BlenderService::new("blender")));
+
+        let response = manager
+            .handle_message(ServiceMessage::Batch(vec![
+                ServiceMessage::Ping,
+                ServiceMessage::CreateCube(cuttle_blender_api::CreateCubeParams {
+                    location: cuttle_blender_api::Vec3::zero(),
+                    name: "Cube".to_string(),
+                    size: 2.0,
+                    on_collision: None,
+                }),
+                ServiceMessage::Stop,
+                ServiceMessage::ListObjects,
+            ]))
+            .await;
+
+        match response {
+            ServiceResponse::BatchResults(results) => {
+                assert_eq!(results.len(), 4);
+                assert!(matches!(results[0], ServiceResponse::Pong));
+                assert!(matches!(results[1], ServiceResponse::Created));
+                assert!(matches!(results[2], ServiceResponse::Error(_)));
+                match &results[3] {
+                    ServiceResponse::ObjectList(objects) => assert_eq!(objects, &vec!["Cube"]),
+                    other => panic!("Expected object list, got {other:?}"),
+                }
+            }
+            other => panic!("Expected batch results, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_service_collision_policy() {
+        let config = RuntimeConfig {
+            name_collision: Some(cuttle_blender_api::NameCollisionPolicy::AutoSuffix),
+            ..Default::default()
+        };
+        let mut service = BlenderService::with_config("blender", config);
+        let cube = || {
+            ServiceMessage::CreateCube(cuttle_blender_api::CreateCubeParams {
+                location: cuttle_blender_api::Vec3::zero(),
+                name: "Cube".to_string(),
+                size: 2.0,
+                on_collision: None,
+            })
+        };
+
+        assert!(matches!(
+            service.handle_message(cube()).await,
+            ServiceResponse::Created
+        ));
+        match service.handle_message(cube()).await {
+            ServiceResponse::CreatedAs(name) => assert_eq!(name, "Cube.001"),
+            other => panic!("Expected created as response, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_service_material_presets() {
+        let mut service = BlenderService::new("blender");
+        let preset = |preset: &str| {
+            ServiceMessage::CreateMaterialFromPreset(
+                cuttle_blender_api::CreateMaterialFromPresetParams {
+                    name: "Ring".to_string(),
+                    preset: preset.to_string(),
+                    on_collision: None,
+                },
+            )
+        };
+
+        match service.handle_message(preset("unobtainium")).await {
+            ServiceResponse::Error(e) => assert!(e.contains("unobtainium")),
+            other => panic!("Expected error response, got {other:?}"),
+        }
+        assert!(matches!(
+            service.handle_message(preset("gold")).await,
+            ServiceResponse::Created
+        ));
+        let get = ServiceMessage::GetMaterial(cuttle_blender_api::GetMaterialParams {
+            name: "Ring".to_string(),
+        });
+        match service.handle_message(get).await {
+            ServiceResponse::MaterialData(material) => assert_eq!(material.metallic, 1.0),
+            other => panic!("Expected material data, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_call_with_retry_times_out() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let slow = tokio::task::spawn_blocking(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(200));
+        });
+
+        let mut slow = Some(slow);
+        let result = call_with_retry(
+            "get_world",
+            Duration::from_millis(10),
+            3,
+            Duration::ZERO,
+            || slow.take().expect("Timed out call was started again"),
+            |_| false,
+        )
+        .await;
+
+        let failed = result.expect_err("Slow call didn't time out");
+        match failed.response {
+            ServiceResponse::TimedOut {
+                operation,
+                timeout_ms,
+                attempts,
+            } => {
+                assert_eq!(operation, "get_world");
+                assert_eq!(timeout_ms, 10);
+                assert_eq!(attempts, 3);
+            }
+            other => panic!("Expected timeout, got {other:?}"),
+        }
+        // Retries wait on the call already running instead of starting it again
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert!(failed.still_running.is_some());
+
+        let fast = call_with_retry(
+            "ping",
+            Duration::from_secs(5),
+            1,
+            Duration::ZERO,
+            || tokio::task::spawn_blocking(|| 42),
+            |_| false,
+        )
+        .await;
+        assert!(matches!(fast, Ok(42)));
+    }
+
+    // Drops the connection on the first call, and never has the object asked for
+    struct FlakyTransport {
+        calls: Arc<std::sync::atomic::AtomicU32>,
+    }
+
+    impl cuttle_blender_api::JsonTransport for FlakyTransport {
+        fn call(&self, operation: &str, _params: &str) -> Result<String, BlenderApiError> {
+            let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            match operation {
+                _ if call == 0 => Err(BlenderApiError::OperationFailed {
+                    message: "Connection reset".to_string(),
+                }),
+                "get_object" => Err(BlenderApiError::ObjectNotFound {
+                    name: "Missing".to_string(),
+                }),
+                _ => Ok("[]".to_string()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_reads_are_issued_again() {
+        use std::sync::atomic::Ordering;
+
+        let calls = Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let api = cuttle_blender_api::JsonBlenderApi::new(FlakyTransport {
+            calls: calls.clone(),
+        });
+        let config = RuntimeConfig {
+            retry: crate::config::RetryPolicy {
+                max_retries: 2,
+                backoff: Duration::ZERO,
+            },
+            ..Default::default()
+        };
+        let mut service = BlenderService::with_api("blender", Box::new(api), config);
+
+        assert!(matches!(
+            service.handle_message(ServiceMessage::ListObjects).await,
+            ServiceResponse::ObjectList(_)
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // The same call would only fail the same way again
+        let missing = ServiceMessage::GetObject(cuttle_blender_api::GetObjectParams {
+            name: "Missing".to_string(),
+        });
+        assert!(matches!(
+            service.handle_message(missing).await,
+            ServiceResponse::Error(_)
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    // Hangs on get_world, answers everything else right away
+    struct SlowTransport;
+
+    impl cuttle_blender_api::JsonTransport for SlowTransport {
+        fn call(&self, operation: &str, _params: &str) -> Result<String, BlenderApiError> {
+            if operation == "get_world" {
+                std::thread::sleep(Duration::from_millis(500));
+            }
+            Ok("[]".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stalled_backend_fails_fast() {
+        let config = RuntimeConfig {
+            default_timeout: Duration::from_millis(20),
+            retry: crate::config::RetryPolicy {
+                max_retries: 0,
+                backoff: Duration::ZERO,
+            },
+            ..Default::default()
+        };
+        let api = cuttle_blender_api::JsonBlenderApi::new(SlowTransport);
+        let mut service = BlenderService::with_api("blender", Box::new(api), config);
+
+        assert!(matches!(
+            service.handle_message(ServiceMessage::GetWorld).await,
+            ServiceResponse::TimedOut { .. }
+        ));
+
+        // The timed out call still holds the api, so this must not wait for it
+        let start = std::time::Instant::now();
+        match service.handle_message(ServiceMessage::ListObjects).await {
+            ServiceResponse::Error(e) => assert!(e.contains("unresponsive")),
+            other => panic!("Expected error response, got {other:?}"),
+        }
+        assert!(start.elapsed() < Duration::from_millis(200));
+
+        // Once it finishes the backend is used again
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        assert!(matches!(
+            service.handle_message(ServiceMessage::ListObjects).await,
+            ServiceResponse::ObjectList(_)
+        ));
+    }
+
     #[tokio::test]
     async fn test_ping_service() {
         let mut service = PingService::new("test");
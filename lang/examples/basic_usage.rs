@@ -2,25 +2,36 @@ use cuttle_lang::{BlenderNodeGraph, parse_geometry_nodes, parse_geometry_nodes_w
 
 fn main() {
     // Parse a simple cube node
-    let cube_input = "cube { size: 2.0 }";
+    let cube_input = "cube { size: 2.0 }\noutput";
     let cube_graph = parse_geometry_nodes(cube_input).expect("Failed to parse cube");
     println!("Parsed cube node: {cube_graph:?}");
 
     // Convert to Blender format
-    let blender_cube: BlenderNodeGraph = cube_graph.into();
+    let blender_cube = BlenderNodeGraph::try_from(cube_graph).expect("Failed to convert cube");
     println!("Blender format: {blender_cube:?}");
 
-    // Parse a value node
-    let value_input = "value 42";
-    let value_graph = parse_geometry_nodes(value_input).expect("Failed to parse value");
-    println!("Parsed value node: {value_graph:?}");
+    // Name nodes and connect their sockets, here a value driving the sphere's radius
+    let connected_input = "size = value 0.5\nball = sphere\nout = output\nsize.value -> ball.radius\nball.mesh -> out.geometry";
+    let connected_graph =
+        parse_geometry_nodes(connected_input).expect("Failed to parse connected nodes");
+    println!("Parsed connected nodes: {connected_graph:?}");
 
-    // Convert to Blender format
-    let blender_value: BlenderNodeGraph = value_graph.into();
-    println!("Blender format: {blender_value:?}");
+    // Convert to Blender format, where connections become links between node sockets
+    let blender_connected =
+        BlenderNodeGraph::try_from(connected_graph).expect("Failed to convert connected nodes");
+    println!("Blender format: {blender_connected:?}");
+
+    // Reference an object that already exists in the scene
+    let object_input =
+        "suzanne = object \"Suzanne\"\nout = output\nsuzanne.geometry -> out.geometry";
+    let object_graph = parse_geometry_nodes(object_input).expect("Failed to parse object");
+    let blender_object =
+        BlenderNodeGraph::try_from(object_graph).expect("Failed to convert object");
+    println!("Blender format: {blender_object:?}");
 
     // Serialize to JSON
-    let json = serde_json::to_string_pretty(&blender_value).expect("Failed to serialize to JSON");
+    let json =
+        serde_json::to_string_pretty(&blender_connected).expect("Failed to serialize to JSON");
     println!("JSON representation:\n{json}");
 
     // Demonstrate error reporting
@@ -46,12 +57,12 @@ fn main() {
         }
     }
 
-    // Invalid number
-    let invalid_number = "value abc";
-    match parse_geometry_nodes_with_errors(invalid_number) {
+    // Connection to a node that was never named
+    let missing_node = "ball = sphere\nsize.value -> ball.radius";
+    match parse_geometry_nodes_with_errors(missing_node) {
         Ok(_) => println!("Unexpected success"),
         Err(error_report) => {
-            println!("Error parsing '{invalid_number}':");
+            println!("Error parsing '{missing_node}':");
             println!("{error_report}");
         }
     }
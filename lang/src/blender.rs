@@ -1,5 +1,6 @@
-use crate::{Node, NodeGraph, Value};
+use crate::{ContentHasher, GraphError, Node, NodeGraph, NodeId, Value};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BlenderNode {
@@ -27,7 +28,7 @@ pub enum BlenderValue {
     String(String),
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct BlenderNodeGraph {
     pub nodes: Vec<BlenderNode>,
     pub links: Vec<BlenderLink>,
@@ -66,6 +67,33 @@ impl From<BlenderValue> for Value {
     }
 }
 
+fn input_socket(name: &str, socket_type: &str, value: BlenderValue) -> BlenderSocket {
+    BlenderSocket {
+        name: name.to_string(),
+        socket_type: socket_type.to_string(),
+        default_value: Some(value),
+    }
+}
+
+fn output_socket(name: &str, socket_type: &str) -> BlenderSocket {
+    BlenderSocket {
+        name: name.to_string(),
+        socket_type: socket_type.to_string(),
+        default_value: None,
+    }
+}
+
+// Mesh primitive nodes only differ in their inputs; all of them output a single Mesh
+fn mesh_primitive(node_type: &str, inputs: Vec<BlenderSocket>) -> BlenderNode {
+    BlenderNode {
+        node_type: node_type.to_string(),
+        location: (0.0, 0.0),
+        inputs,
+        outputs: vec![output_socket("Mesh", "NodeSocketGeometry")],
+        parameters: std::collections::HashMap::new(),
+    }
+}
+
 impl From<Node> for BlenderNode {
     fn from(node: Node) -> Self {
         match node {
@@ -99,17 +127,576 @@ impl From<Node> for BlenderNode {
                     parameters,
                 }
             }
+            // Subdivisions scale the segments and rings as they do for `CreateSphereParams`
+            Node::Sphere {
+                radius,
+                subdivisions,
+                ..
+            } => {
+                let scaled = |factor: i64| match &subdivisions {
+                    Value::Integer(subdivisions) => {
+                        BlenderValue::Integer(subdivisions.saturating_mul(factor))
+                    }
+                    other => other.clone().into(),
+                };
+                mesh_primitive(
+                    "GeometryNodeMeshUVSphere",
+                    vec![
+                        input_socket("Segments", "NodeSocketInt", scaled(4)),
+                        input_socket("Rings", "NodeSocketInt", scaled(2)),
+                        input_socket("Radius", "NodeSocketFloat", radius.into()),
+                    ],
+                )
+            }
+            Node::Cylinder {
+                vertices,
+                radius,
+                depth,
+                ..
+            } => mesh_primitive(
+                "GeometryNodeMeshCylinder",
+                vec![
+                    input_socket("Vertices", "NodeSocketInt", vertices.into()),
+                    input_socket("Radius", "NodeSocketFloat", radius.into()),
+                    input_socket("Depth", "NodeSocketFloat", depth.into()),
+                ],
+            ),
+            Node::Cone {
+                vertices,
+                radius_top,
+                radius_bottom,
+                depth,
+                ..
+            } => mesh_primitive(
+                "GeometryNodeMeshCone",
+                vec![
+                    input_socket("Vertices", "NodeSocketInt", vertices.into()),
+                    input_socket("Radius Top", "NodeSocketFloat", radius_top.into()),
+                    input_socket("Radius Bottom", "NodeSocketFloat", radius_bottom.into()),
+                    input_socket("Depth", "NodeSocketFloat", depth.into()),
+                ],
+            ),
+            Node::Math {
+                operation,
+                a,
+                b,
+                clamp,
+                ..
+            } => {
+                let mut parameters = std::collections::HashMap::new();
+                parameters.insert(
+                    "operation".to_string(),
+                    BlenderValue::String(operation.blender_name().to_string()),
+                );
+                parameters.insert("use_clamp".to_string(), BlenderValue::Boolean(clamp));
+                // Every Math input is named "Value", so sockets use their unique identifiers
+                BlenderNode {
+                    node_type: "ShaderNodeMath".to_string(),
+                    location: (0.0, 0.0),
+                    inputs: vec![
+                        input_socket("Value", "NodeSocketFloat", a.into()),
+                        input_socket("Value_001", "NodeSocketFloat", b.into()),
+                    ],
+                    outputs: vec![output_socket("Value", "NodeSocketFloat")],
+                    parameters,
+                }
+            }
+            Node::VectorMath {
+                operation, a, b, ..
+            } => {
+                let mut parameters = std::collections::HashMap::new();
+                parameters.insert(
+                    "operation".to_string(),
+                    BlenderValue::String(operation.blender_name().to_string()),
+                );
+                BlenderNode {
+                    node_type: "ShaderNodeVectorMath".to_string(),
+                    location: (0.0, 0.0),
+                    inputs: vec![
+                        input_socket("Vector", "NodeSocketVector", a.into()),
+                        input_socket("Vector_001", "NodeSocketVector", b.into()),
+                    ],
+                    outputs: vec![output_socket("Vector", "NodeSocketVector")],
+                    parameters,
+                }
+            }
+            // The Mix node's data type follows its inputs: any vector or color makes it mix those
+            Node::Mix { factor, a, b, .. } => {
+                let (data_type, socket_type) = match (&a, &b) {
+                    (Value::Color(..), _) | (_, Value::Color(..)) => ("RGBA", "NodeSocketColor"),
+                    (Value::Vector(..), _) | (_, Value::Vector(..)) => {
+                        ("VECTOR", "NodeSocketVector")
+                    }
+                    _ => ("FLOAT", "NodeSocketFloat"),
+                };
+                let mut parameters = std::collections::HashMap::new();
+                parameters.insert(
+                    "data_type".to_string(),
+                    BlenderValue::String(data_type.to_string()),
+                );
+                BlenderNode {
+                    node_type: "ShaderNodeMix".to_string(),
+                    location: (0.0, 0.0),
+                    inputs: vec![
+                        input_socket("Factor", "NodeSocketFloat", factor.into()),
+                        input_socket("A", socket_type, a.into()),
+                        input_socket("B", socket_type, b.into()),
+                    ],
+                    outputs: vec![output_socket("Result", socket_type)],
+                    parameters,
+                }
+            }
+            // The texture coordinates default to each point's position when nothing is linked
+            Node::Noise {
+                scale,
+                detail,
+                roughness,
+                lacunarity,
+                distortion,
+                ..
+            } => BlenderNode {
+                node_type: "ShaderNodeTexNoise".to_string(),
+                location: (0.0, 0.0),
+                inputs: vec![
+                    output_socket("Vector", "NodeSocketVector"),
+                    input_socket("Scale", "NodeSocketFloat", scale.into()),
+                    input_socket("Detail", "NodeSocketFloat", detail.into()),
+                    input_socket("Roughness", "NodeSocketFloat", roughness.into()),
+                    input_socket("Lacunarity", "NodeSocketFloat", lacunarity.into()),
+                    input_socket("Distortion", "NodeSocketFloat", distortion.into()),
+                ],
+                outputs: vec![
+                    output_socket("Fac", "NodeSocketFloat"),
+                    output_socket("Color", "NodeSocketColor"),
+                ],
+                parameters: std::collections::HashMap::new(),
+            },
+            Node::WhiteNoise { .. } => BlenderNode {
+                node_type: "ShaderNodeTexWhiteNoise".to_string(),
+                location: (0.0, 0.0),
+                inputs: vec![output_socket("Vector", "NodeSocketVector")],
+                outputs: vec![
+                    output_socket("Value", "NodeSocketFloat"),
+                    output_socket("Color", "NodeSocketColor"),
+                ],
+                parameters: std::collections::HashMap::new(),
+            },
+            // Like Mix, the data type follows the range: vectors, integers or floats. The ID
+            // defaults to each element's index when nothing is linked
+            Node::Random { seed, min, max, .. } => {
+                let (data_type, socket_type) = match (&min, &max) {
+                    (Value::Vector(..), _) | (_, Value::Vector(..)) => {
+                        ("FLOAT_VECTOR", "NodeSocketVector")
+                    }
+                    (Value::Integer(_), Value::Integer(_)) => ("INT", "NodeSocketInt"),
+                    _ => ("FLOAT", "NodeSocketFloat"),
+                };
+                let mut parameters = std::collections::HashMap::new();
+                parameters.insert(
+                    "data_type".to_string(),
+                    BlenderValue::String(data_type.to_string()),
+                );
+                BlenderNode {
+                    node_type: "FunctionNodeRandomValue".to_string(),
+                    location: (0.0, 0.0),
+                    inputs: vec![
+                        input_socket("Min", socket_type, min.into()),
+                        input_socket("Max", socket_type, max.into()),
+                        output_socket("ID", "NodeSocketInt"),
+                        input_socket("Seed", "NodeSocketInt", seed.into()),
+                    ],
+                    outputs: vec![output_socket("Value", socket_type)],
+                    parameters,
+                }
+            }
+            // Selection and offset are fields that default to everything and to the normals
+            Node::ExtrudeMesh {
+                offset_scale,
+                individual,
+                ..
+            } => {
+                let mut parameters = std::collections::HashMap::new();
+                parameters.insert(
+                    "mode".to_string(),
+                    BlenderValue::String("FACES".to_string()),
+                );
+                BlenderNode {
+                    node_type: "GeometryNodeExtrudeMesh".to_string(),
+                    location: (0.0, 0.0),
+                    inputs: vec![
+                        output_socket("Mesh", "NodeSocketGeometry"),
+                        output_socket("Selection", "NodeSocketBool"),
+                        output_socket("Offset", "NodeSocketVector"),
+                        input_socket("Offset Scale", "NodeSocketFloat", offset_scale.into()),
+                        input_socket(
+                            "Individual",
+                            "NodeSocketBool",
+                            BlenderValue::Boolean(individual),
+                        ),
+                    ],
+                    outputs: vec![
+                        output_socket("Mesh", "NodeSocketGeometry"),
+                        output_socket("Top", "NodeSocketBool"),
+                        output_socket("Side", "NodeSocketBool"),
+                    ],
+                    parameters,
+                }
+            }
+            Node::SubdivideMesh { level, .. } => BlenderNode {
+                node_type: "GeometryNodeSubdivideMesh".to_string(),
+                location: (0.0, 0.0),
+                inputs: vec![
+                    output_socket("Mesh", "NodeSocketGeometry"),
+                    input_socket("Level", "NodeSocketInt", level.into()),
+                ],
+                outputs: vec![output_socket("Mesh", "NodeSocketGeometry")],
+                parameters: std::collections::HashMap::new(),
+            },
+            Node::MeshBoolean { operation, .. } => {
+                let mut parameters = std::collections::HashMap::new();
+                parameters.insert(
+                    "operation".to_string(),
+                    BlenderValue::String(operation.blender_name().to_string()),
+                );
+                BlenderNode {
+                    node_type: "GeometryNodeMeshBoolean".to_string(),
+                    location: (0.0, 0.0),
+                    inputs: vec![
+                        output_socket("Mesh 1", "NodeSocketGeometry"),
+                        output_socket("Mesh 2", "NodeSocketGeometry"),
+                        input_socket(
+                            "Self Intersection",
+                            "NodeSocketBool",
+                            BlenderValue::Boolean(false),
+                        ),
+                        input_socket(
+                            "Hole Tolerant",
+                            "NodeSocketBool",
+                            BlenderValue::Boolean(false),
+                        ),
+                    ],
+                    outputs: vec![
+                        output_socket("Mesh", "NodeSocketGeometry"),
+                        output_socket("Intersecting Edges", "NodeSocketBool"),
+                    ],
+                    parameters,
+                }
+            }
+            Node::JoinGeometry { .. } => BlenderNode {
+                node_type: "GeometryNodeJoinGeometry".to_string(),
+                location: (0.0, 0.0),
+                inputs: vec![output_socket("Geometry", "NodeSocketGeometry")],
+                outputs: vec![output_socket("Geometry", "NodeSocketGeometry")],
+                parameters: std::collections::HashMap::new(),
+            },
+            Node::DistributePoints { density, .. } => {
+                let mut parameters = std::collections::HashMap::new();
+                parameters.insert(
+                    "distribute_method".to_string(),
+                    BlenderValue::String("RANDOM".to_string()),
+                );
+                BlenderNode {
+                    node_type: "GeometryNodeDistributePointsOnFaces".to_string(),
+                    location: (0.0, 0.0),
+                    inputs: vec![
+                        output_socket("Mesh", "NodeSocketGeometry"),
+                        output_socket("Selection", "NodeSocketBool"),
+                        input_socket("Density", "NodeSocketFloat", density.into()),
+                        input_socket("Seed", "NodeSocketInt", BlenderValue::Integer(0)),
+                    ],
+                    outputs: vec![
+                        output_socket("Points", "NodeSocketGeometry"),
+                        output_socket("Normal", "NodeSocketVector"),
+                        output_socket("Rotation", "NodeSocketRotation"),
+                    ],
+                    parameters,
+                }
+            }
+            // Selection, instance index and rotation are fields, left unset to use their defaults
+            Node::InstanceOnPoints { .. } => BlenderNode {
+                node_type: "GeometryNodeInstanceOnPoints".to_string(),
+                location: (0.0, 0.0),
+                inputs: vec![
+                    output_socket("Points", "NodeSocketGeometry"),
+                    output_socket("Selection", "NodeSocketBool"),
+                    output_socket("Instance", "NodeSocketGeometry"),
+                    input_socket(
+                        "Pick Instance",
+                        "NodeSocketBool",
+                        BlenderValue::Boolean(false),
+                    ),
+                    output_socket("Instance Index", "NodeSocketInt"),
+                    output_socket("Rotation", "NodeSocketRotation"),
+                    input_socket(
+                        "Scale",
+                        "NodeSocketVector",
+                        BlenderValue::Vector(1.0, 1.0, 1.0),
+                    ),
+                ],
+                outputs: vec![output_socket("Instances", "NodeSocketGeometry")],
+                parameters: std::collections::HashMap::new(),
+            },
+            Node::Position { .. } => BlenderNode {
+                node_type: "GeometryNodeInputPosition".to_string(),
+                location: (0.0, 0.0),
+                inputs: vec![],
+                outputs: vec![output_socket("Position", "NodeSocketVector")],
+                parameters: std::collections::HashMap::new(),
+            },
+            Node::Normal { .. } => BlenderNode {
+                node_type: "GeometryNodeInputNormal".to_string(),
+                location: (0.0, 0.0),
+                inputs: vec![],
+                outputs: vec![output_socket("Normal", "NodeSocketVector")],
+                parameters: std::collections::HashMap::new(),
+            },
+            Node::NamedAttribute { name, .. } => {
+                let mut parameters = std::collections::HashMap::new();
+                parameters.insert(
+                    "data_type".to_string(),
+                    BlenderValue::String("FLOAT".to_string()),
+                );
+                BlenderNode {
+                    node_type: "GeometryNodeInputNamedAttribute".to_string(),
+                    location: (0.0, 0.0),
+                    inputs: vec![input_socket(
+                        "Name",
+                        "NodeSocketString",
+                        BlenderValue::String(name),
+                    )],
+                    outputs: vec![
+                        output_socket("Attribute", "NodeSocketFloat"),
+                        output_socket("Exists", "NodeSocketBool"),
+                    ],
+                    parameters,
+                }
+            }
+            Node::Object { name, .. } => {
+                let socket = |name: &str, socket_type: &str| BlenderSocket {
+                    name: name.to_string(),
+                    socket_type: socket_type.to_string(),
+                    default_value: None,
+                };
+                let mut parameters = std::collections::HashMap::new();
+                parameters.insert(
+                    "transform_space".to_string(),
+                    BlenderValue::String("ORIGINAL".to_string()),
+                );
+                BlenderNode {
+                    node_type: "GeometryNodeObjectInfo".to_string(),
+                    location: (0.0, 0.0),
+                    inputs: vec![BlenderSocket {
+                        name: "Object".to_string(),
+                        socket_type: "NodeSocketObject".to_string(),
+                        default_value: Some(BlenderValue::String(name)),
+                    }],
+                    outputs: vec![
+                        socket("Location", "NodeSocketVector"),
+                        socket("Rotation", "NodeSocketRotation"),
+                        socket("Scale", "NodeSocketVector"),
+                        socket("Geometry", "NodeSocketGeometry"),
+                    ],
+                    parameters,
+                }
+            }
+            Node::Output { .. } => BlenderNode {
+                node_type: "NodeGroupOutput".to_string(),
+                location: (0.0, 0.0),
+                inputs: vec![output_socket("Geometry", "NodeSocketGeometry")],
+                outputs: vec![],
+                parameters: std::collections::HashMap::new(),
+            },
         }
     }
 }
 
-impl From<NodeGraph> for BlenderNodeGraph {
-    fn from(graph: NodeGraph) -> Self {
-        let blender_nodes: Vec<BlenderNode> = graph.nodes.into_iter().map(|n| n.into()).collect();
+// Distance between neighbouring columns and rows of nodes in the node editor
+const NODE_SPACING: (f64, f64) = (250.0, 200.0);
+
+// Nodes go left to right in columns, each one column right of the furthest node connected into
+// it, so links flow the way they are usually drawn in Blender. Locations are in `nodes` order.
+fn layout(graph: &NodeGraph) -> Vec<(f64, f64)> {
+    let mut columns: std::collections::HashMap<&NodeId, usize> = Default::default();
+    let mut rows: Vec<usize> = Vec::new();
+    let mut locations = std::collections::HashMap::new();
+    for node in graph.topological_order() {
+        let column = graph
+            .connections
+            .iter()
+            .filter(|connection| &connection.to_node == node.id())
+            .filter_map(|connection| columns.get(&connection.from_node))
+            .map(|column| column + 1)
+            .max()
+            .unwrap_or(0);
+        columns.insert(node.id(), column);
+        if rows.len() <= column {
+            rows.resize(column + 1, 0);
+        }
+        let row = rows[column];
+        rows[column] += 1;
+        locations.insert(
+            node.id(),
+            (
+                column as f64 * NODE_SPACING.0,
+                -(row as f64) * NODE_SPACING.1,
+            ),
+        );
+    }
+    graph
+        .nodes
+        .iter()
+        .map(|node| locations.get(node.id()).copied().unwrap_or_default())
+        .collect()
+}
 
-        BlenderNodeGraph {
+// Blender only evaluates a tree through its Group Output, so a graph without exactly one is
+// rejected rather than loaded as a tree that does nothing
+impl TryFrom<NodeGraph> for BlenderNodeGraph {
+    type Error = GraphError;
+
+    fn try_from(graph: NodeGraph) -> Result<Self, GraphError> {
+        let outputs = graph
+            .nodes
+            .iter()
+            .filter(|node| matches!(node, Node::Output { .. }))
+            .map(|node| node.id().clone())
+            .collect::<Vec<_>>();
+        match outputs.len() {
+            0 => return Err(GraphError::MissingOutput),
+            1 => {}
+            _ => return Err(GraphError::MultipleOutputs { ids: outputs }),
+        }
+
+        let locations = layout(&graph);
+        let blender_nodes: Vec<BlenderNode> = graph
+            .nodes
+            .iter()
+            .zip(locations)
+            .map(|(node, location)| BlenderNode {
+                location,
+                ..node.clone().into()
+            })
+            .collect();
+
+        // Links refer to nodes by their position and to sockets by their Blender name
+        let index_of = |id: &NodeId| {
+            graph
+                .nodes
+                .iter()
+                .position(|n| n.id() == id)
+                .ok_or_else(|| GraphError::UnknownNode { id: id.clone() })
+        };
+        let check_socket = |sockets: &[BlenderSocket], node: &NodeId, socket: &str| {
+            if sockets.iter().any(|s| s.name == socket) {
+                Ok(())
+            } else {
+                Err(GraphError::UnknownSocket {
+                    node: node.clone(),
+                    socket: socket.to_string(),
+                })
+            }
+        };
+        let links = graph
+            .connections
+            .iter()
+            .map(|connection| {
+                let from_node = index_of(&connection.from_node)?;
+                let to_node = index_of(&connection.to_node)?;
+                check_socket(
+                    &blender_nodes[from_node].outputs,
+                    &connection.from_node,
+                    &connection.from_output,
+                )?;
+                check_socket(
+                    &blender_nodes[to_node].inputs,
+                    &connection.to_node,
+                    &connection.to_input,
+                )?;
+                Ok(BlenderLink {
+                    from_node,
+                    from_socket: connection.from_output.clone(),
+                    to_node,
+                    to_socket: connection.to_input.clone(),
+                })
+            })
+            .collect::<Result<_, GraphError>>()?;
+
+        Ok(BlenderNodeGraph {
             nodes: blender_nodes,
-            links: vec![], // TODO: Convert connections
+            links,
+        })
+    }
+}
+
+impl BlenderNodeGraph {
+    /// Stable hash of the graph contents, used to skip re-applying an unchanged graph.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = ContentHasher::default();
+        for node in &self.nodes {
+            // Parameters are hashed in key order, a HashMap iterates in no fixed one
+            let parameters: BTreeMap<_, _> = node.parameters.iter().collect();
+            hasher.write_json(&(
+                &node.node_type,
+                node.location,
+                &node.inputs,
+                &node.outputs,
+                parameters,
+            ));
+        }
+        hasher.write_json(&self.links);
+        hasher.finish()
+    }
+
+    /// Keep only the nodes within `max_depth` links upstream of an output node, where output
+    /// nodes are the ones nothing links out of. Links are remapped to the remaining nodes.
+    pub fn truncate_depth(&self, max_depth: usize) -> BlenderNodeGraph {
+        let mut depths = vec![None; self.nodes.len()];
+        let mut frontier = (0..self.nodes.len())
+            .filter(|&index| !self.links.iter().any(|link| link.from_node == index))
+            .collect::<Vec<_>>();
+        for &index in &frontier {
+            depths[index] = Some(0);
+        }
+
+        for depth in 1..=max_depth {
+            let mut next = Vec::new();
+            for link in &self.links {
+                let upstream = link.from_node;
+                if frontier.contains(&link.to_node)
+                    && upstream < depths.len()
+                    && depths[upstream].is_none()
+                {
+                    depths[upstream] = Some(depth);
+                    next.push(upstream);
+                }
+            }
+            frontier = next;
         }
+
+        let mut remap = vec![None; self.nodes.len()];
+        let mut nodes = Vec::new();
+        for (index, node) in self.nodes.iter().enumerate() {
+            if depths[index].is_some() {
+                remap[index] = Some(nodes.len());
+                nodes.push(node.clone());
+            }
+        }
+
+        let links = self
+            .links
+            .iter()
+            .filter_map(|link| {
+                let from_node = (*remap.get(link.from_node)?)?;
+                let to_node = (*remap.get(link.to_node)?)?;
+                Some(BlenderLink {
+                    from_node,
+                    from_socket: link.from_socket.clone(),
+                    to_node,
+                    to_socket: link.to_socket.clone(),
+                })
+            })
+            .collect();
+
+        BlenderNodeGraph { nodes, links }
     }
 }
@@ -0,0 +1,341 @@
+//! Every node type the language knows, for tooling.
+//!
+//! Editors, the LSP and the Blender addon offer completions and docs from [`node_catalog`], so
+//! they stay in step with the parser: fields and their defaults come from the same schemas that
+//! parse them, and sockets from the same conversion that builds the Blender nodes.
+
+use crate::decompile::literal;
+use crate::parser::{
+    CONE, CUBE, CYLINDER, DISTRIBUTE_POINTS, EXTRUDE_MESH, FieldKind, FieldSchema, MATH, MIX,
+    NOISE, RANDOM, SPHERE, SUBDIVIDE_MESH, VECTOR_MATH,
+};
+use crate::{
+    BlenderNode, BlenderSocket, BooleanOperation, MathOperation, Value, VectorMathOperation,
+    parse_geometry_nodes,
+};
+use serde::Serialize;
+
+/// The values a field accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    Number,
+    Integer,
+    Boolean,
+    Vector,
+}
+
+impl FieldType {
+    pub fn name(self) -> &'static str {
+        match self {
+            FieldType::Number => "number",
+            FieldType::Integer => "integer",
+            FieldType::Boolean => "boolean",
+            FieldType::Vector => "vector",
+        }
+    }
+}
+
+/// A field a node type takes in braces, e.g. `radius` in `sphere { radius: 2 }`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldInfo {
+    pub name: &'static str,
+    pub kind: FieldType,
+    /// The value when the field is left out, or `None` when it has to be given
+    pub default: Option<Value>,
+    pub description: &'static str,
+}
+
+impl FieldInfo {
+    /// The default as it would be written in source, e.g. `2.0`.
+    pub fn default_source(&self) -> Option<String> {
+        self.default.as_ref().map(literal)
+    }
+}
+
+/// A node type: how it is written and what it connects to.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeTypeInfo {
+    pub keyword: &'static str,
+    pub description: &'static str,
+    /// Operations written after the keyword, e.g. `add` in `math add`
+    pub operations: Vec<&'static str>,
+    pub fields: Vec<FieldInfo>,
+    /// Socket names as connections write them, e.g. `radius_top`
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    /// What to write instead, for node types that are deprecated
+    pub deprecated: Option<&'static str>,
+}
+
+struct Entry {
+    keyword: &'static str,
+    description: &'static str,
+    /// Source for a node of this type with default fields, used to look up its sockets
+    sample: &'static str,
+    fields: &'static [FieldSchema],
+}
+
+const ENTRIES: &[Entry] = &[
+    Entry {
+        keyword: CUBE.keyword,
+        description: "A cube mesh.",
+        sample: "cube",
+        fields: CUBE.fields,
+    },
+    Entry {
+        keyword: SPHERE.keyword,
+        description: "A UV sphere mesh.",
+        sample: "sphere",
+        fields: SPHERE.fields,
+    },
+    Entry {
+        keyword: CYLINDER.keyword,
+        description: "A cylinder mesh.",
+        sample: "cylinder",
+        fields: CYLINDER.fields,
+    },
+    Entry {
+        keyword: CONE.keyword,
+        description: "A cone mesh, or a truncated cone when `radius_top` isn't zero.",
+        sample: "cone",
+        fields: CONE.fields,
+    },
+    Entry {
+        keyword: MATH.keyword,
+        description: "A math operation on two numbers, e.g. `math add { a: 1, b: 2 }`.",
+        sample: "math add",
+        fields: MATH.fields,
+    },
+    Entry {
+        keyword: VECTOR_MATH.keyword,
+        description: "A math operation on two vectors, e.g. `vector_math cross_product`.",
+        sample: "vector_math add",
+        fields: VECTOR_MATH.fields,
+    },
+    Entry {
+        keyword: MIX.keyword,
+        description: "Blends between `a` and `b` by `factor`.",
+        sample: "mix",
+        fields: MIX.fields,
+    },
+    Entry {
+        keyword: NOISE.keyword,
+        description: "Perlin noise, sampled at each point's position unless a vector is linked \
+                      into `vector`.",
+        sample: "noise",
+        fields: NOISE.fields,
+    },
+    Entry {
+        keyword: "white_noise",
+        description: "A random value for each position, without any smoothing.",
+        sample: "white_noise",
+        fields: &[],
+    },
+    Entry {
+        keyword: RANDOM.keyword,
+        description: "A random value per element between `min` and `max`. Integer bounds give \
+                      integers and vector bounds give vectors.",
+        sample: "random",
+        fields: RANDOM.fields,
+    },
+    Entry {
+        keyword: EXTRUDE_MESH.keyword,
+        description: "Extrudes the faces of the mesh linked into `mesh` along their normals.",
+        sample: "extrude_mesh",
+        fields: EXTRUDE_MESH.fields,
+    },
+    Entry {
+        keyword: SUBDIVIDE_MESH.keyword,
+        description: "Splits every face of the mesh linked into `mesh` into smaller faces.",
+        sample: "subdivide_mesh",
+        fields: SUBDIVIDE_MESH.fields,
+    },
+    Entry {
+        keyword: "mesh_boolean",
+        description: "Combines meshes with `union`, `intersect` or `difference`, e.g. \
+                      `mesh_boolean difference`. Difference subtracts `mesh_2` from `mesh_1`.",
+        sample: "mesh_boolean difference",
+        fields: &[],
+    },
+    Entry {
+        keyword: "join_geometry",
+        description: "Combines every geometry linked into `geometry` into one.",
+        sample: "join_geometry",
+        fields: &[],
+    },
+    Entry {
+        keyword: DISTRIBUTE_POINTS.keyword,
+        description: "Scatters points randomly over the faces of the mesh linked into `mesh`.",
+        sample: "distribute_points",
+        fields: DISTRIBUTE_POINTS.fields,
+    },
+    Entry {
+        keyword: "instance_on_points",
+        description: "Places a copy of the geometry linked into `instance` on every point \
+                      linked into `points`.",
+        sample: "instance_on_points",
+        fields: &[],
+    },
+    Entry {
+        keyword: "position",
+        description: "The position of each point of the geometry it is used on, e.g. to \
+                      displace points by their height.",
+        sample: "position",
+        fields: &[],
+    },
+    Entry {
+        keyword: "normal",
+        description: "The direction each face or point of the geometry it is used on faces.",
+        sample: "normal",
+        fields: &[],
+    },
+    Entry {
+        keyword: "named_attribute",
+        description: "A float attribute stored on the geometry by name, e.g. \
+                      `named_attribute \"height\"`. `exists` tells whether it was found.",
+        sample: "named_attribute \"Attribute\"",
+        fields: &[],
+    },
+    Entry {
+        keyword: "value",
+        description: "A constant, e.g. `value 1.5`.",
+        sample: "value 0.0",
+        fields: &[],
+    },
+    Entry {
+        keyword: "object",
+        description: "An object from the Blender scene by name, e.g. `object \"Suzanne\"`.",
+        sample: "object \"Object\"",
+        fields: &[],
+    },
+    Entry {
+        keyword: "output",
+        description: "The result of the graph. Link the final geometry into its `geometry` input.",
+        sample: "output",
+        fields: &[],
+    },
+];
+
+// Node types that only still parse so older files keep working, with what to write instead
+const DEPRECATED: &[(&str, &str)] = &[];
+
+/// What to write instead of a deprecated node type, or `None` when `keyword` isn't deprecated.
+pub fn deprecation(keyword: &str) -> Option<&'static str> {
+    DEPRECATED
+        .iter()
+        .find(|(deprecated, _)| *deprecated == keyword)
+        .map(|(_, replacement)| *replacement)
+}
+
+fn operations(keyword: &str) -> Vec<&'static str> {
+    match keyword {
+        "math" => MathOperation::ALL.map(MathOperation::name).to_vec(),
+        "vector_math" => VectorMathOperation::ALL
+            .map(VectorMathOperation::name)
+            .to_vec(),
+        "mesh_boolean" => BooleanOperation::ALL.map(BooleanOperation::name).to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+fn field(schema: &FieldSchema) -> FieldInfo {
+    let kind = match (schema.kind, &schema.default) {
+        (FieldKind::Integer, _) => FieldType::Integer,
+        (FieldKind::Boolean, _) => FieldType::Boolean,
+        (FieldKind::Any, Some(Value::Vector(..))) => FieldType::Vector,
+        (FieldKind::Any, _) => FieldType::Number,
+    };
+    FieldInfo {
+        name: schema.name,
+        kind,
+        default: schema.default.clone(),
+        description: schema.doc,
+    }
+}
+
+fn sockets(sample: &str) -> (Vec<String>, Vec<String>) {
+    let Some(node) = parse_geometry_nodes(sample)
+        .ok()
+        .and_then(|graph| graph.nodes.into_iter().next())
+    else {
+        return Default::default();
+    };
+    let node = BlenderNode::from(node);
+    let names = |sockets: Vec<BlenderSocket>| {
+        sockets
+            .into_iter()
+            .map(|socket| socket.name.to_lowercase().replace(' ', "_"))
+            .collect()
+    };
+    (names(node.inputs), names(node.outputs))
+}
+
+/// Every node type, in the order they are documented.
+pub fn node_catalog() -> Vec<NodeTypeInfo> {
+    ENTRIES
+        .iter()
+        .map(|entry| {
+            let (inputs, outputs) = sockets(entry.sample);
+            NodeTypeInfo {
+                keyword: entry.keyword,
+                description: entry.description,
+                operations: operations(entry.keyword),
+                fields: entry.fields.iter().map(field).collect(),
+                inputs,
+                outputs,
+                deprecated: deprecation(entry.keyword),
+            }
+        })
+        .collect()
+}
+
+/// The node type written as `keyword`.
+pub fn node_type(keyword: &str) -> Option<NodeTypeInfo> {
+    node_catalog()
+        .into_iter()
+        .find(|node| node.keyword == keyword)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalog_describes_every_node_type() {
+        let catalog = node_catalog();
+        for node in &catalog {
+            assert!(
+                !node.outputs.is_empty() || node.keyword == "output",
+                "{} has no sockets",
+                node.keyword
+            );
+            // Defaults parse back as the node's own fields
+            let fields = node
+                .fields
+                .iter()
+                .filter_map(|field| Some(format!("{}: {}", field.name, field.default_source()?)))
+                .collect::<Vec<_>>();
+            if !fields.is_empty() {
+                let source = format!("{} {{ {} }}", node.keyword, fields.join(", "));
+                let source = match node.operations.first() {
+                    Some(operation) => source.replacen(' ', &format!(" {operation} "), 1),
+                    None => source,
+                };
+                parse_geometry_nodes(&source)
+                    .unwrap_or_else(|errors| panic!("{source} failed to parse: {errors:?}"));
+            }
+        }
+
+        let cone = node_type("cone").expect("Missing cone");
+        assert_eq!(cone.fields[1].name, "radius_top");
+        assert_eq!(cone.fields[0].kind, FieldType::Integer);
+        assert_eq!(cone.fields[1].default_source().as_deref(), Some("0.0"));
+        assert!(cone.inputs.contains(&"radius_top".to_string()));
+
+        let math = node_type("math").expect("Missing math");
+        assert_eq!(math.operations.len(), MathOperation::ALL.len());
+        assert!(math.operations.contains(&"power"));
+        assert!(node_type("teapot").is_none());
+    }
+}
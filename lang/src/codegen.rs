@@ -0,0 +1,204 @@
+//! Python scripts that build a node tree with `bpy`.
+//!
+//! For applying parsed graphs without the service bridge: the script can be pasted into
+//! Blender's text editor or run with `blender --python`. It creates a geometry node group and,
+//! when there is an active object, adds it to that object as a Geometry Nodes modifier.
+
+use crate::{BlenderNode, BlenderNodeGraph, BlenderSocket, BlenderValue};
+use std::fmt::Write;
+
+// Sockets are looked up by identifier first, since names repeat on nodes like Math, then by
+// name. Only sockets the node's current settings enable count, because nodes like Random Value
+// have a socket with the same identifier or name for each data type
+const PRELUDE: &str = r#"import bpy
+
+
+def socket(sockets, key):
+    for candidate in sockets:
+        if candidate.identifier == key and candidate.enabled:
+            return candidate
+    for candidate in sockets:
+        if candidate.name == key and candidate.enabled:
+            return candidate
+    return sockets[key]
+
+
+def set_property(node, name, value):
+    if hasattr(node, name):
+        setattr(node, name, value)
+
+
+tree = bpy.data.node_groups.new("Cuttle", "GeometryNodeTree")
+tree.interface.new_socket("Geometry", in_out="OUTPUT", socket_type="NodeSocketGeometry")
+"#;
+
+const ATTACH: &str = r#"
+obj = bpy.context.active_object
+if obj is not None:
+    modifier = obj.modifiers.new("Cuttle", "NODES")
+    modifier.node_group = tree
+"#;
+
+/// A script that recreates `graph` in Blender.
+pub fn to_python_script(graph: &BlenderNodeGraph) -> String {
+    let mut script = PRELUDE.to_string();
+
+    for (index, node) in graph.nodes.iter().enumerate() {
+        write_node(&mut script, index, node);
+    }
+
+    if !graph.links.is_empty() {
+        script.push('\n');
+    }
+    for link in &graph.links {
+        let _ = writeln!(
+            script,
+            "tree.links.new(socket(node_{}.outputs, {}), socket(node_{}.inputs, {}))",
+            link.from_node,
+            string(&link.from_socket),
+            link.to_node,
+            string(&link.to_socket),
+        );
+    }
+
+    script.push_str(ATTACH);
+    script
+}
+
+fn write_node(script: &mut String, index: usize, node: &BlenderNode) {
+    let name = format!("node_{index}");
+    let _ = writeln!(
+        script,
+        "\n{name} = tree.nodes.new({})",
+        string(&node.node_type)
+    );
+    let _ = writeln!(
+        script,
+        "{name}.location = ({}, {})",
+        float(node.location.0),
+        float(node.location.1)
+    );
+
+    // Parameters like a Mix node's data type decide which sockets exist, so they go first
+    let mut parameters = node.parameters.iter().collect::<Vec<_>>();
+    parameters.sort_by_key(|(key, _)| key.as_str());
+    for (key, value) in parameters {
+        let _ = writeln!(
+            script,
+            "set_property({name}, {}, {})",
+            string(key),
+            python_value(value, None)
+        );
+    }
+
+    for (side, sockets) in [("inputs", &node.inputs), ("outputs", &node.outputs)] {
+        for socket in sockets {
+            if let Some(value) = &socket.default_value {
+                let _ = writeln!(
+                    script,
+                    "socket({name}.{side}, {}).default_value = {}",
+                    string(&socket.name),
+                    python_value(value, Some(socket))
+                );
+            }
+        }
+    }
+}
+
+// Scalars are spread over every component of a vector socket, like Blender does for links
+fn python_value(value: &BlenderValue, socket: Option<&BlenderSocket>) -> String {
+    let socket_type = socket.map(|socket| socket.socket_type.as_str());
+    match (value, socket_type) {
+        (BlenderValue::String(name), Some("NodeSocketObject")) => {
+            format!("bpy.data.objects.get({})", string(name))
+        }
+        (BlenderValue::Integer(i), Some("NodeSocketVector")) => format!("({i}, {i}, {i})"),
+        (BlenderValue::Float(f), Some("NodeSocketVector")) => {
+            let f = float(*f);
+            format!("({f}, {f}, {f})")
+        }
+        (BlenderValue::Integer(i), _) => i.to_string(),
+        (BlenderValue::Float(f), _) => float(*f),
+        (BlenderValue::Boolean(b), _) => if *b { "True" } else { "False" }.to_string(),
+        (BlenderValue::Vector(x, y, z), _) => {
+            format!("({}, {}, {})", float(*x), float(*y), float(*z))
+        }
+        (BlenderValue::Color(r, g, b, a), _) => format!(
+            "({}, {}, {}, {})",
+            float(*r),
+            float(*g),
+            float(*b),
+            float(*a)
+        ),
+        (BlenderValue::String(s), _) => string(s),
+    }
+}
+
+fn float(value: f64) -> String {
+    if value.is_nan() {
+        "float('nan')".to_string()
+    } else if value.is_infinite() {
+        let sign = if value < 0.0 { "-" } else { "" };
+        format!("float('{sign}inf')")
+    } else {
+        format!("{value:?}")
+    }
+}
+
+// JSON string escapes are valid in Python string literals
+fn string(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_geometry_nodes;
+
+    #[test]
+    fn scripts_create_nodes_and_links() {
+        let graph = parse_geometry_nodes(
+            "box = cube { size: 2 }\nm = math add { b: 0.5 }\nout = output\nbox.mesh -> out.geometry",
+        )
+        .expect("Failed to parse graph");
+        let graph = BlenderNodeGraph::try_from(graph).expect("Failed to convert graph");
+        let script = to_python_script(&graph);
+
+        assert!(script.starts_with("import bpy\n"));
+        assert!(script.contains("node_0 = tree.nodes.new(\"GeometryNodeMeshCube\")"));
+        assert!(script.contains("socket(node_0.inputs, \"Size\").default_value = (2, 2, 2)"));
+        assert!(script.contains("set_property(node_1, \"operation\", \"ADD\")"));
+        assert!(script.contains("socket(node_1.inputs, \"Value_001\").default_value = 0.5"));
+        assert!(script.contains(
+            "tree.links.new(socket(node_0.outputs, \"Mesh\"), socket(node_2.inputs, \"Geometry\"))"
+        ));
+        // Parameters are set before the sockets they enable
+        let operation = script.find("\"operation\"").unwrap_or_default();
+        let value = script.find("\"Value_001\"").unwrap_or_default();
+        assert!(operation < value);
+    }
+
+    #[test]
+    fn values_become_python_literals() {
+        let object = BlenderSocket {
+            name: "Object".to_string(),
+            socket_type: "NodeSocketObject".to_string(),
+            default_value: None,
+        };
+        assert_eq!(
+            python_value(&BlenderValue::String("Suzanne".to_string()), Some(&object)),
+            "bpy.data.objects.get(\"Suzanne\")"
+        );
+        assert_eq!(python_value(&BlenderValue::Boolean(true), None), "True");
+        assert_eq!(string("Suzanne \"2\""), r#""Suzanne \"2\"""#);
+        assert_eq!(python_value(&BlenderValue::Float(1e-7), None), "1e-7");
+        assert_eq!(
+            python_value(&BlenderValue::Float(f64::NEG_INFINITY), None),
+            "float('-inf')"
+        );
+        assert_eq!(
+            python_value(&BlenderValue::Color(1.0, 0.5, 0.0, 1.0), None),
+            "(1.0, 0.5, 0.0, 1.0)"
+        );
+    }
+}
@@ -0,0 +1,441 @@
+//! Turning Blender node trees back into DSL source.
+//!
+//! Graphs captured from a live Blender session come in as `BlenderNodeGraph`s; converting them
+//! to a `NodeGraph` and printing it with `to_source` gives editable DSL text that parses back to
+//! the same graph.
+
+use crate::{
+    BlenderNode, BlenderNodeGraph, BlenderValue, BooleanOperation, Connection, MathOperation, Node,
+    NodeGraph, NodeId, Value, VectorMathOperation,
+};
+use std::fmt::Write;
+
+fn value(value: &BlenderValue) -> Option<Value> {
+    match value {
+        BlenderValue::String(_) => None,
+        other => Some(other.clone().into()),
+    }
+}
+
+// Node types the DSL has no syntax for are left out, along with their links
+fn node(node: &BlenderNode, index: usize) -> Option<Node> {
+    let input = |name: &str| {
+        node.inputs
+            .iter()
+            .find(|socket| socket.name == name)
+            .and_then(|socket| socket.default_value.as_ref())
+            .and_then(value)
+    };
+    // Math inputs share a name in Blender, so the second one is found by identifier or position
+    let second = |identifier: &str| {
+        node.inputs
+            .iter()
+            .find(|socket| socket.name == identifier)
+            .or_else(|| node.inputs.get(1))
+            .and_then(|socket| socket.default_value.as_ref())
+            .and_then(value)
+    };
+    let operation = || match node.parameters.get("operation") {
+        Some(BlenderValue::String(operation)) => Some(operation.as_str()),
+        _ => None,
+    };
+    let id = |kind: &str| NodeId(format!("{kind}_{index}"));
+    let or = |value: Option<Value>, default: Value| value.unwrap_or(default);
+
+    let node = match node.node_type.as_str() {
+        "GeometryNodeMeshCube" => Node::Cube {
+            id: id("cube"),
+            size: or(input("Size"), Value::Float(2.0)),
+        },
+        "GeometryNodeMeshUVSphere" => Node::Sphere {
+            id: id("sphere"),
+            radius: or(input("Radius"), Value::Float(1.0)),
+            subdivisions: match input("Rings") {
+                Some(Value::Integer(rings)) => Value::Integer(rings / 2),
+                other => or(other, Value::Integer(8)),
+            },
+        },
+        "GeometryNodeMeshCylinder" => Node::Cylinder {
+            id: id("cylinder"),
+            vertices: or(input("Vertices"), Value::Integer(32)),
+            radius: or(input("Radius"), Value::Float(1.0)),
+            depth: or(input("Depth"), Value::Float(2.0)),
+        },
+        "GeometryNodeMeshCone" => Node::Cone {
+            id: id("cone"),
+            vertices: or(input("Vertices"), Value::Integer(32)),
+            radius_top: or(input("Radius Top"), Value::Float(0.0)),
+            radius_bottom: or(input("Radius Bottom"), Value::Float(1.0)),
+            depth: or(input("Depth"), Value::Float(2.0)),
+        },
+        "ShaderNodeMath" => Node::Math {
+            id: id("math"),
+            operation: MathOperation::from_blender_name(operation()?)?,
+            a: or(input("Value"), Value::Float(0.5)),
+            b: or(second("Value_001"), Value::Float(0.5)),
+            clamp: matches!(
+                node.parameters.get("use_clamp"),
+                Some(BlenderValue::Boolean(true))
+            ),
+        },
+        "ShaderNodeVectorMath" => Node::VectorMath {
+            id: id("vector_math"),
+            operation: VectorMathOperation::from_blender_name(operation()?)?,
+            a: or(input("Vector"), Value::Vector(0.0, 0.0, 0.0)),
+            b: or(second("Vector_001"), Value::Vector(0.0, 0.0, 0.0)),
+        },
+        "ShaderNodeMix" => Node::Mix {
+            id: id("mix"),
+            factor: or(input("Factor"), Value::Float(0.5)),
+            a: or(input("A"), Value::Float(0.0)),
+            b: or(input("B"), Value::Float(0.0)),
+        },
+        "ShaderNodeTexNoise" => Node::Noise {
+            id: id("noise"),
+            scale: or(input("Scale"), Value::Float(5.0)),
+            detail: or(input("Detail"), Value::Float(2.0)),
+            roughness: or(input("Roughness"), Value::Float(0.5)),
+            lacunarity: or(input("Lacunarity"), Value::Float(2.0)),
+            distortion: or(input("Distortion"), Value::Float(0.0)),
+        },
+        "ShaderNodeTexWhiteNoise" => Node::WhiteNoise {
+            id: id("white_noise"),
+        },
+        "FunctionNodeRandomValue" => Node::Random {
+            id: id("random"),
+            seed: or(input("Seed"), Value::Integer(0)),
+            min: or(input("Min"), Value::Float(0.0)),
+            max: or(input("Max"), Value::Float(1.0)),
+        },
+        "GeometryNodeExtrudeMesh" => Node::ExtrudeMesh {
+            id: id("extrude_mesh"),
+            offset_scale: or(input("Offset Scale"), Value::Float(1.0)),
+            individual: !matches!(input("Individual"), Some(Value::Boolean(false))),
+        },
+        "GeometryNodeSubdivideMesh" => Node::SubdivideMesh {
+            id: id("subdivide_mesh"),
+            level: or(input("Level"), Value::Integer(1)),
+        },
+        "GeometryNodeMeshBoolean" => Node::MeshBoolean {
+            id: id("mesh_boolean"),
+            operation: BooleanOperation::from_blender_name(operation()?)?,
+        },
+        "GeometryNodeJoinGeometry" => Node::JoinGeometry {
+            id: id("join_geometry"),
+        },
+        "ShaderNodeValue" => Node::Value {
+            id: id("value"),
+            value: or(
+                node.outputs
+                    .first()
+                    .and_then(|socket| socket.default_value.as_ref())
+                    .and_then(value),
+                Value::Float(0.0),
+            ),
+        },
+        "GeometryNodeDistributePointsOnFaces" => Node::DistributePoints {
+            id: id("distribute_points"),
+            density: or(input("Density"), Value::Float(10.0)),
+        },
+        "GeometryNodeInstanceOnPoints" => Node::InstanceOnPoints {
+            id: id("instance_on_points"),
+        },
+        "GeometryNodeInputPosition" => Node::Position { id: id("position") },
+        "GeometryNodeInputNormal" => Node::Normal { id: id("normal") },
+        "GeometryNodeInputNamedAttribute" => Node::NamedAttribute {
+            id: id("named_attribute"),
+            name: match node
+                .inputs
+                .iter()
+                .find(|socket| socket.name == "Name")
+                .and_then(|socket| socket.default_value.as_ref())
+            {
+                Some(BlenderValue::String(name)) => name.clone(),
+                _ => return None,
+            },
+        },
+        "GeometryNodeObjectInfo" => Node::Object {
+            id: id("object"),
+            name: match node
+                .inputs
+                .iter()
+                .find(|socket| socket.name == "Object")
+                .and_then(|socket| socket.default_value.as_ref())
+            {
+                Some(BlenderValue::String(name)) => name.clone(),
+                _ => return None,
+            },
+        },
+        "NodeGroupOutput" => Node::Output { id: id("output") },
+        _ => return None,
+    };
+    Some(node)
+}
+
+// Blender nodes have no DSL labels, so every node gets the id the parser would generate for it
+impl From<BlenderNodeGraph> for NodeGraph {
+    fn from(graph: BlenderNodeGraph) -> Self {
+        let mut result = NodeGraph::new();
+        let mut ids = Vec::with_capacity(graph.nodes.len());
+        for blender_node in &graph.nodes {
+            match node(blender_node, result.nodes.len()) {
+                Some(node) => {
+                    ids.push(Some(node.id().clone()));
+                    result.add_node(node);
+                }
+                None => ids.push(None),
+            }
+        }
+
+        for link in &graph.links {
+            let id = |index: usize| ids.get(index).cloned().flatten();
+            if let (Some(from_node), Some(to_node)) = (id(link.from_node), id(link.to_node)) {
+                result.add_connection(Connection {
+                    from_node,
+                    from_output: link.from_socket.clone(),
+                    to_node,
+                    to_input: link.to_socket.clone(),
+                });
+            }
+        }
+        result
+    }
+}
+
+pub(crate) fn kind(node: &Node) -> &'static str {
+    match node {
+        Node::Value { .. } => "value",
+        Node::Cube { .. } => "cube",
+        Node::Sphere { .. } => "sphere",
+        Node::Cylinder { .. } => "cylinder",
+        Node::Cone { .. } => "cone",
+        Node::Math { .. } => "math",
+        Node::VectorMath { .. } => "vector_math",
+        Node::Mix { .. } => "mix",
+        Node::Noise { .. } => "noise",
+        Node::WhiteNoise { .. } => "white_noise",
+        Node::Random { .. } => "random",
+        Node::ExtrudeMesh { .. } => "extrude_mesh",
+        Node::SubdivideMesh { .. } => "subdivide_mesh",
+        Node::MeshBoolean { .. } => "mesh_boolean",
+        Node::JoinGeometry { .. } => "join_geometry",
+        Node::DistributePoints { .. } => "distribute_points",
+        Node::InstanceOnPoints { .. } => "instance_on_points",
+        Node::Position { .. } => "position",
+        Node::Normal { .. } => "normal",
+        Node::NamedAttribute { .. } => "named_attribute",
+        Node::Object { .. } => "object",
+        Node::Output { .. } => "output",
+    }
+}
+
+// Floats keep their decimal point so they don't parse back as integers
+pub(crate) fn literal(value: &Value) -> String {
+    match value {
+        Value::Float(x) => format!("{x:?}"),
+        Value::Vector(x, y, z) => format!("({x:?}, {y:?}, {z:?})"),
+        Value::Color(r, g, b, a) => format!("({r:?}, {g:?}, {b:?}, {a:?})"),
+        other => other.to_string(),
+    }
+}
+
+fn fields(fields: &[(&str, &Value)]) -> String {
+    let fields = fields
+        .iter()
+        .map(|(name, value)| format!("{name}: {}", literal(value)))
+        .collect::<Vec<_>>();
+    format!(" {{ {} }}", fields.join(", "))
+}
+
+// Sockets are written the way the parser matches them: lowercase, with spaces as underscores
+fn socket(name: &str) -> String {
+    name.to_lowercase().replace(' ', "_")
+}
+
+/// DSL source for `graph`, one node per line followed by its connections.
+///
+/// Nodes are only labeled when their id isn't the one the parser would generate, so the source
+/// parses back to an equal graph.
+pub fn to_source(graph: &NodeGraph) -> String {
+    let mut source = String::new();
+    for (index, node) in graph.nodes.iter().enumerate() {
+        if node.id().0 != format!("{}_{index}", kind(node)) {
+            let _ = write!(source, "{} = ", node.id().0);
+        }
+        let _ = writeln!(source, "{}", node_source(node));
+    }
+
+    if !graph.connections.is_empty() && !graph.nodes.is_empty() {
+        source.push('\n');
+    }
+    for connection in &graph.connections {
+        let _ = writeln!(source, "{}", connection_source(connection));
+    }
+    source
+}
+
+// A node as it is written in the DSL, without its label, e.g. `cube { size: 2.0 }`
+pub(crate) fn node_source(node: &Node) -> String {
+    let body = match node {
+        Node::Value { value, .. } => format!(" {}", literal(value)),
+        Node::Cube { size, .. } => fields(&[("size", size)]),
+        Node::Sphere {
+            radius,
+            subdivisions,
+            ..
+        } => fields(&[("radius", radius), ("subdivisions", subdivisions)]),
+        Node::Cylinder {
+            vertices,
+            radius,
+            depth,
+            ..
+        } => fields(&[("vertices", vertices), ("radius", radius), ("depth", depth)]),
+        Node::Cone {
+            vertices,
+            radius_top,
+            radius_bottom,
+            depth,
+            ..
+        } => fields(&[
+            ("vertices", vertices),
+            ("radius_top", radius_top),
+            ("radius_bottom", radius_bottom),
+            ("depth", depth),
+        ]),
+        Node::Math {
+            operation,
+            a,
+            b,
+            clamp,
+            ..
+        } => format!(
+            " {}{}",
+            operation.name(),
+            fields(&[("a", a), ("b", b), ("clamp", &Value::Boolean(*clamp))])
+        ),
+        Node::VectorMath {
+            operation, a, b, ..
+        } => format!(" {}{}", operation.name(), fields(&[("a", a), ("b", b)])),
+        Node::Mix { factor, a, b, .. } => fields(&[("factor", factor), ("a", a), ("b", b)]),
+        Node::Noise {
+            scale,
+            detail,
+            roughness,
+            lacunarity,
+            distortion,
+            ..
+        } => fields(&[
+            ("scale", scale),
+            ("detail", detail),
+            ("roughness", roughness),
+            ("lacunarity", lacunarity),
+            ("distortion", distortion),
+        ]),
+        Node::WhiteNoise { .. }
+        | Node::JoinGeometry { .. }
+        | Node::InstanceOnPoints { .. }
+        | Node::Position { .. }
+        | Node::Normal { .. } => String::new(),
+        Node::ExtrudeMesh {
+            offset_scale,
+            individual,
+            ..
+        } => fields(&[
+            ("offset_scale", offset_scale),
+            ("individual", &Value::Boolean(*individual)),
+        ]),
+        Node::SubdivideMesh { level, .. } => fields(&[("level", level)]),
+        Node::DistributePoints { density, .. } => fields(&[("density", density)]),
+        Node::MeshBoolean { operation, .. } => format!(" {}", operation.name()),
+        Node::Random { seed, min, max, .. } => {
+            fields(&[("seed", seed), ("min", min), ("max", max)])
+        }
+        Node::NamedAttribute { name, .. } | Node::Object { name, .. } => {
+            format!(" \"{name}\"")
+        }
+        Node::Output { .. } => String::new(),
+    };
+    format!("{}{body}", kind(node))
+}
+
+pub(crate) fn connection_source(connection: &Connection) -> String {
+    format!(
+        "{}.{} -> {}.{}",
+        connection.from_node.0,
+        socket(&connection.from_output),
+        connection.to_node.0,
+        socket(&connection.to_input)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_geometry_nodes;
+
+    #[test]
+    fn blender_graphs_round_trip_through_source() {
+        let input = "value 0.5\nsphere\ncone { vertices: 8 }\nmath power { b: 3 }\nmix { a: (0, 0, 0) }\noutput\nnoise { scale: 3.0 }\nwhite_noise\nrandom { seed: 2, max: 4 }\nmesh_boolean union\nsubdivide_mesh { level: 3 }\nextrude_mesh { individual: false }\njoin_geometry\ndistribute_points { density: 2.5 }\ninstance_on_points\nposition\nnormal\nnamed_attribute \"height\"\nvalue_0.value -> sphere_1.radius\nmath_3.value -> mix_4.factor\nnoise_6.fac -> random_8.id";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse graph");
+        let blender = BlenderNodeGraph::try_from(graph.clone()).expect("Failed to convert graph");
+
+        let decompiled = NodeGraph::from(blender);
+        assert_eq!(decompiled, graph);
+
+        let source = to_source(&decompiled);
+        assert!(
+            source
+                .contains("cone { vertices: 8, radius_top: 0.0, radius_bottom: 1.0, depth: 2.0 }")
+        );
+        assert!(source.contains("math power { a: 0.5, b: 3, clamp: false }"));
+        assert!(source.contains("random { seed: 2, min: 0.0, max: 4 }"));
+        assert!(source.contains("mesh_boolean union"));
+        assert!(source.contains("named_attribute \"height\""));
+        assert!(source.contains("extrude_mesh { offset_scale: 1.0, individual: false }"));
+        assert!(source.ends_with("noise_6.fac -> random_8.id\n"));
+        let reparsed = parse_geometry_nodes(&source).expect("Failed to parse decompiled source");
+        assert_eq!(reparsed, graph);
+    }
+
+    #[test]
+    fn labels_are_kept_and_unknown_nodes_dropped() {
+        let graph = parse_geometry_nodes(
+            "ball = sphere { radius: 1.5 }\nr = value 2.0\nr.value -> ball.radius",
+        )
+        .expect("Failed to parse graph");
+        let source = to_source(&graph);
+        assert!(
+            source.starts_with("ball = sphere { radius: 1.5, subdivisions: 8 }\nr = value 2.0\n")
+        );
+        assert_eq!(
+            parse_geometry_nodes(&source).expect("Failed to parse decompiled source"),
+            graph
+        );
+
+        let mut blender = BlenderNodeGraph::try_from(
+            parse_geometry_nodes("cube\noutput\ncube_0.mesh -> output_1.geometry")
+                .expect("Failed to parse graph"),
+        )
+        .expect("Failed to convert graph");
+        blender.nodes.insert(
+            0,
+            BlenderNode {
+                node_type: "GeometryNodeSetPosition".to_string(),
+                location: (0.0, 0.0),
+                inputs: vec![],
+                outputs: vec![],
+                parameters: std::collections::HashMap::new(),
+            },
+        );
+        for link in &mut blender.links {
+            link.from_node += 1;
+            link.to_node += 1;
+        }
+        let decompiled = NodeGraph::from(blender);
+        assert_eq!(decompiled.nodes.len(), 2);
+        assert_eq!(
+            decompiled.connections[0].from_node,
+            NodeId("cube_0".to_string())
+        );
+    }
+}
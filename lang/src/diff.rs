@@ -0,0 +1,160 @@
+//! Differences between two versions of a graph.
+//!
+//! Nodes are matched by id rather than by position, so reordering a file doesn't show up as a
+//! change, and a node whose fields were edited is one change instead of a removal and an
+//! addition.
+
+use crate::decompile::{connection_source, node_source};
+use crate::{Connection, Node, NodeGraph, NodeId};
+use serde::Serialize;
+use std::fmt;
+
+/// A node with the same id in both graphs but different contents.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeChange {
+    pub id: NodeId,
+    pub before: Node,
+    pub after: Node,
+}
+
+/// What changed from one graph to another, from [`diff_graphs`]. Each list is in the order of
+/// the graph it comes from.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct GraphDiff {
+    pub added_nodes: Vec<Node>,
+    pub removed_nodes: Vec<Node>,
+    pub changed_nodes: Vec<NodeChange>,
+    pub added_connections: Vec<Connection>,
+    pub removed_connections: Vec<Connection>,
+}
+
+impl GraphDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.changed_nodes.is_empty()
+            && self.added_connections.is_empty()
+            && self.removed_connections.is_empty()
+    }
+}
+
+/// Nodes and connections in `after` that aren't in `before` and the other way around, with
+/// nodes matched by id.
+pub fn diff_graphs(before: &NodeGraph, after: &NodeGraph) -> GraphDiff {
+    let mut diff = GraphDiff::default();
+    for node in &before.nodes {
+        match after.find_node(node.id()) {
+            None => diff.removed_nodes.push(node.clone()),
+            Some(changed) if changed != node => diff.changed_nodes.push(NodeChange {
+                id: node.id().clone(),
+                before: node.clone(),
+                after: changed.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    diff.added_nodes = after
+        .nodes
+        .iter()
+        .filter(|node| before.find_node(node.id()).is_none())
+        .cloned()
+        .collect();
+
+    diff.removed_connections = before
+        .connections
+        .iter()
+        .filter(|connection| !after.connections.contains(connection))
+        .cloned()
+        .collect();
+    diff.added_connections = after
+        .connections
+        .iter()
+        .filter(|connection| !before.connections.contains(connection))
+        .cloned()
+        .collect();
+    diff
+}
+
+// One line per difference, `-` for removed and `+` for added, with nodes written as DSL source.
+// Changed nodes are marked `~`, with their old and new source below them.
+impl fmt::Display for GraphDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+        for node in &self.removed_nodes {
+            writeln!(f, "- {} = {}", node.id().0, node_source(node))?;
+        }
+        for node in &self.added_nodes {
+            writeln!(f, "+ {} = {}", node.id().0, node_source(node))?;
+        }
+        for change in &self.changed_nodes {
+            writeln!(f, "~ {}", change.id.0)?;
+            writeln!(f, "    - {}", node_source(&change.before))?;
+            writeln!(f, "    + {}", node_source(&change.after))?;
+        }
+        for connection in &self.removed_connections {
+            writeln!(f, "- {}", connection_source(connection))?;
+        }
+        for connection in &self.added_connections {
+            writeln!(f, "+ {}", connection_source(connection))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Value, parse_geometry_nodes};
+
+    fn parse(input: &str) -> NodeGraph {
+        parse_geometry_nodes(input).expect("Failed to parse graph")
+    }
+
+    #[test]
+    fn nodes_are_matched_by_id() {
+        let before = parse("box = cube\nball = sphere\nout = output\nbox.mesh -> out.geometry");
+        let after = parse(
+            "out = output\nbox = cube { size: 3 }\ncone\nball = sphere\nball.mesh -> out.geometry",
+        );
+        let diff = diff_graphs(&before, &after);
+
+        assert!(diff.removed_nodes.is_empty());
+        assert_eq!(diff.added_nodes.len(), 1);
+        assert_eq!(diff.added_nodes[0].id().0, "cone_2");
+        let [change] = diff.changed_nodes.as_slice() else {
+            panic!("Expected one changed node, got {:?}", diff.changed_nodes);
+        };
+        assert_eq!(change.id.0, "box");
+        assert!(matches!(
+            change.after,
+            Node::Cube {
+                size: Value::Integer(3),
+                ..
+            }
+        ));
+        assert_eq!(diff.removed_connections[0].from_node.0, "box");
+        assert_eq!(diff.added_connections[0].from_node.0, "ball");
+
+        assert_eq!(
+            diff.to_string(),
+            "+ cone_2 = cone { vertices: 32, radius_top: 0.0, radius_bottom: 1.0, depth: 2.0 }\n\
+             ~ box\n    - cube { size: 2.0 }\n    + cube { size: 3 }\n\
+             - box.mesh -> out.geometry\n\
+             + ball.mesh -> out.geometry\n"
+        );
+    }
+
+    #[test]
+    fn identical_graphs_have_no_differences() {
+        let graph = parse("box = cube\noutput\nbox.mesh -> output_1.geometry");
+        let diff = diff_graphs(&graph, &graph.clone());
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "No differences\n");
+
+        let removed = diff_graphs(&graph, &NodeGraph::new());
+        assert_eq!(removed.removed_nodes.len(), 2);
+        assert_eq!(removed.removed_connections.len(), 1);
+    }
+}
@@ -1,7 +1,12 @@
-use ariadne::{ColorGenerator, Label, Report, ReportKind, Source};
+use crate::NodeId;
+#[cfg(feature = "terminal")]
+use ariadne::{ColorGenerator, Config, Label, Report, ReportKind, Source};
 use chumsky::error::Rich;
 use chumsky::span::SimpleSpan;
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::ops::Range;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ParseError {
@@ -45,6 +50,73 @@ pub enum ParseError {
         found: String,
         expected: String,
     },
+    UnknownNode {
+        span: SimpleSpan,
+        name: String,
+    },
+    UnknownVariable {
+        span: SimpleSpan,
+        name: String,
+    },
+    InvalidExpression {
+        span: SimpleSpan,
+        message: String,
+    },
+    UnterminatedComment {
+        span: SimpleSpan,
+    },
+    DuplicateNode {
+        span: SimpleSpan,
+        name: String,
+    },
+    UnknownSocket {
+        span: SimpleSpan,
+        node: String,
+        socket: String,
+        available: Vec<String>,
+    },
+    UnknownGroup {
+        span: SimpleSpan,
+        name: String,
+    },
+    UnknownArgument {
+        span: SimpleSpan,
+        group: String,
+        name: String,
+    },
+    RecursiveGroup {
+        span: SimpleSpan,
+        name: String,
+    },
+    ImportFailed {
+        span: SimpleSpan,
+        path: String,
+        message: String,
+    },
+    ImportCycle {
+        span: SimpleSpan,
+        /// The files in the cycle, starting and ending with the same one
+        cycle: Vec<String>,
+    },
+    ConstantRedefined {
+        span: SimpleSpan,
+        name: String,
+    },
+    /// A `param` without a default that the caller gave no value for
+    MissingParameter {
+        span: SimpleSpan,
+        name: String,
+    },
+    /// A value given by the caller for a `param` the source doesn't declare
+    UnknownParameter {
+        span: SimpleSpan,
+        name: String,
+    },
+    /// A `repeat` whose expansion would pass `limit` nodes, or iterations for loops that make none
+    ExpansionLimit {
+        span: SimpleSpan,
+        limit: usize,
+    },
 }
 
 impl ParseError {
@@ -57,8 +129,55 @@ impl ParseError {
             | ParseError::UnexpectedEndOfInput { span, .. }
             | ParseError::InvalidNodeType { span, .. }
             | ParseError::MissingRequiredField { span, .. }
-            | ParseError::InvalidFieldValue { span, .. } => *span,
+            | ParseError::InvalidFieldValue { span, .. }
+            | ParseError::UnknownNode { span, .. }
+            | ParseError::UnknownVariable { span, .. }
+            | ParseError::InvalidExpression { span, .. }
+            | ParseError::UnterminatedComment { span }
+            | ParseError::DuplicateNode { span, .. }
+            | ParseError::UnknownSocket { span, .. }
+            | ParseError::UnknownGroup { span, .. }
+            | ParseError::UnknownArgument { span, .. }
+            | ParseError::RecursiveGroup { span, .. }
+            | ParseError::ImportFailed { span, .. }
+            | ParseError::ImportCycle { span, .. }
+            | ParseError::ConstantRedefined { span, .. }
+            | ParseError::MissingParameter { span, .. }
+            | ParseError::UnknownParameter { span, .. }
+            | ParseError::ExpansionLimit { span, .. } => *span,
+        }
+    }
+
+    // The same error `offset` bytes further on, for errors found in a slice of a larger source
+    pub(crate) fn offset(mut self, offset: usize) -> Self {
+        match &mut self {
+            ParseError::InvalidNumber { span, .. }
+            | ParseError::InvalidVector { span, .. }
+            | ParseError::InvalidColor { span, .. }
+            | ParseError::UnexpectedToken { span, .. }
+            | ParseError::UnexpectedEndOfInput { span, .. }
+            | ParseError::InvalidNodeType { span, .. }
+            | ParseError::MissingRequiredField { span, .. }
+            | ParseError::InvalidFieldValue { span, .. }
+            | ParseError::UnknownNode { span, .. }
+            | ParseError::UnknownVariable { span, .. }
+            | ParseError::InvalidExpression { span, .. }
+            | ParseError::UnterminatedComment { span }
+            | ParseError::DuplicateNode { span, .. }
+            | ParseError::UnknownSocket { span, .. }
+            | ParseError::UnknownGroup { span, .. }
+            | ParseError::UnknownArgument { span, .. }
+            | ParseError::RecursiveGroup { span, .. }
+            | ParseError::ImportFailed { span, .. }
+            | ParseError::ImportCycle { span, .. }
+            | ParseError::ConstantRedefined { span, .. }
+            | ParseError::MissingParameter { span, .. }
+            | ParseError::UnknownParameter { span, .. }
+            | ParseError::ExpansionLimit { span, .. } => {
+                *span = (span.start + offset..span.end + offset).into();
+            }
         }
+        self
     }
 
     pub fn message(&self) -> String {
@@ -119,6 +238,41 @@ impl ParseError {
             } => {
                 format!("Invalid value '{found}' for field '{field}', expected {expected}")
             }
+            ParseError::UnknownNode { name, .. } => format!("Unknown node '{name}'"),
+            ParseError::UnknownVariable { name, .. } => format!("Unknown variable '{name}'"),
+            ParseError::InvalidExpression { message, .. } => message.clone(),
+            ParseError::UnterminatedComment { .. } => "Unterminated block comment".to_string(),
+            ParseError::DuplicateNode { name, .. } => {
+                format!("Node '{name}' is defined more than once")
+            }
+            ParseError::ConstantRedefined { name, .. } => {
+                format!("Constant '{name}' is already defined")
+            }
+            ParseError::MissingParameter { name, .. } => {
+                format!("Parameter '{name}' has no value")
+            }
+            ParseError::UnknownParameter { name, .. } => {
+                format!("A value was given for '{name}', but there is no param '{name}'")
+            }
+            ParseError::ExpansionLimit { limit, .. } => {
+                format!("Repeat expands past the limit of {limit} nodes")
+            }
+            ParseError::UnknownSocket { node, socket, .. } => {
+                format!("Node '{node}' has no socket '{socket}' on this side of the link")
+            }
+            ParseError::UnknownGroup { name, .. } => format!("Unknown group '{name}'"),
+            ParseError::UnknownArgument { group, name, .. } => {
+                format!("Group '{group}' has no parameter '{name}'")
+            }
+            ParseError::RecursiveGroup { name, .. } => {
+                format!("Group '{name}' calls itself")
+            }
+            ParseError::ImportFailed { path, message, .. } => {
+                format!("Cannot import '{path}': {message}")
+            }
+            ParseError::ImportCycle { cycle, .. } => {
+                format!("Import cycle: {}", cycle.join(" -> "))
+            }
         }
     }
 
@@ -151,6 +305,93 @@ impl ParseError {
             ParseError::InvalidFieldValue { found, .. } => {
                 format!("'{found}' is not valid here")
             }
+            ParseError::UnknownNode { name, .. } | ParseError::UnknownVariable { name, .. } => {
+                format!("'{name}' is not defined")
+            }
+            ParseError::InvalidExpression { .. } => "in this expression".to_string(),
+            ParseError::UnterminatedComment { .. } => "Comment starts here".to_string(),
+            ParseError::DuplicateNode { name, .. } => format!("'{name}' is already defined"),
+            ParseError::ConstantRedefined { name, .. } => format!("'{name}' can't change"),
+            ParseError::MissingParameter { .. } => "Declared without a default".to_string(),
+            ParseError::UnknownParameter { .. } => "Not declared in this file".to_string(),
+            ParseError::ExpansionLimit { .. } => "Too many repetitions".to_string(),
+            ParseError::UnknownSocket { socket, .. } => {
+                format!("'{socket}' is not a socket here")
+            }
+            ParseError::UnknownGroup { name, .. } => format!("'{name}' is not defined"),
+            ParseError::UnknownArgument { name, .. } => format!("'{name}' is not a parameter"),
+            ParseError::RecursiveGroup { .. } => "Called while it is being expanded".to_string(),
+            ParseError::ImportFailed { .. } | ParseError::ImportCycle { .. } => {
+                "Imported here".to_string()
+            }
+        }
+    }
+
+    pub fn help(&self) -> Option<String> {
+        match self {
+            ParseError::InvalidVector {
+                expected_components,
+                ..
+            } => Some(format!(
+                "Vectors must have exactly {expected_components} components: (x, y, z)"
+            )),
+            ParseError::InvalidColor {
+                expected_components,
+                ..
+            } => Some(format!(
+                "Colors must have exactly {expected_components} components: (r, g, b, a)"
+            )),
+            ParseError::InvalidNodeType { valid_types, .. } => {
+                Some(format!("Available node types: {}", valid_types.join(", ")))
+            }
+            ParseError::MissingRequiredField {
+                field, node_type, ..
+            } => Some(format!("Add the '{field}' field to your {node_type} node")),
+            ParseError::UnknownNode { .. } => Some(
+                "Name a node with `name = cube`, or use its generated id like `cube_0`".to_string(),
+            ),
+            ParseError::UnterminatedComment { .. } => {
+                Some("Close the comment with `*/`".to_string())
+            }
+            ParseError::UnknownVariable { name, .. } => Some(format!(
+                "Define it before it is used, e.g. `let {name} = 1.0`"
+            )),
+            ParseError::UnknownSocket { available, .. } => {
+                Some(format!("Available sockets: {}", available.join(", ")))
+            }
+            ParseError::UnknownGroup { name, .. } => {
+                Some(format!("Define it with `group {name}(...) {{ ... }}`"))
+            }
+            ParseError::RecursiveGroup { .. } => {
+                Some("Groups are expanded when called, so they can't call themselves".to_string())
+            }
+            ParseError::ImportCycle { .. } => Some(
+                "Move what the files share into another file and import that instead".to_string(),
+            ),
+            ParseError::ConstantRedefined { .. } => {
+                Some("Use `let` for values that change".to_string())
+            }
+            ParseError::MissingParameter { name, .. } => Some(format!(
+                "Pass a value for it, or give it a default like `param {name}: float = 1.0`"
+            )),
+            ParseError::UnknownParameter { name, .. } => {
+                Some(format!("Declare it with `param {name}: float = 1.0`"))
+            }
+            ParseError::ExpansionLimit { .. } => {
+                Some("Lower the count, or the counts of the loops around it".to_string())
+            }
+            _ => None,
+        }
+    }
+
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let span = self.span();
+        Diagnostic {
+            message: self.message(),
+            label: self.label_message(),
+            span: span.start..span.end,
+            severity: Severity::Error,
+            help: self.help(),
         }
     }
 
@@ -196,95 +437,467 @@ impl fmt::Display for ParseError {
 
 impl std::error::Error for ParseError {}
 
+/// A parsed graph that can't become a Blender node tree.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphError {
+    MissingOutput,
+    MultipleOutputs {
+        ids: Vec<NodeId>,
+    },
+    /// A connection to a node that isn't in the graph
+    UnknownNode {
+        id: NodeId,
+    },
+    /// A connection to a socket the node doesn't have, by its Blender name
+    UnknownSocket {
+        node: NodeId,
+        socket: String,
+    },
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::MissingOutput => {
+                write!(
+                    f,
+                    "Graph has no output node, add `output` and link geometry into it"
+                )
+            }
+            GraphError::MultipleOutputs { ids } => {
+                let ids = ids.iter().map(|id| id.0.as_str()).collect::<Vec<_>>();
+                write!(
+                    f,
+                    "Graph has {} output nodes ({}), expected exactly one",
+                    ids.len(),
+                    ids.join(", ")
+                )
+            }
+            GraphError::UnknownNode { id } => {
+                write!(f, "Connection refers to unknown node '{}'", id.0)
+            }
+            GraphError::UnknownSocket { node, socket } => {
+                write!(f, "Node '{}' has no socket '{socket}'", node.0)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
+
+/// A graph that parses but doesn't make sense, found by [`crate::validate`].
+///
+/// Graphs carry no source positions, so errors point at nodes and connections by their index in
+/// the graph; a [`SourceMap`] turns those back into spans.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SemanticError {
+    UnknownNode {
+        connection: usize,
+        node: NodeId,
+    },
+    UnknownSocket {
+        connection: usize,
+        node: NodeId,
+        socket: String,
+    },
+    SocketTypeMismatch {
+        connection: usize,
+        /// `node.socket` of each end, with its Blender socket type
+        from: String,
+        from_type: String,
+        to: String,
+        to_type: String,
+    },
+    DuplicateId {
+        node: usize,
+        id: NodeId,
+    },
+    Cycle {
+        /// The connection that closes the cycle
+        connection: usize,
+        /// The nodes in the cycle, starting and ending with the same one
+        nodes: Vec<NodeId>,
+    },
+}
+
+// `NodeSocketFloat` is shown as `float`
+fn socket_type_name(socket_type: &str) -> String {
+    socket_type
+        .strip_prefix("NodeSocket")
+        .unwrap_or(socket_type)
+        .to_lowercase()
+}
+
+impl SemanticError {
+    pub fn span(&self, spans: &SourceMap) -> Option<SimpleSpan> {
+        match self {
+            SemanticError::UnknownNode { connection, .. }
+            | SemanticError::UnknownSocket { connection, .. }
+            | SemanticError::SocketTypeMismatch { connection, .. }
+            | SemanticError::Cycle { connection, .. } => spans.connections.get(*connection),
+            SemanticError::DuplicateId { node, .. } => spans.nodes.get(*node),
+        }
+        .copied()
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            SemanticError::UnknownNode { node, .. } => {
+                format!("Connection refers to unknown node '{}'", node.0)
+            }
+            SemanticError::UnknownSocket { node, socket, .. } => {
+                format!("Node '{}' has no socket '{socket}'", node.0)
+            }
+            SemanticError::SocketTypeMismatch {
+                from,
+                from_type,
+                to,
+                to_type,
+                ..
+            } => format!(
+                "Cannot connect {} output '{from}' to {} input '{to}'",
+                socket_type_name(from_type),
+                socket_type_name(to_type)
+            ),
+            SemanticError::DuplicateId { id, .. } => {
+                format!("Node id '{}' is used more than once", id.0)
+            }
+            SemanticError::Cycle { nodes, .. } => {
+                let nodes = nodes.iter().map(|id| id.0.as_str()).collect::<Vec<_>>();
+                format!("Connections form a cycle: {}", nodes.join(" -> "))
+            }
+        }
+    }
+
+    pub fn label_message(&self) -> String {
+        match self {
+            SemanticError::UnknownNode { .. } => "Unknown node".to_string(),
+            SemanticError::UnknownSocket { .. } => "Unknown socket".to_string(),
+            SemanticError::SocketTypeMismatch {
+                from_type, to_type, ..
+            } => format!(
+                "{} into {}",
+                socket_type_name(from_type),
+                socket_type_name(to_type)
+            ),
+            SemanticError::DuplicateId { .. } => "Duplicate id".to_string(),
+            SemanticError::Cycle { .. } => "This connection closes the cycle".to_string(),
+        }
+    }
+
+    pub fn help(&self) -> Option<String> {
+        match self {
+            SemanticError::SocketTypeMismatch { .. } => Some(
+                "Geometry and object sockets only connect to sockets of the same type".to_string(),
+            ),
+            SemanticError::Cycle { .. } => {
+                Some("A node can't depend on its own output".to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Errors without a span in `spans` point at the start of the source.
+    pub fn to_diagnostic(&self, spans: &SourceMap) -> Diagnostic {
+        let span = self.span(spans).unwrap_or_else(|| (0..0).into());
+        Diagnostic {
+            message: self.message(),
+            label: self.label_message(),
+            span: span.start..span.end,
+            severity: Severity::Error,
+            help: self.help(),
+        }
+    }
+}
+
+impl fmt::Display for SemanticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for SemanticError {}
+
+/// Something in a graph that works but is probably a mistake, found by [`crate::lint`].
+///
+/// Like [`SemanticError`], warnings about nodes point at them by their index in the graph.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LintWarning {
+    /// A node that only produces values, like `value` or `math`, whose outputs go nowhere
+    UnusedNode { node: usize, id: NodeId },
+    /// A node type that only still parses so older files keep working
+    DeprecatedNode {
+        node: usize,
+        keyword: &'static str,
+        replacement: &'static str,
+    },
+    /// A variable declared while one of the same name is still in scope
+    ShadowedName {
+        name: String,
+        span: SimpleSpan,
+        /// Where the variable it hides was declared
+        previous: SimpleSpan,
+    },
+}
+
+impl LintWarning {
+    pub fn span(&self, spans: &SourceMap) -> Option<SimpleSpan> {
+        match self {
+            LintWarning::UnusedNode { node, .. } | LintWarning::DeprecatedNode { node, .. } => {
+                spans.nodes.get(*node).copied()
+            }
+            LintWarning::ShadowedName { span, .. } => Some(*span),
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            LintWarning::UnusedNode { id, .. } => {
+                format!("Node '{}' is not connected to anything", id.0)
+            }
+            LintWarning::DeprecatedNode { keyword, .. } => {
+                format!("'{keyword}' nodes are deprecated")
+            }
+            LintWarning::ShadowedName { name, .. } => {
+                format!("'{name}' hides an earlier variable of the same name")
+            }
+        }
+    }
+
+    pub fn label_message(&self) -> String {
+        match self {
+            LintWarning::UnusedNode { .. } => "Unused node".to_string(),
+            LintWarning::DeprecatedNode { .. } => "Deprecated node".to_string(),
+            LintWarning::ShadowedName { .. } => "Shadowed name".to_string(),
+        }
+    }
+
+    pub fn help(&self) -> Option<String> {
+        match self {
+            LintWarning::UnusedNode { .. } => Some(
+                "Connect one of its outputs, or remove it if it's left over from an edit"
+                    .to_string(),
+            ),
+            LintWarning::DeprecatedNode { replacement, .. } => {
+                Some(format!("Use {replacement} instead"))
+            }
+            LintWarning::ShadowedName { .. } => {
+                Some("Rename one of them if they are meant to be different values".to_string())
+            }
+        }
+    }
+
+    /// Warnings without a span in `spans` point at the start of the source.
+    pub fn to_diagnostic(&self, spans: &SourceMap) -> Diagnostic {
+        let span = self.span(spans).unwrap_or_else(|| (0..0).into());
+        Diagnostic {
+            message: self.message(),
+            label: self.label_message(),
+            span: span.start..span.end,
+            severity: Severity::Warning,
+            help: self.help(),
+        }
+    }
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+/// A graph file that can't be read or written as JSON, TOML or YAML.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatError {
+    /// Input that isn't valid JSON, TOML or YAML, or isn't shaped like a graph
+    Syntax {
+        format: &'static str,
+        message: String,
+        /// 1-based line and column, when the format reports one
+        location: Option<(usize, usize)>,
+    },
+    /// A graph that reads fine but fails [`crate::validate`]
+    Invalid { errors: Vec<SemanticError> },
+    /// A file saved by a newer release, with a format version this one doesn't know
+    UnsupportedVersion { version: u32, supported: u32 },
+    /// A migration that couldn't upgrade an older file
+    Migration { from: u32, message: String },
+    Serialize {
+        format: &'static str,
+        message: String,
+    },
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Syntax {
+                format,
+                message,
+                location: Some((line, column)),
+            } => write!(
+                f,
+                "{format} error at line {line}, column {column}: {message}"
+            ),
+            FormatError::Syntax {
+                format, message, ..
+            } => write!(f, "{format} error: {message}"),
+            FormatError::Invalid { errors } => {
+                let messages = errors
+                    .iter()
+                    .map(|error| error.message())
+                    .collect::<Vec<_>>();
+                write!(f, "Invalid graph: {}", messages.join("; "))
+            }
+            FormatError::UnsupportedVersion { version, supported } => write!(
+                f,
+                "Graph format version {version} is newer than this release supports (up to {supported})"
+            ),
+            FormatError::Migration { from, message } => {
+                write!(f, "Cannot upgrade graph from version {from}: {message}")
+            }
+            FormatError::Serialize { format, message } => {
+                write!(f, "Cannot write graph as {format}: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A parse error in a tooling friendly shape, for consumers that can't use terminal output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub message: String,
+    pub label: String,
+    pub span: Range<usize>,
+    pub severity: Severity,
+    pub help: Option<String>,
+}
+
+/// Renders errors against their source. With the `terminal` feature, reports have source
+/// snippets and colors; without it, as in wasm builds, each is a few lines of plain text.
 pub struct ErrorReporter {
+    #[cfg(feature = "terminal")]
     color_generator: ColorGenerator,
 }
 
 impl ErrorReporter {
     pub fn new() -> Self {
         Self {
+            #[cfg(feature = "terminal")]
             color_generator: ColorGenerator::new(),
         }
     }
 
     pub fn report_error(&mut self, error: &ParseError, source: &str, filename: &str) -> String {
-        let mut output = Vec::new();
-        let color = self.color_generator.next();
-
-        let span = error.span();
-        let report = Report::build(ReportKind::Error, filename, span.start)
-            .with_message(error.message())
-            .with_label(
-                Label::new((filename, span.start..span.end))
-                    .with_message(error.label_message())
-                    .with_color(color),
-            );
+        self.render(std::slice::from_ref(error), source, filename, true)
+    }
 
-        let report = match error {
-            ParseError::InvalidVector {
-                expected_components,
-                ..
-            } => report.with_help(format!(
-                "Vectors must have exactly {expected_components} components: (x, y, z)"
-            )),
-            ParseError::InvalidColor {
-                expected_components,
-                ..
-            } => report.with_help(format!(
-                "Colors must have exactly {expected_components} components: (r, g, b, a)"
-            )),
-            ParseError::InvalidNodeType { valid_types, .. } => {
-                report.with_help(format!("Available node types: {}", valid_types.join(", ")))
-            }
-            ParseError::MissingRequiredField {
-                field, node_type, ..
-            } => report.with_help(format!("Add the '{field}' field to your {node_type} node")),
-            _ => report,
-        };
+    pub fn report_errors(&mut self, errors: &[ParseError], source: &str, filename: &str) -> String {
+        self.render(errors, source, filename, true)
+    }
 
-        report
-            .finish()
-            .write((filename, Source::from(source)), &mut output)
-            .expect("Failed to write error report");
+    /// Same report as `report_errors`, without ANSI color codes.
+    pub fn report_plain(&mut self, errors: &[ParseError], source: &str, filename: &str) -> String {
+        self.render(errors, source, filename, false)
+    }
 
-        String::from_utf8(output).expect("Error report contains invalid UTF-8")
+    /// Errors from several files, each rendered against the file it is in.
+    pub fn report_sources(&mut self, errors: &SourceErrors) -> String {
+        errors
+            .errors
+            .iter()
+            .map(|(file, error)| {
+                let source = &errors.sources[*file];
+                let filename = source.path.display().to_string();
+                self.render(std::slice::from_ref(error), &source.source, &filename, true)
+            })
+            .collect()
     }
 
-    pub fn report_errors(&mut self, errors: &[ParseError], source: &str, filename: &str) -> String {
+    /// Semantic errors in a graph parsed from `source`, located with the graph's `spans`.
+    pub fn report_semantic(
+        &mut self,
+        errors: &[SemanticError],
+        spans: &SourceMap,
+        source: &str,
+        filename: &str,
+    ) -> String {
+        let diagnostics = errors
+            .iter()
+            .map(|error| error.to_diagnostic(spans))
+            .collect::<Vec<_>>();
+        self.render_diagnostics(&diagnostics, source, filename, true)
+    }
+
+    /// Lint warnings for a graph parsed from `source`, located with the graph's `spans`.
+    pub fn report_lints(
+        &mut self,
+        warnings: &[LintWarning],
+        spans: &SourceMap,
+        source: &str,
+        filename: &str,
+    ) -> String {
+        let diagnostics = warnings
+            .iter()
+            .map(|warning| warning.to_diagnostic(spans))
+            .collect::<Vec<_>>();
+        self.render_diagnostics(&diagnostics, source, filename, true)
+    }
+
+    pub fn diagnostics(errors: &[ParseError]) -> Vec<Diagnostic> {
+        errors.iter().map(ParseError::to_diagnostic).collect()
+    }
+
+    fn render(
+        &mut self,
+        errors: &[ParseError],
+        source: &str,
+        filename: &str,
+        color: bool,
+    ) -> String {
+        self.render_diagnostics(&Self::diagnostics(errors), source, filename, color)
+    }
+
+    #[cfg(feature = "terminal")]
+    fn render_diagnostics(
+        &mut self,
+        diagnostics: &[Diagnostic],
+        source: &str,
+        filename: &str,
+        color: bool,
+    ) -> String {
         let mut output = Vec::new();
 
-        for error in errors {
-            let color = self.color_generator.next();
-
-            let span = error.span();
-            let report = Report::build(ReportKind::Error, filename, span.start)
-                .with_message(error.message())
-                .with_label(
-                    Label::new((filename, span.start..span.end))
-                        .with_message(error.label_message())
-                        .with_color(color),
-                );
-
-            let report = match error {
-                ParseError::InvalidVector {
-                    expected_components,
-                    ..
-                } => report.with_help(format!(
-                    "Vectors must have exactly {expected_components} components: (x, y, z)"
-                )),
-                ParseError::InvalidColor {
-                    expected_components,
-                    ..
-                } => report.with_help(format!(
-                    "Colors must have exactly {expected_components} components: (r, g, b, a)"
-                )),
-                ParseError::InvalidNodeType { valid_types, .. } => {
-                    report.with_help(format!("Available node types: {}", valid_types.join(", ")))
-                }
-                ParseError::MissingRequiredField {
-                    field, node_type, ..
-                } => report.with_help(format!("Add the '{field}' field to your {node_type} node")),
-                _ => report,
+        for diagnostic in diagnostics {
+            let label =
+                Label::new((filename, diagnostic.span.clone())).with_message(&diagnostic.label);
+            let label = if color {
+                label.with_color(self.color_generator.next())
+            } else {
+                label
+            };
+
+            let kind = match diagnostic.severity {
+                Severity::Error => ReportKind::Error,
+                Severity::Warning => ReportKind::Warning,
+            };
+            let report = Report::build(kind, filename, diagnostic.span.start)
+                .with_config(Config::default().with_color(color))
+                .with_message(&diagnostic.message)
+                .with_label(label);
+
+            let report = match &diagnostic.help {
+                Some(help) => report.with_help(help),
+                None => report,
             };
 
             report
@@ -295,6 +908,41 @@ impl ErrorReporter {
 
         String::from_utf8(output).expect("Error report contains invalid UTF-8")
     }
+
+    // `Error: message`, then where it is and the line it's on, like a compiler without colors
+    #[cfg(not(feature = "terminal"))]
+    fn render_diagnostics(
+        &mut self,
+        diagnostics: &[Diagnostic],
+        source: &str,
+        filename: &str,
+        _color: bool,
+    ) -> String {
+        let mut output = String::new();
+        for diagnostic in diagnostics {
+            let start = diagnostic.span.start.min(source.len());
+            let line_start = source[..start].rfind('\n').map_or(0, |newline| newline + 1);
+            let line_end = source[start..]
+                .find('\n')
+                .map_or(source.len(), |end| start + end);
+            let kind = match diagnostic.severity {
+                Severity::Error => "Error",
+                Severity::Warning => "Warning",
+            };
+            output += &format!(
+                "{kind}: {}\n --> {filename}:{}:{}\n  | {}\n  = {}\n",
+                diagnostic.message,
+                source[..start].matches('\n').count() + 1,
+                source[line_start..start].chars().count() + 1,
+                &source[line_start..line_end],
+                diagnostic.label
+            );
+            if let Some(help) = &diagnostic.help {
+                output += &format!("  = help: {help}\n");
+            }
+        }
+        output
+    }
 }
 
 impl Default for ErrorReporter {
@@ -305,6 +953,29 @@ impl Default for ErrorReporter {
 
 pub type ParseResult<T> = Result<T, Vec<ParseError>>;
 
+/// A DSL file read while parsing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub source: String,
+}
+
+/// Errors from a file and the files it imports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceErrors {
+    pub sources: Vec<SourceFile>,
+    /// Each error with the index in `sources` of the file its span points into
+    pub errors: Vec<(usize, ParseError)>,
+}
+
+/// Where each node and connection of a parsed graph was written, in the same order as the
+/// graph's `nodes` and `connections`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceMap {
+    pub nodes: Vec<SimpleSpan>,
+    pub connections: Vec<SimpleSpan>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -393,6 +1064,35 @@ mod tests {
         assert!(report.contains("Invalid vector"));
     }
 
+    #[test]
+    fn error_reporter_names_the_source_file() {
+        let mut reporter = ErrorReporter::new();
+        let errors = SourceErrors {
+            sources: vec![
+                SourceFile {
+                    path: PathBuf::from("scene.ctl"),
+                    source: "import \"shapes.ctl\"".to_string(),
+                },
+                SourceFile {
+                    path: PathBuf::from("shapes.ctl"),
+                    source: "value abc".to_string(),
+                },
+            ],
+            errors: vec![(
+                1,
+                ParseError::InvalidNumber {
+                    span: SimpleSpan::from(6..9),
+                    found: "abc".to_string(),
+                    expected: "number".to_string(),
+                },
+            )],
+        };
+        let report = reporter.report_sources(&errors);
+
+        assert!(report.contains("shapes.ctl"));
+        assert!(!report.contains("scene.ctl"));
+    }
+
     #[test]
     fn error_reporter_includes_help_messages() {
         let mut reporter = ErrorReporter::new();
@@ -407,6 +1107,42 @@ mod tests {
         assert!(report.contains("Vectors must have exactly 3 components: (x, y, z)"));
     }
 
+    #[test]
+    fn error_reporter_plain_output_has_no_ansi_codes() {
+        let mut reporter = ErrorReporter::new();
+        let errors = vec![ParseError::InvalidVector {
+            span: SimpleSpan::from(6..12),
+            found_components: 2,
+            expected_components: 3,
+        }];
+        let source = "value (1, 2)";
+        let report = reporter.report_plain(&errors, source, "test.txt");
+
+        assert!(!report.contains('\x1b'));
+        assert!(report.contains("Invalid vector"));
+        assert!(report.contains("Vectors must have exactly 3 components: (x, y, z)"));
+    }
+
+    #[test]
+    fn diagnostic_serializes_to_json() {
+        let error = ParseError::InvalidVector {
+            span: SimpleSpan::from(6..12),
+            found_components: 2,
+            expected_components: 3,
+        };
+        let diagnostic = error.to_diagnostic();
+        assert_eq!(diagnostic.span, 6..12);
+        assert_eq!(diagnostic.severity, Severity::Error);
+
+        let json = serde_json::to_value(&diagnostic).expect("Failed to serialize diagnostic");
+        assert_eq!(json["severity"], "error");
+        assert_eq!(json["span"]["start"], 6);
+        assert_eq!(
+            json["help"],
+            "Vectors must have exactly 3 components: (x, y, z)"
+        );
+    }
+
     #[test]
     fn rich_error_conversion() {
         let span = SimpleSpan::from(0..5);
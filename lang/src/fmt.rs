@@ -0,0 +1,465 @@
+//! Canonical formatting of DSL source.
+//!
+//! Source is parsed and printed back with one statement per line, four space indentation inside
+//! blocks, node fields in their declared order and normalized spacing around operators.
+//! Comments are kept: those on their own lines stay before the statement that follows them, and
+//! those after a statement stay at the end of its line.
+
+use crate::decompile::literal;
+use crate::parser::{Statement, parse_statements};
+use crate::{BinaryOp, Comment, ParseResult, ParsedNode, ParsedValue, parse_comments};
+use std::ops::Range;
+
+/// `input` in canonical form, or the errors that stop it from parsing.
+pub fn format_source(input: &str) -> ParseResult<String> {
+    let comments = parse_comments(input)?;
+    let statements = parse_statements(input)?;
+
+    let mut blanked = input.to_string();
+    for comment in &comments {
+        blanked.replace_range(comment.span.clone(), &" ".repeat(comment.span.len()));
+    }
+    let mut formatter = Formatter {
+        input,
+        blanked,
+        comments,
+        next: 0,
+        output: String::new(),
+    };
+    formatter.block(&statements, 0, input.len());
+    Ok(formatter.output)
+}
+
+fn precedence(op: BinaryOp) -> u8 {
+    match op {
+        BinaryOp::Multiply | BinaryOp::Divide => 2,
+        BinaryOp::Add | BinaryOp::Subtract => 1,
+        _ => 0,
+    }
+}
+
+fn symbol(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Subtract => "-",
+        BinaryOp::Multiply => "*",
+        BinaryOp::Divide => "/",
+        BinaryOp::Equal => "==",
+        BinaryOp::NotEqual => "!=",
+        BinaryOp::Less => "<",
+        BinaryOp::LessOrEqual => "<=",
+        BinaryOp::Greater => ">",
+        BinaryOp::GreaterOrEqual => ">=",
+    }
+}
+
+// Parentheses are only kept where precedence needs them; operators are left associative
+pub(crate) fn expression(value: &ParsedValue) -> String {
+    match value {
+        ParsedValue::Literal { value, .. } => literal(value),
+        ParsedValue::Variable { name, .. } => name.clone(),
+        ParsedValue::Measure { value, unit, .. } => format!("{}{}", literal(value), unit.suffix()),
+        ParsedValue::Binary { op, lhs, rhs, .. } => {
+            let operand = |value: &ParsedValue, right: bool| match value {
+                ParsedValue::Binary { op: inner, .. }
+                    if precedence(*inner) < precedence(*op)
+                        || (right && precedence(*inner) == precedence(*op)) =>
+                {
+                    format!("({})", expression(value))
+                }
+                _ => expression(value),
+            };
+            format!(
+                "{} {} {}",
+                operand(lhs, false),
+                symbol(*op),
+                operand(rhs, true)
+            )
+        }
+    }
+}
+
+fn fields(fields: &[(&str, &Option<ParsedValue>)]) -> String {
+    let fields = fields
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .as_ref()
+                .map(|value| format!("{name}: {}", expression(value)))
+        })
+        .collect::<Vec<_>>();
+    if fields.is_empty() {
+        String::new()
+    } else {
+        format!(" {{ {} }}", fields.join(", "))
+    }
+}
+
+pub(crate) fn node(node: &ParsedNode) -> String {
+    match node {
+        ParsedNode::Cube { size } => format!("cube{}", fields(&[("size", size)])),
+        ParsedNode::Sphere {
+            radius,
+            subdivisions,
+        } => format!(
+            "sphere{}",
+            fields(&[("radius", radius), ("subdivisions", subdivisions)])
+        ),
+        ParsedNode::Cylinder {
+            vertices,
+            radius,
+            depth,
+        } => format!(
+            "cylinder{}",
+            fields(&[("vertices", vertices), ("radius", radius), ("depth", depth)])
+        ),
+        ParsedNode::Cone {
+            vertices,
+            radius_top,
+            radius_bottom,
+            depth,
+        } => format!(
+            "cone{}",
+            fields(&[
+                ("vertices", vertices),
+                ("radius_top", radius_top),
+                ("radius_bottom", radius_bottom),
+                ("depth", depth),
+            ])
+        ),
+        ParsedNode::Math {
+            operation,
+            a,
+            b,
+            clamp,
+        } => format!(
+            "math {}{}",
+            operation.name(),
+            fields(&[("a", a), ("b", b), ("clamp", clamp)])
+        ),
+        ParsedNode::VectorMath { operation, a, b } => format!(
+            "vector_math {}{}",
+            operation.name(),
+            fields(&[("a", a), ("b", b)])
+        ),
+        ParsedNode::Mix { factor, a, b } => {
+            format!("mix{}", fields(&[("factor", factor), ("a", a), ("b", b)]))
+        }
+        ParsedNode::Noise {
+            scale,
+            detail,
+            roughness,
+            lacunarity,
+            distortion,
+        } => format!(
+            "noise{}",
+            fields(&[
+                ("scale", scale),
+                ("detail", detail),
+                ("roughness", roughness),
+                ("lacunarity", lacunarity),
+                ("distortion", distortion),
+            ])
+        ),
+        ParsedNode::WhiteNoise => "white_noise".to_string(),
+        ParsedNode::Random { seed, min, max } => format!(
+            "random{}",
+            fields(&[("seed", seed), ("min", min), ("max", max)])
+        ),
+        ParsedNode::ExtrudeMesh {
+            offset_scale,
+            individual,
+        } => format!(
+            "extrude_mesh{}",
+            fields(&[("offset_scale", offset_scale), ("individual", individual)])
+        ),
+        ParsedNode::SubdivideMesh { level } => {
+            format!("subdivide_mesh{}", fields(&[("level", level)]))
+        }
+        ParsedNode::MeshBoolean { operation } => format!("mesh_boolean {}", operation.name()),
+        ParsedNode::JoinGeometry => "join_geometry".to_string(),
+        ParsedNode::DistributePoints { density } => {
+            format!("distribute_points{}", fields(&[("density", density)]))
+        }
+        ParsedNode::InstanceOnPoints => "instance_on_points".to_string(),
+        ParsedNode::Position => "position".to_string(),
+        ParsedNode::Normal => "normal".to_string(),
+        ParsedNode::NamedAttribute { name } => format!("named_attribute \"{name}\""),
+        ParsedNode::Value(value) => format!("value {}", expression(value)),
+        ParsedNode::Object { name } => format!("object \"{name}\""),
+        ParsedNode::Output => "output".to_string(),
+    }
+}
+
+fn labeled(label: &Option<String>, text: String) -> String {
+    match label {
+        Some(label) => format!("{label} = {text}"),
+        None => text,
+    }
+}
+
+// The statements that fit on one line
+fn simple(statement: &Statement) -> Option<String> {
+    let text = match statement {
+        Statement::Node { label, node: n, .. } => labeled(label, node(n)),
+        Statement::Connection { from, to } => {
+            format!("{}.{} -> {}.{}", from.node, from.socket, to.node, to.socket)
+        }
+        Statement::Param {
+            name,
+            kind,
+            default,
+            ..
+        } => match default {
+            Some(default) => format!("param {name}: {} = {}", kind.name(), expression(default)),
+            None => format!("param {name}: {}", kind.name()),
+        },
+        Statement::Let {
+            name,
+            value,
+            constant,
+            ..
+        } => {
+            let keyword = if *constant { "const" } else { "let" };
+            format!("{keyword} {name} = {}", expression(value))
+        }
+        Statement::Call {
+            label, name, args, ..
+        } => {
+            let args = args
+                .iter()
+                .map(|(name, value, _)| format!("{name}: {}", expression(value)))
+                .collect::<Vec<_>>();
+            labeled(label, format!("{name}({})", args.join(", ")))
+        }
+        Statement::Import { path, .. } => format!("import \"{path}\""),
+        _ => return None,
+    };
+    Some(text)
+}
+
+struct Formatter<'a> {
+    input: &'a str,
+    // `input` with comments replaced by spaces, as the parser saw it
+    blanked: String,
+    comments: Vec<Comment>,
+    // The first comment not written yet
+    next: usize,
+    output: String,
+}
+
+impl Formatter<'_> {
+    fn line(&mut self, depth: usize, text: &str) {
+        for _ in 0..depth {
+            self.output.push_str("    ");
+        }
+        self.output.push_str(text);
+        self.output.push('\n');
+    }
+
+    // Statement spans can include the whitespace around them, which may have been a comment
+    fn span(&self, statement: &Statement) -> Range<usize> {
+        let span = statement.span();
+        let text = &self.blanked[span.start..span.end];
+        let start = span.start + (text.len() - text.trim_start().len());
+        start..start + text.trim().len()
+    }
+
+    fn comment_before(&self, end: usize) -> bool {
+        self.comments
+            .get(self.next)
+            .is_some_and(|comment| comment.span.start < end)
+    }
+
+    // Comments before `end` go on their own lines
+    fn leading(&mut self, depth: usize, end: usize) {
+        while self.comment_before(end) {
+            let text = self.comments[self.next].text.trim_end().to_string();
+            self.line(depth, &text);
+            self.next += 1;
+        }
+    }
+
+    // A comment on the same line as the end of a statement stays at the end of that line
+    fn trailing(&mut self, end: usize) {
+        let Some(comment) = self.comments.get(self.next) else {
+            return;
+        };
+        if comment.span.start < end || self.input[end..comment.span.start].contains('\n') {
+            return;
+        }
+        self.output.pop();
+        self.output.push(' ');
+        self.output.push_str(comment.text.trim_end());
+        self.output.push('\n');
+        self.next += 1;
+    }
+
+    // One blank line is kept where the source has any between two statements
+    fn blank_line_between(&self, start: usize, end: usize) -> bool {
+        let gap = &self.input[start..end];
+        let lines = gap.split('\n').collect::<Vec<_>>();
+        lines.len() > 2
+            && lines[1..lines.len() - 1]
+                .iter()
+                .any(|line| line.trim().is_empty())
+    }
+
+    fn block(&mut self, statements: &[Statement], depth: usize, end: usize) {
+        let mut previous: Option<usize> = None;
+        for statement in statements {
+            let span = self.span(statement);
+            if previous.is_some_and(|previous| self.blank_line_between(previous, span.start)) {
+                self.output.push('\n');
+            }
+            self.statement(statement, depth);
+            previous = Some(span.end);
+        }
+        self.leading(depth, end);
+    }
+
+    // A block's `{` ends its header line, and an empty block closes on the same line
+    fn body(&mut self, header: String, body: &[Statement], depth: usize, end: usize) {
+        if body.is_empty() && !self.comment_before(end) {
+            self.line(depth, &format!("{header} {{}}"));
+        } else {
+            self.line(depth, &format!("{header} {{"));
+            self.block(body, depth + 1, end);
+            self.line(depth, "}");
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement, depth: usize) {
+        let span = self.span(statement);
+        if let Some(text) = simple(statement) {
+            // Comments inside a statement, like between its fields, move before it
+            self.leading(depth, span.end);
+            self.line(depth, &text);
+            self.trailing(span.end);
+            return;
+        }
+
+        self.leading(depth, span.start);
+        match statement {
+            Statement::Group {
+                name, params, body, ..
+            } => {
+                let params = params
+                    .iter()
+                    .map(|(name, default)| match default {
+                        Some(default) => format!("{name}: {}", expression(default)),
+                        None => name.clone(),
+                    })
+                    .collect::<Vec<_>>();
+                let header = format!("group {name}({})", params.join(", "));
+                self.body(header, body, depth, span.end);
+            }
+            Statement::Repeat {
+                count,
+                variable,
+                body,
+                ..
+            } => {
+                let header = format!("repeat {} as {variable}", expression(count));
+                self.body(header, body, depth, span.end);
+            }
+            Statement::If { .. } => self.condition(statement, depth, ""),
+            _ => {}
+        }
+        self.trailing(span.end);
+    }
+
+    // An `else` holding only another `if` is written as `else if`
+    fn condition(&mut self, statement: &Statement, depth: usize, prefix: &str) {
+        let Statement::If {
+            condition,
+            then,
+            otherwise,
+            ..
+        } = statement
+        else {
+            return;
+        };
+        let span = self.span(statement);
+        let header = format!("{prefix}if {} {{", expression(condition));
+        self.line(depth, &header);
+        let then_end = otherwise
+            .first()
+            .map_or(span.end, |statement| self.span(statement).start);
+        self.block(then, depth + 1, then_end);
+
+        match otherwise.as_slice() {
+            [] => self.line(depth, "}"),
+            [nested @ Statement::If { .. }] => self.condition(nested, depth, "} else "),
+            _ => {
+                self.line(depth, "} else {");
+                self.block(otherwise, depth + 1, span.end);
+                self.line(depth, "}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_geometry_nodes;
+
+    fn format(input: &str) -> String {
+        format_source(input).expect("Failed to format source")
+    }
+
+    #[test]
+    fn whitespace_and_field_order_are_normalized() {
+        let input = "let   r=1.5*(2+1)\nball=sphere{subdivisions:8,radius:r}\n\n\n\ncube;cylinder {depth: (1 + 2) * 3, radius: 1 - (2 - 3)}\nball.mesh->cube_1.size";
+        assert_eq!(
+            format(input),
+            "let r = 1.5 * (2 + 1)\nball = sphere { radius: r, subdivisions: 8 }\n\ncube\ncylinder { radius: 1 - (2 - 3), depth: (1 + 2) * 3 }\nball.mesh -> cube_1.size\n"
+        );
+        assert_eq!(
+            parse_geometry_nodes(&format(input)).expect("Failed to parse formatted source"),
+            parse_geometry_nodes(input).expect("Failed to parse source")
+        );
+    }
+
+    #[test]
+    fn blocks_are_indented() {
+        let input = "group Pillar(h, r: 0.5) {\ncylinder { depth: h, radius: r }\n}\nrepeat 3 as i { Pillar(h: i) }\nif lod > 1 { sphere } else { if lod == 1 { cube } else {} }";
+        assert_eq!(
+            format(input),
+            "group Pillar(h, r: 0.5) {\n    cylinder { radius: r, depth: h }\n}\nrepeat 3 as i {\n    Pillar(h: i)\n}\nif lod > 1 {\n    sphere\n} else if lod == 1 {\n    cube\n}\n"
+        );
+    }
+
+    #[test]
+    fn comments_are_kept() {
+        let input = "# The scene\ncube # big\n\n/* inner */ sphere { radius: 2 // radius\n}\ngroup A() {\n    cube\n    # last\n}";
+        let formatted = format(input);
+        assert_eq!(
+            formatted,
+            "# The scene\ncube # big\n\n/* inner */\n// radius\nsphere { radius: 2 }\ngroup A() {\n    cube\n    # last\n}\n"
+        );
+        assert_eq!(format(&formatted), formatted);
+    }
+
+    #[test]
+    fn constants_keep_their_units() {
+        assert_eq!(
+            format("const  H=2.4m\nlet a = 90deg*2\ncube { size: H + 5mm }"),
+            "const H = 2.4m\nlet a = 90deg * 2\ncube { size: H + 5mm }\n"
+        );
+    }
+
+    #[test]
+    fn params_keep_their_types() {
+        assert_eq!(
+            format("param  radius :float=1.0\nparam seed: int\nsphere { radius: radius }"),
+            "param radius: float = 1.0\nparam seed: int\nsphere { radius: radius }\n"
+        );
+    }
+
+    #[test]
+    fn invalid_source_is_not_formatted() {
+        assert!(format_source("cube { size: }").is_err());
+    }
+}
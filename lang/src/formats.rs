@@ -0,0 +1,384 @@
+//! Graphs as JSON, TOML and YAML files.
+//!
+//! JSON is how graphs travel between processes, but graph files written by hand read better as
+//! TOML or YAML. All three use the same shape, and loaded graphs are checked with
+//! [`crate::validate`] so mistakes show up when the file is read rather than in Blender.
+//!
+//! Files carry a `version`. Older files are upgraded by [`MIGRATIONS`] before they are read, so
+//! graphs saved by earlier releases keep loading as node schemas change.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Connection, FormatError, Node, NodeGraph, validate};
+
+/// The version written into graph files. Changing the shape of [`Node`] or [`NodeGraph`] means
+/// bumping this and adding a migration from the previous version.
+pub const GRAPH_FORMAT_VERSION: u32 = 1;
+
+/// Rewrites a graph in its JSON shape from one version to the next.
+type Migration = fn(&mut serde_json::Value) -> Result<(), String>;
+
+// `MIGRATIONS[n]` upgrades a version `n` graph to version `n + 1`. Files without a version were
+// written before versioning and count as version 0.
+const MIGRATIONS: [Migration; GRAPH_FORMAT_VERSION as usize] = [
+    // Unversioned graphs already have the version 1 shape
+    |_| Ok(()),
+];
+
+#[derive(Serialize)]
+struct GraphFileRef<'a> {
+    version: u32,
+    nodes: &'a [Node],
+    connections: &'a [Connection],
+}
+
+impl<'a> From<&'a NodeGraph> for GraphFileRef<'a> {
+    fn from(graph: &'a NodeGraph) -> Self {
+        Self {
+            version: GRAPH_FORMAT_VERSION,
+            nodes: &graph.nodes,
+            connections: &graph.connections,
+        }
+    }
+}
+
+// The version is read separately from the header, so it is ignored here
+#[derive(Deserialize)]
+struct GraphFile {
+    nodes: Vec<Node>,
+    connections: Vec<Connection>,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    version: Option<u32>,
+}
+
+// 1-based line and column of a byte offset in `input`
+fn line_column(input: &str, offset: usize) -> (usize, usize) {
+    let before = &input[..offset.min(input.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |newline| newline + 1) + 1;
+    (line, column)
+}
+
+fn checked(graph: NodeGraph) -> Result<NodeGraph, FormatError> {
+    let errors = validate(&graph);
+    if errors.is_empty() {
+        Ok(graph)
+    } else {
+        Err(FormatError::Invalid { errors })
+    }
+}
+
+// Current files are read straight into the graph types, so errors keep their locations. Older
+// files go through the format's generic value so migrations can rewrite them first.
+fn read(
+    format: &'static str,
+    header: Header,
+    current: impl Fn() -> Result<GraphFile, FormatError>,
+    generic: impl FnOnce() -> Result<serde_json::Value, FormatError>,
+) -> Result<NodeGraph, FormatError> {
+    let version = header.version.unwrap_or(0);
+    let file = match version.cmp(&GRAPH_FORMAT_VERSION) {
+        std::cmp::Ordering::Greater => {
+            return Err(FormatError::UnsupportedVersion {
+                version,
+                supported: GRAPH_FORMAT_VERSION,
+            });
+        }
+        std::cmp::Ordering::Equal => current()?,
+        std::cmp::Ordering::Less => {
+            let original = generic()?;
+            let migrated = migrate(original.clone(), version, &MIGRATIONS)?;
+            // Nothing was rewritten, so the text itself can be read with locations intact
+            if migrated == original {
+                current()?
+            } else {
+                serde_json::from_value(migrated).map_err(|error| FormatError::Syntax {
+                    format,
+                    message: error.to_string(),
+                    location: None,
+                })?
+            }
+        }
+    };
+    checked(NodeGraph {
+        nodes: file.nodes,
+        connections: file.connections,
+    })
+}
+
+fn migrate(
+    mut value: serde_json::Value,
+    from: u32,
+    migrations: &[Migration],
+) -> Result<serde_json::Value, FormatError> {
+    for (version, migration) in migrations.iter().enumerate().skip(from as usize) {
+        migration(&mut value).map_err(|message| FormatError::Migration {
+            from: version as u32,
+            message,
+        })?;
+    }
+    Ok(value)
+}
+
+// YAML tags name enum variants, which JSON writes as a map with the variant as its only key
+fn yaml_to_json(value: serde_yaml::Value) -> Result<serde_json::Value, String> {
+    use serde_json::Value as Json;
+    use serde_yaml::Value as Yaml;
+
+    Ok(match value {
+        Yaml::Null => Json::Null,
+        Yaml::Bool(value) => Json::Bool(value),
+        Yaml::Number(number) => serde_json::to_value(number).map_err(|error| error.to_string())?,
+        Yaml::String(value) => Json::String(value),
+        Yaml::Sequence(items) => Json::Array(
+            items
+                .into_iter()
+                .map(yaml_to_json)
+                .collect::<Result<_, _>>()?,
+        ),
+        Yaml::Mapping(mapping) => Json::Object(
+            mapping
+                .into_iter()
+                .map(|(key, value)| match key {
+                    Yaml::String(key) => Ok((key, yaml_to_json(value)?)),
+                    other => Err(format!("Expected a string key, found {other:?}")),
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        Yaml::Tagged(tagged) => {
+            let tag = tagged.tag.to_string().trim_start_matches('!').to_string();
+            Json::Object([(tag, yaml_to_json(tagged.value)?)].into_iter().collect())
+        }
+    })
+}
+
+impl NodeGraph {
+    /// Read a graph written as JSON, upgrading it first if it was saved by an older version.
+    pub fn from_json(input: &str) -> Result<Self, FormatError> {
+        let syntax = |error: serde_json::Error| FormatError::Syntax {
+            format: "JSON",
+            message: error
+                .to_string()
+                .split(" at line ")
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            location: Some((error.line(), error.column())),
+        };
+        let header = serde_json::from_str(input).map_err(syntax)?;
+        read(
+            "JSON",
+            header,
+            || serde_json::from_str(input).map_err(syntax),
+            || serde_json::from_str(input).map_err(syntax),
+        )
+    }
+
+    pub fn to_json(&self) -> Result<String, FormatError> {
+        serde_json::to_string_pretty(&GraphFileRef::from(self)).map_err(|error| {
+            FormatError::Serialize {
+                format: "JSON",
+                message: error.to_string(),
+            }
+        })
+    }
+
+    /// Read a graph written as TOML, with `[[nodes]]` and `[[connections]]` tables.
+    pub fn from_toml(input: &str) -> Result<Self, FormatError> {
+        let syntax = |error: toml::de::Error| FormatError::Syntax {
+            format: "TOML",
+            message: error.message().to_string(),
+            location: error.span().map(|span| line_column(input, span.start)),
+        };
+        let header = toml::from_str(input).map_err(syntax)?;
+        read(
+            "TOML",
+            header,
+            || toml::from_str(input).map_err(syntax),
+            || toml::from_str(input).map_err(syntax),
+        )
+    }
+
+    pub fn to_toml(&self) -> Result<String, FormatError> {
+        toml::to_string_pretty(&GraphFileRef::from(self)).map_err(|error| FormatError::Serialize {
+            format: "TOML",
+            message: error.to_string(),
+        })
+    }
+
+    /// Read a graph written as YAML, with `nodes` and `connections` lists. Node and value types
+    /// are tags, e.g. `- !Sphere` followed by the node's fields and `radius: !Float 1.5`.
+    pub fn from_yaml(input: &str) -> Result<Self, FormatError> {
+        let syntax = |error: serde_yaml::Error| FormatError::Syntax {
+            format: "YAML",
+            // The location is reported on its own, so it is left out of the message
+            message: error
+                .to_string()
+                .split(" at line ")
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            location: error
+                .location()
+                .map(|location| (location.line(), location.column())),
+        };
+        let header = serde_yaml::from_str(input).map_err(syntax)?;
+        read(
+            "YAML",
+            header,
+            || serde_yaml::from_str(input).map_err(syntax),
+            || {
+                let value = serde_yaml::from_str(input).map_err(syntax)?;
+                yaml_to_json(value).map_err(|message| FormatError::Syntax {
+                    format: "YAML",
+                    message,
+                    location: None,
+                })
+            },
+        )
+    }
+
+    pub fn to_yaml(&self) -> Result<String, FormatError> {
+        serde_yaml::to_string(&GraphFileRef::from(self)).map_err(|error| FormatError::Serialize {
+            format: "YAML",
+            message: error.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SemanticError, parse_geometry_nodes};
+
+    fn graph() -> NodeGraph {
+        parse_geometry_nodes(
+            "size = value 2\nbox = cube\nm = math multiply { b: 1.5 }\noutput\nsize.value -> m.value\nm.value -> box.size\nbox.mesh -> output_3.geometry",
+        )
+        .expect("Failed to parse graph")
+    }
+
+    #[test]
+    fn graphs_round_trip_through_toml_and_yaml() {
+        let graph = graph();
+
+        let toml = graph.to_toml().expect("Failed to write TOML");
+        assert!(toml.contains("[[nodes]]"));
+        assert_eq!(NodeGraph::from_toml(&toml), Ok(graph.clone()));
+
+        let yaml = graph.to_yaml().expect("Failed to write YAML");
+        // Node types and value types are YAML tags, like `- !Cube`
+        assert!(yaml.contains("!Cube"));
+        assert_eq!(NodeGraph::from_yaml(&yaml), Ok(graph));
+    }
+
+    #[test]
+    fn files_carry_the_format_version() {
+        let graph = graph();
+        let json = graph.to_json().expect("Failed to write JSON");
+        assert!(json.contains(&format!("\"version\": {GRAPH_FORMAT_VERSION}")));
+        assert_eq!(NodeGraph::from_json(&json), Ok(graph.clone()));
+
+        let toml = graph.to_toml().expect("Failed to write TOML");
+        assert!(toml.starts_with(&format!("version = {GRAPH_FORMAT_VERSION}")));
+        let yaml = graph.to_yaml().expect("Failed to write YAML");
+        assert!(yaml.starts_with(&format!("version: {GRAPH_FORMAT_VERSION}")));
+    }
+
+    #[test]
+    fn unversioned_graphs_are_migrated() {
+        let graph = graph();
+        let unversioned = serde_json::to_string(&graph).expect("Failed to write JSON");
+        assert_eq!(NodeGraph::from_json(&unversioned), Ok(graph.clone()));
+
+        let unversioned = serde_yaml::to_string(&graph).expect("Failed to write YAML");
+        assert_eq!(NodeGraph::from_yaml(&unversioned), Ok(graph.clone()));
+
+        let unversioned = toml::to_string(&graph).expect("Failed to write TOML");
+        assert_eq!(NodeGraph::from_toml(&unversioned), Ok(graph));
+    }
+
+    #[test]
+    fn migrations_run_in_order_from_the_file_version() {
+        let rename: Migration = |value| {
+            let graph = value.as_object_mut().ok_or("Expected an object")?;
+            let nodes = graph.remove("shapes").ok_or("Missing shapes")?;
+            graph.insert("nodes".to_string(), nodes);
+            Ok(())
+        };
+        let fail: Migration = |_| Err("Should not run".to_string());
+        let value = serde_json::json!({ "shapes": [], "connections": [] });
+
+        let migrated = migrate(value.clone(), 1, &[fail, rename]).expect("Failed to migrate");
+        assert_eq!(
+            migrated,
+            serde_json::json!({ "nodes": [], "connections": [] })
+        );
+
+        let error = migrate(value, 0, &[fail, rename]).expect_err("Expected a failure");
+        assert_eq!(
+            error.to_string(),
+            "Cannot upgrade graph from version 0: Should not run"
+        );
+    }
+
+    #[test]
+    fn newer_versions_are_rejected() {
+        let json = format!(
+            "{{ \"version\": {}, \"nodes\": [], \"connections\": [] }}",
+            GRAPH_FORMAT_VERSION + 1
+        );
+        let error = NodeGraph::from_json(&json).expect_err("Expected a version error");
+        assert_eq!(
+            error,
+            FormatError::UnsupportedVersion {
+                version: GRAPH_FORMAT_VERSION + 1,
+                supported: GRAPH_FORMAT_VERSION,
+            }
+        );
+        assert!(error.to_string().contains("newer"));
+    }
+
+    #[test]
+    fn syntax_errors_have_locations() {
+        let error = NodeGraph::from_toml("nodes = []\nconnections = [\n  { from_node = 1 },\n]")
+            .expect_err("Expected a TOML error");
+        let FormatError::Syntax {
+            format, location, ..
+        } = &error
+        else {
+            panic!("Expected a syntax error, got {error:?}");
+        };
+        assert_eq!(*format, "TOML");
+        assert_eq!(location.map(|(line, _)| line), Some(3));
+        assert!(error.to_string().starts_with("TOML error at line 3"));
+
+        let error = NodeGraph::from_yaml("nodes:\n  - !Sphere\n    id: ball\n    radius: 1\n")
+            .expect_err("Expected a YAML error");
+        assert!(matches!(
+            error,
+            FormatError::Syntax {
+                format: "YAML",
+                location: Some((4, _)),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn loaded_graphs_are_validated() {
+        let mut graph = graph();
+        graph.connections[0].to_input = "Missing".to_string();
+        let toml = graph.to_toml().expect("Failed to write TOML");
+        let error = NodeGraph::from_toml(&toml).expect_err("Expected an invalid graph");
+        assert!(matches!(
+            &error,
+            FormatError::Invalid { errors }
+                if matches!(errors[..], [SemanticError::UnknownSocket { connection: 0, .. }])
+        ));
+        assert!(error.to_string().contains("'Missing'"));
+    }
+}
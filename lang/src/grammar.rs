@@ -0,0 +1,268 @@
+//! A tree-sitter grammar for syntax highlighting in editors.
+//!
+//! The node keywords and operations come from [`node_catalog`], so a node type added to the
+//! parser is highlighted without anyone editing the grammar by hand. `cuttle grammar` writes the
+//! grammar and its highlight queries out for `tree-sitter generate`.
+
+use crate::parser::ParamType;
+use crate::{NodeTypeInfo, Unit, node_catalog};
+
+const PRELUDE: &str = r#"// Generated by `cuttle grammar` from the node catalog; edit lang/src/grammar.rs instead.
+
+const commaSep = (rule) => optional(seq(rule, repeat(seq(',', rule)), optional(',')));
+
+module.exports = grammar({
+  name: 'cuttle',
+
+  extras: $ => [/\s/, $.comment],
+
+  word: $ => $.identifier,
+
+  rules: {
+    source_file: $ => repeat(choice($._statement, ';')),
+
+    _statement: $ => choice(
+      $.param_declaration,
+      $.binding,
+      $.group_definition,
+      $.repeat_statement,
+      $.if_statement,
+      $.import_statement,
+      $.connection,
+      $.node_statement,
+    ),
+
+    param_declaration: $ => seq(
+      'param',
+      field('name', $.identifier),
+      ':',
+      field('type', $.param_type),
+      optional(seq('=', field('default', $._expression))),
+    ),
+
+    binding: $ => seq(
+      choice('let', 'const'),
+      field('name', $.identifier),
+      '=',
+      field('value', $._expression),
+    ),
+
+    group_definition: $ => seq(
+      'group',
+      field('name', $.identifier),
+      '(',
+      commaSep($.parameter),
+      ')',
+      field('body', $.block),
+    ),
+
+    parameter: $ => seq(
+      field('name', $.identifier),
+      optional(seq(':', field('default', $._expression))),
+    ),
+
+    block: $ => seq('{', repeat(choice($._statement, ';')), '}'),
+
+    repeat_statement: $ => seq(
+      'repeat',
+      field('count', $._expression),
+      'as',
+      field('variable', $.identifier),
+      field('body', $.block),
+    ),
+
+    if_statement: $ => seq(
+      'if',
+      field('condition', $._expression),
+      field('then', $.block),
+      optional(seq('else', field('otherwise', choice($.block, $.if_statement)))),
+    ),
+
+    import_statement: $ => seq('import', field('path', $.string)),
+
+    connection: $ => seq(field('from', $.endpoint), '->', field('to', $.endpoint)),
+
+    endpoint: $ => seq(field('node', $.identifier), '.', field('socket', $.identifier)),
+
+    node_statement: $ => seq(
+      optional(seq(field('label', $.identifier), '=')),
+      choice($.node, $.group_call),
+    ),
+
+    group_call: $ => seq(field('name', $.identifier), '(', commaSep($.argument), ')'),
+
+    argument: $ => seq(field('name', $.identifier), ':', field('value', $._expression)),
+
+    fields: $ => seq('{', commaSep($.field_assignment), '}'),
+
+    field_assignment: $ => seq(field('name', $.identifier), ':', field('value', $._expression)),
+
+    _expression: $ => choice(
+      $.binary_expression,
+      $.parenthesized_expression,
+      $.tuple,
+      $.measure,
+      $.number,
+      $.boolean,
+      $.identifier,
+    ),
+
+    binary_expression: $ => choice(
+      prec.left(1, seq($._expression, choice('==', '!=', '<=', '>=', '<', '>'), $._expression)),
+      prec.left(2, seq($._expression, choice('+', '-'), $._expression)),
+      prec.left(3, seq($._expression, choice('*', '/'), $._expression)),
+    ),
+
+    parenthesized_expression: $ => seq('(', $._expression, ')'),
+
+    // Vectors have three components and colors four
+    tuple: $ => seq('(', $.number, repeat1(seq(',', $.number)), ')'),
+
+    boolean: $ => choice('true', 'false'),
+
+    number: $ => /[+-]?(\d+\.?\d*|\.\d+)([eE][+-]?\d+)?/,
+
+    string: $ => /"[^"]*"/,
+
+    identifier: $ => /[A-Za-z_][A-Za-z0-9_]*/,
+
+    comment: $ => token(choice(
+      seq('#', /.*/),
+      seq('//', /.*/),
+      seq('/*', /[^*]*\*+([^/*][^*]*\*+)*/, '/'),
+    )),
+"#;
+
+const HIGHLIGHTS: &str = r#"; Generated by `cuttle grammar`; edit lang/src/grammar.rs instead.
+
+(comment) @comment
+(string) @string
+(number) @number
+(unit) @type
+(boolean) @constant.builtin
+(param_type) @type.builtin
+(node_type) @function.builtin
+(operation) @constant
+
+[
+  "let"
+  "const"
+  "param"
+  "group"
+  "repeat"
+  "as"
+  "if"
+  "else"
+  "import"
+] @keyword
+
+(group_definition name: (identifier) @function)
+(group_call name: (identifier) @function.call)
+(parameter name: (identifier) @variable.parameter)
+(argument name: (identifier) @variable.parameter)
+(field_assignment name: (identifier) @property)
+(endpoint socket: (identifier) @property)
+(node_statement label: (identifier) @label)
+(identifier) @variable
+
+[
+  "->"
+  "="
+  "+"
+  "-"
+  "*"
+  "/"
+  "=="
+  "!="
+  "<"
+  "<="
+  ">"
+  ">="
+] @operator
+
+["{" "}" "(" ")"] @punctuation.bracket
+["," "." ":" ";"] @punctuation.delimiter
+"#;
+
+fn quoted(names: impl IntoIterator<Item = &'static str>) -> String {
+    names
+        .into_iter()
+        .map(|name| format!("'{name}'"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// `cube`, `math add { ... }`, `value 1.5` or `object "Suzanne"`, the way the parser reads it
+fn node_rule(node: &NodeTypeInfo) -> String {
+    let mut rule = format!("seq(field('type', alias('{}', $.node_type))", node.keyword);
+    if !node.operations.is_empty() {
+        rule += &format!(
+            ", field('operation', alias(choice({}), $.operation))",
+            quoted(node.operations.iter().copied())
+        );
+    }
+    // Nodes written with an argument instead of fields
+    match node.keyword {
+        "value" => rule += ", field('value', $._expression)",
+        "named_attribute" | "object" => rule += ", field('name', $.string)",
+        _ => {}
+    }
+    if !node.fields.is_empty() {
+        rule += ", optional($.fields)";
+    }
+    rule + ")"
+}
+
+/// The tree-sitter `grammar.js` for the language.
+pub fn tree_sitter_grammar() -> String {
+    let mut grammar = PRELUDE.to_string();
+
+    grammar += "\n    node: $ => choice(\n";
+    for node in node_catalog() {
+        grammar += &format!("      {},\n", node_rule(&node));
+    }
+    grammar += "    ),\n";
+
+    grammar += &format!(
+        "\n    param_type: $ => choice({}),\n",
+        quoted(ParamType::ALL.map(ParamType::name))
+    );
+    // Units are written right after the number, as in `90deg`
+    grammar += &format!(
+        "\n    measure: $ => seq($.number, alias(token.immediate(choice({})), $.unit)),\n",
+        quoted(Unit::ALL.map(Unit::suffix))
+    );
+
+    grammar += "  },\n});\n";
+    grammar
+}
+
+/// The tree-sitter `queries/highlights.scm` for [`tree_sitter_grammar`].
+pub fn highlights_query() -> &'static str {
+    HIGHLIGHTS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grammar_covers_the_catalog() {
+        let grammar = tree_sitter_grammar();
+        for node in node_catalog() {
+            assert!(
+                grammar.contains(&format!("alias('{}', $.node_type)", node.keyword)),
+                "{} is missing from the grammar",
+                node.keyword
+            );
+        }
+        assert!(grammar.contains("choice('add', 'subtract', 'multiply'"));
+        assert!(grammar.contains("alias('sphere', $.node_type)), optional($.fields))"));
+        assert!(grammar.contains("alias('object', $.node_type)), field('name', $.string))"));
+        assert!(grammar.contains("choice('mm', 'cm', 'm', 'deg', 'rad')"));
+
+        let opened = grammar.matches(['(', '{']).count();
+        let closed = grammar.matches([')', '}']).count();
+        assert_eq!(opened, closed);
+    }
+}
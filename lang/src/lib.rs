@@ -2,13 +2,33 @@ use serde::{Deserialize, Serialize};
 
 pub mod ast;
 pub mod blender;
+pub mod catalog;
+pub mod codegen;
+pub mod decompile;
+pub mod diff;
 pub mod error;
+pub mod fmt;
+pub mod formats;
+pub mod grammar;
+pub mod lint;
+pub mod optimize;
+pub mod outline;
 pub mod parser;
+pub mod validate;
 
 pub use ast::*;
 pub use blender::*;
+pub use catalog::*;
+pub use decompile::*;
+pub use diff::*;
 pub use error::*;
+pub use fmt::*;
+pub use grammar::*;
+pub use lint::*;
+pub use optimize::*;
+pub use outline::*;
 pub use parser::*;
+pub use validate::*;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Value {
@@ -19,6 +39,18 @@ pub enum Value {
     Color(f64, f64, f64, f64),
 }
 
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Integer(i) => write!(f, "{i}"),
+            Value::Float(x) => write!(f, "{x}"),
+            Value::Boolean(b) => write!(f, "{b}"),
+            Value::Vector(x, y, z) => write!(f, "({x}, {y}, {z})"),
+            Value::Color(r, g, b, a) => write!(f, "({r}, {g}, {b}, {a})"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct NodeId(pub String);
 
@@ -32,8 +64,297 @@ pub struct Connection {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Node {
-    Value { id: NodeId, value: Value },
-    Cube { id: NodeId, size: Value },
+    Value {
+        id: NodeId,
+        value: Value,
+    },
+    Cube {
+        id: NodeId,
+        size: Value,
+    },
+    Sphere {
+        id: NodeId,
+        radius: Value,
+        subdivisions: Value,
+    },
+    Cylinder {
+        id: NodeId,
+        vertices: Value,
+        radius: Value,
+        depth: Value,
+    },
+    Cone {
+        id: NodeId,
+        vertices: Value,
+        radius_top: Value,
+        radius_bottom: Value,
+        depth: Value,
+    },
+    Math {
+        id: NodeId,
+        operation: MathOperation,
+        a: Value,
+        b: Value,
+        clamp: bool,
+    },
+    VectorMath {
+        id: NodeId,
+        operation: VectorMathOperation,
+        a: Value,
+        b: Value,
+    },
+    Mix {
+        id: NodeId,
+        factor: Value,
+        a: Value,
+        b: Value,
+    },
+    // Procedural noise, sampled at each point's position unless a vector is linked in
+    Noise {
+        id: NodeId,
+        scale: Value,
+        detail: Value,
+        roughness: Value,
+        lacunarity: Value,
+        distortion: Value,
+    },
+    WhiteNoise {
+        id: NodeId,
+    },
+    // A random value per element between `min` and `max`, the same for the same seed
+    Random {
+        id: NodeId,
+        seed: Value,
+        min: Value,
+        max: Value,
+    },
+    // Moves faces, edges or points along their normals, adding side faces to connect them
+    ExtrudeMesh {
+        id: NodeId,
+        offset_scale: Value,
+        individual: bool,
+    },
+    SubdivideMesh {
+        id: NodeId,
+        level: Value,
+    },
+    // Difference subtracts `mesh_2` from `mesh_1`; union and intersect only use `mesh_2`, which
+    // takes any number of links
+    MeshBoolean {
+        id: NodeId,
+        operation: BooleanOperation,
+    },
+    // Combines every geometry linked into its single input
+    JoinGeometry {
+        id: NodeId,
+    },
+    // Scatters points randomly over the faces of a mesh, `density` points per square meter
+    DistributePoints {
+        id: NodeId,
+        density: Value,
+    },
+    // Places a copy of the `instance` geometry on every point of `points`
+    InstanceOnPoints {
+        id: NodeId,
+    },
+    // Fields evaluated for each element of the geometry they are used on
+    Position {
+        id: NodeId,
+    },
+    Normal {
+        id: NodeId,
+    },
+    // A float attribute stored on the geometry under `name`, such as one painted in Blender
+    NamedAttribute {
+        id: NodeId,
+        name: String,
+    },
+    // An object that already exists in the scene, looked up by name
+    Object {
+        id: NodeId,
+        name: String,
+    },
+    // The Group Output of the tree; the geometry linked into it is the result
+    Output {
+        id: NodeId,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MathOperation {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+    Minimum,
+    Maximum,
+}
+
+impl MathOperation {
+    pub const ALL: [MathOperation; 7] = [
+        Self::Add,
+        Self::Subtract,
+        Self::Multiply,
+        Self::Divide,
+        Self::Power,
+        Self::Minimum,
+        Self::Maximum,
+    ];
+
+    /// The operation as written in the DSL, e.g. `add`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "add" => Some(Self::Add),
+            "subtract" => Some(Self::Subtract),
+            "multiply" => Some(Self::Multiply),
+            "divide" => Some(Self::Divide),
+            "power" => Some(Self::Power),
+            "minimum" => Some(Self::Minimum),
+            "maximum" => Some(Self::Maximum),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Add => "add",
+            Self::Subtract => "subtract",
+            Self::Multiply => "multiply",
+            Self::Divide => "divide",
+            Self::Power => "power",
+            Self::Minimum => "minimum",
+            Self::Maximum => "maximum",
+        }
+    }
+
+    /// The operation for a Blender Math node `operation` value, e.g. `POWER`.
+    pub fn from_blender_name(name: &str) -> Option<Self> {
+        Self::from_name(&name.to_lowercase())
+    }
+
+    /// The `operation` enum value of Blender's Math node.
+    pub fn blender_name(self) -> &'static str {
+        match self {
+            Self::Add => "ADD",
+            Self::Subtract => "SUBTRACT",
+            Self::Multiply => "MULTIPLY",
+            Self::Divide => "DIVIDE",
+            Self::Power => "POWER",
+            Self::Minimum => "MINIMUM",
+            Self::Maximum => "MAXIMUM",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VectorMathOperation {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    CrossProduct,
+    Minimum,
+    Maximum,
+}
+
+impl VectorMathOperation {
+    pub const ALL: [VectorMathOperation; 7] = [
+        Self::Add,
+        Self::Subtract,
+        Self::Multiply,
+        Self::Divide,
+        Self::CrossProduct,
+        Self::Minimum,
+        Self::Maximum,
+    ];
+
+    /// The operation as written in the DSL, e.g. `cross_product`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "add" => Some(Self::Add),
+            "subtract" => Some(Self::Subtract),
+            "multiply" => Some(Self::Multiply),
+            "divide" => Some(Self::Divide),
+            "cross_product" => Some(Self::CrossProduct),
+            "minimum" => Some(Self::Minimum),
+            "maximum" => Some(Self::Maximum),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Add => "add",
+            Self::Subtract => "subtract",
+            Self::Multiply => "multiply",
+            Self::Divide => "divide",
+            Self::CrossProduct => "cross_product",
+            Self::Minimum => "minimum",
+            Self::Maximum => "maximum",
+        }
+    }
+
+    /// The operation for a Blender Vector Math node `operation` value, e.g. `CROSS_PRODUCT`.
+    pub fn from_blender_name(name: &str) -> Option<Self> {
+        Self::from_name(&name.to_lowercase())
+    }
+
+    /// The `operation` enum value of Blender's Vector Math node.
+    pub fn blender_name(self) -> &'static str {
+        match self {
+            Self::Add => "ADD",
+            Self::Subtract => "SUBTRACT",
+            Self::Multiply => "MULTIPLY",
+            Self::Divide => "DIVIDE",
+            Self::CrossProduct => "CROSS_PRODUCT",
+            Self::Minimum => "MINIMUM",
+            Self::Maximum => "MAXIMUM",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BooleanOperation {
+    Intersect,
+    Union,
+    Difference,
+}
+
+impl BooleanOperation {
+    pub const ALL: [BooleanOperation; 3] = [Self::Intersect, Self::Union, Self::Difference];
+
+    /// The operation as written in the DSL, e.g. `difference`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "intersect" => Some(Self::Intersect),
+            "union" => Some(Self::Union),
+            "difference" => Some(Self::Difference),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Intersect => "intersect",
+            Self::Union => "union",
+            Self::Difference => "difference",
+        }
+    }
+
+    /// The operation for a Blender Mesh Boolean node `operation` value, e.g. `DIFFERENCE`.
+    pub fn from_blender_name(name: &str) -> Option<Self> {
+        Self::from_name(&name.to_lowercase())
+    }
+
+    /// The `operation` enum value of Blender's Mesh Boolean node.
+    pub fn blender_name(self) -> &'static str {
+        match self {
+            Self::Intersect => "INTERSECT",
+            Self::Union => "UNION",
+            Self::Difference => "DIFFERENCE",
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -47,6 +368,26 @@ impl Node {
         match self {
             Node::Value { id, .. } => id,
             Node::Cube { id, .. } => id,
+            Node::Sphere { id, .. } => id,
+            Node::Cylinder { id, .. } => id,
+            Node::Cone { id, .. } => id,
+            Node::Math { id, .. } => id,
+            Node::VectorMath { id, .. } => id,
+            Node::Mix { id, .. } => id,
+            Node::Noise { id, .. } => id,
+            Node::WhiteNoise { id } => id,
+            Node::Random { id, .. } => id,
+            Node::ExtrudeMesh { id, .. } => id,
+            Node::SubdivideMesh { id, .. } => id,
+            Node::MeshBoolean { id, .. } => id,
+            Node::JoinGeometry { id } => id,
+            Node::DistributePoints { id, .. } => id,
+            Node::InstanceOnPoints { id } => id,
+            Node::Position { id } => id,
+            Node::Normal { id } => id,
+            Node::NamedAttribute { id, .. } => id,
+            Node::Object { id, .. } => id,
+            Node::Output { id } => id,
         }
     }
 }
@@ -70,6 +411,102 @@ impl NodeGraph {
     pub fn find_node(&self, id: &NodeId) -> Option<&Node> {
         self.nodes.iter().find(|n| n.id() == id)
     }
+
+    // Each connection between known nodes as the indexes of the nodes it links
+    fn edges(&self) -> Vec<(usize, usize)> {
+        let index_of = |id: &NodeId| self.nodes.iter().position(|n| n.id() == id);
+        self.connections
+            .iter()
+            .filter_map(|c| Some((index_of(&c.from_node)?, index_of(&c.to_node)?)))
+            .collect()
+    }
+
+    /// Nodes ordered so each comes after every node connected into it. Ties keep the order of
+    /// `nodes`, so the result is the same every time. Nodes on a cycle can't be ordered and come
+    /// last, in the order of `nodes`.
+    pub fn topological_order(&self) -> Vec<&Node> {
+        let edges = self.edges();
+        let mut incoming = vec![0; self.nodes.len()];
+        for (_, to) in &edges {
+            incoming[*to] += 1;
+        }
+        let mut ready = std::collections::BTreeSet::new();
+        ready.extend((0..self.nodes.len()).filter(|index| incoming[*index] == 0));
+
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut placed = vec![false; self.nodes.len()];
+        while let Some(index) = ready.pop_first() {
+            order.push(index);
+            placed[index] = true;
+            for (_, to) in edges.iter().filter(|(from, _)| *from == index) {
+                incoming[*to] -= 1;
+                if incoming[*to] == 0 {
+                    ready.insert(*to);
+                }
+            }
+        }
+        order.extend((0..self.nodes.len()).filter(|index| !placed[*index]));
+        order.into_iter().map(|index| &self.nodes[index]).collect()
+    }
+
+    /// Nodes with nothing connected into them, in the order of `nodes`.
+    pub fn roots(&self) -> Vec<&Node> {
+        let edges = self.edges();
+        (0..self.nodes.len())
+            .filter(|index| edges.iter().all(|(_, to)| to != index))
+            .map(|index| &self.nodes[index])
+            .collect()
+    }
+
+    /// Nodes not connected into anything, in the order of `nodes`.
+    pub fn leaves(&self) -> Vec<&Node> {
+        let edges = self.edges();
+        (0..self.nodes.len())
+            .filter(|index| edges.iter().all(|(from, _)| from != index))
+            .map(|index| &self.nodes[index])
+            .collect()
+    }
+
+    /// Stable hash of the graph contents, used to skip re-applying an unchanged graph.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = ContentHasher::default();
+        hasher.write_json(self);
+        hasher.finish()
+    }
+}
+
+/// FNV-1a over JSON encodings streamed into it, so hashes are stable across runs and toolchains.
+pub(crate) struct ContentHasher(u64);
+
+impl Default for ContentHasher {
+    fn default() -> Self {
+        ContentHasher(0xcbf29ce484222325)
+    }
+}
+
+impl ContentHasher {
+    pub(crate) fn write_json<T: Serialize + ?Sized>(&mut self, value: &T) {
+        // Writes into the hasher can't fail and the graph types have no non-string map keys,
+        // so serializing them never errors
+        let _ = serde_json::to_writer(&mut *self, value);
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl std::io::Write for ContentHasher {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for byte in buf {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x100000001b3);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
 impl Default for NodeGraph {
@@ -84,11 +521,11 @@ mod integration_tests {
 
     #[test]
     fn test_parse_and_convert_cube() {
-        let input = "cube { size: 2.0 }";
+        let input = "cube { size: 2.0 }\noutput";
         let graph = parse_geometry_nodes(input).expect("Failed to parse cube in test");
 
         // Test parsing
-        assert_eq!(graph.nodes.len(), 1);
+        assert_eq!(graph.nodes.len(), 2);
         match &graph.nodes[0] {
             Node::Cube { size, .. } => {
                 assert_eq!(size, &Value::Float(2.0));
@@ -97,19 +534,316 @@ mod integration_tests {
         }
 
         // Test conversion to Blender format
-        let blender_graph: BlenderNodeGraph = graph.into();
-        assert_eq!(blender_graph.nodes.len(), 1);
+        let blender_graph = BlenderNodeGraph::try_from(graph).expect("Failed to convert graph");
+        assert_eq!(blender_graph.nodes.len(), 2);
         assert_eq!(blender_graph.nodes[0].node_type, "GeometryNodeMeshCube");
         assert_eq!(blender_graph.nodes[0].inputs[0].name, "Size");
     }
 
+    #[test]
+    fn test_parse_and_convert_sphere() {
+        let graph = parse_geometry_nodes("sphere { radius: 0.5, subdivisions: 12 }\noutput")
+            .expect("Failed to parse sphere");
+        let blender_graph = BlenderNodeGraph::try_from(graph).expect("Failed to convert graph");
+        let node = &blender_graph.nodes[0];
+
+        assert_eq!(node.node_type, "GeometryNodeMeshUVSphere");
+        let input = |name: &str| {
+            node.inputs
+                .iter()
+                .find(|socket| socket.name == name)
+                .and_then(|socket| socket.default_value.clone())
+        };
+        assert_eq!(input("Segments"), Some(BlenderValue::Integer(48)));
+        assert_eq!(input("Rings"), Some(BlenderValue::Integer(24)));
+        assert_eq!(input("Radius"), Some(BlenderValue::Float(0.5)));
+        assert_eq!(node.outputs[0].name, "Mesh");
+    }
+
+    #[test]
+    fn test_parse_and_convert_cylinder_and_cone() {
+        let graph =
+            parse_geometry_nodes("cylinder { vertices: 8, radius: 0.5, depth: 3.0 }\noutput")
+                .expect("Failed to parse cylinder");
+        let blender_graph = BlenderNodeGraph::try_from(graph).expect("Failed to convert graph");
+        let node = &blender_graph.nodes[0];
+        assert_eq!(node.node_type, "GeometryNodeMeshCylinder");
+        let names = node
+            .inputs
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["Vertices", "Radius", "Depth"]);
+        assert_eq!(node.inputs[0].default_value, Some(BlenderValue::Integer(8)));
+
+        let graph = parse_geometry_nodes("cone; output").expect("Failed to parse cone");
+        let blender_graph = BlenderNodeGraph::try_from(graph).expect("Failed to convert graph");
+        let node = &blender_graph.nodes[0];
+        assert_eq!(node.node_type, "GeometryNodeMeshCone");
+        assert_eq!(node.inputs[1].name, "Radius Top");
+        assert_eq!(node.inputs[1].default_value, Some(BlenderValue::Float(0.0)));
+        assert_eq!(node.outputs[0].name, "Mesh");
+    }
+
+    #[test]
+    fn test_connections_become_links() {
+        let graph =
+            parse_geometry_nodes("r = value 0.5\nball = sphere\nr.value -> ball.radius\noutput")
+                .expect("Failed to parse connection");
+        let blender_graph = BlenderNodeGraph::try_from(graph).expect("Failed to convert graph");
+        assert_eq!(
+            blender_graph.links,
+            vec![BlenderLink {
+                from_node: 0,
+                from_socket: "Value".to_string(),
+                to_node: 1,
+                to_socket: "Radius".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_multi_node_graphs_keep_all_links() {
+        let input = "a = value 1\nb = value 2\nm = math multiply\nbox = cube\nout = output\na.value -> m.value\nb.value -> m.value_001\nm.value -> box.size\nbox.mesh -> out.geometry";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse graph");
+        let blender_graph = BlenderNodeGraph::try_from(graph).expect("Failed to convert graph");
+        let links = blender_graph
+            .links
+            .iter()
+            .map(|l| {
+                (
+                    l.from_node,
+                    l.from_socket.as_str(),
+                    l.to_node,
+                    l.to_socket.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            links,
+            [
+                (0, "Value", 2, "Value"),
+                (1, "Value", 2, "Value_001"),
+                (2, "Value", 3, "Size"),
+                (3, "Mesh", 4, "Geometry"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_links_need_known_nodes_and_sockets() {
+        let graph = parse_geometry_nodes("box = cube\nout = output\nbox.mesh -> out.geometry")
+            .expect("Failed to parse graph");
+        let with_connection = |from: &str, output: &str| {
+            let mut graph = graph.clone();
+            graph.connections[0].from_node = NodeId(from.to_string());
+            graph.connections[0].from_output = output.to_string();
+            BlenderNodeGraph::try_from(graph)
+        };
+        assert_eq!(
+            with_connection("ball", "Mesh"),
+            Err(GraphError::UnknownNode {
+                id: NodeId("ball".to_string())
+            })
+        );
+        assert_eq!(
+            with_connection("box", "Volume"),
+            Err(GraphError::UnknownSocket {
+                node: NodeId("box".to_string()),
+                socket: "Volume".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_and_convert_math() {
+        let graph = parse_geometry_nodes(
+            "math power { a: 2, b: 3, clamp: true }\nvector_math add\nmix { a: (0, 0, 0), b: 1 }\noutput",
+        )
+        .expect("Failed to parse math nodes");
+        let blender_graph = BlenderNodeGraph::try_from(graph).expect("Failed to convert graph");
+
+        let math = &blender_graph.nodes[0];
+        assert_eq!(math.node_type, "ShaderNodeMath");
+        assert_eq!(
+            math.parameters.get("operation"),
+            Some(&BlenderValue::String("POWER".to_string()))
+        );
+        assert_eq!(
+            math.parameters.get("use_clamp"),
+            Some(&BlenderValue::Boolean(true))
+        );
+        assert_eq!(math.inputs[1].name, "Value_001");
+        assert_eq!(math.inputs[1].default_value, Some(BlenderValue::Integer(3)));
+
+        let vector_math = &blender_graph.nodes[1];
+        assert_eq!(vector_math.node_type, "ShaderNodeVectorMath");
+        assert_eq!(vector_math.inputs[0].socket_type, "NodeSocketVector");
+
+        let mix = &blender_graph.nodes[2];
+        assert_eq!(mix.node_type, "ShaderNodeMix");
+        assert_eq!(
+            mix.parameters.get("data_type"),
+            Some(&BlenderValue::String("VECTOR".to_string()))
+        );
+        assert_eq!(mix.outputs[0].name, "Result");
+    }
+
+    #[test]
+    fn test_parse_and_convert_noise() {
+        let input = "n = noise { scale: 2, detail: 4 }\nw = white_noise\nr = random { seed: 7, min: 1, max: 5 }\nscatter = random { max: (1, 1, 0) }\nn.fac -> r.id\nw.value -> scatter.min\noutput";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse noise nodes");
+        match &graph.nodes[0] {
+            Node::Noise {
+                scale, roughness, ..
+            } => {
+                assert_eq!(scale, &Value::Integer(2));
+                assert_eq!(roughness, &Value::Float(0.5));
+            }
+            _ => panic!("Expected Noise node"),
+        }
+        let blender_graph = BlenderNodeGraph::try_from(graph).expect("Failed to convert graph");
+
+        let noise = &blender_graph.nodes[0];
+        assert_eq!(noise.node_type, "ShaderNodeTexNoise");
+        assert_eq!(noise.outputs[0].name, "Fac");
+        assert_eq!(blender_graph.nodes[1].node_type, "ShaderNodeTexWhiteNoise");
+
+        let random = &blender_graph.nodes[2];
+        assert_eq!(random.node_type, "FunctionNodeRandomValue");
+        assert_eq!(
+            random.parameters.get("data_type"),
+            Some(&BlenderValue::String("INT".to_string()))
+        );
+        assert_eq!(
+            random.inputs[3].default_value,
+            Some(BlenderValue::Integer(7))
+        );
+        assert_eq!(
+            blender_graph.nodes[3].parameters.get("data_type"),
+            Some(&BlenderValue::String("FLOAT_VECTOR".to_string()))
+        );
+        assert_eq!(blender_graph.links[0].to_socket, "ID");
+        assert_eq!(blender_graph.links[1].from_socket, "Value");
+
+        assert!(parse_geometry_nodes("random { seed: 1.5 }").is_err());
+    }
+
+    #[test]
+    fn test_parse_and_convert_mesh_operations() {
+        let input = "box = cube\nball = sphere\ncut = mesh_boolean difference\nsmooth = subdivide_mesh { level: 2 }\nx = extrude_mesh { offset_scale: 0.5, individual: false }\nall = join_geometry\nbox.mesh -> cut.mesh_1\nball.mesh -> cut.mesh_2\ncut.mesh -> smooth.mesh\nsmooth.mesh -> x.mesh\nx.mesh -> all.geometry\nball.mesh -> all.geometry\noutput\nall.geometry -> output_6.geometry";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse mesh operations");
+        assert!(matches!(
+            graph.nodes[2],
+            Node::MeshBoolean {
+                operation: BooleanOperation::Difference,
+                ..
+            }
+        ));
+        let blender_graph = BlenderNodeGraph::try_from(graph).expect("Failed to convert graph");
+
+        let boolean = &blender_graph.nodes[2];
+        assert_eq!(boolean.node_type, "GeometryNodeMeshBoolean");
+        assert_eq!(
+            boolean.parameters.get("operation"),
+            Some(&BlenderValue::String("DIFFERENCE".to_string()))
+        );
+        assert_eq!(blender_graph.links[0].to_socket, "Mesh 1");
+        assert_eq!(blender_graph.links[1].to_socket, "Mesh 2");
+
+        let subdivide = &blender_graph.nodes[3];
+        assert_eq!(subdivide.node_type, "GeometryNodeSubdivideMesh");
+        assert_eq!(
+            subdivide.inputs[1].default_value,
+            Some(BlenderValue::Integer(2))
+        );
+
+        let extrude = &blender_graph.nodes[4];
+        assert_eq!(extrude.node_type, "GeometryNodeExtrudeMesh");
+        assert_eq!(
+            extrude.inputs[3].default_value,
+            Some(BlenderValue::Float(0.5))
+        );
+        assert_eq!(
+            extrude.inputs[4].default_value,
+            Some(BlenderValue::Boolean(false))
+        );
+
+        // Join takes any number of links into its one input
+        assert_eq!(blender_graph.nodes[5].node_type, "GeometryNodeJoinGeometry");
+        assert_eq!(blender_graph.links[4].to_socket, "Geometry");
+        assert_eq!(blender_graph.links[5].to_socket, "Geometry");
+
+        assert!(parse_geometry_nodes("mesh_boolean subtract").is_err());
+        assert!(parse_geometry_nodes("subdivide_mesh { level: 1.5 }").is_err());
+    }
+
+    #[test]
+    fn test_parse_and_convert_attributes() {
+        let input = "p = position\nn = normal\nh = named_attribute \"height\"\nup = vector_math multiply\nmoved = vector_math add\nn.normal -> up.vector\nh.attribute -> up.vector_001\np.position -> moved.vector\nup.vector -> moved.vector_001\noutput";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse attribute nodes");
+        match &graph.nodes[2] {
+            Node::NamedAttribute { name, .. } => assert_eq!(name, "height"),
+            _ => panic!("Expected NamedAttribute node"),
+        }
+        let blender_graph = BlenderNodeGraph::try_from(graph).expect("Failed to convert graph");
+
+        let position = &blender_graph.nodes[0];
+        assert_eq!(position.node_type, "GeometryNodeInputPosition");
+        assert!(position.inputs.is_empty());
+        assert_eq!(position.outputs[0].socket_type, "NodeSocketVector");
+        assert_eq!(blender_graph.nodes[1].node_type, "GeometryNodeInputNormal");
+
+        let attribute = &blender_graph.nodes[2];
+        assert_eq!(attribute.node_type, "GeometryNodeInputNamedAttribute");
+        assert_eq!(
+            attribute.inputs[0].default_value,
+            Some(BlenderValue::String("height".to_string()))
+        );
+        assert_eq!(attribute.outputs[1].name, "Exists");
+        assert_eq!(blender_graph.links[1].from_socket, "Attribute");
+        assert_eq!(blender_graph.links[2].from_socket, "Position");
+
+        assert!(parse_geometry_nodes("named_attribute \"\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_and_convert_scattering() {
+        let input = "ground = cube { size: 10 }\npebble = sphere { radius: 0.1 }\nscatter = distribute_points { density: 3 }\nplace = instance_on_points\noutput\nground.mesh -> scatter.mesh\nscatter.points -> place.points\npebble.mesh -> place.instance\nplace.instances -> output_4.geometry";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse scattering nodes");
+        match &graph.nodes[2] {
+            Node::DistributePoints { density, .. } => assert_eq!(density, &Value::Integer(3)),
+            _ => panic!("Expected DistributePoints node"),
+        }
+        assert!(validate(&graph).is_empty());
+        let blender_graph = BlenderNodeGraph::try_from(graph).expect("Failed to convert graph");
+
+        let distribute = &blender_graph.nodes[2];
+        assert_eq!(distribute.node_type, "GeometryNodeDistributePointsOnFaces");
+        assert_eq!(
+            distribute.inputs[2].default_value,
+            Some(BlenderValue::Integer(3))
+        );
+        assert_eq!(distribute.outputs[0].name, "Points");
+
+        let instance = &blender_graph.nodes[3];
+        assert_eq!(instance.node_type, "GeometryNodeInstanceOnPoints");
+        assert_eq!(
+            instance.inputs[6].default_value,
+            Some(BlenderValue::Vector(1.0, 1.0, 1.0))
+        );
+        assert_eq!(blender_graph.links[1].to_socket, "Points");
+        assert_eq!(blender_graph.links[2].to_socket, "Instance");
+        assert_eq!(blender_graph.links[3].from_socket, "Instances");
+    }
+
     #[test]
     fn test_parse_and_convert_value() {
-        let input = "value 42";
+        let input = "value 42\noutput";
         let graph = parse_geometry_nodes(input).expect("Failed to parse cube in test");
 
         // Test parsing
-        assert_eq!(graph.nodes.len(), 1);
+        assert_eq!(graph.nodes.len(), 2);
         match &graph.nodes[0] {
             Node::Value { value, .. } => {
                 assert_eq!(value, &Value::Integer(42));
@@ -118,8 +852,8 @@ mod integration_tests {
         }
 
         // Test conversion to Blender format
-        let blender_graph: BlenderNodeGraph = graph.into();
-        assert_eq!(blender_graph.nodes.len(), 1);
+        let blender_graph = BlenderNodeGraph::try_from(graph).expect("Failed to convert graph");
+        assert_eq!(blender_graph.nodes.len(), 2);
         assert_eq!(blender_graph.nodes[0].node_type, "ShaderNodeValue");
         assert_eq!(blender_graph.nodes[0].outputs[0].name, "Value");
     }
@@ -132,6 +866,144 @@ mod integration_tests {
         assert_eq!(original_value, converted_back);
     }
 
+    #[test]
+    fn test_parse_and_convert_object() {
+        let graph =
+            parse_geometry_nodes("object \"Suzanne\"\noutput").expect("Failed to parse object");
+        let blender_graph = BlenderNodeGraph::try_from(graph).expect("Failed to convert graph");
+        let node = &blender_graph.nodes[0];
+
+        assert_eq!(node.node_type, "GeometryNodeObjectInfo");
+        assert_eq!(
+            node.inputs[0].default_value,
+            Some(BlenderValue::String("Suzanne".to_string()))
+        );
+        assert!(node.outputs.iter().any(|socket| socket.name == "Geometry"));
+    }
+
+    #[test]
+    fn test_graphs_need_one_output() {
+        let graph = parse_geometry_nodes("cube\nresult = output\ncube_0.mesh -> result.geometry")
+            .expect("Failed to parse output");
+        let blender_graph = BlenderNodeGraph::try_from(graph).expect("Failed to convert graph");
+        assert_eq!(blender_graph.nodes[1].node_type, "NodeGroupOutput");
+        assert_eq!(blender_graph.links[0].to_socket, "Geometry");
+
+        let graph = parse_geometry_nodes("cube").expect("Failed to parse cube");
+        assert_eq!(
+            BlenderNodeGraph::try_from(graph),
+            Err(GraphError::MissingOutput)
+        );
+
+        let graph = parse_geometry_nodes("output; output").expect("Failed to parse outputs");
+        assert!(matches!(
+            BlenderNodeGraph::try_from(graph),
+            Err(GraphError::MultipleOutputs { ids }) if ids.len() == 2
+        ));
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let graph = parse_geometry_nodes("cube { size: 2.0 }").expect("Failed to parse cube");
+        let same = parse_geometry_nodes("cube {size: 2.0}").expect("Failed to parse cube");
+        let different = parse_geometry_nodes("cube { size: 3.0 }").expect("Failed to parse cube");
+
+        assert_eq!(graph.content_hash(), same.content_hash());
+        assert_ne!(graph.content_hash(), different.content_hash());
+    }
+
+    #[test]
+    fn test_blender_content_hash_ignores_parameter_order() {
+        let node = |parameters: &[(&str, BlenderValue)]| BlenderNodeGraph {
+            nodes: vec![BlenderNode {
+                node_type: "GeometryNodeMeshCube".to_string(),
+                location: (0.0, 0.0),
+                inputs: vec![],
+                outputs: vec![],
+                parameters: parameters
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), value.clone()))
+                    .collect(),
+            }],
+            links: vec![],
+        };
+        let a = ("a", BlenderValue::Integer(1));
+        let b = ("b", BlenderValue::Float(2.0));
+
+        let graph = node(&[a.clone(), b.clone()]);
+        assert_eq!(graph.content_hash(), node(&[b, a.clone()]).content_hash());
+        assert_ne!(graph.content_hash(), node(&[a]).content_hash());
+    }
+
+    #[test]
+    fn test_truncate_depth() {
+        let node = |node_type: &str| BlenderNode {
+            node_type: node_type.to_string(),
+            location: (0.0, 0.0),
+            inputs: vec![],
+            outputs: vec![],
+            parameters: std::collections::HashMap::new(),
+        };
+        let link = |from_node, to_node| BlenderLink {
+            from_node,
+            from_socket: "Out".to_string(),
+            to_node,
+            to_socket: "In".to_string(),
+        };
+        // Texture -> Ramp -> BSDF -> Output
+        let graph = BlenderNodeGraph {
+            nodes: vec![node("Texture"), node("Output"), node("BSDF"), node("Ramp")],
+            links: vec![link(0, 3), link(3, 2), link(2, 1)],
+        };
+
+        let truncated = graph.truncate_depth(1);
+        let types = truncated
+            .nodes
+            .iter()
+            .map(|n| n.node_type.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(types, vec!["Output", "BSDF"]);
+        assert_eq!(truncated.links.len(), 1);
+        assert_eq!(truncated.links[0].from_node, 1);
+        assert_eq!(truncated.links[0].to_node, 0);
+
+        assert_eq!(graph.truncate_depth(3), graph);
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let input = "out = output\nm = math add\nv = value 1\nball = sphere\nball.mesh -> out.geometry\nm.value -> ball.radius\nv.value -> m.value";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse graph");
+        let ids = |nodes: Vec<&Node>| {
+            nodes
+                .into_iter()
+                .map(|n| n.id().0.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(graph.topological_order()), ["v", "m", "ball", "out"]);
+        assert_eq!(ids(graph.roots()), ["v"]);
+        assert_eq!(ids(graph.leaves()), ["out"]);
+
+        // Nodes on a cycle come last
+        let cyclic = parse_geometry_nodes(
+            "a = math add\nb = math add\nc = cube\na.value -> b.value\nb.value -> a.value",
+        )
+        .expect("Failed to parse graph");
+        assert_eq!(ids(cyclic.topological_order()), ["c", "a", "b"]);
+    }
+
+    #[test]
+    fn test_nodes_are_laid_out_in_columns() {
+        let input = "out = output\nbox = cube\nv = value 1\nw = value 2\nv.value -> box.size\nbox.mesh -> out.geometry";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse graph");
+        let graph = BlenderNodeGraph::try_from(graph).expect("Failed to convert graph");
+        let locations = graph.nodes.iter().map(|n| n.location).collect::<Vec<_>>();
+        assert_eq!(
+            locations,
+            [(500.0, 0.0), (250.0, 0.0), (0.0, 0.0), (0.0, -200.0)]
+        );
+    }
+
     #[test]
     fn test_serialization() {
         let graph = NodeGraph {
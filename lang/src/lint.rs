@@ -0,0 +1,180 @@
+//! Warnings about graphs that work but are probably not what was meant.
+//!
+//! Unlike [`crate::validate`], nothing found here stops a graph from running in Blender, so it's
+//! up to the caller whether warnings fail a build, as with `cuttle check --deny-warnings`.
+
+use crate::decompile::kind;
+use crate::parser::{Statement, parse_statements};
+use crate::{
+    BlenderNode, LintWarning, NodeGraph, NodeId, ParseResult, SourceMap, deprecation,
+    parse_geometry_nodes_with_spans,
+};
+use chumsky::span::SimpleSpan;
+use std::collections::{HashMap, HashSet};
+
+// Sockets a node's result can leave through without being linked, as the geometry of the graph
+const GEOMETRY: &[&str] = &["NodeSocketGeometry", "NodeSocketObject"];
+
+/// Nodes whose values go nowhere and nodes of deprecated types.
+pub fn lint(graph: &NodeGraph) -> Vec<LintWarning> {
+    let used = graph
+        .connections
+        .iter()
+        .map(|connection| &connection.from_node)
+        .collect::<HashSet<&NodeId>>();
+
+    let mut warnings = Vec::new();
+    for (index, node) in graph.nodes.iter().enumerate() {
+        let keyword = kind(node);
+        if let Some(replacement) = deprecation(keyword) {
+            warnings.push(LintWarning::DeprecatedNode {
+                node: index,
+                keyword,
+                replacement,
+            });
+        }
+
+        let outputs = BlenderNode::from(node.clone()).outputs;
+        let values_only = !outputs.is_empty()
+            && outputs
+                .iter()
+                .all(|socket| !GEOMETRY.contains(&socket.socket_type.as_str()));
+        if values_only && !used.contains(node.id()) {
+            warnings.push(LintWarning::UnusedNode {
+                node: index,
+                id: node.id().clone(),
+            });
+        }
+    }
+    warnings
+}
+
+// A statement up to the `{` of its block, e.g. `repeat 5 as i`
+fn header(source: &str, span: SimpleSpan) -> SimpleSpan {
+    let text = &source[span.into_range()];
+    let end = text.find('{').map_or(span.end, |brace| {
+        span.start + text[..brace].trim_end().len()
+    });
+    (span.start..end).into()
+}
+
+fn declare(
+    scope: &mut HashMap<String, SimpleSpan>,
+    warnings: &mut Vec<LintWarning>,
+    name: &str,
+    span: SimpleSpan,
+) {
+    if let Some(previous) = scope.insert(name.to_string(), span) {
+        warnings.push(LintWarning::ShadowedName {
+            name: name.to_string(),
+            span,
+            previous,
+        });
+    }
+}
+
+// Variables in scope are kept with where they were declared. Group parameters and loop
+// variables are only in scope in their block.
+fn shadowing(
+    source: &str,
+    statements: &[Statement],
+    scope: &mut HashMap<String, SimpleSpan>,
+    warnings: &mut Vec<LintWarning>,
+) {
+    for statement in statements {
+        match statement {
+            Statement::Let { name, span, .. } | Statement::Param { name, span, .. } => {
+                declare(scope, warnings, name, *span);
+            }
+            Statement::Group {
+                params, body, span, ..
+            } => {
+                let mut inner = scope.clone();
+                for (param, _) in params {
+                    declare(&mut inner, warnings, param, header(source, *span));
+                }
+                shadowing(source, body, &mut inner, warnings);
+            }
+            Statement::Repeat {
+                variable,
+                body,
+                span,
+                ..
+            } => {
+                let mut inner = scope.clone();
+                declare(&mut inner, warnings, variable, header(source, *span));
+                shadowing(source, body, &mut inner, warnings);
+            }
+            // Only one branch runs, so each starts from the enclosing scope, and what either of
+            // them declares is in scope after the `if`
+            Statement::If {
+                then, otherwise, ..
+            } => {
+                let mut declared = Vec::new();
+                for branch in [then, otherwise] {
+                    let mut inner = scope.clone();
+                    shadowing(source, branch, &mut inner, warnings);
+                    declared.extend(inner);
+                }
+                for (name, span) in declared {
+                    scope.entry(name).or_insert(span);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// [`lint`] for the graph `source` parses to, along with variables that hide another of the same
+/// name, in source order. The spans locate the warnings for
+/// [`ErrorReporter::report_lints`](crate::ErrorReporter::report_lints).
+pub fn lint_source(source: &str) -> ParseResult<(Vec<LintWarning>, SourceMap)> {
+    let (graph, spans) = parse_geometry_nodes_with_spans(source)?;
+    let mut warnings = Vec::new();
+    shadowing(
+        source,
+        &parse_statements(source)?,
+        &mut HashMap::new(),
+        &mut warnings,
+    );
+    warnings.extend(lint(&graph));
+    warnings.sort_by_key(|warning| warning.span(&spans).map(|span| span.start));
+    Ok((warnings, spans))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorReporter;
+
+    #[test]
+    fn lint_finds_unused_nodes_and_shadowed_names() {
+        let source = "let size = 2\nlet size = 3\nbox = cube { size: size }\nheight = value 1.5\nscale = value 2\nscale.value -> box.size\nrepeat 2 as size { sphere }\nif size > 2 { let r = 1 } else { let r = 2 }\nlet r = 4\noutput";
+        let (warnings, spans) = lint_source(source).expect("Failed to lint");
+
+        let found = warnings
+            .iter()
+            .map(|warning| {
+                let span = warning.span(&spans).expect("Warning without a span");
+                (warning.label_message(), &source[span.into_range()])
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            [
+                ("Shadowed name".to_string(), "let size = 3"),
+                ("Unused node".to_string(), "height = value 1.5"),
+                ("Shadowed name".to_string(), "repeat 2 as size"),
+                ("Shadowed name".to_string(), "let r = 4"),
+            ]
+        );
+        assert!(matches!(
+            &warnings[1],
+            LintWarning::UnusedNode { id, .. } if id.0 == "height"
+        ));
+
+        let report = ErrorReporter::new().report_lints(&warnings, &spans, source, "scene.ctl");
+        assert_eq!(report.matches("Warning:").count(), 4);
+        assert!(!report.contains("Error:"));
+    }
+}
@@ -0,0 +1,209 @@
+//! Passes that make a graph smaller without changing what it builds.
+//!
+//! Graphs expanded from groups and `repeat` loops often compute values that never change, or
+//! build nodes nothing uses. Optimizing before converting keeps the Blender node tree down to
+//! the nodes that matter.
+
+use crate::{MathOperation, Node, NodeGraph, NodeId, Value};
+use std::collections::HashSet;
+
+/// Run every pass on `graph`: constant folding, then dead node removal, which also removes the
+/// values the folded nodes used to read.
+pub fn optimize(graph: &mut NodeGraph) {
+    fold_constants(graph);
+    remove_dead_nodes(graph);
+}
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Integer(i) => Some(*i as f64),
+        Value::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+// Results match Blender's Math node, which gives 0 rather than infinity or NaN where it can
+fn evaluate(operation: MathOperation, a: f64, b: f64) -> f64 {
+    match operation {
+        MathOperation::Add => a + b,
+        MathOperation::Subtract => a - b,
+        MathOperation::Multiply => a * b,
+        MathOperation::Divide if b == 0.0 => 0.0,
+        MathOperation::Divide => a / b,
+        MathOperation::Power if a < 0.0 && b.fract() != 0.0 => 0.0,
+        MathOperation::Power => a.powf(b),
+        MathOperation::Minimum => a.min(b),
+        MathOperation::Maximum => a.max(b),
+    }
+}
+
+// The number going into `socket` of the node `id`: the node's own field when nothing is linked
+// to it, or a linked `Value` node's number
+fn constant_input(graph: &NodeGraph, id: &NodeId, socket: &str, field: &Value) -> Option<f64> {
+    let mut links = graph
+        .connections
+        .iter()
+        .filter(|connection| connection.to_node == *id && connection.to_input == socket);
+    match (links.next(), links.next()) {
+        (None, _) => number(field),
+        (Some(link), None) => match graph.find_node(&link.from_node)? {
+            Node::Value { value, .. } => number(value),
+            _ => None,
+        },
+        (Some(_), Some(_)) => None,
+    }
+}
+
+/// Replace `math` nodes whose inputs are all constant with `value` nodes holding the result.
+/// Chains fold one after the other, so `(1 + 2) * 3` becomes a single value.
+///
+/// Folded nodes keep their id, and the links into them are removed.
+pub fn fold_constants(graph: &mut NodeGraph) {
+    loop {
+        let folded = graph.nodes.iter().enumerate().find_map(|(index, node)| {
+            let Node::Math {
+                id,
+                operation,
+                a,
+                b,
+                clamp,
+            } = node
+            else {
+                return None;
+            };
+            let a = constant_input(graph, id, "Value", a)?;
+            let b = constant_input(graph, id, "Value_001", b)?;
+            let result = evaluate(*operation, a, b);
+            let result = if *clamp {
+                result.clamp(0.0, 1.0)
+            } else {
+                result
+            };
+            Some((index, id.clone(), result))
+        });
+        let Some((index, id, result)) = folded else {
+            return;
+        };
+        graph
+            .connections
+            .retain(|connection| connection.to_node != id);
+        graph.nodes[index] = Node::Value {
+            id,
+            value: Value::Float(result),
+        };
+    }
+}
+
+/// Remove nodes that nothing reaching an `output` node depends on, along with their links.
+/// Graphs without an output are left alone, since there is nothing to measure against.
+pub fn remove_dead_nodes(graph: &mut NodeGraph) {
+    let mut live = graph
+        .nodes
+        .iter()
+        .filter(|node| matches!(node, Node::Output { .. }))
+        .map(|node| node.id().clone())
+        .collect::<HashSet<_>>();
+    if live.is_empty() {
+        return;
+    }
+    loop {
+        let before = live.len();
+        for connection in &graph.connections {
+            if live.contains(&connection.to_node) {
+                live.insert(connection.from_node.clone());
+            }
+        }
+        if live.len() == before {
+            break;
+        }
+    }
+
+    graph.nodes.retain(|node| live.contains(node.id()));
+    graph.connections.retain(|connection| {
+        live.contains(&connection.from_node) && live.contains(&connection.to_node)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_geometry_nodes;
+
+    fn optimized(input: &str) -> NodeGraph {
+        let mut graph = parse_geometry_nodes(input).expect("Failed to parse graph");
+        optimize(&mut graph);
+        graph
+    }
+
+    #[test]
+    fn constant_math_is_folded() {
+        let graph = optimized(
+            "three = value 3\nsum = math add { a: 1, b: 2 }\nscaled = math multiply\nbox = cube\noutput\nsum.value -> scaled.value\nthree.value -> scaled.value_001\nscaled.value -> box.size\nbox.mesh -> output_4.geometry",
+        );
+        let ids = graph
+            .nodes
+            .iter()
+            .map(|node| node.id().0.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["scaled", "box", "output_4"]);
+        assert_eq!(
+            graph.nodes[0],
+            Node::Value {
+                id: NodeId("scaled".to_string()),
+                value: Value::Float(9.0),
+            }
+        );
+        // The folded node still feeds the cube
+        assert_eq!(graph.connections.len(), 2);
+        assert_eq!(graph.connections[0].from_node.0, "scaled");
+    }
+
+    #[test]
+    fn folding_follows_blender() {
+        let value = |input: &str| {
+            let mut graph = parse_geometry_nodes(input).expect("Failed to parse graph");
+            fold_constants(&mut graph);
+            match &graph.nodes[0] {
+                Node::Value { value, .. } => value.clone(),
+                other => panic!("Expected a folded value, got {other:?}"),
+            }
+        };
+        assert_eq!(value("math divide { a: 1, b: 0 }"), Value::Float(0.0));
+        assert_eq!(value("math power { a: -8, b: 0.5 }"), Value::Float(0.0));
+        assert_eq!(
+            value("math add { a: 0.75, b: 0.5, clamp: true }"),
+            Value::Float(1.0)
+        );
+    }
+
+    #[test]
+    fn inputs_that_change_are_not_folded() {
+        let graph = optimized(
+            "n = noise\nm = math add\nball = sphere\noutput\nn.fac -> m.value\nm.value -> ball.radius\nball.mesh -> output_3.geometry",
+        );
+        assert!(matches!(graph.nodes[1], Node::Math { .. }));
+        assert_eq!(graph.nodes.len(), 4);
+
+        // Vectors aren't numbers Math can fold
+        let graph = optimized("v = value (1, 2, 3)\nm = math add\nv.value -> m.value");
+        assert!(matches!(graph.nodes[1], Node::Math { .. }));
+    }
+
+    #[test]
+    fn unused_nodes_are_removed() {
+        let graph = optimized(
+            "box = cube\nunused = sphere\nhelper = math add\noutput\nbox.mesh -> output_3.geometry\nunused.mesh -> helper.value",
+        );
+        let ids = graph
+            .nodes
+            .iter()
+            .map(|node| node.id().0.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["box", "output_3"]);
+        assert_eq!(graph.connections.len(), 1);
+
+        // Without an output nothing can be called unused
+        let graph = optimized("box = cube\nball = sphere");
+        assert_eq!(graph.nodes.len(), 2);
+    }
+}
@@ -0,0 +1,159 @@
+//! The named things in a file, for editor outlines.
+//!
+//! An outline only needs the file to parse, so it works on files whose imports or connections
+//! don't resolve yet, and without a language server.
+
+use crate::ParseResult;
+use crate::fmt::{expression, node};
+use crate::parser::{Statement, parse_statements};
+use serde::Serialize;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolKind {
+    /// A labeled node or group call, e.g. `ball = sphere`
+    Node,
+    Group,
+    /// A `const`, or a `param` callers can override
+    Constant,
+}
+
+impl SymbolKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            SymbolKind::Node => "node",
+            SymbolKind::Group => "group",
+            SymbolKind::Constant => "constant",
+        }
+    }
+}
+
+/// A named statement in the source, from [`outline`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// What the name stands for in canonical form, e.g. `sphere { radius: 2.0 }`
+    pub detail: String,
+    /// Byte range of the whole statement
+    pub span: Range<usize>,
+    /// Symbols declared inside a group
+    pub children: Vec<Symbol>,
+}
+
+fn symbols(statements: &[Statement], outline: &mut Vec<Symbol>) {
+    for statement in statements {
+        let span = statement.span().into_range();
+        let symbol = |name: &str, kind, detail| Symbol {
+            name: name.to_string(),
+            kind,
+            detail,
+            span: span.clone(),
+            children: Vec::new(),
+        };
+        match statement {
+            Statement::Node {
+                label: Some(label),
+                node: parsed,
+                ..
+            } => outline.push(symbol(label, SymbolKind::Node, node(parsed))),
+            Statement::Call {
+                label: Some(label),
+                name,
+                args,
+                ..
+            } => {
+                let args = args
+                    .iter()
+                    .map(|(name, value, _)| format!("{name}: {}", expression(value)))
+                    .collect::<Vec<_>>();
+                let detail = format!("{name}({})", args.join(", "));
+                outline.push(symbol(label, SymbolKind::Node, detail));
+            }
+            Statement::Let {
+                name,
+                value,
+                constant: true,
+                ..
+            } => outline.push(symbol(name, SymbolKind::Constant, expression(value))),
+            Statement::Param {
+                name,
+                kind,
+                default,
+                ..
+            } => {
+                let detail = match default {
+                    Some(default) => format!("param {} = {}", kind.name(), expression(default)),
+                    None => format!("param {}", kind.name()),
+                };
+                outline.push(symbol(name, SymbolKind::Constant, detail));
+            }
+            Statement::Group {
+                name, params, body, ..
+            } => {
+                let params = params
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>();
+                let mut group = symbol(name, SymbolKind::Group, format!("({})", params.join(", ")));
+                symbols(body, &mut group.children);
+                outline.push(group);
+            }
+            // Blocks don't have names, so what they declare is listed where they are
+            Statement::Repeat { body, .. } => symbols(body, outline),
+            Statement::If {
+                then, otherwise, ..
+            } => {
+                symbols(then, outline);
+                symbols(otherwise, outline);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The labeled nodes, groups and constants of `source`, in the order they are written.
+pub fn outline(source: &str) -> ParseResult<Vec<Symbol>> {
+    let statements = parse_statements(source)?;
+    let mut outline = Vec::new();
+    symbols(&statements, &mut outline);
+    Ok(outline)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn outline_lists_named_statements() {
+        let source = "param radius: float = 1\nconst height = 2m\n\ngroup post(h) {\n    shaft = cylinder { depth: h }\n    cube\n}\n\nleft = post(h: height)\nrepeat 2 as i {\n    ball = sphere { radius: radius }\n}\noutput";
+        let outline = outline(source).expect("Failed to outline");
+
+        let names = outline
+            .iter()
+            .map(|symbol| (symbol.name.as_str(), symbol.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                ("radius", SymbolKind::Constant),
+                ("height", SymbolKind::Constant),
+                ("post", SymbolKind::Group),
+                ("left", SymbolKind::Node),
+                ("ball", SymbolKind::Node),
+            ]
+        );
+        assert_eq!(outline[0].detail, "param float = 1");
+        assert_eq!(&source[outline[1].span.clone()], "const height = 2m");
+
+        let post = &outline[2];
+        assert_eq!(post.detail, "(h)");
+        assert_eq!(post.children.len(), 1);
+        assert_eq!(post.children[0].name, "shaft");
+        assert_eq!(post.children[0].detail, "cylinder { depth: h }");
+        assert_eq!(outline[3].detail, "post(h: height)");
+
+        assert!(super::outline("cube {").is_err());
+    }
+}
@@ -1,40 +1,385 @@
-use crate::{ErrorReporter, Node, NodeGraph, NodeId, ParseError, ParseResult, Value};
-use chumsky::error::Rich;
-use chumsky::primitive::{choice, end, just};
+use crate::{
+    BlenderNode, BlenderSocket, BooleanOperation, Connection, ErrorReporter, MathOperation, Node,
+    NodeGraph, NodeId, ParseError, ParseResult, SourceErrors, SourceFile, SourceMap, Value,
+    VectorMathOperation,
+};
+use chumsky::error::{Rich, RichReason};
+use chumsky::input::MapExtra;
+use chumsky::primitive::{any, choice, end, just, none_of, one_of};
+use chumsky::recovery::via_parser;
+use chumsky::recursive::recursive;
+use chumsky::span::SimpleSpan;
 use chumsky::{IterParser, Parser, extra, text};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// A field value as written: a literal, or a `let` variable or arithmetic expression that is
+/// folded to a constant when the graph is built.
+#[derive(Clone, Debug)]
+pub enum ParsedValue {
+    Literal {
+        value: Value,
+        span: SimpleSpan,
+    },
+    Variable {
+        name: String,
+        span: SimpleSpan,
+    },
+    Binary {
+        op: BinaryOp,
+        lhs: Box<ParsedValue>,
+        rhs: Box<ParsedValue>,
+        span: SimpleSpan,
+    },
+    /// A number with a unit, like `2.4m` or `90deg`
+    Measure {
+        value: Value,
+        unit: Unit,
+        span: SimpleSpan,
+    },
+}
+
+/// The type declared for a `param`, e.g. `float` in `param radius: float = 1.0`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ParamType {
+    Float,
+    Int,
+    Bool,
+    Vector,
+    Color,
+}
+
+impl ParamType {
+    pub(crate) const ALL: [ParamType; 5] = [
+        ParamType::Float,
+        ParamType::Int,
+        ParamType::Bool,
+        ParamType::Vector,
+        ParamType::Color,
+    ];
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            ParamType::Float => "float",
+            ParamType::Int => "int",
+            ParamType::Bool => "bool",
+            ParamType::Vector => "vector",
+            ParamType::Color => "color",
+        }
+    }
+
+    // Integers are floats too, so `param radius: float = 1` works
+    fn check(self, value: Value) -> Result<Value, Value> {
+        match (self, value) {
+            (ParamType::Float, Value::Integer(i)) => Ok(Value::Float(i as f64)),
+            (ParamType::Float, value @ Value::Float(_))
+            | (ParamType::Int, value @ Value::Integer(_))
+            | (ParamType::Bool, value @ Value::Boolean(_))
+            | (ParamType::Vector, value @ Value::Vector(..))
+            | (ParamType::Color, value @ Value::Color(..)) => Ok(value),
+            (_, other) => Err(other),
+        }
+    }
+
+    fn expected(self) -> &'static str {
+        match self {
+            ParamType::Float => "a float",
+            ParamType::Int => "an integer",
+            ParamType::Bool => "a boolean",
+            ParamType::Vector => "a vector",
+            ParamType::Color => "a color",
+        }
+    }
+}
+
+impl ParsedValue {
+    pub(crate) fn span(&self) -> SimpleSpan {
+        match self {
+            ParsedValue::Literal { span, .. }
+            | ParsedValue::Variable { span, .. }
+            | ParsedValue::Binary { span, .. }
+            | ParsedValue::Measure { span, .. } => *span,
+        }
+    }
+}
+
+/// A unit suffix on a number. Values are converted to Blender's own units, meters and radians,
+/// when the graph is built.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Unit {
+    Meter,
+    Centimeter,
+    Millimeter,
+    Degree,
+    Radian,
+}
+
+impl Unit {
+    /// Longer suffixes come first, so `mm` isn't read as `m` followed by `m`.
+    pub const ALL: [Unit; 5] = [
+        Unit::Millimeter,
+        Unit::Centimeter,
+        Unit::Meter,
+        Unit::Degree,
+        Unit::Radian,
+    ];
+
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Unit::Meter => "m",
+            Unit::Centimeter => "cm",
+            Unit::Millimeter => "mm",
+            Unit::Degree => "deg",
+            Unit::Radian => "rad",
+        }
+    }
+
+    /// How many meters or radians one of this unit is.
+    pub fn factor(self) -> f64 {
+        match self {
+            Unit::Meter | Unit::Radian => 1.0,
+            Unit::Centimeter => 0.01,
+            Unit::Millimeter => 0.001,
+            Unit::Degree => std::f64::consts::PI / 180.0,
+        }
+    }
+
+    fn convert(self, value: &Value) -> Value {
+        match *value {
+            Value::Integer(i) => Value::Float(i as f64 * self.factor()),
+            Value::Float(f) => Value::Float(f * self.factor()),
+            ref other => other.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl BinaryOp {
+    fn scalar(self, lhs: f64, rhs: f64) -> Result<f64, String> {
+        match self {
+            BinaryOp::Add => Ok(lhs + rhs),
+            BinaryOp::Subtract => Ok(lhs - rhs),
+            BinaryOp::Multiply => Ok(lhs * rhs),
+            BinaryOp::Divide if rhs == 0.0 => Err("Division by zero".to_string()),
+            BinaryOp::Divide => Ok(lhs / rhs),
+            _ => Err(format!("{self:?} is not arithmetic")),
+        }
+    }
+
+    // Numbers compare by value whatever their type, anything else only by equality
+    fn compare(self, lhs: &Value, rhs: &Value) -> Result<Value, String> {
+        let number = |value: &Value| match *value {
+            Value::Integer(i) => Some(i as f64),
+            Value::Float(f) => Some(f),
+            _ => None,
+        };
+        let result = match (number(lhs), number(rhs), self) {
+            (Some(a), Some(b), BinaryOp::Equal) => a == b,
+            (Some(a), Some(b), BinaryOp::NotEqual) => a != b,
+            (Some(a), Some(b), BinaryOp::Less) => a < b,
+            (Some(a), Some(b), BinaryOp::LessOrEqual) => a <= b,
+            (Some(a), Some(b), BinaryOp::Greater) => a > b,
+            (Some(a), Some(b), _) => a >= b,
+            (_, _, BinaryOp::Equal) => lhs == rhs,
+            (_, _, BinaryOp::NotEqual) => lhs != rhs,
+            _ => return Err(format!("Cannot compare {lhs} and {rhs}")),
+        };
+        Ok(Value::Boolean(result))
+    }
+
+    /// Integers stay integers except under division, and vectors work per component, with a
+    /// scalar on either side applying to every component. Comparisons give a boolean.
+    pub fn apply(self, lhs: &Value, rhs: &Value) -> Result<Value, String> {
+        if !matches!(
+            self,
+            BinaryOp::Add | BinaryOp::Subtract | BinaryOp::Multiply | BinaryOp::Divide
+        ) {
+            return self.compare(lhs, rhs);
+        }
+        let vector = |value: &Value| match *value {
+            Value::Integer(i) => Some((i as f64, i as f64, i as f64)),
+            Value::Float(f) => Some((f, f, f)),
+            Value::Vector(x, y, z) => Some((x, y, z)),
+            Value::Boolean(_) | Value::Color(..) => None,
+        };
+        match (lhs, rhs) {
+            (Value::Integer(a), Value::Integer(b)) if self != BinaryOp::Divide => {
+                let result = match self {
+                    BinaryOp::Add => a.checked_add(*b),
+                    BinaryOp::Subtract => a.checked_sub(*b),
+                    _ => a.checked_mul(*b),
+                };
+                result
+                    .map(Value::Integer)
+                    .ok_or_else(|| "Integer overflow".to_string())
+            }
+            (Value::Integer(_) | Value::Float(_), Value::Integer(_) | Value::Float(_)) => {
+                let (a, ..) = vector(lhs).unwrap_or_default();
+                let (b, ..) = vector(rhs).unwrap_or_default();
+                self.scalar(a, b).map(Value::Float)
+            }
+            _ => match (vector(lhs), vector(rhs)) {
+                (Some(a), Some(b)) => Ok(Value::Vector(
+                    self.scalar(a.0, b.0)?,
+                    self.scalar(a.1, b.1)?,
+                    self.scalar(a.2, b.2)?,
+                )),
+                _ => Err(format!("Cannot apply {self:?} to {lhs} and {rhs}")),
+            },
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
 pub enum ParsedNode {
-    Cube { size: Option<Value> },
-    Value(Value),
+    Cube {
+        size: Option<ParsedValue>,
+    },
+    Sphere {
+        radius: Option<ParsedValue>,
+        subdivisions: Option<ParsedValue>,
+    },
+    Cylinder {
+        vertices: Option<ParsedValue>,
+        radius: Option<ParsedValue>,
+        depth: Option<ParsedValue>,
+    },
+    Cone {
+        vertices: Option<ParsedValue>,
+        radius_top: Option<ParsedValue>,
+        radius_bottom: Option<ParsedValue>,
+        depth: Option<ParsedValue>,
+    },
+    Math {
+        operation: MathOperation,
+        a: Option<ParsedValue>,
+        b: Option<ParsedValue>,
+        clamp: Option<ParsedValue>,
+    },
+    VectorMath {
+        operation: VectorMathOperation,
+        a: Option<ParsedValue>,
+        b: Option<ParsedValue>,
+    },
+    Mix {
+        factor: Option<ParsedValue>,
+        a: Option<ParsedValue>,
+        b: Option<ParsedValue>,
+    },
+    Noise {
+        scale: Option<ParsedValue>,
+        detail: Option<ParsedValue>,
+        roughness: Option<ParsedValue>,
+        lacunarity: Option<ParsedValue>,
+        distortion: Option<ParsedValue>,
+    },
+    WhiteNoise,
+    Random {
+        seed: Option<ParsedValue>,
+        min: Option<ParsedValue>,
+        max: Option<ParsedValue>,
+    },
+    ExtrudeMesh {
+        offset_scale: Option<ParsedValue>,
+        individual: Option<ParsedValue>,
+    },
+    SubdivideMesh {
+        level: Option<ParsedValue>,
+    },
+    MeshBoolean {
+        operation: BooleanOperation,
+    },
+    JoinGeometry,
+    DistributePoints {
+        density: Option<ParsedValue>,
+    },
+    InstanceOnPoints,
+    Position,
+    Normal,
+    NamedAttribute {
+        name: String,
+    },
+    Value(ParsedValue),
+    Object {
+        name: String,
+    },
+    Output,
 }
 
-fn number_parser<'src>() -> impl Parser<'src, &'src str, f64, extra::Err<Rich<'src, char>>> {
-    text::int(10)
-        .then(just('.').then(text::digits(10)).or_not())
+fn parse_number(text: &str) -> Result<Value, &'static str> {
+    if text.contains(['.', 'e', 'E']) {
+        text.parse::<f64>()
+            .map(Value::Float)
+            .map_err(|_| "a number like 1.5, -.5 or 2e-3")
+    } else {
+        text.parse::<i64>()
+            .map(Value::Integer)
+            .map_err(|_| "an integer that fits in 64 bits")
+    }
+}
+
+// Takes everything number shaped, so `1.2.3` or `1e` is reported as one bad number rather than a
+// number followed by junk. Bad numbers are reported without stopping the parse; see `number_error`.
+fn number_literal_parser<'src>()
+-> impl Parser<'src, &'src str, Value, extra::Err<Rich<'src, char>>> + Clone {
+    let mantissa = any()
+        .filter(|c: &char| c.is_ascii_digit() || *c == '.')
+        .repeated()
+        .at_least(1);
+    let exponent = one_of("eE")
+        .then(one_of("+-").or_not())
+        .then(any().filter(char::is_ascii_digit).repeated());
+
+    one_of("+-")
+        .or_not()
+        .then(mantissa)
+        .then(exponent.or_not())
         .to_slice()
-        .try_map(|s: &str, span| {
-            s.parse::<f64>()
-                .map_err(|_| Rich::custom(span, format!("'{s}' is not a valid number")))
+        .validate(|text: &str, e, emitter| {
+            parse_number(text).unwrap_or_else(|expected| {
+                emitter.emit(Rich::custom(e.span(), expected));
+                Value::Integer(0)
+            })
         })
 }
 
-fn value_parser<'src>() -> impl Parser<'src, &'src str, Value, extra::Err<Rich<'src, char>>> {
-    let float = text::int(10)
-        .then(just('.').then(text::digits(10)))
-        .to_slice()
-        .try_map(|s: &str, span| {
-            s.parse::<f64>()
-                .map(Value::Float)
-                .map_err(|_| Rich::custom(span, format!("'{s}' is not a valid float")))
-        });
+fn number_parser<'src>() -> impl Parser<'src, &'src str, f64, extra::Err<Rich<'src, char>>> {
+    number_literal_parser().map(|value| match value {
+        Value::Integer(i) => i as f64,
+        Value::Float(f) => f,
+        _ => unreachable!("number literals are integers or floats"),
+    })
+}
 
-    let integer = text::int(10).to_slice().try_map(|s: &str, span| {
-        s.parse::<i64>()
-            .map(Value::Integer)
-            .map_err(|_| Rich::custom(span, format!("'{s}' is not a valid integer")))
-    });
+// Errors from `number_literal_parser` are the custom errors whose span is a number
+fn number_error(error: &Rich<'_, char>, source: &str) -> Option<ParseError> {
+    let RichReason::Custom(expected) = error.reason() else {
+        return None;
+    };
+    let found = source.get(error.span().into_range())?;
+    let number_like = found.starts_with(|c: char| c.is_ascii_digit() || "+-.".contains(c));
+    number_like.then(|| ParseError::InvalidNumber {
+        span: *error.span(),
+        found: found.to_string(),
+        expected: expected.clone(),
+    })
+}
 
+fn value_parser<'src>() -> impl Parser<'src, &'src str, Value, extra::Err<Rich<'src, char>>> {
     let boolean = just("true")
         .to(Value::Boolean(true))
         .or(just("false").to(Value::Boolean(false)));
@@ -60,94 +405,2240 @@ fn value_parser<'src>() -> impl Parser<'src, &'src str, Value, extra::Err<Rich<'
             }
         });
 
-    let color = just('(')
-        .ignore_then(
-            number_parser()
-                .separated_by(just(',').padded())
-                .collect::<Vec<_>>(),
-        )
-        .then_ignore(just(')'))
-        .try_map(|coords, span| {
-            if coords.len() == 4 {
-                Ok(Value::Color(coords[0], coords[1], coords[2], coords[3]))
-            } else {
-                Err(Rich::custom(
-                    span,
-                    format!(
-                        "Color must have exactly 4 components, found {}",
-                        coords.len()
-                    ),
-                ))
+    let color = just('(')
+        .ignore_then(
+            number_parser()
+                .separated_by(just(',').padded())
+                .collect::<Vec<_>>(),
+        )
+        .then_ignore(just(')'))
+        .try_map(|coords, span| {
+            if coords.len() == 4 {
+                Ok(Value::Color(coords[0], coords[1], coords[2], coords[3]))
+            } else {
+                Err(Rich::custom(
+                    span,
+                    format!(
+                        "Color must have exactly 4 components, found {}",
+                        coords.len()
+                    ),
+                ))
+            }
+        });
+
+    choice((number_literal_parser(), boolean, vector, color))
+}
+
+// Literals and variables combined with `+ - * /` and parentheses, e.g. `2.0 * scale + 0.5`.
+// `true` and `false` are always booleans, never variables.
+fn field_value_parser<'src>()
+-> impl Parser<'src, &'src str, ParsedValue, extra::Err<Rich<'src, char>>> {
+    recursive(|expression| {
+        let variable = text::ident().try_map(|name: &str, span| match name {
+            "true" | "false" => Err(Rich::custom(span, "Booleans are not variables")),
+            _ => Ok(ParsedValue::Variable {
+                name: name.to_string(),
+                span,
+            }),
+        });
+
+        let unit = choice(Unit::ALL.map(|unit| just(unit.suffix()).to(unit)));
+        let number = number_literal_parser()
+            .then(unit.or_not())
+            .map_with(|(value, unit), e| match unit {
+                Some(unit) => ParsedValue::Measure {
+                    value,
+                    unit,
+                    span: e.span(),
+                },
+                None => ParsedValue::Literal {
+                    value,
+                    span: e.span(),
+                },
+            });
+
+        // Vector and color literals are tried before a parenthesized expression
+        let atom = choice((
+            variable,
+            number,
+            value_parser().map_with(|value, e| ParsedValue::Literal {
+                value,
+                span: e.span(),
+            }),
+            expression.delimited_by(
+                just('(').then(text::inline_whitespace()),
+                text::inline_whitespace().then(just(')')),
+            ),
+        ))
+        .boxed();
+
+        let binary =
+            |lhs, (op, rhs), e: &mut MapExtra<'src, '_, &'src str, _>| ParsedValue::Binary {
+                op,
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+                span: e.span(),
+            };
+        let operator = |symbol, op| just(symbol).padded_by(text::inline_whitespace()).to(op);
+
+        let product = atom
+            .clone()
+            .foldl_with(
+                choice((
+                    operator('*', BinaryOp::Multiply),
+                    operator('/', BinaryOp::Divide),
+                ))
+                .then(atom)
+                .repeated(),
+                binary,
+            )
+            .boxed();
+        let sum = product
+            .clone()
+            .foldl_with(
+                choice((
+                    operator('+', BinaryOp::Add),
+                    operator('-', BinaryOp::Subtract),
+                ))
+                .then(product)
+                .repeated(),
+                binary,
+            )
+            .boxed();
+
+        // Comparisons bind loosest and don't chain, `1 < x < 3` is an error
+        let comparison = choice((
+            just("==").to(BinaryOp::Equal),
+            just("!=").to(BinaryOp::NotEqual),
+            just("<=").to(BinaryOp::LessOrEqual),
+            just(">=").to(BinaryOp::GreaterOrEqual),
+            just('<').to(BinaryOp::Less),
+            just('>').to(BinaryOp::Greater),
+        ))
+        .padded_by(text::inline_whitespace());
+        sum.clone()
+            .foldl_with(comparison.then(sum).or_not(), binary)
+    })
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum FieldKind {
+    Any,
+    Integer,
+    Boolean,
+}
+
+impl FieldKind {
+    fn accepts(self, value: &Value) -> bool {
+        match self {
+            FieldKind::Any => true,
+            FieldKind::Integer => matches!(value, Value::Integer(_)),
+            FieldKind::Boolean => matches!(value, Value::Boolean(_)),
+        }
+    }
+
+    fn expected(self) -> &'static str {
+        match self {
+            FieldKind::Any => "a value",
+            FieldKind::Integer => "an integer",
+            FieldKind::Boolean => "a boolean",
+        }
+    }
+}
+
+// A field a node takes in braces: the values it accepts, and its value when it is left out.
+// Fields without a default have to be given. `doc` is what tooling shows through the catalog.
+pub(crate) struct FieldSchema {
+    pub(crate) name: &'static str,
+    pub(crate) kind: FieldKind,
+    pub(crate) default: Option<Value>,
+    pub(crate) doc: &'static str,
+}
+
+const fn optional(
+    name: &'static str,
+    kind: FieldKind,
+    default: Value,
+    doc: &'static str,
+) -> FieldSchema {
+    FieldSchema {
+        name,
+        kind,
+        default: Some(default),
+        doc,
+    }
+}
+
+// Every field of a node type, in the order `Scope::fields` resolves them
+pub(crate) struct NodeSchema {
+    pub(crate) keyword: &'static str,
+    pub(crate) fields: &'static [FieldSchema],
+}
+
+pub(crate) const CUBE: NodeSchema = NodeSchema {
+    keyword: "cube",
+    fields: &[optional(
+        "size",
+        FieldKind::Any,
+        Value::Float(2.0),
+        "Side length, a number or a vector",
+    )],
+};
+
+// Subdivisions means what it does for `CreateSphereParams`, 4x segments around and 2x rings top
+// to bottom, so the default of 8 is Blender's 32 segment, 16 ring UV sphere
+pub(crate) const SPHERE: NodeSchema = NodeSchema {
+    keyword: "sphere",
+    fields: &[
+        optional(
+            "radius",
+            FieldKind::Any,
+            Value::Float(1.0),
+            "Distance from the center to the surface",
+        ),
+        optional(
+            "subdivisions",
+            FieldKind::Integer,
+            Value::Integer(8),
+            "Detail of the sphere, giving 4x segments and 2x rings",
+        ),
+    ],
+};
+
+// Cylinder and cone defaults also follow Blender's nodes
+pub(crate) const CYLINDER: NodeSchema = NodeSchema {
+    keyword: "cylinder",
+    fields: &[
+        optional(
+            "vertices",
+            FieldKind::Integer,
+            Value::Integer(32),
+            "Vertices around each cap",
+        ),
+        optional(
+            "radius",
+            FieldKind::Any,
+            Value::Float(1.0),
+            "Radius of the caps",
+        ),
+        optional("depth", FieldKind::Any, Value::Float(2.0), "Height along Z"),
+    ],
+};
+
+pub(crate) const CONE: NodeSchema = NodeSchema {
+    keyword: "cone",
+    fields: &[
+        optional(
+            "vertices",
+            FieldKind::Integer,
+            Value::Integer(32),
+            "Vertices around the base",
+        ),
+        optional(
+            "radius_top",
+            FieldKind::Any,
+            Value::Float(0.0),
+            "Radius at the top, 0 for a point",
+        ),
+        optional(
+            "radius_bottom",
+            FieldKind::Any,
+            Value::Float(1.0),
+            "Radius at the base",
+        ),
+        optional("depth", FieldKind::Any, Value::Float(2.0), "Height along Z"),
+    ],
+};
+
+// Math defaults match Blender's, and clamping is off like in a fresh node
+pub(crate) const MATH: NodeSchema = NodeSchema {
+    keyword: "math",
+    fields: &[
+        optional("a", FieldKind::Any, Value::Float(0.5), "The first operand"),
+        optional("b", FieldKind::Any, Value::Float(0.5), "The second operand"),
+        optional(
+            "clamp",
+            FieldKind::Boolean,
+            Value::Boolean(false),
+            "Clamp the result between 0 and 1",
+        ),
+    ],
+};
+
+pub(crate) const VECTOR_MATH: NodeSchema = NodeSchema {
+    keyword: "vector_math",
+    fields: &[
+        optional(
+            "a",
+            FieldKind::Any,
+            Value::Vector(0.0, 0.0, 0.0),
+            "The first vector",
+        ),
+        optional(
+            "b",
+            FieldKind::Any,
+            Value::Vector(0.0, 0.0, 0.0),
+            "The second vector",
+        ),
+    ],
+};
+
+pub(crate) const MIX: NodeSchema = NodeSchema {
+    keyword: "mix",
+    fields: &[
+        optional(
+            "factor",
+            FieldKind::Any,
+            Value::Float(0.5),
+            "How much of `b` to blend in",
+        ),
+        optional(
+            "a",
+            FieldKind::Any,
+            Value::Float(0.0),
+            "The result at factor 0",
+        ),
+        optional(
+            "b",
+            FieldKind::Any,
+            Value::Float(0.0),
+            "The result at factor 1",
+        ),
+    ],
+};
+
+// Noise defaults are those of Blender's Noise Texture node
+pub(crate) const NOISE: NodeSchema = NodeSchema {
+    keyword: "noise",
+    fields: &[
+        optional(
+            "scale",
+            FieldKind::Any,
+            Value::Float(5.0),
+            "Frequency of the noise",
+        ),
+        optional(
+            "detail",
+            FieldKind::Any,
+            Value::Float(2.0),
+            "Octaves of detail",
+        ),
+        optional(
+            "roughness",
+            FieldKind::Any,
+            Value::Float(0.5),
+            "How much each octave adds",
+        ),
+        optional(
+            "lacunarity",
+            FieldKind::Any,
+            Value::Float(2.0),
+            "Scale between octaves",
+        ),
+        optional(
+            "distortion",
+            FieldKind::Any,
+            Value::Float(0.0),
+            "How much the noise is warped",
+        ),
+    ],
+};
+
+pub(crate) const RANDOM: NodeSchema = NodeSchema {
+    keyword: "random",
+    fields: &[
+        optional(
+            "seed",
+            FieldKind::Integer,
+            Value::Integer(0),
+            "The same seed gives the same values",
+        ),
+        optional("min", FieldKind::Any, Value::Float(0.0), "The lowest value"),
+        optional(
+            "max",
+            FieldKind::Any,
+            Value::Float(1.0),
+            "The highest value",
+        ),
+    ],
+};
+
+// Blender extrudes each face on its own by default
+pub(crate) const EXTRUDE_MESH: NodeSchema = NodeSchema {
+    keyword: "extrude_mesh",
+    fields: &[
+        optional(
+            "offset_scale",
+            FieldKind::Any,
+            Value::Float(1.0),
+            "Distance to extrude",
+        ),
+        optional(
+            "individual",
+            FieldKind::Boolean,
+            Value::Boolean(true),
+            "Extrude each face on its own",
+        ),
+    ],
+};
+
+pub(crate) const SUBDIVIDE_MESH: NodeSchema = NodeSchema {
+    keyword: "subdivide_mesh",
+    fields: &[optional(
+        "level",
+        FieldKind::Integer,
+        Value::Integer(1),
+        "Times to subdivide",
+    )],
+};
+
+pub(crate) const DISTRIBUTE_POINTS: NodeSchema = NodeSchema {
+    keyword: "distribute_points",
+    fields: &[optional(
+        "density",
+        FieldKind::Any,
+        Value::Float(10.0),
+        "Points per square meter",
+    )],
+};
+
+type Fields = Vec<(&'static str, ParsedValue)>;
+
+// Optional `{ name: value, ... }` after a node keyword, where every field has to be in `schema`.
+// Values are checked against the schema once variables and expressions are resolved.
+fn fields_parser<'src>(
+    schema: &'static NodeSchema,
+) -> impl Parser<'src, &'src str, Fields, extra::Err<Rich<'src, char>>> {
+    let field = text::ident()
+        .then_ignore(just(':').padded())
+        .then(field_value_parser())
+        .try_map(move |(name, value): (&str, ParsedValue), span| {
+            let Some(field) = schema.fields.iter().find(|field| field.name == name) else {
+                let expected = schema
+                    .fields
+                    .iter()
+                    .map(|field| field.name)
+                    .collect::<Vec<_>>();
+                return Err(Rich::custom(
+                    span,
+                    format!(
+                        "Unknown {} field '{name}', expected one of: {}",
+                        schema.keyword,
+                        expected.join(", ")
+                    ),
+                ));
+            };
+            Ok((field.name, value))
+        });
+
+    let fields = field
+        .padded()
+        .separated_by(just(','))
+        .allow_trailing()
+        .collect::<Vec<_>>()
+        .delimited_by(just('{'), just('}'));
+
+    text::whitespace()
+        .ignore_then(fields)
+        .or_not()
+        .map(Option::unwrap_or_default)
+}
+
+// `keyword`, or `keyword { name: value, ... }`, e.g. `sphere { radius: 1.5, subdivisions: 16 }`
+fn primitive_parser<'src>(
+    schema: &'static NodeSchema,
+) -> impl Parser<'src, &'src str, Fields, extra::Err<Rich<'src, char>>> {
+    just(schema.keyword).ignore_then(fields_parser(schema))
+}
+
+// The operation named after a node keyword, like `add` in `math add { a: 1, b: 2 }`
+fn operation_parser<'src, T: Clone + 'src>(
+    keyword: &'static str,
+    from_name: fn(&str) -> Option<T>,
+) -> impl Parser<'src, &'src str, T, extra::Err<Rich<'src, char>>> {
+    just(keyword)
+        .ignore_then(text::inline_whitespace().at_least(1))
+        .ignore_then(text::ident())
+        .try_map(move |name: &str, span| {
+            from_name(name)
+                .ok_or_else(|| Rich::custom(span, format!("Unknown {keyword} operation '{name}'")))
+        })
+}
+
+fn math_parser<'src>() -> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    operation_parser(MATH.keyword, MathOperation::from_name)
+        .then(fields_parser(&MATH))
+        .map(|(operation, fields)| ParsedNode::Math {
+            operation,
+            a: field(&fields, "a"),
+            b: field(&fields, "b"),
+            clamp: field(&fields, "clamp"),
+        })
+}
+
+fn vector_math_parser<'src>()
+-> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    operation_parser(VECTOR_MATH.keyword, VectorMathOperation::from_name)
+        .then(fields_parser(&VECTOR_MATH))
+        .map(|(operation, fields)| ParsedNode::VectorMath {
+            operation,
+            a: field(&fields, "a"),
+            b: field(&fields, "b"),
+        })
+}
+
+fn mix_parser<'src>() -> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    primitive_parser(&MIX).map(|fields| ParsedNode::Mix {
+        factor: field(&fields, "factor"),
+        a: field(&fields, "a"),
+        b: field(&fields, "b"),
+    })
+}
+
+fn noise_parser<'src>() -> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    primitive_parser(&NOISE).map(|fields| ParsedNode::Noise {
+        scale: field(&fields, "scale"),
+        detail: field(&fields, "detail"),
+        roughness: field(&fields, "roughness"),
+        lacunarity: field(&fields, "lacunarity"),
+        distortion: field(&fields, "distortion"),
+    })
+}
+
+fn white_noise_parser<'src>()
+-> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    just("white_noise").to(ParsedNode::WhiteNoise)
+}
+
+fn random_parser<'src>() -> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    primitive_parser(&RANDOM).map(|fields| ParsedNode::Random {
+        seed: field(&fields, "seed"),
+        min: field(&fields, "min"),
+        max: field(&fields, "max"),
+    })
+}
+
+fn extrude_mesh_parser<'src>()
+-> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    primitive_parser(&EXTRUDE_MESH).map(|fields| ParsedNode::ExtrudeMesh {
+        offset_scale: field(&fields, "offset_scale"),
+        individual: field(&fields, "individual"),
+    })
+}
+
+fn subdivide_mesh_parser<'src>()
+-> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    primitive_parser(&SUBDIVIDE_MESH).map(|fields| ParsedNode::SubdivideMesh {
+        level: field(&fields, "level"),
+    })
+}
+
+fn mesh_boolean_parser<'src>()
+-> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    operation_parser("mesh_boolean", BooleanOperation::from_name)
+        .map(|operation| ParsedNode::MeshBoolean { operation })
+}
+
+fn join_geometry_parser<'src>()
+-> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    just("join_geometry").to(ParsedNode::JoinGeometry)
+}
+
+// Later fields win, so `{ radius: 1, radius: 2 }` means 2
+fn field(fields: &Fields, name: &str) -> Option<ParsedValue> {
+    fields
+        .iter()
+        .rev()
+        .find(|(field, _)| *field == name)
+        .map(|(_, value)| value.clone())
+}
+
+fn sphere_parser<'src>() -> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    primitive_parser(&SPHERE).map(|fields| ParsedNode::Sphere {
+        radius: field(&fields, "radius"),
+        subdivisions: field(&fields, "subdivisions"),
+    })
+}
+
+fn cylinder_parser<'src>() -> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>>
+{
+    primitive_parser(&CYLINDER).map(|fields| ParsedNode::Cylinder {
+        vertices: field(&fields, "vertices"),
+        radius: field(&fields, "radius"),
+        depth: field(&fields, "depth"),
+    })
+}
+
+fn cone_parser<'src>() -> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    primitive_parser(&CONE).map(|fields| ParsedNode::Cone {
+        vertices: field(&fields, "vertices"),
+        radius_top: field(&fields, "radius_top"),
+        radius_bottom: field(&fields, "radius_bottom"),
+        depth: field(&fields, "depth"),
+    })
+}
+
+fn cube_parser<'src>() -> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    primitive_parser(&CUBE).map(|fields| ParsedNode::Cube {
+        size: field(&fields, "size"),
+    })
+}
+
+fn value_node_parser<'src>()
+-> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    just("value")
+        .ignore_then(text::whitespace().ignore_then(field_value_parser()))
+        .map(ParsedNode::Value)
+}
+
+fn distribute_points_parser<'src>()
+-> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    primitive_parser(&DISTRIBUTE_POINTS).map(|fields| ParsedNode::DistributePoints {
+        density: field(&fields, "density"),
+    })
+}
+
+fn instance_on_points_parser<'src>()
+-> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    // Matched as a whole word, so names starting with "in" fail where they start
+    text::keyword("instance_on_points").to(ParsedNode::InstanceOnPoints)
+}
+
+fn position_parser<'src>() -> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>>
+{
+    just("position").to(ParsedNode::Position)
+}
+
+fn normal_parser<'src>() -> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    just("normal").to(ParsedNode::Normal)
+}
+
+// A name in double quotes after whitespace, like `"Suzanne"`; `what` names it in errors
+fn quoted_name_parser<'src>(
+    what: &'static str,
+) -> impl Parser<'src, &'src str, String, extra::Err<Rich<'src, char>>> {
+    let name = none_of('"')
+        .repeated()
+        .to_slice()
+        .delimited_by(just('"'), just('"'))
+        .try_map(move |name: &str, span| {
+            if name.trim().is_empty() {
+                Err(Rich::custom(span, format!("{what} name cannot be empty")))
+            } else {
+                Ok(name.to_string())
+            }
+        });
+    text::whitespace().ignore_then(name)
+}
+
+fn named_attribute_parser<'src>()
+-> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    just("named_attribute")
+        .ignore_then(quoted_name_parser("Attribute"))
+        .map(|name| ParsedNode::NamedAttribute { name })
+}
+
+// References an object that already exists in the scene, e.g. `object "Suzanne"`
+fn object_parser<'src>() -> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    just("object")
+        .ignore_then(quoted_name_parser("Object"))
+        .map(|name| ParsedNode::Object { name })
+}
+
+fn output_parser<'src>() -> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    just("output").to(ParsedNode::Output)
+}
+
+fn node_parser<'src>() -> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
+    choice((
+        cube_parser(),
+        sphere_parser(),
+        cylinder_parser(),
+        cone_parser(),
+        math_parser(),
+        vector_math_parser(),
+        mix_parser(),
+        noise_parser(),
+        white_noise_parser(),
+        random_parser(),
+        extrude_mesh_parser(),
+        subdivide_mesh_parser(),
+        mesh_boolean_parser(),
+        join_geometry_parser(),
+        distribute_points_parser(),
+        instance_on_points_parser(),
+        position_parser(),
+        normal_parser(),
+        named_attribute_parser(),
+        value_node_parser(),
+        object_parser(),
+        output_parser(),
+    ))
+    .padded_by(text::inline_whitespace())
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Endpoint {
+    pub(crate) node: String,
+    pub(crate) socket: String,
+    pub(crate) span: SimpleSpan,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Statement {
+    Node {
+        label: Option<String>,
+        node: Box<ParsedNode>,
+        span: SimpleSpan,
+    },
+    Connection {
+        from: Endpoint,
+        to: Endpoint,
+    },
+    // `param name: type = default`, a constant the caller can give another value
+    Param {
+        name: String,
+        kind: ParamType,
+        default: Option<ParsedValue>,
+        span: SimpleSpan,
+    },
+    // `let name = value`, or `const name = value` when `constant`
+    Let {
+        name: String,
+        value: ParsedValue,
+        constant: bool,
+        span: SimpleSpan,
+    },
+    Group {
+        name: String,
+        params: Vec<(String, Option<ParsedValue>)>,
+        body: Vec<Statement>,
+        span: SimpleSpan,
+    },
+    Call {
+        label: Option<String>,
+        name: String,
+        args: Vec<(String, ParsedValue, SimpleSpan)>,
+        span: SimpleSpan,
+    },
+    Repeat {
+        count: ParsedValue,
+        count_span: SimpleSpan,
+        variable: String,
+        body: Vec<Statement>,
+        span: SimpleSpan,
+    },
+    If {
+        condition: ParsedValue,
+        condition_span: SimpleSpan,
+        then: Vec<Statement>,
+        otherwise: Vec<Statement>,
+        span: SimpleSpan,
+    },
+    Import {
+        path: String,
+        span: SimpleSpan,
+    },
+    // The statements of an imported file, once `Loader` has read it
+    Imported {
+        file: usize,
+        body: Vec<Statement>,
+    },
+}
+
+impl Statement {
+    // Where the statement is written; imported statements are in another file
+    pub(crate) fn span(&self) -> SimpleSpan {
+        match self {
+            Statement::Connection { from, to } => (from.span.start..to.span.end).into(),
+            Statement::Node { span, .. }
+            | Statement::Param { span, .. }
+            | Statement::Let { span, .. }
+            | Statement::Group { span, .. }
+            | Statement::Call { span, .. }
+            | Statement::Repeat { span, .. }
+            | Statement::If { span, .. }
+            | Statement::Import { span, .. } => *span,
+            Statement::Imported { .. } => (0..0).into(),
+        }
+    }
+}
+
+fn separator_parser<'src>() -> impl Parser<'src, &'src str, (), extra::Err<Rich<'src, char>>> + Clone
+{
+    just(';')
+        .ignored()
+        .or(text::newline())
+        .then(text::whitespace())
+        .ignored()
+}
+
+// The rest of a statement that failed to parse: everything up to the end of its line, along
+// with any `{ ... }` it opens even when that spans lines
+fn skip_statement_parser<'src>()
+-> impl Parser<'src, &'src str, (), extra::Err<Rich<'src, char>>> + Clone {
+    let braces = recursive(|braces| {
+        just('{')
+            .then(choice((braces, none_of("{}").ignored())).repeated())
+            .then(just('}'))
+            .ignored()
+    });
+    choice((braces, none_of("\n;{}").ignored()))
+        .repeated()
+        .at_least(1)
+}
+
+// `let r = 1.5`, `ball = sphere`, `cube`, a link between sockets like `r.value -> ball.radius`,
+// a `group Name(param, param: default) { ... }` definition, a `Name(param: value)` call, a
+// `repeat 5 as i { ... }` loop, an `if lod > 1 { ... } else { ... }` condition or an
+// `import "materials.ctl"` of another file.
+//
+// A statement that doesn't parse is reported and skipped, giving None, so the statements after
+// it are still checked and one run shows every error in the file.
+fn statement_parser<'src>()
+-> impl Parser<'src, &'src str, Option<Statement>, extra::Err<Rich<'src, char>>> + Clone {
+    recursive(|statement| {
+        let block = statement
+            .clone()
+            .separated_by(separator_parser())
+            .allow_trailing()
+            .collect::<Vec<Option<Statement>>>()
+            .map(|statements| statements.into_iter().flatten().collect::<Vec<_>>())
+            .padded()
+            .delimited_by(just('{'), just('}'));
+
+        let endpoint = text::ident()
+            .then_ignore(just('.'))
+            .then(text::ident())
+            .map_with(|(node, socket): (&str, &str), e| Endpoint {
+                node: node.to_string(),
+                socket: socket.to_string(),
+                span: e.span(),
+            });
+
+        let connection = endpoint
+            .then_ignore(just("->").padded_by(text::inline_whitespace()))
+            .then(endpoint)
+            .map(|(from, to)| Statement::Connection { from, to });
+
+        let argument = text::ident()
+            .then_ignore(just(':').padded())
+            .then(field_value_parser())
+            .map_with(|(name, value): (&str, ParsedValue), e| (name.to_string(), value, e.span()));
+        let call = text::ident()
+            .then(
+                argument
+                    .padded()
+                    .separated_by(just(','))
+                    .allow_trailing()
+                    .collect::<Vec<_>>()
+                    .delimited_by(just('('), just(')')),
+            )
+            .map(|(name, args): (&str, _)| (name.to_string(), args));
+
+        let labeled = text::ident()
+            .then_ignore(just('=').padded_by(text::inline_whitespace()))
+            .or_not()
+            .map(|label: Option<&str>| label.map(str::to_string));
+        let call = labeled
+            .then(call)
+            .map_with(|(label, (name, args)), e| Statement::Call {
+                label,
+                name,
+                args,
+                span: e.span(),
+            });
+        let node = labeled
+            .then(node_parser())
+            .map_with(|(label, node), e| Statement::Node {
+                label,
+                node: Box::new(node),
+                span: e.span(),
+            });
+
+        let binding = choice((just("let").to(false), just("const").to(true)))
+            .then_ignore(text::inline_whitespace().at_least(1))
+            .then(text::ident())
+            .then_ignore(text::inline_whitespace())
+            .then_ignore(just('='))
+            .then(field_value_parser().padded_by(text::inline_whitespace()))
+            .map_with(
+                |((constant, name), value): ((bool, &str), ParsedValue), e| Statement::Let {
+                    name: name.to_string(),
+                    value,
+                    constant,
+                    span: e.span(),
+                },
+            );
+
+        let param_type = choice(ParamType::ALL.map(|kind| just(kind.name()).to(kind)));
+        let declaration = just("param")
+            .ignore_then(text::inline_whitespace().at_least(1))
+            .ignore_then(text::ident())
+            .then_ignore(just(':').padded_by(text::inline_whitespace()))
+            .then(param_type)
+            .then(
+                just('=')
+                    .padded_by(text::inline_whitespace())
+                    .ignore_then(field_value_parser())
+                    .or_not(),
+            )
+            .map_with(
+                |((name, kind), default): ((&str, _), _), e| Statement::Param {
+                    name: name.to_string(),
+                    kind,
+                    default,
+                    span: e.span(),
+                },
+            );
+
+        let param = text::ident()
+            .then(
+                just(':')
+                    .padded()
+                    .ignore_then(field_value_parser())
+                    .or_not(),
+            )
+            .map(|(name, default): (&str, _)| (name.to_string(), default));
+        let group = just("group")
+            .ignore_then(text::inline_whitespace().at_least(1))
+            .ignore_then(text::ident())
+            .then(
+                param
+                    .padded()
+                    .separated_by(just(','))
+                    .allow_trailing()
+                    .collect::<Vec<_>>()
+                    .delimited_by(just('('), just(')')),
+            )
+            .then_ignore(text::inline_whitespace())
+            .then(block.clone())
+            .map_with(
+                |((name, params), body): ((&str, _), _), e| Statement::Group {
+                    name: name.to_string(),
+                    params,
+                    body,
+                    span: e.span(),
+                },
+            );
+
+        let repeat = just("repeat")
+            .ignore_then(text::inline_whitespace().at_least(1))
+            .ignore_then(field_value_parser().map_with(|count, e| (count, e.span())))
+            .then_ignore(text::inline_whitespace().at_least(1))
+            .then_ignore(just("as"))
+            .then_ignore(text::inline_whitespace().at_least(1))
+            .then(text::ident())
+            .then_ignore(text::inline_whitespace())
+            .then(block.clone())
+            .map_with(
+                |(((count, count_span), variable), body): ((_, &str), _), e| Statement::Repeat {
+                    count,
+                    count_span,
+                    variable: variable.to_string(),
+                    body,
+                    span: e.span(),
+                },
+            );
+
+        let condition = just("if")
+            .ignore_then(text::inline_whitespace().at_least(1))
+            .ignore_then(field_value_parser().map_with(|condition, e| (condition, e.span())))
+            .then_ignore(text::inline_whitespace())
+            .then(block.clone())
+            .then(
+                text::whitespace()
+                    .ignore_then(just("else"))
+                    .ignore_then(text::inline_whitespace())
+                    .ignore_then(choice((
+                        block,
+                        just("if")
+                            .rewind()
+                            .ignore_then(statement)
+                            .map(|statement| statement.into_iter().collect()),
+                    )))
+                    .or_not(),
+            )
+            .map_with(
+                |(((condition, condition_span), then), otherwise), e| Statement::If {
+                    condition,
+                    condition_span,
+                    then,
+                    otherwise: otherwise.unwrap_or_default(),
+                    span: e.span(),
+                },
+            );
+
+        let import = just("import")
+            .ignore_then(text::inline_whitespace().at_least(1))
+            .ignore_then(
+                none_of('"')
+                    .repeated()
+                    .at_least(1)
+                    .to_slice()
+                    .delimited_by(just('"'), just('"')),
+            )
+            .map_with(|path: &str, e| Statement::Import {
+                path: path.to_string(),
+                span: e.span(),
+            });
+
+        choice((
+            group,
+            repeat,
+            condition,
+            import,
+            declaration,
+            binding,
+            connection,
+            call,
+            node,
+        ))
+        // Only a whole statement counts, so `cube { size: }` fails here instead of parsing as
+        // `cube` followed by junk
+        .then_ignore(
+            text::inline_whitespace()
+                .then(choice((one_of("\r\n;}").ignored(), end())))
+                .rewind(),
+        )
+        .map(Some)
+        .recover_with(via_parser(skip_statement_parser().to(None)))
+        .padded_by(text::inline_whitespace())
+        .boxed()
+    })
+}
+
+// Statements separated by newlines or semicolons, e.g. `cube; sphere { radius: 0.5 }`
+fn program_parser<'src>()
+-> impl Parser<'src, &'src str, Vec<Statement>, extra::Err<Rich<'src, char>>> {
+    statement_parser()
+        .separated_by(separator_parser())
+        .allow_trailing()
+        .at_least(1)
+        .collect::<Vec<_>>()
+        .map(|statements| statements.into_iter().flatten().collect())
+        .padded()
+}
+
+#[derive(Clone, Debug)]
+struct Group {
+    params: Vec<(String, Option<ParsedValue>)>,
+    body: Vec<Statement>,
+    // The file the group is defined in, which its body's spans point into
+    file: usize,
+}
+
+// Values of the `let` statements and groups seen so far
+#[derive(Clone, Default)]
+struct Scope {
+    variables: HashMap<String, Value>,
+    // Variables defined with `const`, which can't be defined again
+    constants: HashSet<String>,
+    groups: HashMap<String, Group>,
+}
+
+impl Scope {
+    fn resolve(&self, value: ParsedValue) -> Result<Value, ParseError> {
+        match value {
+            ParsedValue::Literal { value, .. } => Ok(value),
+            ParsedValue::Variable { name, span } => self
+                .variables
+                .get(&name)
+                .cloned()
+                .ok_or(ParseError::UnknownVariable { span, name }),
+            ParsedValue::Binary { op, lhs, rhs, span } => {
+                let lhs = self.resolve(*lhs)?;
+                let rhs = self.resolve(*rhs)?;
+                op.apply(&lhs, &rhs)
+                    .map_err(|message| ParseError::InvalidExpression { span, message })
+            }
+            ParsedValue::Measure { value, unit, .. } => Ok(unit.convert(&value)),
+        }
+    }
+
+    // The values of a node's fields, given in the order of `schema`, with the defaults filled in
+    // for the ones left out. `span` is the node's, for fields that are missing.
+    fn fields<const N: usize>(
+        &self,
+        schema: &NodeSchema,
+        values: [Option<ParsedValue>; N],
+        span: SimpleSpan,
+    ) -> Result<[Value; N], ParseError> {
+        let mut resolved = [const { Value::Boolean(false) }; N];
+        for ((resolved, value), field) in resolved.iter_mut().zip(values).zip(schema.fields) {
+            *resolved = match value {
+                Some(value) => {
+                    let span = value.span();
+                    match self.resolve(value)? {
+                        value if field.kind.accepts(&value) => value,
+                        other => {
+                            return Err(ParseError::InvalidFieldValue {
+                                span,
+                                field: field.name.to_string(),
+                                found: other.to_string(),
+                                expected: field.kind.expected().to_string(),
+                            });
+                        }
+                    }
+                }
+                None => field
+                    .default
+                    .clone()
+                    .ok_or_else(|| ParseError::MissingRequiredField {
+                        span,
+                        field: field.name.to_string(),
+                        node_type: schema.keyword.to_string(),
+                    })?,
+            };
+        }
+        Ok(resolved)
+    }
+}
+
+// Unnamed nodes are numbered by their position in the program, e.g. `cube_0`, `sphere_1`
+fn build_node(
+    parsed_node: ParsedNode,
+    id: impl Fn(&str) -> NodeId,
+    scope: &Scope,
+    span: SimpleSpan,
+) -> Result<Node, ParseError> {
+    let node = match parsed_node {
+        ParsedNode::Cube { size } => {
+            let [size] = scope.fields(&CUBE, [size], span)?;
+            Node::Cube {
+                id: id("cube"),
+                size,
+            }
+        }
+        ParsedNode::Sphere {
+            radius,
+            subdivisions,
+        } => {
+            let [radius, subdivisions] = scope.fields(&SPHERE, [radius, subdivisions], span)?;
+            Node::Sphere {
+                id: id("sphere"),
+                radius,
+                subdivisions,
+            }
+        }
+        ParsedNode::Cylinder {
+            vertices,
+            radius,
+            depth,
+        } => {
+            let [vertices, radius, depth] =
+                scope.fields(&CYLINDER, [vertices, radius, depth], span)?;
+            Node::Cylinder {
+                id: id("cylinder"),
+                vertices,
+                radius,
+                depth,
+            }
+        }
+        ParsedNode::Cone {
+            vertices,
+            radius_top,
+            radius_bottom,
+            depth,
+        } => {
+            let [vertices, radius_top, radius_bottom, depth] =
+                scope.fields(&CONE, [vertices, radius_top, radius_bottom, depth], span)?;
+            Node::Cone {
+                id: id("cone"),
+                vertices,
+                radius_top,
+                radius_bottom,
+                depth,
+            }
+        }
+        ParsedNode::Value(value) => Node::Value {
+            id: id("value"),
+            value: scope.resolve(value)?,
+        },
+        ParsedNode::Math {
+            operation,
+            a,
+            b,
+            clamp,
+        } => {
+            let [a, b, clamp] = scope.fields(&MATH, [a, b, clamp], span)?;
+            Node::Math {
+                id: id("math"),
+                operation,
+                a,
+                b,
+                clamp: clamp == Value::Boolean(true),
+            }
+        }
+        // A scalar stands for the same value on every axis
+        ParsedNode::VectorMath { operation, a, b } => {
+            let vector = |value: Value| match value {
+                Value::Integer(i) => Value::Vector(i as f64, i as f64, i as f64),
+                Value::Float(f) => Value::Vector(f, f, f),
+                other => other,
+            };
+            let [a, b] = scope.fields(&VECTOR_MATH, [a, b], span)?;
+            Node::VectorMath {
+                id: id("vector_math"),
+                operation,
+                a: vector(a),
+                b: vector(b),
+            }
+        }
+        ParsedNode::Mix { factor, a, b } => {
+            let [factor, a, b] = scope.fields(&MIX, [factor, a, b], span)?;
+            Node::Mix {
+                id: id("mix"),
+                factor,
+                a,
+                b,
+            }
+        }
+        ParsedNode::Noise {
+            scale,
+            detail,
+            roughness,
+            lacunarity,
+            distortion,
+        } => {
+            let [scale, detail, roughness, lacunarity, distortion] = scope.fields(
+                &NOISE,
+                [scale, detail, roughness, lacunarity, distortion],
+                span,
+            )?;
+            Node::Noise {
+                id: id("noise"),
+                scale,
+                detail,
+                roughness,
+                lacunarity,
+                distortion,
+            }
+        }
+        ParsedNode::WhiteNoise => Node::WhiteNoise {
+            id: id("white_noise"),
+        },
+        ParsedNode::Random { seed, min, max } => {
+            let [seed, min, max] = scope.fields(&RANDOM, [seed, min, max], span)?;
+            Node::Random {
+                id: id("random"),
+                seed,
+                min,
+                max,
+            }
+        }
+        ParsedNode::ExtrudeMesh {
+            offset_scale,
+            individual,
+        } => {
+            let [offset_scale, individual] =
+                scope.fields(&EXTRUDE_MESH, [offset_scale, individual], span)?;
+            Node::ExtrudeMesh {
+                id: id("extrude_mesh"),
+                offset_scale,
+                individual: individual == Value::Boolean(true),
+            }
+        }
+        ParsedNode::SubdivideMesh { level } => {
+            let [level] = scope.fields(&SUBDIVIDE_MESH, [level], span)?;
+            Node::SubdivideMesh {
+                id: id("subdivide_mesh"),
+                level,
+            }
+        }
+        ParsedNode::MeshBoolean { operation } => Node::MeshBoolean {
+            id: id("mesh_boolean"),
+            operation,
+        },
+        ParsedNode::JoinGeometry => Node::JoinGeometry {
+            id: id("join_geometry"),
+        },
+        ParsedNode::DistributePoints { density } => {
+            let [density] = scope.fields(&DISTRIBUTE_POINTS, [density], span)?;
+            Node::DistributePoints {
+                id: id("distribute_points"),
+                density,
+            }
+        }
+        ParsedNode::InstanceOnPoints => Node::InstanceOnPoints {
+            id: id("instance_on_points"),
+        },
+        ParsedNode::Position => Node::Position { id: id("position") },
+        ParsedNode::Normal => Node::Normal { id: id("normal") },
+        ParsedNode::NamedAttribute { name } => Node::NamedAttribute {
+            id: id("named_attribute"),
+            name,
+        },
+        ParsedNode::Object { name } => Node::Object {
+            id: id("object"),
+            name,
+        },
+        ParsedNode::Output => Node::Output { id: id("output") },
+    };
+    Ok(node)
+}
+
+// Sockets are written in snake case, so `radius_top` finds Blender's "Radius Top" and
+// `value_001` finds "Value_001"
+fn find_socket<'a>(sockets: &'a [BlenderSocket], written: &str) -> Option<&'a str> {
+    let written = written.replace('_', " ");
+    sockets
+        .iter()
+        .find(|socket| socket.name.replace('_', " ").eq_ignore_ascii_case(&written))
+        .map(|socket| socket.name.as_str())
+}
+
+fn resolve_socket(
+    node: Option<&Node>,
+    endpoint: &Endpoint,
+    output: bool,
+) -> Result<(NodeId, String), ParseError> {
+    let node = node.ok_or_else(|| ParseError::UnknownNode {
+        span: endpoint.span,
+        name: endpoint.node.clone(),
+    })?;
+    let blender_node = BlenderNode::from(node.clone());
+    let sockets = if output {
+        &blender_node.outputs
+    } else {
+        &blender_node.inputs
+    };
+    let socket =
+        find_socket(sockets, &endpoint.socket).ok_or_else(|| ParseError::UnknownSocket {
+            span: endpoint.span,
+            node: endpoint.node.clone(),
+            socket: endpoint.socket.clone(),
+            available: sockets.iter().map(|s| s.name.clone()).collect(),
+        })?;
+    Ok((node.id().clone(), socket.to_string()))
+}
+
+// Names declared in a group body are prefixed with the name of the call, and names declared in a
+// repeat body get the iteration as a suffix, so expanding a body twice doesn't create the same
+// nodes twice. Other names are left alone.
+#[derive(Default)]
+struct Naming {
+    prefix: String,
+    names: HashMap<String, String>,
+}
+
+// Labels declared in `body`, including those in nested repeats which share its names
+fn labels(body: &[Statement]) -> Vec<&str> {
+    body.iter()
+        .flat_map(|statement| match statement {
+            Statement::Node {
+                label: Some(label), ..
+            }
+            | Statement::Call {
+                label: Some(label), ..
+            } => vec![label.as_str()],
+            Statement::Repeat { body, .. } | Statement::Imported { body, .. } => labels(body),
+            Statement::If {
+                then, otherwise, ..
+            } => labels(then).into_iter().chain(labels(otherwise)).collect(),
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+impl Naming {
+    fn group(instance: &str, body: &[Statement]) -> Self {
+        let names = labels(body)
+            .into_iter()
+            .map(|label| (label.to_string(), format!("{instance}_{label}")))
+            .collect();
+        Self {
+            prefix: format!("{instance}_"),
+            names,
+        }
+    }
+
+    fn iteration(&self, index: i64, body: &[Statement]) -> Self {
+        let mut names = self.names.clone();
+        for label in labels(body) {
+            names.insert(label.to_string(), format!("{}_{index}", self.name(label)));
+        }
+        Self {
+            prefix: self.prefix.clone(),
+            names,
+        }
+    }
+
+    fn name(&self, name: &str) -> String {
+        self.names
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    fn generated(&self, id: String) -> String {
+        format!("{}{id}", self.prefix)
+    }
+
+    fn endpoint(&self, endpoint: Endpoint) -> Endpoint {
+        Endpoint {
+            node: self.name(&endpoint.node),
+            ..endpoint
+        }
+    }
+}
+
+#[derive(Default)]
+struct Builder {
+    graph: NodeGraph,
+    // The position of each node in `graph` by id, so large graphs don't search it for every node
+    index: HashMap<NodeId, usize>,
+    links: Vec<(usize, Endpoint, Endpoint)>,
+    // Errors with the index of the file they are in, 0 unless there are imports
+    errors: Vec<(usize, ParseError)>,
+    file: usize,
+    // Groups being expanded, to stop a group from calling itself forever
+    calls: Vec<String>,
+    spans: SourceMap,
+    // Values given by the caller for `param` statements, and the params declared so far
+    params: HashMap<String, Value>,
+    declared: HashSet<String>,
+    // Loop iterations run so far, and whether expansion was stopped at the limit
+    iterations: usize,
+    expansion_stopped: bool,
+}
+
+// Loops past this are almost always a mistake, and expanding them would hang the editors, which
+// parse on every keystroke
+const MAX_EXPANSION: usize = 100_000;
+
+impl Builder {
+    // Variables have to be defined before they are used, but groups can be called anywhere in the
+    // block that defines them
+    fn block(&mut self, statements: Vec<Statement>, scope: &mut Scope, naming: &Naming) {
+        for statement in &statements {
+            if let Statement::Group {
+                name, params, body, ..
+            } = statement
+            {
+                let group = Group {
+                    params: params.clone(),
+                    body: body.clone(),
+                    file: self.file,
+                };
+                scope.groups.insert(name.clone(), group);
+            }
+        }
+
+        for statement in statements {
+            match statement {
+                Statement::Param {
+                    name,
+                    kind,
+                    default,
+                    span,
+                } => {
+                    if scope.constants.contains(&name) {
+                        self.error(ParseError::ConstantRedefined { span, name });
+                        continue;
+                    }
+                    self.declared.insert(name.clone());
+                    let value = match (self.params.get(&name), default) {
+                        (Some(value), _) => Ok((value.clone(), span)),
+                        (None, Some(default)) => {
+                            let span = default.span();
+                            scope.resolve(default).map(|value| (value, span))
+                        }
+                        (None, None) => Err(ParseError::MissingParameter {
+                            span,
+                            name: name.clone(),
+                        }),
+                    };
+                    let value = value.and_then(|(value, span)| {
+                        kind.check(value)
+                            .map_err(|other| ParseError::InvalidFieldValue {
+                                span,
+                                field: name.clone(),
+                                found: other.to_string(),
+                                expected: kind.expected().to_string(),
+                            })
+                    });
+                    match value {
+                        Ok(value) => {
+                            scope.variables.insert(name.clone(), value);
+                            scope.constants.insert(name);
+                        }
+                        Err(error) => self.error(error),
+                    }
+                }
+                Statement::Let {
+                    name,
+                    value,
+                    constant,
+                    span,
+                } => {
+                    if scope.constants.contains(&name) {
+                        self.error(ParseError::ConstantRedefined { span, name });
+                        continue;
+                    }
+                    match scope.resolve(value) {
+                        Ok(value) => {
+                            scope.variables.insert(name.clone(), value);
+                            if constant {
+                                scope.constants.insert(name);
+                            }
+                        }
+                        Err(error) => self.error(error),
+                    }
+                }
+                Statement::Node { label, node, span } => {
+                    let index = self.graph.nodes.len();
+                    let id = |kind: &str| {
+                        NodeId(match &label {
+                            Some(label) => naming.name(label),
+                            None => naming.generated(format!("{kind}_{index}")),
+                        })
+                    };
+                    match build_node(*node, id, scope, span) {
+                        Ok(node) => self.add_node(node, span),
+                        Err(error) => self.error(error),
+                    }
+                }
+                Statement::Connection { from, to } => {
+                    self.links
+                        .push((self.file, naming.endpoint(from), naming.endpoint(to)))
+                }
+                Statement::Group { .. } => {}
+                Statement::Import { path, span } => self.error(ParseError::ImportFailed {
+                    span,
+                    path,
+                    message: "imports are only resolved when parsing a file".to_string(),
+                }),
+                // Imported statements share the enclosing block's names and variables
+                Statement::Imported { file, body } => {
+                    let outer = std::mem::replace(&mut self.file, file);
+                    self.block(body, scope, naming);
+                    self.file = outer;
+                }
+                Statement::Repeat {
+                    count,
+                    count_span,
+                    variable,
+                    body,
+                    ..
+                } => self.repeat(count, count_span, variable, body, scope, naming),
+                // The branch taken is part of the enclosing block, so its variables stay visible
+                Statement::If {
+                    condition,
+                    condition_span,
+                    then,
+                    otherwise,
+                    ..
+                } => match scope.resolve(condition) {
+                    Ok(Value::Boolean(condition)) => {
+                        let branch = if condition { then } else { otherwise };
+                        self.block(branch, scope, naming);
+                    }
+                    Ok(other) => self.error(ParseError::InvalidFieldValue {
+                        span: condition_span,
+                        field: "if".to_string(),
+                        found: other.to_string(),
+                        expected: "boolean".to_string(),
+                    }),
+                    Err(error) => self.error(error),
+                },
+                Statement::Call {
+                    label,
+                    name,
+                    args,
+                    span,
+                } => {
+                    let instance = match &label {
+                        Some(label) => naming.name(label),
+                        None => naming.generated(format!("{name}_{}", self.graph.nodes.len())),
+                    };
+                    self.call(&instance, name, args, span, scope);
+                }
+            }
+        }
+    }
+
+    // Every iteration sees the loop variable on top of the enclosing scope, starting from 0
+    fn repeat(
+        &mut self,
+        count: ParsedValue,
+        span: SimpleSpan,
+        variable: String,
+        body: Vec<Statement>,
+        scope: &Scope,
+        naming: &Naming,
+    ) {
+        let count = match scope.resolve(count) {
+            Ok(Value::Integer(count)) if count >= 0 => count,
+            Ok(other) => {
+                self.error(ParseError::InvalidFieldValue {
+                    span,
+                    field: "repeat".to_string(),
+                    found: other.to_string(),
+                    expected: "non-negative integer".to_string(),
+                });
+                return;
+            }
+            Err(error) => {
+                self.error(error);
+                return;
+            }
+        };
+
+        for index in 0..count {
+            // Iterations count as well as nodes, so nested loops that make nothing still end
+            self.iterations += 1;
+            if self.expansion_stopped {
+                return;
+            }
+            if self.graph.nodes.len() >= MAX_EXPANSION || self.iterations > MAX_EXPANSION {
+                self.expansion_stopped = true;
+                self.error(ParseError::ExpansionLimit {
+                    span,
+                    limit: MAX_EXPANSION,
+                });
+                return;
+            }
+            let mut inner = scope.clone();
+            inner
+                .variables
+                .insert(variable.clone(), Value::Integer(index));
+            self.block(body.clone(), &mut inner, &naming.iteration(index, &body));
+        }
+    }
+
+    fn error(&mut self, error: ParseError) {
+        self.errors.push((self.file, error));
+    }
+
+    fn add_node(&mut self, node: Node, span: SimpleSpan) {
+        if self.index.contains_key(node.id()) {
+            self.error(ParseError::DuplicateNode {
+                span,
+                name: node.id().0.clone(),
+            });
+        } else {
+            self.index.insert(node.id().clone(), self.graph.nodes.len());
+            self.graph.add_node(node);
+            self.spans.nodes.push(span);
+        }
+    }
+
+    // Arguments and defaults are evaluated where the group is called, and the body sees the
+    // caller's variables with the parameters on top
+    fn call(
+        &mut self,
+        instance: &str,
+        name: String,
+        args: Vec<(String, ParsedValue, SimpleSpan)>,
+        span: SimpleSpan,
+        scope: &Scope,
+    ) {
+        let Some(group) = scope.groups.get(&name).cloned() else {
+            self.error(ParseError::UnknownGroup { span, name });
+            return;
+        };
+        if self.calls.contains(&name) {
+            self.error(ParseError::RecursiveGroup { span, name });
+            return;
+        }
+
+        let mut inner = scope.clone();
+        let mut given = HashSet::new();
+        for (arg, value, arg_span) in args {
+            if !group.params.iter().any(|(param, _)| *param == arg) {
+                self.error(ParseError::UnknownArgument {
+                    span: arg_span,
+                    group: name.clone(),
+                    name: arg,
+                });
+                continue;
+            }
+            match scope.resolve(value) {
+                Ok(value) => {
+                    inner.variables.insert(arg.clone(), value);
+                }
+                Err(error) => self.error(error),
+            }
+            given.insert(arg);
+        }
+        for (param, default) in &group.params {
+            if given.contains(param) {
+                continue;
+            }
+            match default {
+                Some(default) => match scope.resolve(default.clone()) {
+                    Ok(value) => {
+                        inner.variables.insert(param.clone(), value);
+                    }
+                    Err(error) => self.error(error),
+                },
+                None => self.error(ParseError::MissingRequiredField {
+                    span,
+                    field: param.clone(),
+                    node_type: name.clone(),
+                }),
+            }
+        }
+
+        let naming = Naming::group(instance, &group.body);
+        self.calls.push(name);
+        let outer = std::mem::replace(&mut self.file, group.file);
+        self.block(group.body, &mut inner, &naming);
+        self.file = outer;
+        self.calls.pop();
+    }
+
+    fn node(&self, name: &str) -> Option<&Node> {
+        let index = self.index.get(&NodeId(name.to_string()))?;
+        self.graph.nodes.get(*index)
+    }
+
+    // Connections are resolved once every node exists, so they may refer to nodes declared later
+    fn connect(&mut self) {
+        // Params given by the caller that no `param` statement declares, most likely misspelled
+        let mut unknown = self
+            .params
+            .keys()
+            .filter(|name| !self.declared.contains(*name))
+            .cloned()
+            .collect::<Vec<_>>();
+        unknown.sort();
+        for name in unknown {
+            self.error(ParseError::UnknownParameter {
+                span: (0..0).into(),
+                name,
+            });
+        }
+        for (file, from, to) in std::mem::take(&mut self.links) {
+            match (
+                resolve_socket(self.node(&from.node), &from, true),
+                resolve_socket(self.node(&to.node), &to, false),
+            ) {
+                (Ok((from_node, from_output)), Ok((to_node, to_input))) => {
+                    self.graph.add_connection(Connection {
+                        from_node,
+                        from_output,
+                        to_node,
+                        to_input,
+                    });
+                    self.spans
+                        .connections
+                        .push((from.span.start..to.span.end).into());
+                }
+                (from, to) => self
+                    .errors
+                    .extend(from.err().into_iter().chain(to.err()).map(|e| (file, e))),
+            }
+        }
+    }
+
+    fn finish(mut self) -> Result<(NodeGraph, SourceMap), Vec<(usize, ParseError)>> {
+        self.connect();
+        if self.errors.is_empty() {
+            Ok((self.graph, self.spans))
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+fn build_graph(
+    statements: Vec<Statement>,
+    variables: &HashMap<String, Value>,
+    params: &HashMap<String, Value>,
+) -> ParseResult<(NodeGraph, SourceMap)> {
+    let mut scope = Scope {
+        variables: variables.clone(),
+        ..Default::default()
+    };
+    let mut builder = Builder {
+        params: params.clone(),
+        ..Default::default()
+    };
+    builder.block(statements, &mut scope, &Naming::default());
+    builder
+        .finish()
+        .map_err(|errors| errors.into_iter().map(|(_, error)| error).collect())
+}
+
+// Reads a file and everything it imports. Each file is read once, so importing the same file
+// from two places doesn't define its nodes twice.
+#[derive(Default)]
+struct Loader {
+    sources: Vec<SourceFile>,
+    errors: Vec<(usize, ParseError)>,
+    // Files being read, innermost last, as canonical paths and as written
+    stack: Vec<(PathBuf, String)>,
+    loaded: HashSet<PathBuf>,
+    // Source not saved to disk yet, like an editor's open buffer, by canonical path
+    unsaved: HashMap<PathBuf, String>,
+}
+
+impl Loader {
+    // The index of the file and its statements with imports resolved, None when there is nothing
+    // to add, either because of an error or because the file was already read
+    fn load(
+        &mut self,
+        path: &Path,
+        importer: Option<(usize, SimpleSpan)>,
+    ) -> Option<(usize, Vec<Statement>)> {
+        let key = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let shown = path.display().to_string();
+        let (importer, span) = importer.unwrap_or((0, (0..0).into()));
+
+        if let Some(start) = self.stack.iter().position(|(open, _)| *open == key) {
+            let mut cycle: Vec<_> = self.stack[start..]
+                .iter()
+                .map(|(_, shown)| shown.clone())
+                .collect();
+            cycle.push(shown);
+            self.errors
+                .push((importer, ParseError::ImportCycle { span, cycle }));
+            return None;
+        }
+        if !self.loaded.insert(key.clone()) {
+            return None;
+        }
+
+        let source = match self
+            .unsaved
+            .remove(&key)
+            .map_or_else(|| std::fs::read_to_string(path), Ok)
+        {
+            Ok(source) => source,
+            Err(e) => {
+                // The first file has nothing importing it, so its error is reported against
+                // itself
+                if self.sources.is_empty() {
+                    self.sources.push(SourceFile {
+                        path: path.to_path_buf(),
+                        source: String::new(),
+                    });
+                }
+                let error = ParseError::ImportFailed {
+                    span,
+                    path: shown,
+                    message: e.to_string(),
+                };
+                self.errors.push((importer, error));
+                return None;
+            }
+        };
+
+        let file = self.sources.len();
+        let statements = parse_statements(&source);
+        self.sources.push(SourceFile {
+            path: path.to_path_buf(),
+            source,
+        });
+        match statements {
+            Ok(statements) => {
+                let base = path.parent().unwrap_or(Path::new(""));
+                self.stack.push((key, shown));
+                let statements = self.resolve(file, statements, base);
+                self.stack.pop();
+                Some((file, statements))
+            }
+            Err(errors) => {
+                self.errors
+                    .extend(errors.into_iter().map(|error| (file, error)));
+                None
+            }
+        }
+    }
+
+    // Import paths are relative to the importing file
+    fn resolve(&mut self, file: usize, statements: Vec<Statement>, base: &Path) -> Vec<Statement> {
+        statements
+            .into_iter()
+            .map(|statement| match statement {
+                Statement::Import { path, span } => {
+                    match self.load(&base.join(path), Some((file, span))) {
+                        Some((file, body)) => Statement::Imported { file, body },
+                        None => Statement::Imported {
+                            file,
+                            body: Vec::new(),
+                        },
+                    }
+                }
+                mut statement => {
+                    match &mut statement {
+                        Statement::Group { body, .. } | Statement::Repeat { body, .. } => {
+                            *body = self.resolve(file, std::mem::take(body), base);
+                        }
+                        Statement::If {
+                            then, otherwise, ..
+                        } => {
+                            *then = self.resolve(file, std::mem::take(then), base);
+                            *otherwise = self.resolve(file, std::mem::take(otherwise), base);
+                        }
+                        _ => {}
+                    }
+                    statement
+                }
+            })
+            .collect()
+    }
+}
+
+/// A comment in DSL source: `# ...` or `// ...` to the end of the line, or `/* ... */`.
+///
+/// The parser skips comments, but tooling like formatters and editors can find them here.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub span: Range<usize>,
+    /// The comment including its delimiters
+    pub text: String,
+}
+
+/// Every comment in `input`, in source order.
+pub fn parse_comments(input: &str) -> ParseResult<Vec<Comment>> {
+    let mut comments = Vec::new();
+    let mut rest = 0;
+    while let Some(offset) = input[rest..].find(['"', '#', '/']) {
+        let start = rest + offset;
+        let after = &input[start + 1..];
+        let end = match input.as_bytes()[start] {
+            // Object and attribute names may contain comment markers
+            b'"' => {
+                rest = after.find('"').map_or(input.len(), |end| start + end + 2);
+                continue;
+            }
+            b'#' => start + 1 + after.find(['\n', '\r']).unwrap_or(after.len()),
+            _ if after.starts_with('/') => {
+                start + 1 + after.find(['\n', '\r']).unwrap_or(after.len())
+            }
+            _ if after.starts_with('*') => match after[1..].find("*/") {
+                Some(end) => start + end + 4,
+                None => {
+                    return Err(vec![ParseError::UnterminatedComment {
+                        span: (start..input.len()).into(),
+                    }]);
+                }
+            },
+            // Division
+            _ => {
+                rest = start + 1;
+                continue;
+            }
+        };
+        comments.push(Comment {
+            span: start..end,
+            text: input[start..end].to_string(),
+        });
+        rest = end;
+    }
+    Ok(comments)
+}
+
+pub fn parse_geometry_nodes(input: &str) -> ParseResult<NodeGraph> {
+    parse_geometry_nodes_with_variables(input, &HashMap::new())
+}
+
+/// Parse `input` with `variables` already defined, as if by `let` statements before the first
+/// line. This is how one file describes variant scenes, e.g. `if lod > 1 { ... }`.
+pub fn parse_geometry_nodes_with_variables(
+    input: &str,
+    variables: &HashMap<String, Value>,
+) -> ParseResult<NodeGraph> {
+    build_graph(parse_statements(input)?, variables, &HashMap::new()).map(|(graph, _)| graph)
+}
+
+/// Parse `input` with `params` as the values of its `param` statements, in place of their
+/// defaults. One file can then be built with many values, e.g. `param radius: float = 1.0`
+/// swept over several radii. Giving a value for a param `input` doesn't declare is an error.
+pub fn parse_geometry_nodes_with_params(
+    input: &str,
+    params: &HashMap<String, Value>,
+) -> ParseResult<NodeGraph> {
+    build_graph(parse_statements(input)?, &HashMap::new(), params).map(|(graph, _)| graph)
+}
+
+/// Parse `input` along with where each node and connection was written, for reporting errors
+/// found later, like those from [`crate::validate`], against the source.
+pub fn parse_geometry_nodes_with_spans(input: &str) -> ParseResult<(NodeGraph, SourceMap)> {
+    build_graph(parse_statements(input)?, &HashMap::new(), &HashMap::new())
+}
+
+/// Parse the file at `path`, with `import "other.ctl"` statements resolved relative to the file
+/// that contains them. Imported files share one namespace, so their variables, groups and nodes
+/// can be used by the importing file after the import.
+pub fn parse_geometry_nodes_file(
+    path: impl AsRef<Path>,
+    variables: &HashMap<String, Value>,
+) -> Result<NodeGraph, SourceErrors> {
+    load_graph(Loader::default(), path.as_ref(), variables, &HashMap::new())
+}
+
+/// Like `parse_geometry_nodes_file`, with `params` as the values of `param` statements in the
+/// file and its imports, as in [`parse_geometry_nodes_with_params`].
+pub fn parse_geometry_nodes_file_with_params(
+    path: impl AsRef<Path>,
+    params: &HashMap<String, Value>,
+) -> Result<NodeGraph, SourceErrors> {
+    load_graph(Loader::default(), path.as_ref(), &HashMap::new(), params)
+}
+
+/// Like `parse_geometry_nodes_file`, with `source` used as the content of the file at `path`
+/// instead of what is on disk. Editors use this for buffers with unsaved changes; imports are
+/// still read from disk.
+pub fn parse_geometry_nodes_source(
+    path: impl AsRef<Path>,
+    source: &str,
+    variables: &HashMap<String, Value>,
+) -> Result<NodeGraph, SourceErrors> {
+    let path = path.as_ref();
+    let key = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let loader = Loader {
+        unsaved: HashMap::from([(key, source.to_string())]),
+        ..Default::default()
+    };
+    load_graph(loader, path, variables, &HashMap::new())
+}
+
+fn load_graph(
+    mut loader: Loader,
+    path: &Path,
+    variables: &HashMap<String, Value>,
+    params: &HashMap<String, Value>,
+) -> Result<NodeGraph, SourceErrors> {
+    let statements = loader.load(path, None);
+    if !loader.errors.is_empty() {
+        return Err(SourceErrors {
+            sources: loader.sources,
+            errors: loader.errors,
+        });
+    }
+
+    let mut scope = Scope {
+        variables: variables.clone(),
+        ..Default::default()
+    };
+    let mut builder = Builder {
+        params: params.clone(),
+        ..Default::default()
+    };
+    if let Some((file, statements)) = statements {
+        builder.file = file;
+        builder.block(statements, &mut scope, &Naming::default());
+    }
+    builder
+        .finish()
+        .map(|(graph, _)| graph)
+        .map_err(|errors| SourceErrors {
+            sources: loader.sources,
+            errors,
+        })
+}
+
+/// A node or connection of a graph, as yielded by [`parse_nodes_iter`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphItem {
+    Node(Node),
+    Connection(Connection),
+}
+
+/// The nodes of a source as its statements are parsed, from [`parse_nodes_iter`].
+pub struct NodeStream {
+    // The source with comments blanked out
+    source: String,
+    position: usize,
+    // Errors the builder finds carry the offset of the statement they are in, where they would
+    // otherwise carry the file; see `advance`
+    builder: Builder,
+    scope: Scope,
+    ready: VecDeque<Result<GraphItem, ParseError>>,
+    connected: bool,
+}
+
+impl NodeStream {
+    // Parses and builds the next top-level statement, false once there are none left. The
+    // statement is parsed on its own, so its spans start from its first byte; the builder's
+    // `file` is set to that offset for its errors to be moved back into place.
+    fn advance(&mut self) -> bool {
+        let rest = &self.source[self.position..];
+        let start = self.position + rest.len()
+            - rest
+                .trim_start_matches(|c: char| c.is_whitespace() || c == ';')
+                .len();
+        if start == self.source.len() {
+            return false;
+        }
+        let end = statement_end(&self.source, start);
+        self.position = end;
+
+        match parse_program(self.source[start..end].trim_end()) {
+            Ok(statements) => {
+                let built = self.builder.graph.nodes.len();
+                self.builder.file = start;
+                self.builder
+                    .block(statements, &mut self.scope, &Naming::default());
+                let nodes = self.builder.graph.nodes[built..].iter().cloned();
+                self.ready
+                    .extend(nodes.map(|node| Ok(GraphItem::Node(node))));
             }
-        });
+            Err(errors) => {
+                let errors = errors.into_iter().map(|error| Err(error.offset(start)));
+                self.ready.extend(errors);
+            }
+        }
+        self.take_errors();
+        true
+    }
 
-    choice((float, integer, boolean, vector, color))
+    fn take_errors(&mut self) {
+        let errors = self.builder.errors.drain(..);
+        let errors = errors.map(|(offset, error)| Err(error.offset(offset)));
+        self.ready.extend(errors);
+    }
 }
 
-fn cube_parser<'src>() -> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
-    let with_braces = just("cube")
-        .ignore_then(just('{').padded())
-        .ignore_then(just("size:").padded().ignore_then(value_parser()))
-        .then_ignore(just('}').padded())
-        .map(|size| ParsedNode::Cube { size: Some(size) });
+impl Iterator for NodeStream {
+    type Item = Result<GraphItem, ParseError>;
 
-    let without_braces = just("cube").map(|_| ParsedNode::Cube { size: None });
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.ready.is_empty() {
+            if self.advance() {
+                continue;
+            }
+            if self.connected {
+                return None;
+            }
+            self.connected = true;
+            self.builder.connect();
+            let connections = std::mem::take(&mut self.builder.graph.connections);
+            self.ready.extend(
+                connections
+                    .into_iter()
+                    .map(|connection| Ok(GraphItem::Connection(connection))),
+            );
+            self.take_errors();
+        }
+        self.ready.pop_front()
+    }
+}
 
-    choice((with_braces, without_braces))
+// Where the top-level statement starting at `start` ends: at the first newline or semicolon
+// outside of braces, parentheses and quotes, unless the next line continues an `if` with `else`
+fn statement_end(source: &str, start: usize) -> usize {
+    let mut depth = 0usize;
+    let mut quoted = false;
+    for (index, byte) in source.bytes().enumerate().skip(start) {
+        match byte {
+            b'"' => quoted = !quoted,
+            _ if quoted => {}
+            b'{' | b'(' => depth += 1,
+            b'}' | b')' => depth = depth.saturating_sub(1),
+            b';' if depth == 0 => return index,
+            b'\n' | b'\r' if depth == 0 => {
+                let next = source[index..].trim_start();
+                let is_else = next.strip_prefix("else").is_some_and(|after| {
+                    !after.starts_with(|c: char| c.is_alphanumeric() || c == '_')
+                });
+                if !is_else {
+                    return index;
+                }
+            }
+            _ => {}
+        }
+    }
+    source.len()
 }
 
-fn value_node_parser<'src>()
--> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
-    just("value")
-        .ignore_then(value_parser().padded())
-        .map(ParsedNode::Value)
+/// Parse `input` one top-level statement at a time, yielding each node as soon as its statement
+/// is built rather than once the whole file has parsed. Errors are yielded as they are found, and
+/// the statements after them are still read. Connections can name nodes declared further down, so
+/// they come after every node.
+///
+/// This is for generated files with tens of thousands of nodes, so validation can start before
+/// the whole file has parsed. Only one statement's syntax is held at a time, but memory still
+/// grows with the file: the source is kept, as is every node built, to resolve connections
+/// against. Unlike [`parse_geometry_nodes`], a group has to be defined before a top-level
+/// statement calls it.
+pub fn parse_nodes_iter(input: &str) -> NodeStream {
+    let (source, ready) = match blank_comments(input) {
+        Ok(source) => (source, VecDeque::new()),
+        Err(errors) => (String::new(), errors.into_iter().map(Err).collect()),
+    };
+    NodeStream {
+        source,
+        position: 0,
+        builder: Builder::default(),
+        scope: Scope::default(),
+        ready,
+        connected: false,
+    }
 }
 
-fn node_parser<'src>() -> impl Parser<'src, &'src str, ParsedNode, extra::Err<Rich<'src, char>>> {
-    choice((cube_parser(), value_node_parser())).padded()
+// Comments become spaces of the same length, so every span still points into `input`
+fn blank_comments(input: &str) -> ParseResult<String> {
+    let mut source = input.to_string();
+    for comment in parse_comments(input)? {
+        let blank = " ".repeat(comment.span.len());
+        source.replace_range(comment.span, &blank);
+    }
+    Ok(source)
 }
 
-pub fn parse_geometry_nodes(input: &str) -> ParseResult<NodeGraph> {
-    let parser = node_parser().then_ignore(end());
+pub(crate) fn parse_statements(input: &str) -> ParseResult<Vec<Statement>> {
+    parse_program(&blank_comments(input)?)
+}
+
+// Parses source that has had its comments blanked out
+fn parse_program(source: &str) -> ParseResult<Vec<Statement>> {
+    let parser = program_parser().then_ignore(end());
 
-    let (parsed_node, errors) = parser.parse(input).into_output_errors();
+    let (statements, errors) = parser.parse(source).into_output_errors();
 
     if !errors.is_empty() {
         let parse_errors = errors
             .into_iter()
-            .map(ParseError::from_rich)
+            .map(|error| {
+                number_error(&error, source).unwrap_or_else(|| ParseError::from_rich(error))
+            })
             .collect::<Vec<_>>();
         return Err(parse_errors);
     }
 
-    if let Some(parsed_node) = parsed_node {
-        let mut graph = NodeGraph::new();
-        let node_counter = 0;
-
-        let node = match parsed_node {
-            ParsedNode::Cube { size } => {
-                let size_value = size.unwrap_or(Value::Float(2.0));
-                Node::Cube {
-                    id: NodeId(format!("cube_{node_counter}")),
-                    size: size_value,
-                }
-            }
-            ParsedNode::Value(value) => Node::Value {
-                id: NodeId(format!("value_{node_counter}")),
-                value,
-            },
-        };
-
-        graph.add_node(node);
-        Ok(graph)
+    if let Some(statements) = statements {
+        Ok(statements)
     } else {
         Err(vec![ParseError::UnexpectedEndOfInput {
-            span: (0..input.len()).into(),
-            expected: vec!["cube".to_string(), "value".to_string()],
+            span: (0..source.len()).into(),
+            expected: vec![
+                "cube".to_string(),
+                "sphere".to_string(),
+                "cylinder".to_string(),
+                "cone".to_string(),
+                "math".to_string(),
+                "vector_math".to_string(),
+                "mix".to_string(),
+                "noise".to_string(),
+                "white_noise".to_string(),
+                "random".to_string(),
+                "extrude_mesh".to_string(),
+                "subdivide_mesh".to_string(),
+                "mesh_boolean".to_string(),
+                "join_geometry".to_string(),
+                "distribute_points".to_string(),
+                "instance_on_points".to_string(),
+                "position".to_string(),
+                "normal".to_string(),
+                "named_attribute".to_string(),
+                "value".to_string(),
+                "object".to_string(),
+                "output".to_string(),
+            ],
         }])
     }
 }
 
+pub fn parse_geometry_nodes_file_with_errors(path: impl AsRef<Path>) -> Result<NodeGraph, String> {
+    parse_geometry_nodes_file(path, &HashMap::new())
+        .map_err(|errors| ErrorReporter::new().report_sources(&errors))
+}
+
 pub fn parse_geometry_nodes_with_errors(input: &str) -> Result<NodeGraph, String> {
     match parse_geometry_nodes(input) {
         Ok(graph) => Ok(graph),
@@ -178,6 +2669,659 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_sphere() {
+        let graph = parse_geometry_nodes("sphere { radius: 1.5, subdivisions: 8 }")
+            .expect("Failed to parse sphere");
+        assert_eq!(graph.nodes.len(), 1);
+        match &graph.nodes[0] {
+            Node::Sphere {
+                radius,
+                subdivisions,
+                ..
+            } => {
+                assert_eq!(radius, &Value::Float(1.5));
+                assert_eq!(subdivisions, &Value::Integer(8));
+            }
+            _ => panic!("Expected Sphere node"),
+        }
+    }
+
+    #[test]
+    fn parse_sphere_defaults() {
+        for input in ["sphere", "sphere { subdivisions: 8 }", "sphere {}"] {
+            let graph = parse_geometry_nodes(input).expect("Failed to parse sphere");
+            match &graph.nodes[0] {
+                Node::Sphere {
+                    radius,
+                    subdivisions,
+                    ..
+                } => {
+                    assert_eq!(radius, &Value::Float(1.0));
+                    assert_eq!(subdivisions, &Value::Integer(8));
+                }
+                _ => panic!("Expected Sphere node"),
+            }
+        }
+
+        assert!(parse_geometry_nodes("sphere { subdivisions: 2.5 }").is_err());
+        assert!(parse_geometry_nodes("sphere { size: 1.0 }").is_err());
+    }
+
+    #[test]
+    fn parse_cylinder_and_cone() {
+        let graph = parse_geometry_nodes("cylinder { vertices: 12, depth: 4.0 }")
+            .expect("Failed to parse cylinder");
+        assert_eq!(
+            graph.nodes[0],
+            Node::Cylinder {
+                id: NodeId("cylinder_0".to_string()),
+                vertices: Value::Integer(12),
+                radius: Value::Float(1.0),
+                depth: Value::Float(4.0),
+            }
+        );
+
+        let graph = parse_geometry_nodes("cone { radius_top: 0.5, radius_bottom: 1.5 }")
+            .expect("Failed to parse cone");
+        assert_eq!(
+            graph.nodes[0],
+            Node::Cone {
+                id: NodeId("cone_0".to_string()),
+                vertices: Value::Integer(32),
+                radius_top: Value::Float(0.5),
+                radius_bottom: Value::Float(1.5),
+                depth: Value::Float(2.0),
+            }
+        );
+
+        assert!(parse_geometry_nodes("cylinder { vertices: 3.5 }").is_err());
+        assert!(parse_geometry_nodes("cone { radius: 1.0 }").is_err());
+    }
+
+    #[test]
+    fn parse_multiple_nodes() {
+        let input = "cube { size: 1.0 }\nsphere\n\nvalue 3; object \"Suzanne\"\n";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse program");
+        let ids = graph
+            .nodes
+            .iter()
+            .map(|node| node.id().0.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["cube_0", "sphere_1", "value_2", "object_3"]);
+
+        let graph = parse_geometry_nodes("sphere {\n  radius: 2.0,\n  subdivisions: 4\n}\ncone")
+            .expect("Failed to parse multi-line fields");
+        assert_eq!(graph.nodes.len(), 2);
+
+        // Nodes on one line still need a separator
+        assert!(parse_geometry_nodes("cube sphere").is_err());
+        assert!(parse_geometry_nodes("").is_err());
+    }
+
+    #[test]
+    fn parse_connections() {
+        let input = "ball = sphere\nball_size = value 0.5\nball_size.value -> ball.radius";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse connection");
+        assert_eq!(graph.nodes[0].id(), &NodeId("ball".to_string()));
+        assert_eq!(
+            graph.connections,
+            vec![Connection {
+                from_node: NodeId("ball_size".to_string()),
+                from_output: "Value".to_string(),
+                to_node: NodeId("ball".to_string()),
+                to_input: "Radius".to_string(),
+            }]
+        );
+
+        // Generated ids can be linked too, and links may come before the nodes they use
+        let graph = parse_geometry_nodes("value_1.value -> cone_0.radius_top; cone; value 2.0")
+            .expect("Failed to parse forward connection");
+        assert_eq!(graph.connections[0].to_input, "Radius Top");
+    }
+
+    #[test]
+    fn parse_invalid_connections() {
+        let errors = parse_geometry_nodes("cube\nmissing.value -> cube_0.size")
+            .expect_err("Unknown node should fail");
+        assert!(matches!(&errors[0], ParseError::UnknownNode { name, .. } if name == "missing"));
+
+        let errors = parse_geometry_nodes("value 1; cube; value_0.value -> cube_1.mesh")
+            .expect_err("Linking into an output should fail");
+        assert!(matches!(
+            &errors[0],
+            ParseError::UnknownSocket { available, .. } if available == &vec!["Size".to_string()]
+        ));
+
+        let errors =
+            parse_geometry_nodes("a = cube; a = sphere").expect_err("Duplicate names should fail");
+        assert!(matches!(&errors[0], ParseError::DuplicateNode { name, .. } if name == "a"));
+    }
+
+    #[test]
+    fn parse_variables() {
+        let input = "let r = 1.5\nlet rings = 8\nlet d = r\nsphere { radius: r, subdivisions: rings }\ncylinder { depth: d }\nvalue rings";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse variables");
+        match &graph.nodes[0] {
+            Node::Sphere {
+                radius,
+                subdivisions,
+                ..
+            } => {
+                assert_eq!(radius, &Value::Float(1.5));
+                assert_eq!(subdivisions, &Value::Integer(8));
+            }
+            _ => panic!("Expected Sphere node"),
+        }
+        match &graph.nodes[1] {
+            Node::Cylinder { depth, .. } => assert_eq!(depth, &Value::Float(1.5)),
+            _ => panic!("Expected Cylinder node"),
+        }
+        match &graph.nodes[2] {
+            Node::Value { value, .. } => assert_eq!(value, &Value::Integer(8)),
+            _ => panic!("Expected Value node"),
+        }
+
+        // `true` is still a boolean, not a variable
+        let graph = parse_geometry_nodes("value true").expect("Failed to parse boolean");
+        assert!(matches!(
+            &graph.nodes[0],
+            Node::Value {
+                value: Value::Boolean(true),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_invalid_variables() {
+        let errors = parse_geometry_nodes("sphere { radius: r }\nlet r = 1.0")
+            .expect_err("Variables must be defined before use");
+        assert!(matches!(&errors[0], ParseError::UnknownVariable { name, .. } if name == "r"));
+
+        let errors = parse_geometry_nodes("let n = 2.5; cone { vertices: n }")
+            .expect_err("Float vertices should fail");
+        assert!(matches!(
+            &errors[0],
+            ParseError::InvalidFieldValue { field, .. } if field == "vertices"
+        ));
+    }
+
+    #[test]
+    fn fields_follow_the_node_schema() {
+        // Cube takes its fields like every other node
+        let graph = parse_geometry_nodes("cube { size: 3 }\ncube").expect("Failed to parse");
+        assert!(matches!(&graph.nodes[0], Node::Cube { size, .. } if size == &Value::Integer(3)));
+        assert!(matches!(&graph.nodes[1], Node::Cube { size, .. } if size == &Value::Float(2.0)));
+        assert!(parse_geometry_nodes("cube { depth: 1 }").is_err());
+
+        // Literals are checked like variables, pointing at the value
+        let errors =
+            parse_geometry_nodes("sphere { subdivisions: 2.5 }").expect_err("Expected error");
+        assert!(matches!(
+            &errors[0],
+            ParseError::InvalidFieldValue { field, found, expected, span }
+                if field == "subdivisions" && found == "2.5" && expected == "an integer"
+                    && span.into_range() == (23..26)
+        ));
+        let errors = parse_geometry_nodes("math add { clamp: 1 }").expect_err("Expected error");
+        assert!(matches!(
+            &errors[0],
+            ParseError::InvalidFieldValue { field, .. } if field == "clamp"
+        ));
+
+        const SCHEMA: NodeSchema = NodeSchema {
+            keyword: "gear",
+            fields: &[
+                FieldSchema {
+                    name: "teeth",
+                    kind: FieldKind::Integer,
+                    default: None,
+                    doc: "",
+                },
+                optional("radius", FieldKind::Any, Value::Float(1.0), ""),
+            ],
+        };
+        let scope = Scope::default();
+        let error = scope
+            .fields(&SCHEMA, [None, None], (0..4).into())
+            .expect_err("Required fields have to be given");
+        assert!(matches!(
+            error,
+            ParseError::MissingRequiredField { field, node_type, .. }
+                if field == "teeth" && node_type == "gear"
+        ));
+        let literal = |value| ParsedValue::Literal {
+            value,
+            span: (0..0).into(),
+        };
+        let fields = scope
+            .fields(
+                &SCHEMA,
+                [Some(literal(Value::Integer(12))), None],
+                (0..4).into(),
+            )
+            .expect("Failed to resolve fields");
+        assert_eq!(fields, [Value::Integer(12), Value::Float(1.0)]);
+    }
+
+    #[test]
+    fn parse_params() {
+        let input = "param radius: float = 1\nparam segments: int = 16\nparam smooth: bool\nif smooth { sphere { radius: radius, subdivisions: segments } }";
+        let parse = |params: &[(&str, Value)]| {
+            let params = params
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect::<HashMap<_, _>>();
+            parse_geometry_nodes_with_params(input, &params)
+        };
+
+        // Defaults are used for params that aren't given, with integers taken as floats
+        let graph = parse(&[("smooth", Value::Boolean(true))]).expect("Failed to parse params");
+        match &graph.nodes[0] {
+            Node::Sphere {
+                radius,
+                subdivisions,
+                ..
+            } => {
+                assert_eq!(radius, &Value::Float(1.0));
+                assert_eq!(subdivisions, &Value::Integer(16));
+            }
+            _ => panic!("Expected Sphere node"),
+        }
+
+        let graph = parse(&[
+            ("smooth", Value::Boolean(true)),
+            ("radius", Value::Float(2.5)),
+        ])
+        .expect("Failed to parse params");
+        assert!(
+            matches!(&graph.nodes[0], Node::Sphere { radius, .. } if radius == &Value::Float(2.5))
+        );
+
+        let errors = parse(&[]).expect_err("Params without defaults need a value");
+        assert!(
+            matches!(&errors[0], ParseError::MissingParameter { name, .. } if name == "smooth")
+        );
+
+        let errors = parse(&[
+            ("smooth", Value::Boolean(true)),
+            ("segments", Value::Float(1.5)),
+        ])
+        .expect_err("Params are type checked");
+        assert!(matches!(
+            &errors[0],
+            ParseError::InvalidFieldValue { field, expected, .. }
+                if field == "segments" && expected == "an integer"
+        ));
+
+        let errors = parse(&[
+            ("smooth", Value::Boolean(true)),
+            ("raduis", Value::Float(2.0)),
+        ])
+        .expect_err("Unknown params should fail");
+        assert!(
+            matches!(&errors[0], ParseError::UnknownParameter { name, .. } if name == "raduis")
+        );
+
+        // Params are constants
+        let errors = parse_geometry_nodes("param size: float = 1\nlet size = 2\ncube")
+            .expect_err("Params can't be redefined");
+        assert!(matches!(&errors[0], ParseError::ConstantRedefined { name, .. } if name == "size"));
+    }
+
+    #[test]
+    fn parse_groups() {
+        let input = "group Pillar(height, radius: 0.5) {\n    shaft = cylinder { radius: radius, depth: height }\n    cap = sphere { radius: radius }\n    shaft.mesh -> cap.radius\n}\nleft = Pillar(height: 3.0)\nPillar(height: 1, radius: 0.25)";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse groups");
+        let ids: Vec<_> = graph
+            .nodes
+            .iter()
+            .map(|node| node.id().0.as_str())
+            .collect();
+        assert_eq!(
+            ids,
+            vec!["left_shaft", "left_cap", "Pillar_2_shaft", "Pillar_2_cap"]
+        );
+        match &graph.nodes[0] {
+            Node::Cylinder { radius, depth, .. } => {
+                assert_eq!(radius, &Value::Float(0.5));
+                assert_eq!(depth, &Value::Float(3.0));
+            }
+            _ => panic!("Expected Cylinder node"),
+        }
+        match &graph.nodes[3] {
+            Node::Sphere { radius, .. } => assert_eq!(radius, &Value::Float(0.25)),
+            _ => panic!("Expected Sphere node"),
+        }
+        // Links inside the body stay inside each instance
+        assert_eq!(graph.connections.len(), 2);
+        assert_eq!(
+            graph.connections[1].from_node,
+            NodeId("Pillar_2_shaft".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_invalid_groups() {
+        let errors = parse_geometry_nodes("group Box(size) { cube { size: size } }\nBox()")
+            .expect_err("Missing arguments should fail");
+        assert!(matches!(
+            &errors[0],
+            ParseError::MissingRequiredField { field, node_type, .. }
+                if field == "size" && node_type == "Box"
+        ));
+
+        let errors = parse_geometry_nodes("group Box(size) { cube }\nBox(size: 1, depth: 2)")
+            .expect_err("Unknown arguments should fail");
+        assert!(matches!(
+            &errors[0],
+            ParseError::UnknownArgument { name, .. } if name == "depth"
+        ));
+
+        let errors = parse_geometry_nodes("Missing()").expect_err("Unknown groups should fail");
+        assert!(matches!(&errors[0], ParseError::UnknownGroup { name, .. } if name == "Missing"));
+
+        let errors = parse_geometry_nodes("group Loop() { cube; Loop() }\nLoop()")
+            .expect_err("Recursive groups should fail");
+        assert!(matches!(&errors[0], ParseError::RecursiveGroup { name, .. } if name == "Loop"));
+    }
+
+    #[test]
+    fn repeat_expansion_is_bounded() {
+        // The error points at the count of the loop that reached the limit
+        for (input, count_start) in [
+            ("repeat 1000000000 as i { cube }", 7),
+            ("repeat 1000000 as i { repeat 1000000 as j { } }", 29),
+            ("repeat 1000 as i { repeat 1000 as j { cube } }", 26),
+        ] {
+            let errors = parse_geometry_nodes(input).expect_err("Expected the limit to be hit");
+            assert_eq!(errors.len(), 1, "{input}");
+            assert!(
+                matches!(
+                    &errors[0],
+                    ParseError::ExpansionLimit { span, .. } if span.start == count_start
+                ),
+                "{:?}",
+                errors[0]
+            );
+        }
+    }
+
+    #[test]
+    fn parse_repeat() {
+        let input = "repeat 3 as i {\n    step = cube { size: (i + 1) * 0.5 }\n    value i\n}\nrepeat 2 as row { repeat 2 as column { tile = value row * 2 + column } }";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse repeat");
+        let ids: Vec<_> = graph
+            .nodes
+            .iter()
+            .map(|node| node.id().0.as_str())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                "step_0", "value_1", "step_1", "value_3", "step_2", "value_5", "tile_0_0",
+                "tile_0_1", "tile_1_0", "tile_1_1"
+            ]
+        );
+        match &graph.nodes[4] {
+            Node::Cube { size, .. } => assert_eq!(size, &Value::Float(1.5)),
+            _ => panic!("Expected Cube node"),
+        }
+        match &graph.nodes[8] {
+            Node::Value { value, .. } => assert_eq!(value, &Value::Integer(2)),
+            _ => panic!("Expected Value node"),
+        }
+
+        let graph = parse_geometry_nodes("repeat 0 as i { cube }\nsphere")
+            .expect("Failed to parse empty repeat");
+        assert_eq!(graph.nodes.len(), 1);
+
+        let errors = parse_geometry_nodes("repeat 2.5 as i { cube }")
+            .expect_err("Fractional counts should fail");
+        assert!(matches!(
+            &errors[0],
+            ParseError::InvalidFieldValue { field, found, .. } if field == "repeat" && found == "2.5"
+        ));
+    }
+
+    #[test]
+    fn parse_conditions() {
+        let input = "if lod > 1 {\n    let segments = 64\n} else if lod == 1 {\n    let segments = 16\n} else {\n    let segments = 8\n}\nsphere { subdivisions: segments }\nif debug { helper = cube }";
+        let parse = |lod, debug| {
+            let variables = HashMap::from([
+                ("lod".to_string(), Value::Integer(lod)),
+                ("debug".to_string(), Value::Boolean(debug)),
+            ]);
+            parse_geometry_nodes_with_variables(input, &variables)
+                .expect("Failed to parse conditions")
+        };
+
+        let graph = parse(2, true);
+        assert_eq!(graph.nodes.len(), 2);
+        match &graph.nodes[0] {
+            Node::Sphere { subdivisions, .. } => assert_eq!(subdivisions, &Value::Integer(64)),
+            _ => panic!("Expected Sphere node"),
+        }
+        assert_eq!(graph.nodes[1].id(), &NodeId("helper".to_string()));
+
+        let graph = parse(0, false);
+        assert_eq!(graph.nodes.len(), 1);
+        match &graph.nodes[0] {
+            Node::Sphere { subdivisions, .. } => assert_eq!(subdivisions, &Value::Integer(8)),
+            _ => panic!("Expected Sphere node"),
+        }
+
+        let errors = parse_geometry_nodes("if 1 { cube }").expect_err("Numbers aren't conditions");
+        assert!(matches!(
+            &errors[0],
+            ParseError::InvalidFieldValue { field, .. } if field == "if"
+        ));
+        let errors =
+            parse_geometry_nodes("if missing { cube }").expect_err("Conditions need variables");
+        assert!(
+            matches!(&errors[0], ParseError::UnknownVariable { name, .. } if name == "missing")
+        );
+    }
+
+    #[test]
+    fn parse_imports() {
+        let dir = std::env::temp_dir().join(format!("cuttle_imports_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("parts")).expect("Failed to create directory");
+        let write = |name: &str, content: &str| {
+            std::fs::write(dir.join(name), content).expect("Failed to write file");
+        };
+        write(
+            "parts/shapes.ctl",
+            "import \"sizes.ctl\"\ngroup Ball(r: radius) { ball = sphere { radius: r } }",
+        );
+        write("parts/sizes.ctl", "let radius = 0.5");
+        write(
+            "scene.ctl",
+            "import \"parts/shapes.ctl\"\nimport \"parts/sizes.ctl\"\nBall()\ncube { size: radius * 4 }",
+        );
+        let graph = parse_geometry_nodes_file(dir.join("scene.ctl"), &HashMap::new())
+            .expect("Failed to parse imports");
+        assert_eq!(graph.nodes.len(), 2);
+        match &graph.nodes[0] {
+            Node::Sphere { radius, .. } => assert_eq!(radius, &Value::Float(0.5)),
+            _ => panic!("Expected Sphere node"),
+        }
+
+        // Errors point into the file they come from
+        write("broken.ctl", "import \"parts/bad.ctl\"");
+        write("parts/bad.ctl", "cube\nsphere { radius: missing }");
+        let errors = parse_geometry_nodes_file(dir.join("broken.ctl"), &HashMap::new())
+            .expect_err("Unknown variables should fail");
+        let (file, error) = &errors.errors[0];
+        assert_eq!(errors.sources[*file].path, dir.join("parts/bad.ctl"));
+        assert_eq!(error.span(), SimpleSpan::from(22..29));
+
+        write("a.ctl", "import \"b.ctl\"\ncube");
+        write("b.ctl", "import \"a.ctl\"");
+        let errors = parse_geometry_nodes_file(dir.join("a.ctl"), &HashMap::new())
+            .expect_err("Import cycles should fail");
+        assert!(matches!(
+            &errors.errors[0],
+            (1, ParseError::ImportCycle { cycle, .. }) if cycle.len() == 3
+        ));
+
+        let errors = parse_geometry_nodes_file(dir.join("missing.ctl"), &HashMap::new())
+            .expect_err("Missing files should fail");
+        assert!(matches!(
+            &errors.errors[0],
+            (0, ParseError::ImportFailed { .. })
+        ));
+
+        // Unsaved source replaces the file on disk, but not the files it imports
+        let graph = parse_geometry_nodes_source(
+            dir.join("scene.ctl"),
+            "import \"parts/sizes.ctl\"\ncube { size: radius }",
+            &HashMap::new(),
+        )
+        .expect("Failed to parse unsaved source");
+        assert_eq!(graph.nodes.len(), 1);
+
+        let errors = parse_geometry_nodes("import \"a.ctl\"").expect_err("Imports need a file");
+        assert!(matches!(&errors[0], ParseError::ImportFailed { .. }));
+
+        std::fs::remove_dir_all(&dir).expect("Failed to clean up");
+    }
+
+    #[test]
+    fn parse_arithmetic() {
+        let input = "let scale = 2\ncube { size: 2.0 * scale + 0.5 }\ncylinder { vertices: (scale + 1) * 4, depth: 1 / 4 }\nvalue (1.0, 2.0, 3.0) * scale - 1";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse expressions");
+        match &graph.nodes[0] {
+            Node::Cube { size, .. } => assert_eq!(size, &Value::Float(4.5)),
+            _ => panic!("Expected Cube node"),
+        }
+        match &graph.nodes[1] {
+            Node::Cylinder {
+                vertices, depth, ..
+            } => {
+                assert_eq!(vertices, &Value::Integer(12));
+                assert_eq!(depth, &Value::Float(0.25));
+            }
+            _ => panic!("Expected Cylinder node"),
+        }
+        match &graph.nodes[2] {
+            Node::Value { value, .. } => assert_eq!(value, &Value::Vector(1.0, 3.0, 5.0)),
+            _ => panic!("Expected Value node"),
+        }
+    }
+
+    #[test]
+    fn parse_invalid_arithmetic() {
+        let errors = parse_geometry_nodes("value 1 / 0").expect_err("Division by zero should fail");
+        assert!(matches!(&errors[0], ParseError::InvalidExpression { .. }));
+
+        let errors = parse_geometry_nodes("value true + 1").expect_err("Booleans have no sum");
+        assert!(matches!(&errors[0], ParseError::InvalidExpression { .. }));
+
+        let errors = parse_geometry_nodes("sphere { subdivisions: 16 / 2 }")
+            .expect_err("Division never gives an integer");
+        assert!(matches!(
+            &errors[0],
+            ParseError::InvalidFieldValue { found, .. } if found == "8"
+        ));
+    }
+
+    #[test]
+    fn parse_math_nodes() {
+        let input = "math add { a: 1.0, b: 2.0, clamp: true }\nvector_math cross_product { a: (1, 0, 0), b: 2 }\nmix { factor: 0.25 }\nm = math maximum\nm.value -> math_0.value_001";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse math nodes");
+        assert_eq!(
+            graph.nodes[0],
+            Node::Math {
+                id: NodeId("math_0".to_string()),
+                operation: MathOperation::Add,
+                a: Value::Float(1.0),
+                b: Value::Float(2.0),
+                clamp: true,
+            }
+        );
+        match &graph.nodes[1] {
+            Node::VectorMath { operation, b, .. } => {
+                assert_eq!(operation, &VectorMathOperation::CrossProduct);
+                assert_eq!(b, &Value::Vector(2.0, 2.0, 2.0));
+            }
+            _ => panic!("Expected VectorMath node"),
+        }
+        match &graph.nodes[2] {
+            Node::Mix { factor, .. } => assert_eq!(factor, &Value::Float(0.25)),
+            _ => panic!("Expected Mix node"),
+        }
+        assert_eq!(graph.connections[0].to_input, "Value_001");
+
+        assert!(parse_geometry_nodes("math modulo_everything").is_err());
+        assert!(parse_geometry_nodes("math add { clamp: 1 }").is_err());
+        assert!(parse_geometry_nodes("math").is_err());
+    }
+
+    #[test]
+    fn parse_with_comments() {
+        let input = "# A ball\nball = sphere { radius: 2.0 } // big\n/* a cube,\n   for scale */ cube\nobject \"Not # a comment\"\nvalue 4 / 2";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse with comments");
+        assert_eq!(graph.nodes.len(), 4);
+        match &graph.nodes[2] {
+            Node::Object { name, .. } => assert_eq!(name, "Not # a comment"),
+            _ => panic!("Expected Object node"),
+        }
+
+        let comments = parse_comments(input).expect("Failed to find comments");
+        let texts = comments.iter().map(|c| c.text.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            vec!["# A ball", "// big", "/* a cube,\n   for scale */"]
+        );
+        assert_eq!(&input[comments[1].span.clone()], "// big");
+
+        let errors = parse_geometry_nodes("cube /* never closed").expect_err("Expected error");
+        assert!(matches!(errors[0], ParseError::UnterminatedComment { .. }));
+    }
+
+    #[test]
+    fn comments_keep_error_spans() {
+        let errors = parse_geometry_nodes("/* note */ cube { size: }").expect_err("Expected error");
+        assert_eq!(errors[0].span().start, "/* note */ cube { size: ".len());
+    }
+
+    #[test]
+    fn parse_signed_and_scientific_numbers() {
+        let value = |input: &str| match parse_geometry_nodes(input)
+            .expect("Failed to parse number")
+            .nodes
+            .remove(0)
+        {
+            Node::Value { value, .. } => value,
+            other => panic!("Expected Value node, got {other:?}"),
+        };
+        assert_eq!(value("value -1.5"), Value::Float(-1.5));
+        assert_eq!(value("value -7"), Value::Integer(-7));
+        assert_eq!(value("value 1e-3"), Value::Float(0.001));
+        assert_eq!(value("value +2.5E2"), Value::Float(250.0));
+        assert_eq!(value("value .5"), Value::Float(0.5));
+        assert_eq!(value("value (-1, .5, 1e1)"), Value::Vector(-1.0, 0.5, 10.0));
+        // A minus between operands is still subtraction
+        assert_eq!(value("value 3-1"), Value::Integer(2));
+        assert_eq!(value("value 3 - -1"), Value::Integer(4));
+    }
+
+    #[test]
+    fn invalid_numbers_are_diagnosed() {
+        for input in ["value 1.2.3", "value 1e", "value 99999999999999999999"] {
+            let errors = parse_geometry_nodes(input).expect_err("Expected invalid number");
+            match &errors[0] {
+                ParseError::InvalidNumber { span, found, .. } => {
+                    assert_eq!(found, &input["value ".len()..]);
+                    assert_eq!(span.start, "value ".len());
+                }
+                other => panic!("Expected InvalidNumber for {input}, got {other:?}"),
+            }
+        }
+    }
+
     #[test]
     fn parse_value_node() {
         let input = "value 42";
@@ -208,6 +3352,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_object_reference() {
+        let graph =
+            parse_geometry_nodes("object \"Suzanne Head\"").expect("Failed to parse object");
+        assert_eq!(graph.nodes.len(), 1);
+        match &graph.nodes[0] {
+            Node::Object { name, .. } => assert_eq!(name, "Suzanne Head"),
+            _ => panic!("Expected Object node"),
+        }
+
+        assert!(parse_geometry_nodes("object \"\"").is_err());
+        assert!(parse_geometry_nodes("object Suzanne").is_err());
+    }
+
     #[test]
     fn parse_invalid_input() {
         let input = "invalid syntax";
@@ -235,6 +3393,87 @@ mod tests {
         assert!(!errors.is_empty());
     }
 
+    #[test]
+    fn parse_constants_with_units() {
+        let input = "const WALL_HEIGHT = 2.4m\nconst TRIM = 15cm\nconst ANGLE = 90deg\ncylinder { depth: WALL_HEIGHT - TRIM, radius: 250mm, vertices: 8 }\nvalue ANGLE + 1rad";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse units");
+        match &graph.nodes[0] {
+            Node::Cylinder { radius, depth, .. } => {
+                assert_eq!(radius, &Value::Float(0.25));
+                let Value::Float(depth) = depth else {
+                    panic!("Expected float depth");
+                };
+                assert!((depth - 2.25).abs() < 1e-9);
+            }
+            _ => panic!("Expected Cylinder node"),
+        }
+        match &graph.nodes[1] {
+            Node::Value {
+                value: Value::Float(angle),
+                ..
+            } => assert!((angle - (std::f64::consts::FRAC_PI_2 + 1.0)).abs() < 1e-9),
+            _ => panic!("Expected float Value node"),
+        }
+
+        let errors = parse_geometry_nodes("const H = 2m\nlet H = 3m\ncube")
+            .expect_err("Constants can't be redefined");
+        assert!(matches!(
+            &errors[0],
+            ParseError::ConstantRedefined { name, span } if name == "H" && span.start == 13
+        ));
+        // Units make floats, which integer fields don't take
+        let errors = parse_geometry_nodes("cylinder { vertices: 8m }").expect_err("Expected error");
+        assert!(matches!(
+            &errors[0],
+            ParseError::InvalidFieldValue { field, .. } if field == "vertices"
+        ));
+    }
+
+    #[test]
+    fn parse_errors_in_several_statements() {
+        let input = "cube { size: }\nsphere { radius: 1.0 }\ncylinder { depth: 2.0.0 }\ngroup A() {\n    cone { vertices: ) }\n    cube\n}\nmath nope";
+        let errors = parse_geometry_nodes(input).expect_err("Broken statements should fail");
+        let lines = errors
+            .iter()
+            .map(|error| input[..error.span().start].matches('\n').count())
+            .collect::<Vec<_>>();
+        assert_eq!(lines, [0, 2, 4, 7]);
+
+        let report = ErrorReporter::new().report_plain(&errors, input, "scene.ctl");
+        assert_eq!(report.matches("Error:").count(), 4);
+    }
+
+    #[test]
+    fn stream_nodes_as_statements_parse() {
+        let input = "const size = 2 # the cube's\ngroup post(h) {\n    shaft = cylinder { depth: h }\n}\nbox = cube { size: size }; left = post(h: 3)\nlink = math add\nbox.mesh -> out.geometry\nif size > 1 {\n    sphere\n}\nelse {\n    cone\n}\nrepeat 2 as i { value i }\nout = output";
+        let graph = parse_geometry_nodes(input).expect("Failed to parse stream input");
+
+        let items = parse_nodes_iter(input)
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Failed to stream nodes");
+        let mut nodes = Vec::new();
+        let mut connections = Vec::new();
+        for item in items {
+            match item {
+                GraphItem::Node(node) => {
+                    assert!(connections.is_empty(), "Connections come after every node");
+                    nodes.push(node);
+                }
+                GraphItem::Connection(connection) => connections.push(connection),
+            }
+        }
+        assert_eq!(nodes, graph.nodes);
+        assert_eq!(connections, graph.connections);
+
+        let input = "cube\nsphere { radius: }\nball = sphere { radius: nope }\nball.mesh -> missing.geometry";
+        // Statements after a syntax error are still built, unlike with `parse_geometry_nodes`
+        let errors = parse_nodes_iter(input)
+            .filter_map(Result::err)
+            .map(|error| &input[error.span().into_range()])
+            .collect::<Vec<_>>();
+        assert_eq!(errors, ["}", "nope", "ball.mesh", "missing.geometry"]);
+    }
+
     #[test]
     fn error_formatting() {
         let input = "invalid syntax";
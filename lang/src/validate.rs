@@ -0,0 +1,254 @@
+//! Checks over a whole graph, for mistakes that are only visible once every node and connection
+//! is known.
+//!
+//! Parsed graphs already have their node names and sockets checked, but graphs can also come
+//! from JSON or from Blender, and the parser doesn't look at socket types or cycles.
+
+use crate::{BlenderNode, BlenderSocket, NodeGraph, NodeId, SemanticError};
+use std::collections::HashMap;
+
+// Blender converts between data sockets when linking them, but geometry and objects only link to
+// their own type
+fn compatible(from: &str, to: &str) -> bool {
+    const DATA: &[&str] = &[
+        "NodeSocketFloat",
+        "NodeSocketInt",
+        "NodeSocketBool",
+        "NodeSocketVector",
+        "NodeSocketColor",
+        "NodeSocketRotation",
+    ];
+    from == to || (DATA.contains(&from) && DATA.contains(&to))
+}
+
+fn find<'a>(sockets: &'a [BlenderSocket], name: &str) -> Option<&'a BlenderSocket> {
+    sockets.iter().find(|socket| socket.name == name)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+    New,
+    Open,
+    Done,
+}
+
+struct Cycles<'a> {
+    ids: &'a [&'a NodeId],
+    // Outgoing connections of each node, as the node they go to and the connection index
+    edges: Vec<Vec<(usize, usize)>>,
+    visits: Vec<Visit>,
+    stack: Vec<usize>,
+    errors: Vec<SemanticError>,
+}
+
+impl Cycles<'_> {
+    fn visit(&mut self, node: usize) {
+        self.visits[node] = Visit::Open;
+        self.stack.push(node);
+        for (next, connection) in self.edges[node].clone() {
+            match self.visits[next] {
+                Visit::New => self.visit(next),
+                Visit::Open => {
+                    let start = self.stack.iter().rposition(|open| *open == next);
+                    let mut nodes = self.stack[start.unwrap_or_default()..]
+                        .iter()
+                        .map(|index| self.ids[*index].clone())
+                        .collect::<Vec<_>>();
+                    nodes.push(self.ids[next].clone());
+                    self.errors.push(SemanticError::Cycle { connection, nodes });
+                }
+                Visit::Done => {}
+            }
+        }
+        self.stack.pop();
+        self.visits[node] = Visit::Done;
+    }
+}
+
+/// Everything wrong with `graph` that would stop it from working in Blender: connections to
+/// nodes or sockets that don't exist, links between incompatible socket types, node ids used
+/// twice and connections that loop back on themselves.
+pub fn validate(graph: &NodeGraph) -> Vec<SemanticError> {
+    let mut errors = Vec::new();
+
+    // Later nodes with a taken id are the duplicates
+    let mut indexes: HashMap<&NodeId, usize> = HashMap::new();
+    let mut ids = Vec::new();
+    let mut nodes = Vec::new();
+    for (index, node) in graph.nodes.iter().enumerate() {
+        if indexes.contains_key(node.id()) {
+            errors.push(SemanticError::DuplicateId {
+                node: index,
+                id: node.id().clone(),
+            });
+        } else {
+            indexes.insert(node.id(), ids.len());
+            ids.push(node.id());
+            nodes.push(BlenderNode::from(node.clone()));
+        }
+    }
+
+    let mut edges = vec![Vec::new(); ids.len()];
+    for (index, connection) in graph.connections.iter().enumerate() {
+        let mut unknown = false;
+        for node in [&connection.from_node, &connection.to_node] {
+            if !indexes.contains_key(node) {
+                unknown = true;
+                errors.push(SemanticError::UnknownNode {
+                    connection: index,
+                    node: node.clone(),
+                });
+            }
+        }
+        if unknown {
+            continue;
+        }
+
+        let from = indexes[&connection.from_node];
+        let to = indexes[&connection.to_node];
+        let from_socket = find(&nodes[from].outputs, &connection.from_output);
+        let to_socket = find(&nodes[to].inputs, &connection.to_input);
+        match (from_socket, to_socket) {
+            // Links between sockets that don't exist aren't followed when looking for cycles
+            (Some(from_socket), Some(to_socket)) => {
+                edges[from].push((to, index));
+                if !compatible(&from_socket.socket_type, &to_socket.socket_type) {
+                    errors.push(SemanticError::SocketTypeMismatch {
+                        connection: index,
+                        from: format!("{}.{}", connection.from_node.0, from_socket.name),
+                        from_type: from_socket.socket_type.clone(),
+                        to: format!("{}.{}", connection.to_node.0, to_socket.name),
+                        to_type: to_socket.socket_type.clone(),
+                    });
+                }
+            }
+            (from_socket, to_socket) => {
+                if from_socket.is_none() {
+                    errors.push(SemanticError::UnknownSocket {
+                        connection: index,
+                        node: connection.from_node.clone(),
+                        socket: connection.from_output.clone(),
+                    });
+                }
+                if to_socket.is_none() {
+                    errors.push(SemanticError::UnknownSocket {
+                        connection: index,
+                        node: connection.to_node.clone(),
+                        socket: connection.to_input.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    let mut cycles = Cycles {
+        ids: &ids,
+        edges,
+        visits: vec![Visit::New; ids.len()],
+        stack: Vec::new(),
+        errors,
+    };
+    for node in 0..ids.len() {
+        if cycles.visits[node] == Visit::New {
+            cycles.visit(node);
+        }
+    }
+    cycles.errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Connection, ErrorReporter, Node, Value, parse_geometry_nodes_with_spans};
+
+    fn connection(from: &str, output: &str, to: &str, input: &str) -> Connection {
+        Connection {
+            from_node: NodeId(from.to_string()),
+            from_output: output.to_string(),
+            to_node: NodeId(to.to_string()),
+            to_input: input.to_string(),
+        }
+    }
+
+    #[test]
+    fn valid_graphs_have_no_errors() {
+        let (graph, _) = parse_geometry_nodes_with_spans(
+            "v = value 2\nm = math add\nball = sphere\noutput\nv.value -> m.value\nm.value -> ball.radius\nball.mesh -> output_3.geometry",
+        )
+        .expect("Failed to parse graph");
+        assert!(validate(&graph).is_empty());
+    }
+
+    #[test]
+    fn socket_types_must_be_compatible() {
+        let source = "box = cube\nm = math add\nbox.mesh -> m.value";
+        let (graph, spans) = parse_geometry_nodes_with_spans(source).expect("Failed to parse");
+        let errors = validate(&graph);
+        assert_eq!(
+            errors,
+            vec![SemanticError::SocketTypeMismatch {
+                connection: 0,
+                from: "box.Mesh".to_string(),
+                from_type: "NodeSocketGeometry".to_string(),
+                to: "m.Value".to_string(),
+                to_type: "NodeSocketFloat".to_string(),
+            }]
+        );
+        assert_eq!(errors[0].span(&spans), Some((24..43).into()));
+
+        let report = ErrorReporter::new().report_semantic(&errors, &spans, source, "scene.ctl");
+        assert!(report.contains("Cannot connect geometry output 'box.Mesh' to float input"));
+        assert!(report.contains("scene.ctl"));
+    }
+
+    #[test]
+    fn cycles_are_found() {
+        let (graph, spans) = parse_geometry_nodes_with_spans(
+            "a = math add\nb = math add\nc = math add\na.value -> b.value\nb.value -> c.value\nc.value -> a.value",
+        )
+        .expect("Failed to parse graph");
+        let errors = validate(&graph);
+        let [SemanticError::Cycle { connection, nodes }] = errors.as_slice() else {
+            panic!("Expected one cycle, got {errors:?}");
+        };
+        assert_eq!(*connection, 2);
+        let nodes = nodes.iter().map(|id| id.0.as_str()).collect::<Vec<_>>();
+        assert_eq!(nodes, ["a", "b", "c", "a"]);
+        assert!(errors[0].span(&spans).is_some());
+    }
+
+    #[test]
+    fn unknown_references_and_duplicates_are_found() {
+        let mut graph = NodeGraph::new();
+        for _ in 0..2 {
+            graph.add_node(Node::Value {
+                id: NodeId("v".to_string()),
+                value: Value::Float(1.0),
+            });
+        }
+        graph.add_connection(connection("v", "Value", "missing", "Value"));
+        graph.add_connection(connection("v", "Size", "v", "Value"));
+
+        let errors = validate(&graph);
+        // Value nodes have no inputs, so both ends of the second connection are unknown
+        assert_eq!(errors.len(), 4);
+        assert!(matches!(
+            &errors[0],
+            SemanticError::DuplicateId { node: 1, id } if id.0 == "v"
+        ));
+        assert!(matches!(
+            &errors[1],
+            SemanticError::UnknownNode { connection: 0, node } if node.0 == "missing"
+        ));
+        assert!(matches!(
+            &errors[2],
+            SemanticError::UnknownSocket { connection: 1, socket, .. } if socket == "Size"
+        ));
+        assert!(matches!(
+            &errors[3],
+            SemanticError::UnknownSocket { connection: 1, socket, .. } if socket == "Value"
+        ));
+        // Graphs built in code have no spans
+        assert_eq!(errors[0].span(&Default::default()), None);
+    }
+}
@@ -0,0 +1,281 @@
+//! What the server knows about a document, kept separate from the protocol plumbing so it can be
+//! tested on plain strings.
+
+use cuttle_lang::{
+    FieldInfo, NodeTypeInfo, ParseError, node_catalog, node_type, parse_geometry_nodes,
+    parse_geometry_nodes_source,
+};
+use std::collections::HashMap;
+use std::path::Path;
+use tower_lsp::lsp_types::{
+    CompletionItem, CompletionItemKind, Diagnostic, DiagnosticSeverity, Position, Range,
+};
+
+// What a field takes, e.g. "Vertices around each cap (integer, default 32)"
+fn field_detail(field: &FieldInfo) -> String {
+    match field.default_source() {
+        Some(default) => format!(
+            "{} ({}, default {default})",
+            field.description,
+            field.kind.name()
+        ),
+        None => format!("{} ({})", field.description, field.kind.name()),
+    }
+}
+
+/// The LSP position of byte `offset` in `text`. Columns count UTF-16 code units, the protocol's
+/// default encoding.
+pub(crate) fn position(text: &str, offset: usize) -> Position {
+    let offset = offset.min(text.len());
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    Position {
+        line: before.matches('\n').count() as u32,
+        character: before[line_start..].encode_utf16().count() as u32,
+    }
+}
+
+/// The byte offset of `position` in `text`, clamped to the end of its line.
+pub(crate) fn offset(text: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match text[line_start..].find('\n') {
+            Some(newline) => line_start += newline + 1,
+            None => return text.len(),
+        }
+    }
+    let line = &text[line_start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+    let mut units = 0;
+    for (index, c) in line.char_indices() {
+        if units >= position.character as usize {
+            return line_start + index;
+        }
+        units += c.len_utf16();
+    }
+    line_start + line.len()
+}
+
+fn diagnostic(text: &str, error: &ParseError) -> Diagnostic {
+    let diagnostic = error.to_diagnostic();
+    let message = match diagnostic.help {
+        Some(help) => format!("{}\nhelp: {help}", diagnostic.message),
+        None => diagnostic.message,
+    };
+    Diagnostic {
+        range: Range {
+            start: position(text, diagnostic.span.start),
+            end: position(text, diagnostic.span.end),
+        },
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some("cuttle".to_string()),
+        message,
+        ..Default::default()
+    }
+}
+
+/// Parse errors in `text`. With a `path`, imports are resolved relative to it, and errors in
+/// imported files are shown at the start of the document since they have no span in it.
+pub(crate) fn diagnostics(path: Option<&Path>, text: &str) -> Vec<Diagnostic> {
+    let Some(path) = path else {
+        return match parse_geometry_nodes(text) {
+            Ok(_) => Vec::new(),
+            Err(errors) => errors.iter().map(|error| diagnostic(text, error)).collect(),
+        };
+    };
+
+    let Err(errors) = parse_geometry_nodes_source(path, text, &HashMap::new()) else {
+        return Vec::new();
+    };
+    errors
+        .errors
+        .iter()
+        .map(|(file, error)| {
+            if *file == 0 {
+                return diagnostic(text, error);
+            }
+            let path = errors.sources[*file].path.display();
+            Diagnostic {
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some("cuttle".to_string()),
+                message: format!("{path}: {}", error.message()),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+fn is_word(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Documentation for the node type under `position`.
+pub(crate) fn hover(text: &str, position: Position) -> Option<(Range, String)> {
+    let at = offset(text, position);
+    let start = text[..at]
+        .rfind(|c: char| !is_word(c))
+        .map_or(0, |index| index + 1);
+    let end = text[at..]
+        .find(|c: char| !is_word(c))
+        .map_or(text.len(), |index| at + index);
+    let info = node_type(&text[start..end])?;
+
+    let mut contents = format!("**{}**\n\n{}", info.keyword, info.description);
+    if !info.operations.is_empty() {
+        contents.push_str(&format!(
+            "\n\nOperations: `{}`",
+            info.operations.join("`, `")
+        ));
+    }
+    if !info.fields.is_empty() {
+        contents.push_str("\n\nFields:");
+        for field in &info.fields {
+            contents.push_str(&format!("\n- `{}`: {}", field.name, field_detail(field)));
+        }
+    }
+    for (title, names) in [("Inputs", &info.inputs), ("Outputs", &info.outputs)] {
+        if !names.is_empty() {
+            contents.push_str(&format!("\n\n{title}: `{}`", names.join("`, `")));
+        }
+    }
+    let range = Range {
+        start: self::position(text, start),
+        end: self::position(text, end),
+    };
+    Some((range, contents))
+}
+
+// The node type whose fields `before` ends inside, and the text written inside its braces
+fn open_fields(before: &str) -> Option<(NodeTypeInfo, &str)> {
+    let brace = before.rfind(['{', '}'])?;
+    if !before[brace..].starts_with('{') {
+        return None;
+    }
+    let header = &before[..brace];
+    let header = &header[header
+        .rfind(['\n', ';', '{', '}'])
+        .map_or(0, |index| index + 1)..];
+    // Skip a `label =`
+    let header = header.rsplit('=').next().unwrap_or(header);
+    let info = node_type(header.split_whitespace().next()?)?;
+    Some((info, &before[brace + 1..]))
+}
+
+/// Completions at `position`: the fields a node hasn't set yet inside its braces, and node types
+/// everywhere else.
+pub(crate) fn completions(text: &str, position: Position) -> Vec<CompletionItem> {
+    let before = &text[..offset(text, position)];
+    if let Some((info, inside)) = open_fields(before) {
+        // Only where a field name goes: right after `{` or a `,`
+        let current = inside.rsplit(',').next().unwrap_or(inside);
+        if !current.trim_start().chars().all(is_word) {
+            return Vec::new();
+        }
+        let written = inside
+            .split(',')
+            .filter_map(|field| field.split(':').next())
+            .map(str::trim)
+            .collect::<Vec<_>>();
+        return info
+            .fields
+            .iter()
+            .filter(|field| !written.contains(&field.name))
+            .map(|field| CompletionItem {
+                label: field.name.to_string(),
+                kind: Some(CompletionItemKind::FIELD),
+                detail: Some(field_detail(field)),
+                insert_text: Some(format!("{}: ", field.name)),
+                ..Default::default()
+            })
+            .collect();
+    }
+    node_catalog()
+        .into_iter()
+        .map(|info| CompletionItem {
+            label: info.keyword.to_string(),
+            kind: Some(CompletionItemKind::CLASS),
+            detail: Some(info.description.to_string()),
+            ..Default::default()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(line: u32, character: u32) -> Position {
+        Position { line, character }
+    }
+
+    fn labels(items: Vec<CompletionItem>) -> Vec<String> {
+        items.into_iter().map(|item| item.label).collect()
+    }
+
+    #[test]
+    fn positions_count_utf16_units() {
+        let text = "object \"é\"\ncube";
+        assert_eq!(position(text, 12), at(1, 0));
+        assert_eq!(position(text, 10), at(0, 9));
+        assert_eq!(offset(text, at(0, 9)), 10);
+        assert_eq!(offset(text, at(1, 2)), 14);
+        assert_eq!(offset(text, at(0, 100)), 11);
+        assert_eq!(offset(text, at(5, 0)), text.len());
+    }
+
+    #[test]
+    fn parse_errors_become_diagnostics() {
+        assert!(diagnostics(None, "cube { size: 2 }").is_empty());
+
+        let diagnostics = diagnostics(None, "cube\nsphere { radius: missing }");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].range,
+            Range {
+                start: at(1, 17),
+                end: at(1, 24)
+            }
+        );
+        assert!(diagnostics[0].message.contains("missing"));
+    }
+
+    #[test]
+    fn node_types_have_hover_docs() {
+        let text = "ball = sphere { radius: 2 }";
+        let (range, contents) = hover(text, at(0, 9)).expect("Expected hover for sphere");
+        assert_eq!(
+            range,
+            Range {
+                start: at(0, 7),
+                end: at(0, 13)
+            }
+        );
+        assert!(contents.contains(
+            "`subdivisions`: Detail of the sphere, giving 4x segments and 2x rings (integer, default 8)"
+        ));
+        assert!(contents.contains("Outputs: `mesh`"));
+        assert!(hover(text, at(0, 1)).is_none());
+        assert!(
+            hover("cone", at(0, 0)).is_some_and(|(_, contents)| contents.contains("radius_top"))
+        );
+    }
+
+    #[test]
+    fn completion_offers_missing_fields() {
+        let text = "c = cone { radius_top: 1, ";
+        assert_eq!(
+            labels(completions(text, at(0, 26))),
+            ["vertices", "radius_bottom", "depth"]
+        );
+        assert_eq!(
+            labels(completions("math add {", at(0, 10))),
+            ["a", "b", "clamp"]
+        );
+        // Values aren't field names
+        assert!(completions("cube { size: ", at(0, 13)).is_empty());
+        // Outside of a node's braces, and in blocks, node types are offered
+        let types = labels(completions("repeat 2 as i {\n    ", at(1, 4)));
+        assert!(types.contains(&"cylinder".to_string()));
+        assert_eq!(types.len(), node_catalog().len());
+    }
+}
@@ -0,0 +1,143 @@
+//! A language server for cuttle DSL files.
+//!
+//! Editors get parse errors as diagnostics while typing, documentation when hovering a node type,
+//! and completion of node types and of the fields a node hasn't set yet. Documents are parsed
+//! from the editor's buffer, so diagnostics don't wait for a save.
+
+mod analysis;
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{
+    CompletionOptions, CompletionParams, CompletionResponse, DidChangeTextDocumentParams,
+    DidCloseTextDocumentParams, DidOpenTextDocumentParams, Hover, HoverContents, HoverParams,
+    HoverProviderCapability, InitializeParams, InitializeResult, InitializedParams, MarkupContent,
+    MarkupKind, MessageType, ServerCapabilities, ServerInfo, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Url,
+};
+use tower_lsp::{Client, LanguageServer, LspService, Server};
+
+pub struct Backend {
+    client: Client,
+    documents: RwLock<HashMap<Url, String>>,
+}
+
+impl Backend {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            documents: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn document(&self, uri: &Url) -> Option<String> {
+        self.documents
+            .read()
+            .ok()
+            .and_then(|documents| documents.get(uri).cloned())
+    }
+
+    async fn update(&self, uri: Url, text: String, version: i32) {
+        // Imports can only be resolved for documents that are files
+        let path = uri.to_file_path().ok();
+        let diagnostics = analysis::diagnostics(path.as_deref(), &text);
+        if let Ok(mut documents) = self.documents.write() {
+            documents.insert(uri.clone(), text);
+        }
+        self.client
+            .publish_diagnostics(uri, diagnostics, Some(version))
+            .await;
+    }
+}
+
+#[tower_lsp::async_trait]
+impl LanguageServer for Backend {
+    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+        Ok(InitializeResult {
+            capabilities: ServerCapabilities {
+                text_document_sync: Some(TextDocumentSyncCapability::Kind(
+                    TextDocumentSyncKind::FULL,
+                )),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                completion_provider: Some(CompletionOptions {
+                    trigger_characters: Some(vec!["{".to_string(), ",".to_string()]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            server_info: Some(ServerInfo {
+                name: env!("CARGO_PKG_NAME").to_string(),
+                version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            }),
+        })
+    }
+
+    async fn initialized(&self, _: InitializedParams) {
+        self.client
+            .log_message(MessageType::INFO, "cuttle language server initialized")
+            .await;
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        let document = params.text_document;
+        self.update(document.uri, document.text, document.version)
+            .await;
+    }
+
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        // Changes are full documents, as requested in `initialize`
+        if let Some(change) = params.content_changes.into_iter().last() {
+            let document = params.text_document;
+            self.update(document.uri, change.text, document.version)
+                .await;
+        }
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri;
+        if let Ok(mut documents) = self.documents.write() {
+            documents.remove(&uri);
+        }
+        self.client.publish_diagnostics(uri, Vec::new(), None).await;
+    }
+
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let position = params.text_document_position_params;
+        let Some(text) = self.document(&position.text_document.uri) else {
+            return Ok(None);
+        };
+        Ok(
+            analysis::hover(&text, position.position).map(|(range, contents)| Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: contents,
+                }),
+                range: Some(range),
+            }),
+        )
+    }
+
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let position = params.text_document_position;
+        let Some(text) = self.document(&position.text_document.uri) else {
+            return Ok(None);
+        };
+        Ok(Some(CompletionResponse::Array(analysis::completions(
+            &text,
+            position.position,
+        ))))
+    }
+}
+
+/// Serve the language server over stdin and stdout until the client disconnects.
+pub async fn serve_stdio() {
+    let (service, socket) = LspService::new(Backend::new);
+    Server::new(tokio::io::stdin(), tokio::io::stdout(), socket)
+        .serve(service)
+        .await;
+}
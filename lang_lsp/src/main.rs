@@ -0,0 +1,4 @@
+#[tokio::main]
+async fn main() {
+    cuttle_lang_lsp::serve_stdio().await;
+}
@@ -0,0 +1,68 @@
+//! cuttle_lang for the browser playground.
+//!
+//! Both functions take DSL source and return JSON. When the source doesn't parse, the error is a
+//! JSON array of [`Diagnostic`]s, so the playground can underline each span itself rather than
+//! showing a rendered report.
+
+use cuttle_lang::{BlenderNodeGraph, Diagnostic, ErrorReporter, Severity, parse_geometry_nodes};
+use wasm_bindgen::prelude::*;
+
+fn diagnostics_json(diagnostics: &[Diagnostic]) -> String {
+    serde_json::to_string(diagnostics).unwrap_or_else(|error| error.to_string())
+}
+
+fn parse_json(source: &str) -> Result<String, String> {
+    let graph = parse_geometry_nodes(source)
+        .map_err(|errors| diagnostics_json(&ErrorReporter::diagnostics(&errors)))?;
+    serde_json::to_string(&graph).map_err(|error| error.to_string())
+}
+
+fn blender_json(source: &str) -> Result<String, String> {
+    let graph = parse_geometry_nodes(source)
+        .map_err(|errors| diagnostics_json(&ErrorReporter::diagnostics(&errors)))?;
+    // Graph errors are about the whole graph rather than a place in the source
+    let blender_graph = BlenderNodeGraph::try_from(graph).map_err(|error| {
+        diagnostics_json(&[Diagnostic {
+            message: error.to_string(),
+            label: error.to_string(),
+            span: 0..0,
+            severity: Severity::Error,
+            help: None,
+        }])
+    })?;
+    serde_json::to_string(&blender_graph).map_err(|error| error.to_string())
+}
+
+/// The node graph `source` parses to, as JSON.
+#[wasm_bindgen]
+pub fn parse(source: &str) -> Result<String, JsValue> {
+    parse_json(source).map_err(|error| JsValue::from_str(&error))
+}
+
+/// The Blender node tree for `source`, as the JSON the Blender addon loads.
+#[wasm_bindgen]
+pub fn to_blender_json(source: &str) -> Result<String, JsValue> {
+    blender_json(source).map_err(|error| JsValue::from_str(&error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_are_diagnostics_json() {
+        let json = blender_json("box = cube\nout = output\nbox.mesh -> out.geometry")
+            .expect("Failed to convert");
+        assert!(json.contains("GeometryNodeMeshCube"));
+
+        let error = parse_json("ball = sphere {").expect_err("Parsed invalid source");
+        let diagnostics: Vec<Diagnostic> =
+            serde_json::from_str(&error).expect("Error isn't diagnostics");
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+
+        let error = blender_json("ball = sphere").expect_err("Converted without an output");
+        let diagnostics: Vec<Diagnostic> =
+            serde_json::from_str(&error).expect("Error isn't diagnostics");
+        assert_eq!(diagnostics[0].span, 0..0);
+    }
+}
@@ -0,0 +1,44 @@
+use cuttle_blender_api::{BlenderApiError, JsonBlenderApi, JsonTransport};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+
+/// `BlenderApi` backed by Python callables, which do the actual work through `bpy`.
+///
+/// Handlers are keyed by operation name (`create_cube`, `list_objects`, ...). Each one is
+/// called with the operation's parameters as a JSON string (`null` when there are none) and
+/// must return the result as a JSON string (`null` for operations without a result).
+///
+/// Handlers run on the cuttle runtime thread. Blender only allows `bpy` on its main thread,
+/// so handlers that touch scene data should hand the work to the main thread, for example
+/// through a queue drained by `bpy.app.timers`.
+pub type PyBlenderApi = JsonBlenderApi<PyHandlers>;
+
+pub struct PyHandlers {
+    handlers: HashMap<String, Py<PyAny>>,
+}
+
+impl PyHandlers {
+    pub fn new(handlers: HashMap<String, Py<PyAny>>) -> Self {
+        Self { handlers }
+    }
+}
+
+impl JsonTransport for PyHandlers {
+    fn call(&self, operation: &str, params: &str) -> Result<String, BlenderApiError> {
+        let handler =
+            self.handlers
+                .get(operation)
+                .ok_or_else(|| BlenderApiError::OperationFailed {
+                    message: format!("No Python handler registered for {operation}"),
+                })?;
+
+        Python::with_gil(|py| {
+            handler
+                .call1(py, (params,))
+                .and_then(|result| result.extract::<String>(py))
+        })
+        .map_err(|e| BlenderApiError::OperationFailed {
+            message: format!("Python handler for {operation} failed: {e}"),
+        })
+    }
+}
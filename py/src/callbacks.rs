@@ -0,0 +1,103 @@
+//! Response delivery through a callback, so addons don't need a modal operator polling
+//! `try_recv_response`.
+//!
+//! Responses queue up in the bridge until a `bpy.app.timers` timer drains them on Blender's
+//! main thread, where the callback is free to use `bpy`. Only responses to `send_message` and
+//! the other non-blocking functions are delivered; those a blocking operation function waits
+//! on stay with it.
+
+use crate::{bridge, describe};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::sync::Mutex;
+
+// Called with `(request_id, response)` for every response
+static CALLBACK: Mutex<Option<Py<PyAny>>> = Mutex::new(None);
+
+// The registered `ResponseTimer`, kept to unregister it again
+static TIMER: Mutex<Option<Py<PyAny>>> = Mutex::new(None);
+
+fn lock_failed<T>(_: T) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Failed to lock response callback")
+}
+
+/// Timer function for `bpy.app.timers`, returning the seconds until it should run again.
+#[pyclass]
+struct ResponseTimer {
+    interval: f64,
+}
+
+#[pymethods]
+impl ResponseTimer {
+    fn __call__(&self, py: Python<'_>) -> PyResult<Option<f64>> {
+        let Some(callback) = CALLBACK
+            .lock()
+            .map_err(lock_failed)?
+            .as_ref()
+            .map(|callback| callback.clone_ref(py))
+        else {
+            // Unregistered, returning None stops the timer
+            return Ok(None);
+        };
+        let Ok(bridge) = bridge() else {
+            return Ok(Some(self.interval));
+        };
+
+        while let Some(reply) = bridge.try_recv_reply() {
+            // An exception in the callback shouldn't stop delivery of the responses after it
+            if let Err(e) = callback.call1(py, (reply.id, describe(reply.response))) {
+                e.print(py);
+            }
+        }
+
+        Ok(Some(self.interval))
+    }
+}
+
+fn unregister_timer(py: Python<'_>) -> PyResult<()> {
+    let Some(timer) = TIMER.lock().map_err(lock_failed)?.take() else {
+        return Ok(());
+    };
+    let timers = py.import_bound("bpy")?.getattr("app")?.getattr("timers")?;
+    if timers
+        .call_method1("is_registered", (&timer,))?
+        .is_truthy()?
+    {
+        timers.call_method1("unregister", (timer,))?;
+    }
+    Ok(())
+}
+
+/// Call `callback(request_id, response)` on Blender's main thread for every response, checking
+/// for new ones every `interval` seconds. Replaces any callback registered before.
+#[pyfunction]
+#[pyo3(signature = (callback, interval=0.05))]
+fn register_response_callback(py: Python<'_>, callback: Py<PyAny>, interval: f64) -> PyResult<()> {
+    unregister_timer(py)?;
+    *CALLBACK.lock().map_err(lock_failed)? = Some(callback);
+
+    let timer = Py::new(py, ResponseTimer { interval })?.into_any();
+    // Persistent so delivery carries on after a new .blend file is loaded
+    let kwargs = PyDict::new_bound(py);
+    kwargs.set_item("persistent", true)?;
+    py.import_bound("bpy")?
+        .getattr("app")?
+        .getattr("timers")?
+        .call_method("register", (&timer,), Some(&kwargs))?;
+    *TIMER.lock().map_err(lock_failed)? = Some(timer);
+
+    Ok(())
+}
+
+/// Stop calling the response callback. Responses queue up for `try_recv_response` again.
+#[pyfunction]
+fn unregister_response_callback(py: Python<'_>) -> PyResult<()> {
+    *CALLBACK.lock().map_err(lock_failed)? = None;
+    unregister_timer(py)
+}
+
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(register_response_callback, m)?)?;
+    m.add_function(wrap_pyfunction!(unregister_response_callback, m)?)?;
+    Ok(())
+}
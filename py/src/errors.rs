@@ -0,0 +1,223 @@
+//! Exceptions raised by the operation functions, so addon code can catch the failures it
+//! expects with `except cuttle_py.ObjectNotFound:` rather than inspecting messages.
+//!
+//! All of them derive from `CuttleError`, itself a `RuntimeError`.
+
+// `create_exception!` checks pyo3's `gil-refs` feature from within this crate
+#![allow(unexpected_cfgs)]
+
+use cuttle::{BridgeError, ServiceResponse};
+use cuttle_blender_api::BlenderApiError;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyRuntimeError, PyTimeoutError};
+use pyo3::prelude::*;
+
+create_exception!(
+    cuttle_py,
+    CuttleError,
+    PyRuntimeError,
+    "An operation the services couldn't perform."
+);
+create_exception!(
+    cuttle_py,
+    ObjectNotFound,
+    CuttleError,
+    "No object has the given name."
+);
+create_exception!(
+    cuttle_py,
+    MaterialNotFound,
+    CuttleError,
+    "No material has the given name."
+);
+create_exception!(
+    cuttle_py,
+    InvalidParameters,
+    CuttleError,
+    "The operation's parameters were rejected."
+);
+create_exception!(
+    cuttle_py,
+    ParseError,
+    CuttleError,
+    "The source isn't valid cuttle. The message is the error report."
+);
+
+// The exception a failure raises, worked out apart from the `PyErr` so it can be tested
+// without Python
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Exception {
+    Cuttle,
+    ObjectNotFound,
+    MaterialNotFound,
+    InvalidParameters,
+    Timeout,
+}
+
+impl Exception {
+    fn new_err(self, message: String) -> PyErr {
+        match self {
+            Exception::Cuttle => CuttleError::new_err(message),
+            Exception::ObjectNotFound => ObjectNotFound::new_err(message),
+            Exception::MaterialNotFound => MaterialNotFound::new_err(message),
+            Exception::InvalidParameters => InvalidParameters::new_err(message),
+            Exception::Timeout => PyErr::new::<PyTimeoutError, _>(message),
+        }
+    }
+}
+
+fn api_exception(error: &BlenderApiError) -> Exception {
+    match error {
+        BlenderApiError::ObjectNotFound { .. } => Exception::ObjectNotFound,
+        BlenderApiError::MaterialNotFound { .. } | BlenderApiError::PresetNotFound { .. } => {
+            Exception::MaterialNotFound
+        }
+        BlenderApiError::InvalidParameters { .. } => Exception::InvalidParameters,
+        _ => Exception::Cuttle,
+    }
+}
+
+fn failure_exception(response: ServiceResponse) -> (Exception, String) {
+    match response {
+        ServiceResponse::Error(message) => {
+            let error = BlenderApiError::from_message(&message);
+            (api_exception(&error), error.to_string())
+        }
+        ServiceResponse::TimedOut {
+            operation,
+            timeout_ms,
+            attempts,
+        } => (
+            Exception::Timeout,
+            format!("{operation} timed out after {timeout_ms}ms ({attempts} attempt(s))"),
+        ),
+        ServiceResponse::Stopped => (Exception::Cuttle, "Services stopped".to_string()),
+        other => (Exception::Cuttle, format!("Unexpected response: {other:?}")),
+    }
+}
+
+fn bridge_exception(error: &BridgeError) -> Exception {
+    match error {
+        BridgeError::TimedOut { .. } => Exception::Timeout,
+        BridgeError::BridgeClosed => Exception::Cuttle,
+    }
+}
+
+/// The exception for a response the operation doesn't answer with.
+pub fn failure(response: ServiceResponse) -> PyErr {
+    let (exception, message) = failure_exception(response);
+    exception.new_err(message)
+}
+
+/// The exception for a message the bridge couldn't deliver or get a response to.
+pub fn bridge_error(error: BridgeError) -> PyErr {
+    bridge_exception(&error).new_err(error.to_string())
+}
+
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("CuttleError", py.get_type_bound::<CuttleError>())?;
+    m.add("ObjectNotFound", py.get_type_bound::<ObjectNotFound>())?;
+    m.add("MaterialNotFound", py.get_type_bound::<MaterialNotFound>())?;
+    m.add(
+        "InvalidParameters",
+        py.get_type_bound::<InvalidParameters>(),
+    )?;
+    m.add("ParseError", py.get_type_bound::<ParseError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_service_errors_raise_their_exception() {
+        let cases = [
+            (
+                BlenderApiError::ObjectNotFound {
+                    name: "Cube".to_string(),
+                },
+                Exception::ObjectNotFound,
+            ),
+            (
+                BlenderApiError::MaterialNotFound {
+                    name: "Red".to_string(),
+                },
+                Exception::MaterialNotFound,
+            ),
+            (
+                BlenderApiError::PresetNotFound {
+                    name: "gold".to_string(),
+                },
+                Exception::MaterialNotFound,
+            ),
+            (
+                BlenderApiError::InvalidParameters {
+                    message: "size must be positive, got 0".to_string(),
+                },
+                Exception::InvalidParameters,
+            ),
+            (
+                BlenderApiError::ObjectAlreadyExists {
+                    name: "Cube".to_string(),
+                },
+                Exception::Cuttle,
+            ),
+            (
+                BlenderApiError::MaterialAlreadyExists {
+                    name: "Red".to_string(),
+                },
+                Exception::Cuttle,
+            ),
+            (
+                BlenderApiError::OperationFailed {
+                    message: "Blender crashed".to_string(),
+                },
+                Exception::Cuttle,
+            ),
+        ];
+        for (error, exception) in cases {
+            let message = error.to_string();
+            assert_eq!(
+                failure_exception(ServiceResponse::Error(message.clone())),
+                (exception, message)
+            );
+        }
+    }
+
+    #[test]
+    fn test_other_responses_raise_their_exception() {
+        let timed_out = ServiceResponse::TimedOut {
+            operation: "get_world".to_string(),
+            timeout_ms: 500,
+            attempts: 3,
+        };
+        assert_eq!(
+            failure_exception(timed_out),
+            (
+                Exception::Timeout,
+                "get_world timed out after 500ms (3 attempt(s))".to_string()
+            )
+        );
+        assert_eq!(
+            failure_exception(ServiceResponse::Stopped),
+            (Exception::Cuttle, "Services stopped".to_string())
+        );
+        assert_eq!(
+            failure_exception(ServiceResponse::Pong),
+            (Exception::Cuttle, "Unexpected response: Pong".to_string())
+        );
+
+        let timed_out = BridgeError::TimedOut {
+            id: 1,
+            timeout: Duration::from_secs(5),
+        };
+        assert_eq!(bridge_exception(&timed_out), Exception::Timeout);
+        assert_eq!(
+            bridge_exception(&BridgeError::BridgeClosed),
+            Exception::Cuttle
+        );
+    }
+}
@@ -0,0 +1,107 @@
+//! The cuttle language, so the Blender addon can check and show cuttle files without running
+//! the CLI, and apply them to the scene through the services. Only `apply_source` needs the
+//! services running.
+
+use crate::errors::{ParseError, failure};
+use crate::operations::to_python;
+use crate::request;
+use cuttle::{ServiceMessage, ServiceResponse};
+use cuttle_blender_api::{ApplyGeometryNodesParams, CreateMeshParams};
+use cuttle_lang::{BlenderNodeGraph, ErrorReporter, ParseError as LangError, parse_geometry_nodes};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+// Plain text, since Blender's UI and console don't render ANSI colors
+fn report(errors: &[LangError], source: &str) -> String {
+    ErrorReporter::new().report_plain(errors, source, "<source>")
+}
+
+/// The node graph `source` parses to, as a dict of `nodes` and `connections`. Raises
+/// `ParseError` with the error report when it doesn't parse.
+#[pyfunction]
+fn parse(py: Python<'_>, source: &str) -> PyResult<PyObject> {
+    match parse_geometry_nodes(source) {
+        Ok(graph) => to_python(py, &graph),
+        Err(errors) => Err(ParseError::new_err(report(&errors, source))),
+    }
+}
+
+/// The error report for `source`, empty when it parses.
+#[pyfunction]
+fn parse_errors(source: &str) -> String {
+    parse_geometry_nodes(source)
+        .err()
+        .map(|errors| report(&errors, source))
+        .unwrap_or_default()
+}
+
+// Creates `object` as an empty mesh for the modifier to generate into when there isn't one,
+// returning its name and whether it was created
+fn modifier_target(py: Python<'_>, object: String) -> PyResult<(String, bool)> {
+    match request(py, ServiceMessage::ListObjects)? {
+        ServiceResponse::ObjectList(names) if names.contains(&object) => {
+            return Ok((object, false));
+        }
+        ServiceResponse::ObjectList(_) => {}
+        other => return Err(failure(other)),
+    }
+
+    match request(
+        py,
+        ServiceMessage::CreateMesh(CreateMeshParams {
+            name: object.clone(),
+            ..Default::default()
+        }),
+    )? {
+        ServiceResponse::Created => Ok((object, true)),
+        ServiceResponse::CreatedAs(name) => Ok((name, true)),
+        other => Err(failure(other)),
+    }
+}
+
+/// Parse `source` and add it to `object` as a geometry nodes modifier, creating `object` when
+/// it doesn't exist. Returns a dict of the `object`, whether it was `created_object`, the
+/// `modifier` name, whether the graph was `unchanged` from the one last applied to `object` and
+/// so left alone, and the Blender type of every node in `nodes`. `force` applies it regardless.
+#[pyfunction]
+#[pyo3(signature = (source, object="Cuttle".to_string(), force=false))]
+fn apply_source(py: Python<'_>, source: &str, object: String, force: bool) -> PyResult<PyObject> {
+    let graph = parse_geometry_nodes(source)
+        .map_err(|errors| ParseError::new_err(report(&errors, source)))?;
+    let graph = BlenderNodeGraph::try_from(graph)
+        .map_err(|error| ParseError::new_err(error.to_string()))?;
+    let nodes = graph
+        .nodes
+        .iter()
+        .map(|node| node.node_type.clone())
+        .collect::<Vec<_>>();
+
+    let (object, created_object) = modifier_target(py, object)?;
+    let (modifier, unchanged) = match request(
+        py,
+        ServiceMessage::ApplyGeometryNodes(ApplyGeometryNodesParams {
+            object: object.clone(),
+            graph,
+            force,
+        }),
+    )? {
+        ServiceResponse::GeometryNodesApplied(modifier) => (modifier, false),
+        ServiceResponse::GeometryNodesUnchanged(modifier) => (modifier, true),
+        other => return Err(failure(other)),
+    };
+
+    let summary = PyDict::new_bound(py);
+    summary.set_item("object", object)?;
+    summary.set_item("created_object", created_object)?;
+    summary.set_item("modifier", modifier)?;
+    summary.set_item("unchanged", unchanged)?;
+    summary.set_item("nodes", nodes)?;
+    Ok(summary.into_any().unbind())
+}
+
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(parse, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_errors, m)?)?;
+    m.add_function(wrap_pyfunction!(apply_source, m)?)?;
+    Ok(())
+}
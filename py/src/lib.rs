@@ -1,76 +1,263 @@
 #![allow(clippy::useless_conversion)]
 #![allow(unsafe_op_in_unsafe_fn)]
 
-use cuttle::{PyBridge, ServiceMessage, ServiceResponse};
+mod api;
+mod callbacks;
+mod errors;
+mod lang;
+mod logging;
+mod mesh;
+mod msgbus;
+mod operations;
+mod raw;
+mod service;
+mod types;
+
+pub use api::{PyBlenderApi, PyHandlers};
+pub use logging::PythonLogLayer;
+pub use service::CuttleService;
+pub use types::{PyMaterialData, PyObjectData, PyVec3};
+
+use crate::errors::bridge_error;
+use cuttle::{BridgeState, BridgeStatus, PyBridge, ServiceMessage, ServiceResponse};
 use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt};
+
+// The PyBridge the module functions use, replaced when services start again. Its channels are
+// shared by every thread, so nothing waits on another caller's response to send or receive.
+static BRIDGE: Mutex<Option<Arc<PyBridge>>> = Mutex::new(None);
 
-// Global PyBridge instance
-static BRIDGE: OnceLock<Arc<Mutex<PyBridge>>> = OnceLock::new();
+// Python callables registered for BlenderApi operations, used when services start
+static HANDLERS: OnceLock<Mutex<HashMap<String, Py<PyAny>>>> = OnceLock::new();
 
+/// Set up cuttle's logging, to `log_file` or the console. With `logger`, a `logging.Logger` or
+/// the name of one, records go to it instead, along with `log_file` when that is given too.
 #[pyfunction]
-#[pyo3(signature = (log_file=None))]
-fn init_logging(log_file: Option<&str>) -> PyResult<()> {
-    cuttle::init_logging(log_file);
-    Ok(())
+#[pyo3(signature = (log_file=None, logger=None))]
+fn init_logging(
+    py: Python<'_>,
+    log_file: Option<&str>,
+    logger: Option<Bound<'_, PyAny>>,
+) -> PyResult<()> {
+    let Some(logger) = logger else {
+        cuttle::init_logging(log_file);
+        return Ok(());
+    };
+    let logger = if logger.is_instance_of::<pyo3::types::PyString>() {
+        py.import_bound("logging")?
+            .call_method1("getLogger", (logger,))?
+    } else {
+        logger
+    };
+
+    let file_layer = log_file
+        .map(|path| {
+            std::fs::File::create(path).map_err(|e| {
+                PyErr::new::<pyo3::exceptions::PyOSError, _>(format!(
+                    "Failed to create log file {path}: {e}"
+                ))
+            })
+        })
+        .transpose()?
+        .map(|file| fmt::layer().with_writer(file));
+
+    tracing_subscriber::registry()
+        .with(PythonLogLayer::new(logger)?)
+        .with(file_layer)
+        .try_init()
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))
 }
 
+/// Register the Python callable that performs a BlenderApi operation, see `PyBlenderApi`.
 #[pyfunction]
-fn start_services() -> PyResult<()> {
+fn register_handler(operation: String, handler: Py<PyAny>) -> PyResult<()> {
+    if bridge().is_ok_and(|bridge| bridge.state() == BridgeState::Started) {
+        return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+            "Handlers must be registered before services are started",
+        ));
+    }
+
+    HANDLERS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .map_err(|_| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Failed to lock handlers"))?
+        .insert(operation, handler);
+
+    Ok(())
+}
+
+fn lock_bridge() -> PyResult<std::sync::MutexGuard<'static, Option<Arc<PyBridge>>>> {
+    BRIDGE
+        .lock()
+        .map_err(|_| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Failed to lock bridge"))
+}
+
+/// Start the runtime and make it the one the module functions use.
+pub(crate) fn start(py: Python<'_>) -> PyResult<Arc<PyBridge>> {
+    let mut current = lock_bridge()?;
+    if current
+        .as_ref()
+        .is_some_and(|bridge| bridge.state() == BridgeState::Started)
+    {
+        return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+            "Services already started",
+        ));
+    }
+
     let (mut bridge, async_bridge) = PyBridge::new();
+
+    // Without registered handlers the runtime keeps using the mock
+    if let Some(handlers) = HANDLERS.get() {
+        let handlers = handlers.lock().map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Failed to lock handlers")
+        })?;
+        if !handlers.is_empty() {
+            let handlers = handlers
+                .iter()
+                .map(|(operation, handler)| (operation.clone(), handler.clone_ref(py)))
+                .collect();
+            bridge.set_blender_transport(PyHandlers::new(handlers));
+        }
+    }
+
     bridge.start_runtime(async_bridge);
 
-    BRIDGE.set(Arc::new(Mutex::new(bridge))).map_err(|_| {
-        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Services already started")
-    })?;
+    let bridge = Arc::new(bridge);
+    let previous = current.replace(bridge.clone());
+    drop(current);
+    // Dropping a bridge joins its runtime thread, which may need the GIL to log on its way out
+    py.allow_threads(move || drop(previous));
+    Ok(bridge)
+}
 
+/// Stop `bridge`'s runtime and wait for its thread, no longer using it for the module functions.
+pub(crate) fn stop(py: Python<'_>, bridge: Arc<PyBridge>) -> PyResult<()> {
+    let mut current = lock_bridge()?;
+    if current
+        .as_ref()
+        .is_some_and(|current| Arc::ptr_eq(current, &bridge))
+    {
+        *current = None;
+    }
+    drop(current);
+
+    py.allow_threads(move || bridge.stop());
     Ok(())
 }
 
 #[pyfunction]
-fn send_message(msg: String) -> PyResult<()> {
-    let bridge = BRIDGE
-        .get()
-        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Services not started"))?;
+fn start_services(py: Python<'_>) -> PyResult<()> {
+    start(py).map(|_| ())
+}
 
-    let bridge = bridge
-        .lock()
-        .map_err(|_| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Failed to lock bridge"))?;
+/// Stop the services and wait for the runtime's thread to exit. Does nothing when they aren't
+/// running. Call this from the addon's `unregister` so reloading it doesn't leave a runtime
+/// behind; registered handlers are kept for the next start.
+#[pyfunction]
+fn stop_services(py: Python<'_>) -> PyResult<()> {
+    let bridge = lock_bridge()?.clone();
+    match bridge {
+        Some(bridge) => stop(py, bridge),
+        None => Ok(()),
+    }
+}
+
+/// Stop the services if they are running and start them again, with the handlers registered
+/// now.
+#[pyfunction]
+fn restart_services(py: Python<'_>) -> PyResult<()> {
+    stop_services(py)?;
+    start(py).map(|_| ())
+}
+
+/// The services' health as a dict: `state` (`"not_started"`, `"running"`, `"stopping"` or
+/// `"stopped"`), `services` names, `queued_messages` not yet picked up, `pending_responses` not
+/// yet received and `uptime` in seconds, `None` unless running. The state is `"not_started"`
+/// again once `stop_services` has finished.
+#[pyfunction]
+fn status(py: Python<'_>) -> PyResult<PyObject> {
+    let status = lock_bridge()?
+        .as_ref()
+        .map(|bridge| bridge.status())
+        .unwrap_or(BridgeStatus {
+            state: BridgeState::NotStarted,
+            services: Vec::new(),
+            queued_messages: 0,
+            pending_replies: 0,
+            uptime: None,
+        });
+
+    let state = match status.state {
+        BridgeState::NotStarted => "not_started",
+        BridgeState::Started => "running",
+        BridgeState::Stopping => "stopping",
+        BridgeState::Stopped => "stopped",
+    };
+    let dict = PyDict::new_bound(py);
+    dict.set_item("state", state)?;
+    dict.set_item("services", status.services)?;
+    dict.set_item("queued_messages", status.queued_messages)?;
+    dict.set_item("pending_responses", status.pending_replies)?;
+    dict.set_item("uptime", status.uptime.map(|uptime| uptime.as_secs_f64()))?;
+    Ok(dict.into_any().unbind())
+}
+
+fn bridge() -> PyResult<Arc<PyBridge>> {
+    lock_bridge()?
+        .clone()
+        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Services not started"))
+}
+
+/// Send a message to the services, returning its request id. The response to it is returned
+/// along with the same id.
+#[pyfunction]
+fn send_message(py: Python<'_>, msg: String) -> PyResult<u64> {
+    let bridge = bridge()?;
 
     let service_msg = match msg.as_str() {
         "ping" => ServiceMessage::Ping,
         "stop" => ServiceMessage::Stop,
-        _ => {
-            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
-                "Unknown message: {msg}"
-            )));
-        }
+        // Anything else must be a JSON encoded message, e.g. a batch of operations
+        other => serde_json::from_str(other).map_err(|_| {
+            PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Unknown message: {msg}"))
+        })?,
     };
 
-    bridge.send(service_msg).map_err(|e| {
-        PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Send failed: {e}"))
-    })?;
-
-    Ok(())
+    py.allow_threads(move || bridge.send(service_msg))
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Send failed: {e}")))
 }
 
-#[pyfunction]
-fn try_recv_response() -> PyResult<Option<String>> {
-    let bridge = BRIDGE
-        .get()
-        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Services not started"))?;
+/// Send `message` and wait for its response. The GIL is released while waiting, since Python
+/// handlers need it on the runtime thread to answer. Gives up a little after the services' own
+/// timeout for the message, raising `TimeoutError`, and raises `CuttleError` when the runtime
+/// has stopped.
+pub(crate) fn request(py: Python<'_>, message: ServiceMessage) -> PyResult<ServiceResponse> {
+    let bridge = bridge()?;
 
-    let bridge = bridge
-        .lock()
-        .map_err(|_| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>("Failed to lock bridge"))?;
-
-    let response = bridge.try_recv();
+    py.allow_threads(move || {
+        let timeout = bridge.response_timeout(&message);
+        let id = bridge.send_awaited(message).map_err(bridge_error)?;
+        bridge.recv_reply_to(id, timeout).map_err(bridge_error)
+    })
+}
 
-    let result = response.map(|resp| match resp {
+// The text form of a response, as `try_recv_response` and `recv_response` return it
+fn describe(response: ServiceResponse) -> String {
+    match response {
         ServiceResponse::Pong => "pong".to_string(),
+        ServiceResponse::TimedOut {
+            operation,
+            timeout_ms,
+            attempts,
+        } => format!("timed_out: {operation} after {timeout_ms}ms ({attempts} attempt(s))"),
         ServiceResponse::Stopped => "stopped".to_string(),
         ServiceResponse::Error(msg) => format!("error: {msg}"),
         ServiceResponse::Created => "created".to_string(),
+        ServiceResponse::CreatedAs(name) => format!("created_as: {name}"),
         ServiceResponse::ObjectData(data) => format!(
             "object_data: {}",
             serde_json::to_string(&data).unwrap_or_else(|_| "invalid_data".to_string())
@@ -79,20 +266,115 @@ fn try_recv_response() -> PyResult<Option<String>> {
             "material_data: {}",
             serde_json::to_string(&data).unwrap_or_else(|_| "invalid_data".to_string())
         ),
+        ServiceResponse::MaterialNodes(graph) => format!(
+            "material_nodes: {}",
+            serde_json::to_string(&graph).unwrap_or_else(|_| "invalid_data".to_string())
+        ),
+        ServiceResponse::MeshGeometry(geometry) => format!(
+            "mesh_geometry: {}",
+            serde_json::to_string(&geometry).unwrap_or_else(|_| "invalid_data".to_string())
+        ),
+        ServiceResponse::WorldData(data) => format!(
+            "world_data: {}",
+            serde_json::to_string(&data).unwrap_or_else(|_| "invalid_data".to_string())
+        ),
+        ServiceResponse::SceneSettings(settings) => format!(
+            "scene_settings: {}",
+            serde_json::to_string(&settings).unwrap_or_else(|_| "invalid_data".to_string())
+        ),
+        ServiceResponse::Units(units) => format!(
+            "units: {}",
+            serde_json::to_string(&units).unwrap_or_else(|_| "invalid_data".to_string())
+        ),
+        ServiceResponse::Imported(list) => format!("imported: {}", list.join(",")),
+        ServiceResponse::Exported(list) => format!("exported: {}", list.join(",")),
+        ServiceResponse::SceneIr(scene) => format!(
+            "scene_ir: {}",
+            serde_json::to_string(&scene).unwrap_or_else(|_| "invalid_data".to_string())
+        ),
+        ServiceResponse::SceneState(state) => format!(
+            "scene_state: {}",
+            serde_json::to_string(&state).unwrap_or_else(|_| "invalid_data".to_string())
+        ),
+        ServiceResponse::Children(list) => format!("children: {}", list.join(",")),
+        ServiceResponse::Ancestors(list) => format!("ancestors: {}", list.join(",")),
+        ServiceResponse::Dependencies(dependencies) => format!(
+            "dependencies: {}",
+            serde_json::to_string(&dependencies).unwrap_or_else(|_| "invalid_data".to_string())
+        ),
+        ServiceResponse::BoundingBox(bounds) => format!(
+            "bounding_box: {}",
+            serde_json::to_string(&bounds).unwrap_or_else(|_| "invalid_data".to_string())
+        ),
+        ServiceResponse::SelectionChanged => "selection_changed".to_string(),
+        ServiceResponse::Selection(list) => format!("selection: {}", list.join(",")),
+        ServiceResponse::GeometryNodesApplied(modifier) => {
+            format!("geometry_nodes_applied: {modifier}")
+        }
+        ServiceResponse::GeometryNodesUnchanged(modifier) => {
+            format!("geometry_nodes_unchanged: {modifier}")
+        }
+        ServiceResponse::TextureBaked(baked) => format!(
+            "texture_baked: {}",
+            serde_json::to_string(&baked).unwrap_or_else(|_| "invalid_data".to_string())
+        ),
+        ServiceResponse::Property(value) => format!("property: {value}"),
+        ServiceResponse::Objects(objects) => format!(
+            "objects: {}",
+            serde_json::to_string(&objects).unwrap_or_else(|_| "invalid_data".to_string())
+        ),
         ServiceResponse::ObjectList(list) => format!("object_list: {}", list.join(",")),
         ServiceResponse::MaterialList(list) => format!("material_list: {}", list.join(",")),
         ServiceResponse::MeshList(list) => format!("mesh_list: {}", list.join(",")),
         ServiceResponse::SceneCleared => "scene_cleared".to_string(),
-    });
+        ServiceResponse::BlendSaved => "blend_saved".to_string(),
+        ServiceResponse::BlendOpened => "blend_opened".to_string(),
+        ServiceResponse::EventReceived => "event_received".to_string(),
+        ServiceResponse::BatchResults(results) => format!(
+            "batch_results: {}",
+            serde_json::to_string(&results).unwrap_or_else(|_| "invalid_data".to_string())
+        ),
+    }
+}
+
+/// The next response as `(request_id, text)`, or `None` if there isn't one yet.
+#[pyfunction]
+fn try_recv_response(py: Python<'_>) -> PyResult<Option<(u64, String)>> {
+    let bridge = bridge()?;
+
+    let reply = py.allow_threads(move || bridge.try_recv_reply());
+    Ok(reply.map(|reply| (reply.id, describe(reply.response))))
+}
+
+/// Wait up to `timeout_ms` for the next response, as `try_recv_response` returns it, or `None`
+/// if nothing arrived in time. The GIL is released while waiting.
+#[pyfunction]
+fn recv_response(py: Python<'_>, timeout_ms: u64) -> PyResult<Option<(u64, String)>> {
+    let bridge = bridge()?;
 
-    Ok(result)
+    let reply = py.allow_threads(move || bridge.recv_timeout(Duration::from_millis(timeout_ms)));
+    Ok(reply.map(|reply| (reply.id, describe(reply.response))))
 }
 
 #[pymodule]
 fn cuttle_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(init_logging, m)?)?;
+    m.add_function(wrap_pyfunction!(register_handler, m)?)?;
     m.add_function(wrap_pyfunction!(start_services, m)?)?;
+    m.add_function(wrap_pyfunction!(stop_services, m)?)?;
+    m.add_function(wrap_pyfunction!(restart_services, m)?)?;
+    m.add_function(wrap_pyfunction!(status, m)?)?;
     m.add_function(wrap_pyfunction!(send_message, m)?)?;
     m.add_function(wrap_pyfunction!(try_recv_response, m)?)?;
+    m.add_function(wrap_pyfunction!(recv_response, m)?)?;
+    m.add_class::<CuttleService>()?;
+    callbacks::register(m)?;
+    errors::register(m)?;
+    lang::register(m)?;
+    mesh::register(m)?;
+    msgbus::register(m)?;
+    types::register(m)?;
+    operations::register(m)?;
+    raw::register(m)?;
     Ok(())
 }
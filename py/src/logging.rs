@@ -0,0 +1,140 @@
+//! cuttle's tracing events as Python log records, so they show up in Blender's console with
+//! the addon's own logs and go through the same handlers and levels.
+
+use pyo3::prelude::*;
+use std::fmt::{Debug, Write};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::Layer;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::Context;
+
+// Python's `logging` levels, with trace below `logging.DEBUG` as there is no standard one
+fn python_level(level: &Level) -> u8 {
+    match *level {
+        Level::TRACE => 5,
+        Level::DEBUG => 10,
+        Level::INFO => 20,
+        Level::WARN => 30,
+        Level::ERROR => 40,
+    }
+}
+
+// The event's message followed by its other fields as `name=value`
+#[derive(Default)]
+struct Message(String);
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        let separator = if self.0.is_empty() { "" } else { " " };
+        if field.name() == "message" {
+            self.0.insert_str(0, &format!("{value:?}{separator}"));
+        } else {
+            let _ = write!(self.0, "{separator}{}={value:?}", field.name());
+        }
+    }
+}
+
+// The `LevelFilter` letting through what a Python logger at `level` would log
+fn level_filter(level: i64) -> LevelFilter {
+    match level {
+        ..=5 => LevelFilter::TRACE,
+        6..=10 => LevelFilter::DEBUG,
+        11..=20 => LevelFilter::INFO,
+        21..=30 => LevelFilter::WARN,
+        31..=40 => LevelFilter::ERROR,
+        _ => LevelFilter::OFF,
+    }
+}
+
+/// Where `PythonLogLayer` sends records.
+pub trait LogSink: Send + Sync + 'static {
+    fn log(&self, level: u8, message: String);
+}
+
+/// A `logging.Logger`.
+impl LogSink for Py<PyAny> {
+    fn log(&self, level: u8, message: String) {
+        Python::with_gil(|py| {
+            // A failing handler can't be logged about without recursing, so Python reports it
+            if let Err(e) = self.call_method1(py, "log", (level, message)) {
+                e.print(py);
+            }
+        });
+    }
+}
+
+/// Logs every event the logger's level lets through with `logger.log(level, message)`.
+///
+/// Events are filtered before taking the GIL, so the runtime threads don't contend for it over
+/// debug events nobody sees. The level is the logger's effective level when the layer is
+/// created; changing it afterwards only filters on the Python side.
+pub struct PythonLogLayer<L = Py<PyAny>> {
+    logger: L,
+    max_level: LevelFilter,
+}
+
+impl PythonLogLayer {
+    pub fn new(logger: Bound<'_, PyAny>) -> PyResult<Self> {
+        let level: i64 = logger.call_method0("getEffectiveLevel")?.extract()?;
+        Ok(Self::with_level(logger.unbind(), level_filter(level)))
+    }
+}
+
+impl<L: LogSink> PythonLogLayer<L> {
+    fn with_level(logger: L, max_level: LevelFilter) -> Self {
+        Self { logger, max_level }
+    }
+}
+
+impl<S: Subscriber, L: LogSink> Layer<S> for PythonLogLayer<L> {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if event.metadata().level() > &self.max_level {
+            return;
+        }
+
+        let mut message = Message::default();
+        event.record(&mut message);
+        self.logger
+            .log(python_level(event.metadata().level()), message.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[derive(Clone, Default)]
+    struct Records(Arc<Mutex<Vec<(u8, String)>>>);
+
+    impl LogSink for Records {
+        fn log(&self, level: u8, message: String) {
+            self.0
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push((level, message));
+        }
+    }
+
+    #[test]
+    fn test_filtered_events_are_not_logged() {
+        let records = Records::default();
+        // A logger at `logging.WARNING`
+        let layer = PythonLogLayer::with_level(records.clone(), level_filter(30));
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!("Debug message");
+            tracing::info!("Info message");
+            tracing::warn!(operation = "get_world", "Timed out");
+        });
+
+        let records = records.0.lock().unwrap_or_else(|e| e.into_inner());
+        assert_eq!(
+            *records,
+            vec![(30, "Timed out operation=\"get_world\"".to_string())]
+        );
+    }
+}
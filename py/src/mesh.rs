@@ -0,0 +1,355 @@
+//! Mesh data as numpy arrays.
+//!
+//! Meshes with 100k+ vertices are far too slow to pass around as nested lists, one Python
+//! object per coordinate. Instead, `create_mesh_from_data` reads any object supporting the
+//! buffer protocol in one copy, and `get_mesh_geometry` copies the geometry the service sent once
+//! into flat arrays that numpy then views without copying again.
+//!
+//! Arrays use the layout of Blender's `foreach_set`, so they go straight into a `bpy` mesh:
+//!
+//! ```python
+//! geometry = cuttle_py.get_mesh_geometry("Cube")
+//! mesh.vertices.add(len(geometry["vertices"]))
+//! mesh.vertices.foreach_set("co", geometry["vertices"].ravel())
+//! mesh.loops.add(len(geometry["faces"]))
+//! mesh.loops.foreach_set("vertex_index", geometry["faces"])
+//! ```
+
+use crate::errors::failure;
+use crate::request;
+use cuttle::{ServiceMessage, ServiceResponse};
+use cuttle_blender_api::{CreateMeshParams, GetMeshGeometryParams, MeshGeometry, Vec3};
+use pyo3::buffer::{Element, PyBuffer};
+use pyo3::exceptions::{PyBufferError, PyTypeError, PyValueError};
+use pyo3::ffi;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use std::ffi::{CStr, c_int, c_void};
+use std::ptr;
+
+#[derive(Debug)]
+enum ArrayData {
+    Float(Vec<f32>),
+    Int(Vec<i32>),
+}
+
+/// A read only array owning its data, which numpy wraps through the buffer protocol without
+/// copying. `memoryview(array)` works without numpy.
+#[pyclass(module = "cuttle_py", frozen)]
+#[derive(Debug)]
+pub struct MeshArray {
+    data: ArrayData,
+    shape: Vec<isize>,
+    strides: Vec<isize>,
+}
+
+impl MeshArray {
+    fn new(data: ArrayData, shape: Vec<usize>) -> Self {
+        // Both element types are 4 bytes, laid out C contiguous
+        let mut strides = vec![4; shape.len()];
+        for i in (0..shape.len().saturating_sub(1)).rev() {
+            strides[i] = strides[i + 1] * shape[i + 1] as isize;
+        }
+        Self {
+            data,
+            shape: shape.into_iter().map(|n| n as isize).collect(),
+            strides,
+        }
+    }
+
+    fn float(values: Vec<f32>, shape: Vec<usize>) -> Self {
+        Self::new(ArrayData::Float(values), shape)
+    }
+
+    fn int(
+        field: &str,
+        values: impl IntoIterator<Item = usize>,
+        shape: Vec<usize>,
+    ) -> PyResult<Self> {
+        let values = int32(values).map_err(|i| {
+            PyValueError::new_err(format!("{field} value {i} does not fit in an int32 array"))
+        })?;
+        Ok(Self::new(ArrayData::Int(values), shape))
+    }
+}
+
+// Blender's indices are int32, so a larger one can't be handed over; it's returned as the error
+fn int32(values: impl IntoIterator<Item = usize>) -> Result<Vec<i32>, usize> {
+    values
+        .into_iter()
+        .map(|i| i32::try_from(i).map_err(|_| i))
+        .collect()
+}
+
+#[pymethods]
+impl MeshArray {
+    fn __len__(&self) -> usize {
+        self.shape.first().map_or(0, |&n| n as usize)
+    }
+
+    unsafe fn __getbuffer__(
+        slf: Bound<'_, Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        if view.is_null() {
+            return Err(PyBufferError::new_err("View is null"));
+        }
+        if (flags & ffi::PyBUF_WRITABLE) == ffi::PyBUF_WRITABLE {
+            return Err(PyBufferError::new_err("Mesh arrays are read only"));
+        }
+
+        let array = slf.get();
+        let (buf, len, format): (*const c_void, usize, &'static CStr) = match &array.data {
+            ArrayData::Float(values) => (values.as_ptr().cast(), values.len(), c"f"),
+            ArrayData::Int(values) => (values.as_ptr().cast(), values.len(), c"i"),
+        };
+
+        (*view).buf = buf.cast_mut();
+        (*view).len = (len * 4) as isize;
+        (*view).itemsize = 4;
+        (*view).readonly = 1;
+        (*view).ndim = array.shape.len() as c_int;
+        (*view).format = if (flags & ffi::PyBUF_FORMAT) == ffi::PyBUF_FORMAT {
+            format.as_ptr().cast_mut()
+        } else {
+            ptr::null_mut()
+        };
+        // Both live as long as the array, which the view keeps alive through `obj`
+        (*view).shape = if (flags & ffi::PyBUF_ND) == ffi::PyBUF_ND {
+            array.shape.as_ptr().cast_mut()
+        } else {
+            ptr::null_mut()
+        };
+        (*view).strides = if (flags & ffi::PyBUF_STRIDES) == ffi::PyBUF_STRIDES {
+            array.strides.as_ptr().cast_mut()
+        } else {
+            ptr::null_mut()
+        };
+        (*view).suboffsets = ptr::null_mut();
+        (*view).internal = ptr::null_mut();
+        (*view).obj = slf.into_any().into_ptr();
+
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, _view: *mut ffi::Py_buffer) {}
+}
+
+// The buffer's values as one copy, without a Python object per element
+fn read<T: Element + Copy>(buffer: PyBuffer<T>, py: Python<'_>) -> PyResult<(Vec<T>, Vec<usize>)> {
+    Ok((buffer.to_vec(py)?, buffer.shape().to_vec()))
+}
+
+fn read_vertices(vertices: &Bound<'_, PyAny>) -> PyResult<Vec<Vec3>> {
+    let py = vertices.py();
+    let (values, shape) = if let Ok(buffer) = PyBuffer::<f32>::get_bound(vertices) {
+        read(buffer, py)?
+    } else if let Ok(buffer) = PyBuffer::<f64>::get_bound(vertices) {
+        let (values, shape) = read(buffer, py)?;
+        (values.into_iter().map(|v| v as f32).collect(), shape)
+    } else {
+        return Err(PyTypeError::new_err(
+            "vertices must support the buffer protocol, such as a numpy float32 array",
+        ));
+    };
+
+    if values.len() % 3 != 0 || (shape.len() == 2 && shape[1] != 3) {
+        return Err(PyValueError::new_err(format!(
+            "vertices must have shape (n, 3) or (n * 3,), got {shape:?}"
+        )));
+    }
+    Ok(values
+        .chunks_exact(3)
+        .map(|v| Vec3::new(v[0], v[1], v[2]))
+        .collect())
+}
+
+fn read_indices(field: &str, indices: &Bound<'_, PyAny>) -> PyResult<(Vec<usize>, Vec<usize>)> {
+    fn convert<T: Element + Copy + TryInto<usize>>(
+        field: &str,
+        buffer: PyBuffer<T>,
+        py: Python<'_>,
+    ) -> PyResult<(Vec<usize>, Vec<usize>)> {
+        let (values, shape) = read(buffer, py)?;
+        let values = values
+            .into_iter()
+            .map(|i| i.try_into())
+            .collect::<Result<_, _>>()
+            .map_err(|_| PyValueError::new_err(format!("{field} must not be negative")))?;
+        Ok((values, shape))
+    }
+
+    let py = indices.py();
+    // numpy's default integer is int64, Blender's is int32
+    if let Ok(buffer) = PyBuffer::<i32>::get_bound(indices) {
+        convert(field, buffer, py)
+    } else if let Ok(buffer) = PyBuffer::<i64>::get_bound(indices) {
+        convert(field, buffer, py)
+    } else if let Ok(buffer) = PyBuffer::<u32>::get_bound(indices) {
+        convert(field, buffer, py)
+    } else if let Ok(buffer) = PyBuffer::<u64>::get_bound(indices) {
+        convert(field, buffer, py)
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "{field} must support the buffer protocol, such as a numpy int32 array"
+        )))
+    }
+}
+
+fn read_faces(
+    faces: &Bound<'_, PyAny>,
+    face_sizes: Option<&Bound<'_, PyAny>>,
+) -> PyResult<Vec<Vec<usize>>> {
+    let (indices, shape) = read_indices("faces", faces)?;
+
+    let sizes = match face_sizes {
+        Some(face_sizes) => read_indices("face_sizes", face_sizes)?.0,
+        None => match shape[..] {
+            [count, size] => vec![size; count],
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "faces must have shape (n, vertices_per_face) without face_sizes, got \
+                     {shape:?}"
+                )));
+            }
+        },
+    };
+
+    if sizes.iter().sum::<usize>() != indices.len() {
+        return Err(PyValueError::new_err(format!(
+            "face_sizes add up to {} but faces has {} indices",
+            sizes.iter().sum::<usize>(),
+            indices.len()
+        )));
+    }
+
+    let mut remaining = &indices[..];
+    Ok(sizes
+        .into_iter()
+        .map(|size| {
+            let (face, rest) = remaining.split_at(size);
+            remaining = rest;
+            face.to_vec()
+        })
+        .collect())
+}
+
+/// Create a mesh object from `vertices`, an `(n, 3)` float array, and `faces`, either an
+/// `(n, vertices_per_face)` index array or a flat one split by `face_sizes`.
+#[pyfunction]
+#[pyo3(signature = (name, vertices, faces, face_sizes=None, location=(0.0, 0.0, 0.0)))]
+fn create_mesh_from_data(
+    py: Python<'_>,
+    name: String,
+    vertices: &Bound<'_, PyAny>,
+    faces: &Bound<'_, PyAny>,
+    face_sizes: Option<&Bound<'_, PyAny>>,
+    location: (f32, f32, f32),
+) -> PyResult<String> {
+    let (x, y, z) = location;
+    let params = CreateMeshParams {
+        location: Vec3::new(x, y, z),
+        name,
+        vertices: read_vertices(vertices)?,
+        faces: read_faces(faces, face_sizes)?,
+        on_collision: None,
+    };
+
+    match request(py, ServiceMessage::CreateMesh(params.clone()))? {
+        ServiceResponse::Created => Ok(params.name),
+        ServiceResponse::CreatedAs(name) => Ok(name),
+        other => Err(failure(other)),
+    }
+}
+
+/// The object's mesh as a dict of arrays: `vertices` `(n, 3)` float32, `edges` `(n, 2)` int32,
+/// and int32 `faces`, `face_sizes` and `face_materials`, with `faces` holding every face's
+/// vertex indices one after another. Arrays are numpy arrays when numpy is installed and
+/// `MeshArray`s otherwise.
+#[pyfunction]
+fn get_mesh_geometry(py: Python<'_>, name: String) -> PyResult<PyObject> {
+    let geometry = match request(
+        py,
+        ServiceMessage::GetMeshGeometry(GetMeshGeometryParams { name }),
+    )? {
+        ServiceResponse::MeshGeometry(geometry) => geometry,
+        other => return Err(failure(other)),
+    };
+    let MeshGeometry {
+        name,
+        vertices,
+        edges,
+        faces,
+        face_materials,
+    } = geometry;
+
+    let arrays = [
+        (
+            "vertices",
+            MeshArray::float(
+                vertices.iter().flat_map(|v| [v.x, v.y, v.z]).collect(),
+                vec![vertices.len(), 3],
+            ),
+        ),
+        (
+            "edges",
+            MeshArray::int(
+                "edges",
+                edges.iter().flatten().copied(),
+                vec![edges.len(), 2],
+            )?,
+        ),
+        (
+            "faces",
+            MeshArray::int(
+                "faces",
+                faces.iter().flatten().copied(),
+                vec![faces.iter().map(Vec::len).sum()],
+            )?,
+        ),
+        (
+            "face_sizes",
+            MeshArray::int("face_sizes", faces.iter().map(Vec::len), vec![faces.len()])?,
+        ),
+        (
+            "face_materials",
+            MeshArray::int(
+                "face_materials",
+                face_materials.iter().copied(),
+                vec![face_materials.len()],
+            )?,
+        ),
+    ];
+
+    let numpy = py.import_bound("numpy").ok();
+    let dict = PyDict::new_bound(py);
+    dict.set_item("name", name)?;
+    for (key, array) in arrays {
+        let array = Py::new(py, array)?.into_any();
+        match &numpy {
+            Some(numpy) => dict.set_item(key, numpy.call_method1("asarray", (array,))?)?,
+            None => dict.set_item(key, array)?,
+        }
+    }
+    Ok(dict.into_any().unbind())
+}
+
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<MeshArray>()?;
+    m.add_function(wrap_pyfunction!(create_mesh_from_data, m)?)?;
+    m.add_function(wrap_pyfunction!(get_mesh_geometry, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indices_past_int32_are_refused() {
+        assert_eq!(int32([0, 1, i32::MAX as usize]), Ok(vec![0, 1, i32::MAX]));
+        let too_large = i32::MAX as usize + 1;
+        assert_eq!(int32([0, too_large, 2]), Err(too_large));
+    }
+}
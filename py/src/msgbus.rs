@@ -0,0 +1,115 @@
+//! Changes made in the Blender UI, forwarded to the services as `BlenderEvent`s.
+//!
+//! The notify functions are called from msgbus and handler callbacks on Blender's main thread,
+//! so they only queue the event and return its request id rather than waiting for the answer.
+
+use crate::bridge;
+use cuttle::{BlenderEvent, ServiceMessage};
+use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+
+// Registers the callbacks described in `cuttle::bridge::msgbus`. msgbus doesn't say what
+// changed, so the active object and node are reported.
+const SUBSCRIBE: &str = r#"
+import bpy
+import cuttle_py
+
+
+def _on_object_change():
+    obj = bpy.context.view_layer.objects.active
+    if obj is not None:
+        cuttle_py.notify_object_changed(obj.name)
+
+
+def _on_node_change():
+    node = getattr(bpy.context, "active_node", None)
+    if node is not None:
+        cuttle_py.notify_node_changed(node.id_data.name, node.name)
+
+
+@bpy.app.handlers.persistent
+def _on_file_save(*args):
+    cuttle_py.notify_file_saved(bpy.data.filepath)
+
+
+def subscribe(owner):
+    for key, notify in (
+        (bpy.types.Object, _on_object_change),
+        (bpy.types.Node, _on_node_change),
+        (bpy.types.NodeLink, _on_node_change),
+    ):
+        bpy.msgbus.subscribe_rna(key=key, owner=owner, args=(), notify=notify)
+    if _on_file_save not in bpy.app.handlers.save_post:
+        bpy.app.handlers.save_post.append(_on_file_save)
+
+
+def unsubscribe(owner):
+    bpy.msgbus.clear_by_owner(owner)
+    if _on_file_save in bpy.app.handlers.save_post:
+        bpy.app.handlers.save_post.remove(_on_file_save)
+"#;
+
+fn notify(py: Python<'_>, event: BlenderEvent) -> PyResult<u64> {
+    let bridge = bridge()?;
+    py.allow_threads(move || bridge.send(ServiceMessage::Event(event)))
+        .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(format!("Send failed: {e}")))
+}
+
+#[pyfunction]
+fn notify_object_changed(py: Python<'_>, name: String) -> PyResult<u64> {
+    notify(py, BlenderEvent::ObjectChanged { name })
+}
+
+#[pyfunction]
+fn notify_node_changed(py: Python<'_>, tree: String, node: String) -> PyResult<u64> {
+    notify(py, BlenderEvent::NodeChanged { tree, node })
+}
+
+#[pyfunction]
+fn notify_file_saved(py: Python<'_>, path: String) -> PyResult<u64> {
+    notify(py, BlenderEvent::FileSaved { path })
+}
+
+// Loaded once, since unsubscribing has to remove the same handler that was added
+static CALLBACKS: GILOnceCell<Py<PyModule>> = GILOnceCell::new();
+
+fn callbacks(py: Python<'_>) -> PyResult<&Bound<'_, PyModule>> {
+    CALLBACKS
+        .get_or_try_init(py, || {
+            PyModule::from_code_bound(py, SUBSCRIBE, "cuttle_msgbus.py", "cuttle_msgbus")
+                .map(Bound::unbind)
+        })
+        .map(|module| module.bind(py))
+}
+
+/// Forward object, node and file save changes to the services. Returns the msgbus owner,
+/// which `unsubscribe_msgbus` takes to remove the subscriptions again.
+#[pyfunction]
+#[pyo3(signature = (owner=None))]
+fn subscribe_msgbus(py: Python<'_>, owner: Option<PyObject>) -> PyResult<PyObject> {
+    let owner = match owner {
+        Some(owner) => owner,
+        None => py
+            .import_bound("builtins")?
+            .getattr("object")?
+            .call0()?
+            .unbind(),
+    };
+    callbacks(py)?.call_method1("subscribe", (&owner,))?;
+    Ok(owner)
+}
+
+#[pyfunction]
+fn unsubscribe_msgbus(py: Python<'_>, owner: PyObject) -> PyResult<()> {
+    callbacks(py)?.call_method1("unsubscribe", (owner,))?;
+    Ok(())
+}
+
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(notify_object_changed, m)?)?;
+    m.add_function(wrap_pyfunction!(notify_node_changed, m)?)?;
+    m.add_function(wrap_pyfunction!(notify_file_saved, m)?)?;
+    m.add_function(wrap_pyfunction!(subscribe_msgbus, m)?)?;
+    m.add_function(wrap_pyfunction!(unsubscribe_msgbus, m)?)?;
+    Ok(())
+}
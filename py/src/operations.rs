@@ -0,0 +1,865 @@
+//! One Python function per Blender operation.
+//!
+//! Each builds its `ServiceMessage`, waits for the service to answer and returns the result as
+//! Python values: the created name for create operations, `ObjectData` and `MaterialData` for
+//! objects and materials, dicts for other data, lists of names for listings and `None` for
+//! everything else. Failures raise the exceptions in `errors`, or `TimeoutError` for operations
+//! the service gave up on.
+//!
+//! Structured arguments, like node graphs, object filters and property targets, are dicts in
+//! the same form the getters return them in, and enums are their names, like `"METRIC"`.
+//!
+//! Calls block until the service answers, or raise `TimeoutError` a little after the service's
+//! own timeout for the operation. When handlers defer their work to Blender's main thread, call
+//! these from another thread, or the handlers can't run until the call has timed out.
+
+use crate::errors::failure;
+use crate::request;
+use crate::types::{PyMaterialData, PyObjectData};
+use cuttle::{ServiceMessage, ServiceResponse};
+use cuttle_blender_api::{
+    AddRigidBodyParams, ApplyGeometryNodesParams, AssignMaterialParams, BakeTextureParams, Color,
+    CreateCubeParams, CreateInstanceParams, CreateMaterialFromPresetParams, CreateMaterialParams,
+    CreateSphereParams, ExportFormat, ExportSceneParams, GetMaterialNodesParams, GetMaterialParams,
+    GetObjectParams, ImportFileParams, ImportFormat, ObjectFilter, OpenBlendParams,
+    RemoveMaterialSlotParams, Rotation, SaveBlendParams, SelectObjectsParams,
+    SetFaceMaterialsParams, SetLightLinkingParams, SetMaterialNodesParams, SetMaterialSlotParams,
+    SetParentParams, SetShadowVisibilityParams, SetTransformParams, SetWorldParams, UnitSettings,
+    Vec3, WorldBackground,
+};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::path::Path;
+
+fn vec3((x, y, z): (f32, f32, f32)) -> Vec3 {
+    Vec3::new(x, y, z)
+}
+
+fn color((r, g, b, a): (f32, f32, f32, f32)) -> Color {
+    Color::new(r, g, b, a)
+}
+
+// Nested data becomes dicts and lists through `json`, the same shape it has on the wire
+pub(crate) fn to_python<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let json = serde_json::to_string(value).map_err(|e| {
+        PyErr::new::<PyRuntimeError, _>(format!("Failed to serialize response: {e}"))
+    })?;
+    Ok(py
+        .import_bound("json")?
+        .call_method1("loads", (json,))?
+        .unbind())
+}
+
+// Structured arguments come in through `json` too, as the dicts `to_python` would make of them
+fn from_python<T: DeserializeOwned>(
+    py: Python<'_>,
+    name: &str,
+    value: &Bound<'_, PyAny>,
+) -> PyResult<T> {
+    let json: String = py
+        .import_bound("json")?
+        .call_method1("dumps", (value,))?
+        .extract()?;
+    serde_json::from_str(&json)
+        .map_err(|e| PyErr::new::<PyValueError, _>(format!("Invalid {name}: {e}")))
+}
+
+// The responses each kind of operation answers with, anything else being a failure. Kept
+// apart from the requests so they can be tested without Python.
+
+// The name the service created, which differs from `requested` when it had to pick another
+fn created_name(
+    requested: String,
+    response: ServiceResponse,
+) -> Result<String, Box<ServiceResponse>> {
+    match response {
+        ServiceResponse::Created => Ok(requested),
+        ServiceResponse::CreatedAs(name) => Ok(name),
+        other => Err(Box::new(other)),
+    }
+}
+
+fn completed(response: ServiceResponse) -> Result<(), Box<ServiceResponse>> {
+    match response {
+        ServiceResponse::Created
+        | ServiceResponse::SceneCleared
+        | ServiceResponse::SelectionChanged
+        | ServiceResponse::BlendSaved
+        | ServiceResponse::BlendOpened => Ok(()),
+        other => Err(Box::new(other)),
+    }
+}
+
+fn listed_names(response: ServiceResponse) -> Result<Vec<String>, Box<ServiceResponse>> {
+    match response {
+        ServiceResponse::ObjectList(names)
+        | ServiceResponse::MaterialList(names)
+        | ServiceResponse::MeshList(names)
+        | ServiceResponse::Children(names)
+        | ServiceResponse::Ancestors(names)
+        | ServiceResponse::Selection(names)
+        | ServiceResponse::Imported(names)
+        | ServiceResponse::Exported(names) => Ok(names),
+        other => Err(Box::new(other)),
+    }
+}
+
+// The modifier holding the graph, whether or not it had to be updated
+fn applied_modifier(response: ServiceResponse) -> Result<String, Box<ServiceResponse>> {
+    match response {
+        ServiceResponse::GeometryNodesApplied(modifier)
+        | ServiceResponse::GeometryNodesUnchanged(modifier) => Ok(modifier),
+        other => Err(Box::new(other)),
+    }
+}
+
+fn created(py: Python<'_>, requested: String, message: ServiceMessage) -> PyResult<String> {
+    created_name(requested, request(py, message)?).map_err(|response| failure(*response))
+}
+
+fn done(py: Python<'_>, message: ServiceMessage) -> PyResult<()> {
+    completed(request(py, message)?).map_err(|response| failure(*response))
+}
+
+fn names(py: Python<'_>, message: ServiceMessage) -> PyResult<Vec<String>> {
+    listed_names(request(py, message)?).map_err(|response| failure(*response))
+}
+
+fn data(py: Python<'_>, message: ServiceMessage) -> PyResult<PyObject> {
+    match request(py, message)? {
+        ServiceResponse::BoundingBox(bounds) => to_python(py, &bounds),
+        ServiceResponse::Dependencies(dependencies) => to_python(py, &dependencies),
+        ServiceResponse::WorldData(world) => to_python(py, &world),
+        ServiceResponse::SceneSettings(settings) => to_python(py, &settings),
+        ServiceResponse::Units(units) => to_python(py, &units),
+        ServiceResponse::SceneState(state) => to_python(py, &state),
+        ServiceResponse::SceneIr(scene) => to_python(py, &scene),
+        ServiceResponse::MaterialNodes(graph) => to_python(py, &graph),
+        ServiceResponse::TextureBaked(baked) => to_python(py, &baked),
+        ServiceResponse::Property(value) => to_python(py, &value),
+        other => Err(failure(other)),
+    }
+}
+
+fn extension(path: &str) -> &str {
+    Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+}
+
+#[pyfunction]
+#[pyo3(signature = (name=None, location=(0.0, 0.0, 0.0), size=None))]
+fn create_cube(
+    py: Python<'_>,
+    name: Option<String>,
+    location: (f32, f32, f32),
+    size: Option<f32>,
+) -> PyResult<String> {
+    let defaults = CreateCubeParams::default();
+    let params = CreateCubeParams {
+        location: vec3(location),
+        name: name.unwrap_or(defaults.name),
+        size: size.unwrap_or(defaults.size),
+        on_collision: None,
+    };
+    created(py, params.name.clone(), ServiceMessage::CreateCube(params))
+}
+
+#[pyfunction]
+#[pyo3(signature = (name=None, location=(0.0, 0.0, 0.0), radius=None, subdivisions=None))]
+fn create_sphere(
+    py: Python<'_>,
+    name: Option<String>,
+    location: (f32, f32, f32),
+    radius: Option<f32>,
+    subdivisions: Option<u32>,
+) -> PyResult<String> {
+    let defaults = CreateSphereParams::default();
+    let params = CreateSphereParams {
+        location: vec3(location),
+        name: name.unwrap_or(defaults.name),
+        radius: radius.unwrap_or(defaults.radius),
+        subdivisions: subdivisions.unwrap_or(defaults.subdivisions),
+        on_collision: None,
+    };
+    created(
+        py,
+        params.name.clone(),
+        ServiceMessage::CreateSphere(params),
+    )
+}
+
+/// A Principled BSDF material. Colors are `(r, g, b, a)` tuples.
+#[pyfunction]
+#[pyo3(signature = (
+    name,
+    base_color=None,
+    metallic=None,
+    roughness=None,
+    emission_color=None,
+    emission_strength=None,
+))]
+fn create_material(
+    py: Python<'_>,
+    name: String,
+    base_color: Option<(f32, f32, f32, f32)>,
+    metallic: Option<f32>,
+    roughness: Option<f32>,
+    emission_color: Option<(f32, f32, f32, f32)>,
+    emission_strength: Option<f32>,
+) -> PyResult<String> {
+    let defaults = CreateMaterialParams::default();
+    let params = CreateMaterialParams {
+        name,
+        base_color: base_color.map_or(defaults.base_color, color),
+        metallic: metallic.unwrap_or(defaults.metallic),
+        roughness: roughness.unwrap_or(defaults.roughness),
+        emission_color: emission_color.map_or(defaults.emission_color, color),
+        emission_strength: emission_strength.unwrap_or(defaults.emission_strength),
+        ..defaults
+    };
+    created(
+        py,
+        params.name.clone(),
+        ServiceMessage::CreateMaterial(params),
+    )
+}
+
+#[pyfunction]
+fn create_material_from_preset(py: Python<'_>, name: String, preset: String) -> PyResult<String> {
+    let params = CreateMaterialFromPresetParams {
+        name,
+        preset,
+        on_collision: None,
+    };
+    created(
+        py,
+        params.name.clone(),
+        ServiceMessage::CreateMaterialFromPreset(params),
+    )
+}
+
+/// A linked duplicate of `source`, sharing its mesh data.
+#[pyfunction]
+#[pyo3(signature = (source, name, location=(0.0, 0.0, 0.0)))]
+fn create_instance(
+    py: Python<'_>,
+    source: String,
+    name: String,
+    location: (f32, f32, f32),
+) -> PyResult<String> {
+    let params = CreateInstanceParams {
+        source,
+        name,
+        location: vec3(location),
+        on_collision: None,
+    };
+    created(
+        py,
+        params.name.clone(),
+        ServiceMessage::CreateInstance(params),
+    )
+}
+
+#[pyfunction]
+fn assign_material(py: Python<'_>, object_name: String, material_name: String) -> PyResult<()> {
+    done(
+        py,
+        ServiceMessage::AssignMaterial(AssignMaterialParams {
+            object_name,
+            material_name,
+        }),
+    )
+}
+
+/// Put `material` in slot `index` of `object`. An index one past the last slot adds a slot.
+#[pyfunction]
+fn set_material_slot(
+    py: Python<'_>,
+    object: String,
+    index: usize,
+    material: String,
+) -> PyResult<()> {
+    done(
+        py,
+        ServiceMessage::SetMaterialSlot(SetMaterialSlotParams {
+            object,
+            index,
+            material,
+        }),
+    )
+}
+
+#[pyfunction]
+fn remove_material_slot(py: Python<'_>, object: String, index: usize) -> PyResult<()> {
+    done(
+        py,
+        ServiceMessage::RemoveMaterialSlot(RemoveMaterialSlotParams { object, index }),
+    )
+}
+
+/// Give the faces at the indices in `faces` the material in `slot`.
+#[pyfunction]
+fn set_face_materials(
+    py: Python<'_>,
+    object: String,
+    slot: usize,
+    faces: Vec<usize>,
+) -> PyResult<()> {
+    done(
+        py,
+        ServiceMessage::SetFaceMaterials(SetFaceMaterialsParams {
+            object,
+            slot,
+            faces,
+        }),
+    )
+}
+
+#[pyfunction]
+fn get_material_nodes(py: Python<'_>, name: String) -> PyResult<PyObject> {
+    data(
+        py,
+        ServiceMessage::GetMaterialNodes(GetMaterialNodesParams { name }),
+    )
+}
+
+/// Replace the node tree of material `name` with `graph`, a dict like `get_material_nodes`
+/// returns.
+#[pyfunction]
+fn set_material_nodes(py: Python<'_>, name: String, graph: &Bound<'_, PyAny>) -> PyResult<()> {
+    let graph = from_python(py, "graph", graph)?;
+    done(
+        py,
+        ServiceMessage::SetMaterialNodes(SetMaterialNodesParams { name, graph }),
+    )
+}
+
+/// Add `graph`, a node graph dict, to `object` as a geometry nodes modifier. Returns the
+/// modifier's name. The graph last applied to `object` is left alone unless `force` is set.
+#[pyfunction]
+#[pyo3(signature = (object, graph, force=false))]
+fn apply_geometry_nodes(
+    py: Python<'_>,
+    object: String,
+    graph: &Bound<'_, PyAny>,
+    force: bool,
+) -> PyResult<String> {
+    let graph = from_python(py, "graph", graph)?;
+    let message = ServiceMessage::ApplyGeometryNodes(ApplyGeometryNodesParams {
+        object,
+        graph,
+        force,
+    });
+    applied_modifier(request(py, message)?).map_err(|response| failure(*response))
+}
+
+/// Set the world to a plain `color` or the `hdri` image at a path, or Blender's default
+/// background without either.
+#[pyfunction]
+#[pyo3(signature = (color=None, hdri=None, strength=None))]
+fn set_world(
+    py: Python<'_>,
+    color: Option<(f32, f32, f32, f32)>,
+    hdri: Option<String>,
+    strength: Option<f32>,
+) -> PyResult<()> {
+    let defaults = SetWorldParams::default();
+    let background = match (color, hdri) {
+        (Some(_), Some(_)) => {
+            return Err(PyErr::new::<PyValueError, _>(
+                "A world has either a color or an hdri, not both",
+            ));
+        }
+        (Some(rgba), None) => WorldBackground::Color(self::color(rgba)),
+        (None, Some(path)) => WorldBackground::Hdri { path },
+        (None, None) => defaults.background,
+    };
+    done(
+        py,
+        ServiceMessage::SetWorld(SetWorldParams {
+            background,
+            strength: strength.unwrap_or(defaults.strength),
+        }),
+    )
+}
+
+/// Replace the scene settings with `settings`, a dict like `get_scene_settings` returns.
+#[pyfunction]
+fn set_scene_settings(py: Python<'_>, settings: &Bound<'_, PyAny>) -> PyResult<()> {
+    let settings = from_python(py, "scene settings", settings)?;
+    done(py, ServiceMessage::SetSceneSettings(settings))
+}
+
+/// Set the unit `system`, `"NONE"`, `"METRIC"` or `"IMPERIAL"`, and the scene units per
+/// Blender unit.
+#[pyfunction]
+#[pyo3(signature = (system, scale_length=1.0))]
+fn set_units(py: Python<'_>, system: &Bound<'_, PyAny>, scale_length: f32) -> PyResult<()> {
+    let system = from_python(py, "unit system", system)?;
+    done(
+        py,
+        ServiceMessage::SetUnits(UnitSettings {
+            system,
+            scale_length,
+        }),
+    )
+}
+
+#[pyfunction]
+fn set_gravity(py: Python<'_>, gravity: (f32, f32, f32)) -> PyResult<()> {
+    done(py, ServiceMessage::SetGravity(vec3(gravity)))
+}
+
+/// Make `object` a rigid body. `body_type` is `"ACTIVE"` or `"PASSIVE"`.
+#[pyfunction]
+#[pyo3(signature = (object, body_type=None, mass=None, friction=None))]
+fn add_rigid_body(
+    py: Python<'_>,
+    object: String,
+    body_type: Option<&Bound<'_, PyAny>>,
+    mass: Option<f32>,
+    friction: Option<f32>,
+) -> PyResult<()> {
+    let defaults = AddRigidBodyParams::default();
+    let params = AddRigidBodyParams {
+        object,
+        body_type: match body_type {
+            Some(body_type) => from_python(py, "rigid body type", body_type)?,
+            None => defaults.body_type,
+        },
+        mass: mass.unwrap_or(defaults.mass),
+        friction: friction.unwrap_or(defaults.friction),
+    };
+    done(py, ServiceMessage::AddRigidBody(params))
+}
+
+/// The value at `data_path` on `target`, which is `"Scene"`, `"World"`, or a dict like
+/// `{"Object": "Cube"}`, `{"Material": ...}` or `{"Mesh": ...}`.
+#[pyfunction]
+fn get_property(
+    py: Python<'_>,
+    target: &Bound<'_, PyAny>,
+    data_path: String,
+) -> PyResult<PyObject> {
+    let target = from_python(py, "property target", target)?;
+    data(py, ServiceMessage::GetProperty { target, data_path })
+}
+
+/// Set the value at `data_path` on `target`, given as for `get_property`.
+#[pyfunction]
+fn set_property(
+    py: Python<'_>,
+    target: &Bound<'_, PyAny>,
+    data_path: String,
+    value: &Bound<'_, PyAny>,
+) -> PyResult<()> {
+    let target = from_python(py, "property target", target)?;
+    let value = from_python(py, "property value", value)?;
+    done(
+        py,
+        ServiceMessage::SetProperty {
+            target,
+            data_path,
+            value,
+        },
+    )
+}
+
+/// Bake `object`'s `bake_type`, like `"COMBINED"` or `"NORMAL"`, to a square image of
+/// `resolution` pixels at `output_path`. Returns a dict of the image's `path`, `width` and
+/// `height`.
+#[pyfunction]
+#[pyo3(signature = (object, output_path, bake_type=None, resolution=None))]
+fn bake_texture(
+    py: Python<'_>,
+    object: String,
+    output_path: String,
+    bake_type: Option<&Bound<'_, PyAny>>,
+    resolution: Option<u32>,
+) -> PyResult<PyObject> {
+    let defaults = BakeTextureParams::default();
+    let params = BakeTextureParams {
+        object,
+        bake_type: match bake_type {
+            Some(bake_type) => from_python(py, "bake type", bake_type)?,
+            None => defaults.bake_type,
+        },
+        resolution: resolution.unwrap_or(defaults.resolution),
+        output_path,
+    };
+    data(py, ServiceMessage::BakeTexture(params))
+}
+
+/// Limit what `light` shines on to `include_objects`, when given, minus `exclude_objects`.
+#[pyfunction]
+#[pyo3(signature = (light, include_objects=Vec::new(), exclude_objects=Vec::new()))]
+fn set_light_linking(
+    py: Python<'_>,
+    light: String,
+    include_objects: Vec<String>,
+    exclude_objects: Vec<String>,
+) -> PyResult<()> {
+    done(
+        py,
+        ServiceMessage::SetLightLinking(SetLightLinkingParams {
+            light,
+            include_objects,
+            exclude_objects,
+        }),
+    )
+}
+
+/// Set whether `object` casts shadows and whether it catches them. Flags left out keep their
+/// current value.
+#[pyfunction]
+#[pyo3(signature = (object, visible_shadow=None, is_shadow_catcher=None))]
+fn set_shadow_visibility(
+    py: Python<'_>,
+    object: String,
+    visible_shadow: Option<bool>,
+    is_shadow_catcher: Option<bool>,
+) -> PyResult<()> {
+    done(
+        py,
+        ServiceMessage::SetShadowVisibility(SetShadowVisibilityParams {
+            object,
+            visible_shadow,
+            is_shadow_catcher,
+        }),
+    )
+}
+
+/// The objects passing every predicate in `filter`, a dict of any of `object_type`, `name`,
+/// `material`, `collection` and `spatial`. All objects without one.
+#[pyfunction]
+#[pyo3(signature = (filter=None))]
+fn query_objects(py: Python<'_>, filter: Option<&Bound<'_, PyAny>>) -> PyResult<Vec<PyObjectData>> {
+    let filter = match filter {
+        Some(filter) => from_python(py, "object filter", filter)?,
+        None => ObjectFilter::default(),
+    };
+    match request(py, ServiceMessage::QueryObjects(filter))? {
+        ServiceResponse::Objects(objects) => Ok(objects.into_iter().map(Into::into).collect()),
+        other => Err(failure(other)),
+    }
+}
+
+/// Move, rotate or scale an object. Rotation is an XYZ euler in radians, and anything left out
+/// is unchanged.
+#[pyfunction]
+#[pyo3(signature = (object, location=None, rotation=None, scale=None))]
+fn set_transform(
+    py: Python<'_>,
+    object: String,
+    location: Option<(f32, f32, f32)>,
+    rotation: Option<(f32, f32, f32)>,
+    scale: Option<(f32, f32, f32)>,
+) -> PyResult<()> {
+    let params = SetTransformParams {
+        object,
+        location: location.map(vec3),
+        rotation: rotation.map(|(x, y, z)| Rotation::euler(x, y, z)),
+        scale: scale.map(vec3),
+    };
+    done(py, ServiceMessage::SetTransform(params))
+}
+
+/// Parent `child` to `parent`, or clear its parent when `parent` is `None`.
+#[pyfunction]
+#[pyo3(signature = (child, parent=None))]
+fn set_parent(py: Python<'_>, child: String, parent: Option<String>) -> PyResult<()> {
+    done(
+        py,
+        ServiceMessage::SetParent(SetParentParams { child, parent }),
+    )
+}
+
+#[pyfunction]
+fn get_object(py: Python<'_>, name: String) -> PyResult<PyObjectData> {
+    match request(py, ServiceMessage::GetObject(GetObjectParams { name }))? {
+        ServiceResponse::ObjectData(data) => Ok(data.into()),
+        other => Err(failure(other)),
+    }
+}
+
+#[pyfunction]
+fn get_material(py: Python<'_>, name: String) -> PyResult<PyMaterialData> {
+    match request(py, ServiceMessage::GetMaterial(GetMaterialParams { name }))? {
+        ServiceResponse::MaterialData(data) => Ok(data.into()),
+        other => Err(failure(other)),
+    }
+}
+
+#[pyfunction]
+fn get_bounding_box(py: Python<'_>, name: String) -> PyResult<PyObject> {
+    data(py, ServiceMessage::GetBoundingBox(GetObjectParams { name }))
+}
+
+#[pyfunction]
+fn get_dependencies(py: Python<'_>, name: String) -> PyResult<PyObject> {
+    data(
+        py,
+        ServiceMessage::GetDependencies(GetObjectParams { name }),
+    )
+}
+
+#[pyfunction]
+fn get_children(py: Python<'_>, name: String) -> PyResult<Vec<String>> {
+    names(py, ServiceMessage::GetChildren(GetObjectParams { name }))
+}
+
+#[pyfunction]
+fn get_ancestors(py: Python<'_>, name: String) -> PyResult<Vec<String>> {
+    names(py, ServiceMessage::GetAncestors(GetObjectParams { name }))
+}
+
+#[pyfunction]
+fn get_scene_ir(py: Python<'_>) -> PyResult<PyObject> {
+    data(py, ServiceMessage::GetSceneIr)
+}
+
+#[pyfunction]
+fn get_world(py: Python<'_>) -> PyResult<PyObject> {
+    data(py, ServiceMessage::GetWorld)
+}
+
+#[pyfunction]
+fn get_scene_settings(py: Python<'_>) -> PyResult<PyObject> {
+    data(py, ServiceMessage::GetSceneSettings)
+}
+
+#[pyfunction]
+fn get_units(py: Python<'_>) -> PyResult<PyObject> {
+    data(py, ServiceMessage::GetUnits)
+}
+
+#[pyfunction]
+fn get_scene_state(py: Python<'_>) -> PyResult<PyObject> {
+    data(py, ServiceMessage::GetSceneState)
+}
+
+#[pyfunction]
+fn select_objects(py: Python<'_>, names: Vec<String>) -> PyResult<()> {
+    done(
+        py,
+        ServiceMessage::SelectObjects(SelectObjectsParams { names }),
+    )
+}
+
+#[pyfunction]
+fn deselect_all(py: Python<'_>) -> PyResult<()> {
+    done(py, ServiceMessage::DeselectAll)
+}
+
+#[pyfunction]
+fn get_selected(py: Python<'_>) -> PyResult<Vec<String>> {
+    names(py, ServiceMessage::GetSelected)
+}
+
+#[pyfunction]
+fn list_objects(py: Python<'_>) -> PyResult<Vec<String>> {
+    names(py, ServiceMessage::ListObjects)
+}
+
+#[pyfunction]
+fn list_materials(py: Python<'_>) -> PyResult<Vec<String>> {
+    names(py, ServiceMessage::ListMaterials)
+}
+
+#[pyfunction]
+fn list_meshes(py: Python<'_>) -> PyResult<Vec<String>> {
+    names(py, ServiceMessage::ListMeshes)
+}
+
+#[pyfunction]
+fn clear_scene(py: Python<'_>) -> PyResult<()> {
+    done(py, ServiceMessage::ClearScene)
+}
+
+/// Import a model, with the format taken from the file extension. Returns the imported objects.
+#[pyfunction]
+fn import_file(py: Python<'_>, path: String) -> PyResult<Vec<String>> {
+    let format = ImportFormat::from_extension(extension(&path)).ok_or_else(|| {
+        PyErr::new::<PyValueError, _>(format!("Unsupported import format: {path}"))
+    })?;
+    names(
+        py,
+        ServiceMessage::ImportFile(ImportFileParams { path, format }),
+    )
+}
+
+/// Export the scene, with the format taken from the file extension. Returns the exported
+/// objects.
+#[pyfunction]
+#[pyo3(signature = (path, selected_only=false))]
+fn export_scene(py: Python<'_>, path: String, selected_only: bool) -> PyResult<Vec<String>> {
+    let format = ExportFormat::from_extension(extension(&path)).ok_or_else(|| {
+        PyErr::new::<PyValueError, _>(format!("Unsupported export format: {path}"))
+    })?;
+    names(
+        py,
+        ServiceMessage::ExportScene(ExportSceneParams {
+            path,
+            format,
+            selected_only,
+        }),
+    )
+}
+
+#[pyfunction]
+fn save_blend(py: Python<'_>, path: String) -> PyResult<()> {
+    done(py, ServiceMessage::SaveBlend(SaveBlendParams { path }))
+}
+
+#[pyfunction]
+fn open_blend(py: Python<'_>, path: String) -> PyResult<()> {
+    done(py, ServiceMessage::OpenBlend(OpenBlendParams { path }))
+}
+
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(create_cube, m)?)?;
+    m.add_function(wrap_pyfunction!(create_sphere, m)?)?;
+    m.add_function(wrap_pyfunction!(create_material, m)?)?;
+    m.add_function(wrap_pyfunction!(create_material_from_preset, m)?)?;
+    m.add_function(wrap_pyfunction!(create_instance, m)?)?;
+    m.add_function(wrap_pyfunction!(assign_material, m)?)?;
+    m.add_function(wrap_pyfunction!(set_transform, m)?)?;
+    m.add_function(wrap_pyfunction!(set_parent, m)?)?;
+    m.add_function(wrap_pyfunction!(get_object, m)?)?;
+    m.add_function(wrap_pyfunction!(get_material, m)?)?;
+    m.add_function(wrap_pyfunction!(get_bounding_box, m)?)?;
+    m.add_function(wrap_pyfunction!(get_dependencies, m)?)?;
+    m.add_function(wrap_pyfunction!(get_children, m)?)?;
+    m.add_function(wrap_pyfunction!(get_ancestors, m)?)?;
+    m.add_function(wrap_pyfunction!(get_world, m)?)?;
+    m.add_function(wrap_pyfunction!(get_scene_settings, m)?)?;
+    m.add_function(wrap_pyfunction!(get_units, m)?)?;
+    m.add_function(wrap_pyfunction!(get_scene_state, m)?)?;
+    m.add_function(wrap_pyfunction!(select_objects, m)?)?;
+    m.add_function(wrap_pyfunction!(deselect_all, m)?)?;
+    m.add_function(wrap_pyfunction!(get_selected, m)?)?;
+    m.add_function(wrap_pyfunction!(list_objects, m)?)?;
+    m.add_function(wrap_pyfunction!(list_materials, m)?)?;
+    m.add_function(wrap_pyfunction!(list_meshes, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_scene, m)?)?;
+    m.add_function(wrap_pyfunction!(import_file, m)?)?;
+    m.add_function(wrap_pyfunction!(export_scene, m)?)?;
+    m.add_function(wrap_pyfunction!(save_blend, m)?)?;
+    m.add_function(wrap_pyfunction!(open_blend, m)?)?;
+    m.add_function(wrap_pyfunction!(set_material_slot, m)?)?;
+    m.add_function(wrap_pyfunction!(remove_material_slot, m)?)?;
+    m.add_function(wrap_pyfunction!(set_face_materials, m)?)?;
+    m.add_function(wrap_pyfunction!(get_material_nodes, m)?)?;
+    m.add_function(wrap_pyfunction!(set_material_nodes, m)?)?;
+    m.add_function(wrap_pyfunction!(get_scene_ir, m)?)?;
+    m.add_function(wrap_pyfunction!(set_world, m)?)?;
+    m.add_function(wrap_pyfunction!(set_scene_settings, m)?)?;
+    m.add_function(wrap_pyfunction!(set_units, m)?)?;
+    m.add_function(wrap_pyfunction!(set_gravity, m)?)?;
+    m.add_function(wrap_pyfunction!(add_rigid_body, m)?)?;
+    m.add_function(wrap_pyfunction!(get_property, m)?)?;
+    m.add_function(wrap_pyfunction!(set_property, m)?)?;
+    m.add_function(wrap_pyfunction!(apply_geometry_nodes, m)?)?;
+    m.add_function(wrap_pyfunction!(bake_texture, m)?)?;
+    m.add_function(wrap_pyfunction!(set_light_linking, m)?)?;
+    m.add_function(wrap_pyfunction!(set_shadow_visibility, m)?)?;
+    m.add_function(wrap_pyfunction!(query_objects, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unexpected<T: std::fmt::Debug>(result: Result<T, Box<ServiceResponse>>) -> ServiceResponse {
+        *result.expect_err("Expected the response to be a failure")
+    }
+
+    #[test]
+    fn test_created_name() {
+        let created = |response| created_name("Cube".to_string(), response);
+        assert_eq!(
+            created(ServiceResponse::Created).expect("Expected a name"),
+            "Cube"
+        );
+        assert_eq!(
+            created(ServiceResponse::CreatedAs("Cube.001".to_string())).expect("Expected a name"),
+            "Cube.001"
+        );
+        assert!(matches!(
+            unexpected(created(ServiceResponse::Error(
+                "Object already exists: Cube".to_string()
+            ))),
+            ServiceResponse::Error(_)
+        ));
+        assert!(matches!(
+            unexpected(created(ServiceResponse::SceneCleared)),
+            ServiceResponse::SceneCleared
+        ));
+    }
+
+    #[test]
+    fn test_completed() {
+        for response in [
+            ServiceResponse::Created,
+            ServiceResponse::SceneCleared,
+            ServiceResponse::SelectionChanged,
+            ServiceResponse::BlendSaved,
+            ServiceResponse::BlendOpened,
+        ] {
+            completed(response).expect("Expected the operation to be done");
+        }
+        assert!(matches!(
+            unexpected(completed(ServiceResponse::CreatedAs(
+                "Cube.001".to_string()
+            ))),
+            ServiceResponse::CreatedAs(_)
+        ));
+        assert!(matches!(
+            unexpected(completed(ServiceResponse::Stopped)),
+            ServiceResponse::Stopped
+        ));
+    }
+
+    #[test]
+    fn test_applied_modifier() {
+        for response in [
+            ServiceResponse::GeometryNodesApplied("GeometryNodes".to_string()),
+            ServiceResponse::GeometryNodesUnchanged("GeometryNodes".to_string()),
+        ] {
+            assert_eq!(
+                applied_modifier(response).expect("Expected a modifier"),
+                "GeometryNodes"
+            );
+        }
+        assert!(matches!(
+            unexpected(applied_modifier(ServiceResponse::Created)),
+            ServiceResponse::Created
+        ));
+    }
+
+    #[test]
+    fn test_listed_names() {
+        let names = || vec!["A".to_string(), "B".to_string()];
+        for response in [
+            ServiceResponse::ObjectList(names()),
+            ServiceResponse::MaterialList(names()),
+            ServiceResponse::MeshList(names()),
+            ServiceResponse::Children(names()),
+            ServiceResponse::Ancestors(names()),
+            ServiceResponse::Selection(names()),
+            ServiceResponse::Imported(names()),
+            ServiceResponse::Exported(names()),
+        ] {
+            assert_eq!(listed_names(response).expect("Expected names"), names());
+        }
+        assert!(matches!(
+            unexpected(listed_names(ServiceResponse::Created)),
+            ServiceResponse::Created
+        ));
+    }
+}
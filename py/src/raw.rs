@@ -0,0 +1,105 @@
+//! Messages as dicts in their serde form, so message variants added on the Rust side can be
+//! used from Python before they get a function of their own.
+//!
+//! Variants with data are dicts keyed by the variant name, variants without are just the name:
+//!
+//! ```python
+//! id = cuttle_py.send_raw({"CreateCube": {"name": "Box", "location": {"x": 0, "y": 0, "z": 0}, "size": 2.0}})
+//! id = cuttle_py.send_raw("ListObjects")
+//! reply = cuttle_py.recv_raw(timeout_ms=1000)  # {"id": id, "response": {"ObjectList": ["Box"]}}
+//! ```
+
+use crate::bridge;
+use crate::operations::to_python;
+use cuttle::{Reply, ServiceMessage};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use serde_json::{Value, json};
+use std::time::Duration;
+
+// The message in the JSON `json.dumps` makes of its dict
+fn message_from_json(json: &str) -> Result<ServiceMessage, String> {
+    serde_json::from_str(json).map_err(|e| format!("Invalid message: {e}"))
+}
+
+// The reply as the JSON of the dict `recv_raw` returns
+fn reply_to_json(reply: &Reply) -> Value {
+    json!({ "id": reply.id, "response": reply.response })
+}
+
+/// Send `message`, a `ServiceMessage` as a dict or variant name, returning its request id.
+#[pyfunction]
+fn send_raw(py: Python<'_>, message: &Bound<'_, PyAny>) -> PyResult<u64> {
+    let json: String = py
+        .import_bound("json")?
+        .call_method1("dumps", (message,))?
+        .extract()?;
+    let message = message_from_json(&json).map_err(PyErr::new::<PyValueError, _>)?;
+
+    let bridge = bridge()?;
+    py.allow_threads(move || bridge.send(message))
+        .map_err(|e| PyErr::new::<PyRuntimeError, _>(format!("Send failed: {e}")))
+}
+
+/// The next response as a dict of its request `id` and the `ServiceResponse` as `response`.
+/// Waits up to `timeout_ms` for one, or only returns one already waiting without it. `None`
+/// when there is no response.
+#[pyfunction]
+#[pyo3(signature = (timeout_ms=None))]
+fn recv_raw(py: Python<'_>, timeout_ms: Option<u64>) -> PyResult<Option<PyObject>> {
+    let bridge = bridge()?;
+    let reply = py.allow_threads(move || match timeout_ms {
+        Some(timeout_ms) => bridge.recv_timeout(Duration::from_millis(timeout_ms)),
+        None => bridge.try_recv_reply(),
+    });
+    reply
+        .map(|reply| to_python(py, &reply_to_json(&reply)))
+        .transpose()
+}
+
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(send_raw, m)?)?;
+    m.add_function(wrap_pyfunction!(recv_raw, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cuttle::ServiceResponse;
+
+    #[test]
+    fn test_messages_from_json() {
+        assert!(matches!(
+            message_from_json(r#""ListObjects""#),
+            Ok(ServiceMessage::ListObjects)
+        ));
+
+        let create =
+            r#"{"CreateCube": {"name": "Box", "location": {"x": 0, "y": 1, "z": 2}, "size": 2.0}}"#;
+        match message_from_json(create) {
+            Ok(ServiceMessage::CreateCube(params)) => {
+                assert_eq!(params.name, "Box");
+                assert_eq!(params.location.z, 2.0);
+                assert_eq!(params.size, 2.0);
+            }
+            other => panic!("Expected CreateCube, got {other:?}"),
+        }
+
+        let error = message_from_json(r#"{"Teleport": {}}"#).expect_err("Expected an error");
+        assert!(error.starts_with("Invalid message: "), "{error}");
+    }
+
+    #[test]
+    fn test_reply_to_json() {
+        let reply = |response| Reply { id: 7, response };
+        assert_eq!(
+            reply_to_json(&reply(ServiceResponse::ObjectList(vec!["Box".to_string()]))),
+            json!({ "id": 7, "response": { "ObjectList": ["Box"] } })
+        );
+        assert_eq!(
+            reply_to_json(&reply(ServiceResponse::SceneCleared)),
+            json!({ "id": 7, "response": "SceneCleared" })
+        );
+    }
+}
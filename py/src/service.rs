@@ -0,0 +1,52 @@
+use crate::{start, stop};
+use cuttle::PyBridge;
+use pyo3::prelude::*;
+use std::sync::Arc;
+
+/// Services for the length of a `with` block:
+///
+/// ```python
+/// with cuttle_py.CuttleService():
+///     cuttle_py.create_cube(name="Box")
+/// ```
+///
+/// Entering starts the runtime, which the module functions use until the block exits. Exiting
+/// stops it and waits for its thread, even when the block raised, so services can be started
+/// again afterwards.
+#[pyclass(module = "cuttle_py")]
+pub struct CuttleService {
+    bridge: Option<Arc<PyBridge>>,
+}
+
+#[pymethods]
+impl CuttleService {
+    #[new]
+    fn new() -> Self {
+        Self { bridge: None }
+    }
+
+    fn __enter__(mut slf: PyRefMut<'_, Self>) -> PyResult<PyRefMut<'_, Self>> {
+        if slf.bridge.is_some() {
+            return Err(PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(
+                "CuttleService is already running",
+            ));
+        }
+        slf.bridge = Some(start(slf.py())?);
+        Ok(slf)
+    }
+
+    #[pyo3(signature = (_exc_type=None, _exc_value=None, _traceback=None))]
+    fn __exit__(
+        &mut self,
+        py: Python<'_>,
+        _exc_type: Option<PyObject>,
+        _exc_value: Option<PyObject>,
+        _traceback: Option<PyObject>,
+    ) -> PyResult<bool> {
+        if let Some(bridge) = self.bridge.take() {
+            stop(py, bridge)?;
+        }
+        // Exceptions from the block propagate
+        Ok(false)
+    }
+}
@@ -0,0 +1,170 @@
+//! Python classes for the data query functions return, so addon code reads `obj.location.x`
+//! instead of digging through dicts.
+//!
+//! Objects and materials are snapshots of the scene when they were queried, so their attributes
+//! are read only.
+
+use cuttle_blender_api::{Color, MaterialData, ObjectData, Rotation, Vec3};
+use pyo3::prelude::*;
+
+#[pyclass(name = "Vec3", module = "cuttle_py", eq)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PyVec3 {
+    #[pyo3(get, set)]
+    pub x: f32,
+    #[pyo3(get, set)]
+    pub y: f32,
+    #[pyo3(get, set)]
+    pub z: f32,
+}
+
+#[pymethods]
+impl PyVec3 {
+    #[new]
+    #[pyo3(signature = (x=0.0, y=0.0, z=0.0))]
+    fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    fn to_tuple(&self) -> (f32, f32, f32) {
+        (self.x, self.y, self.z)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Vec3({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+impl From<Vec3> for PyVec3 {
+    fn from(v: Vec3) -> Self {
+        Self {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+impl From<PyVec3> for Vec3 {
+    fn from(v: PyVec3) -> Self {
+        Vec3::new(v.x, v.y, v.z)
+    }
+}
+
+fn rgba(color: Color) -> (f32, f32, f32, f32) {
+    (color.r, color.g, color.b, color.a)
+}
+
+#[pyclass(name = "ObjectData", module = "cuttle_py", frozen, get_all)]
+#[derive(Debug, Clone)]
+pub struct PyObjectData {
+    pub name: String,
+    /// Blender's `Object.type`, such as `"MESH"`.
+    pub object_type: String,
+    pub location: PyVec3,
+    /// Blender's `Object.rotation_mode`: an euler order such as `"XYZ"`, `"QUATERNION"` or
+    /// `"AXIS_ANGLE"`.
+    pub rotation_mode: String,
+    /// `(x, y, z)` in radians for eulers, `(w, x, y, z)` for quaternions and
+    /// `(angle, x, y, z)` for axis angles.
+    pub rotation: Vec<f32>,
+    pub scale: PyVec3,
+    pub materials: Vec<String>,
+    pub vertex_count: Option<usize>,
+    pub face_count: Option<usize>,
+    pub parent: Option<String>,
+    pub instance_of: Option<String>,
+}
+
+#[pymethods]
+impl PyObjectData {
+    fn __repr__(&self) -> String {
+        format!(
+            "ObjectData(name={:?}, object_type={:?}, location={})",
+            self.name,
+            self.object_type,
+            self.location.__repr__()
+        )
+    }
+}
+
+impl From<ObjectData> for PyObjectData {
+    fn from(data: ObjectData) -> Self {
+        let (rotation_mode, rotation) = match data.rotation {
+            Rotation::Euler { order, x, y, z } => (format!("{order:?}"), vec![x, y, z]),
+            Rotation::Quaternion { w, x, y, z } => ("QUATERNION".to_string(), vec![w, x, y, z]),
+            Rotation::AxisAngle { angle, x, y, z } => {
+                ("AXIS_ANGLE".to_string(), vec![angle, x, y, z])
+            }
+        };
+        Self {
+            name: data.name,
+            object_type: data.object_type.into(),
+            location: data.location.into(),
+            rotation_mode,
+            rotation,
+            scale: data.scale.into(),
+            materials: data.materials,
+            vertex_count: data.vertex_count,
+            face_count: data.face_count,
+            parent: data.parent,
+            instance_of: data.instance_of,
+        }
+    }
+}
+
+/// A Principled BSDF material. Colors are `(r, g, b, a)` tuples.
+#[pyclass(name = "MaterialData", module = "cuttle_py", frozen, get_all)]
+#[derive(Debug, Clone)]
+pub struct PyMaterialData {
+    pub name: String,
+    pub use_nodes: bool,
+    pub base_color: (f32, f32, f32, f32),
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emission_color: (f32, f32, f32, f32),
+    pub emission_strength: f32,
+    pub alpha: f32,
+    pub ior: f32,
+    pub specular: f32,
+    pub transmission: f32,
+    pub normal_strength: f32,
+    pub node_count: usize,
+}
+
+#[pymethods]
+impl PyMaterialData {
+    fn __repr__(&self) -> String {
+        format!(
+            "MaterialData(name={:?}, base_color={:?})",
+            self.name, self.base_color
+        )
+    }
+}
+
+impl From<MaterialData> for PyMaterialData {
+    fn from(data: MaterialData) -> Self {
+        Self {
+            name: data.name,
+            use_nodes: data.use_nodes,
+            base_color: rgba(data.base_color),
+            metallic: data.metallic,
+            roughness: data.roughness,
+            emission_color: rgba(data.emission_color),
+            emission_strength: data.emission_strength,
+            alpha: data.alpha,
+            ior: data.ior,
+            specular: data.specular,
+            transmission: data.transmission,
+            normal_strength: data.normal_strength,
+            node_count: data.node_count,
+        }
+    }
+}
+
+pub fn register(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyVec3>()?;
+    m.add_class::<PyObjectData>()?;
+    m.add_class::<PyMaterialData>()?;
+    Ok(())
+}